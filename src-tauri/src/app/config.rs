@@ -6,24 +6,12 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
-const CONFIG_FILE: &str = "app_config.json";
+use crate::domain::answer_grading::GradingConfig;
+use crate::domain::review_order::ReviewOrder;
 
-/// 科目配置
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct SubjectConfig {
-    /// 是否归档（不参与推荐）
-    #[serde(default)]
-    pub archived: bool,
-    /// 该科每日推荐题数限制，None=使用全局值，Some(0)=不推荐
-    #[serde(default)]
-    pub recommendation_limit: Option<u32>,
-}
+pub use crate::domain::subject::SubjectConfig;
 
-impl Default for SubjectConfig {
-    fn default() -> Self {
-        Self { archived: false, recommendation_limit: None }
-    }
-}
+const CONFIG_FILE: &str = "app_config.json";
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AppSettings {
@@ -42,11 +30,25 @@ pub struct AppSettings {
     /// 推荐随机性系数
     #[serde(default = "default_recommendation_randomness")]
     pub recommendation_randomness: f64,
+    /// 单个知识点每日最多入选题数（0=不限制），避免一个薄弱知识点占满整科名额
+    #[serde(default = "default_max_per_knowledge_point")]
+    pub max_per_knowledge_point: u32,
+    /// 复习会话默认出题顺序（可在单次调用中覆盖）
+    #[serde(default)]
+    pub review_order: ReviewOrder,
+    /// 答错后追加的同知识点巩固题数（0=关闭）
+    #[serde(default = "default_remedial_follow_up_limit")]
+    pub remedial_follow_up_limit: u32,
+    /// 简答题自动判分的规范化规则
+    #[serde(default)]
+    pub answer_grading: GradingConfig,
     /// 显示推荐调试信息（开发用）
     #[serde(default = "default_show_debug_info")]
     pub show_debug_info: bool,
-    /// 科目配置映射
-    #[serde(default)]
+    /// 科目配置映射（科目名 -> 配置）。
+    /// 科目配置已存入数据库 `subject` 表，运行时由 `server::subject::load_settings` 从库中填充；
+    /// 这里只读取旧版配置文件中的值用于一次性导入，不再写回配置文件。
+    #[serde(default, skip_serializing)]
     pub subjects: HashMap<String, SubjectConfig>,
     /// 本地时区相对 UTC 的偏移小时数（东时区为正，西时区为负）。
     /// 例如 UTC+8 = 8，UTC-5 = -5。
@@ -63,6 +65,8 @@ fn default_per_subject_daily_limit() -> u32 { 10 }
 fn default_new_question_ratio() -> f64 { 0.3 }
 fn default_new_question_guarantee_ratio() -> f64 { 0.2 }
 fn default_recommendation_randomness() -> f64 { 1.0 }
+fn default_max_per_knowledge_point() -> u32 { 3 }
+fn default_remedial_follow_up_limit() -> u32 { 3 }
 fn default_show_debug_info() -> bool { true }
 fn default_timezone_offset_hours() -> i32 { 8 }
 fn default_day_cutoff_hour() -> i32 { 3 }
@@ -75,6 +79,10 @@ impl Default for AppSettings {
             new_question_ratio: default_new_question_ratio(),
             new_question_guarantee_ratio: default_new_question_guarantee_ratio(),
             recommendation_randomness: default_recommendation_randomness(),
+            max_per_knowledge_point: default_max_per_knowledge_point(),
+            review_order: ReviewOrder::default(),
+            remedial_follow_up_limit: default_remedial_follow_up_limit(),
+            answer_grading: GradingConfig::default(),
             show_debug_info: default_show_debug_info(),
             subjects: HashMap::new(),
             timezone_offset_hours: default_timezone_offset_hours(),
@@ -27,6 +27,11 @@ pub fn init_note(root: PathBuf) -> Result<AppInner, InitError> {
     let mut conn = conn;
     migrate(&mut conn).expect("数据库迁移失败");
 
+    // 旧版把科目配置按名称存在配置文件里，导入到科目表
+    if let Err(e) = crate::server::subject::import_legacy_settings(&conn) {
+        eprintln!("failed to import legacy subject configs: {}", e);
+    }
+
     // 将 root 写入到程序根目录下的 app_config.json，便于下次启动直接读取
 
     match app_config::save_root(&ctx.root) {
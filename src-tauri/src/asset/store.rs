@@ -12,7 +12,7 @@ use std::time::SystemTime;
 use uuid::Uuid;
 
 use crate::asset::error::StorageError;
-use crate::asset::fs::{copy_file, move_file};
+use crate::asset::fs::{copy_file, move_file, remove_file};
 use crate::asset::path::{PathBuilder, StorageLayout};
 
 /// 资源元数据（纯文件系统信息，不包含业务引用）
@@ -166,6 +166,14 @@ impl AssetStore {
         Ok(res)
     }
 
+    /// 删除刚由 `save_many` 保存、还没有登记到数据库的文件（登记失败时回退用）
+    pub fn discard(&self, metas: &[AssetMeta]) -> Result<(), StorageError> {
+        for meta in metas {
+            remove_file(&self.root.join(&meta.relative_path))?;
+        }
+        Ok(())
+    }
+
     /// 物理删除回收区中的指定条目（从存储根目录中删除回收路径文件）。
     pub fn delete_physical(&self, entries: &[(Uuid, String, PathBuf)]) -> Result<(), StorageError> {
         for (_id, _ext, recycle_relative) in entries {
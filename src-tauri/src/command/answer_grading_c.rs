@@ -0,0 +1,114 @@
+//! 简答题判分命令层
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::AppState;
+use crate::domain::answer_grading::{AnswerGrading, GradingStats};
+use crate::domain::enums::ReviewResult;
+use crate::domain::ids::QuestionId;
+use crate::server::answer_grading;
+use crate::util::time::now_ts;
+
+/// 一次判分的建议
+#[derive(Serialize, Deserialize)]
+pub struct AnswerGradingData {
+    pub id: i64,
+    pub question_id: i64,
+    pub response: String,
+    /// 判分时的标准答案
+    pub expected: String,
+    /// "CORRECT" / "FUZZY" / "WRONG"
+    pub suggested: String,
+}
+
+impl From<AnswerGrading> for AnswerGradingData {
+    fn from(g: AnswerGrading) -> Self {
+        Self {
+            id: g.id,
+            question_id: g.question_id,
+            response: g.response,
+            expected: g.expected,
+            suggested: g.suggested.as_str().to_string(),
+        }
+    }
+}
+
+/// 判分建议的准确率
+#[derive(Serialize, Deserialize)]
+pub struct GradingStatsData {
+    pub decided_count: i64,
+    pub agreed_count: i64,
+    /// 建议被采纳的比例，0..1
+    pub accuracy: f64,
+    /// 建议与最终结果的各种组合及次数
+    pub pairs: Vec<GradingPairData>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GradingPairData {
+    pub suggested: String,
+    pub final_result: String,
+    pub count: i64,
+}
+
+impl From<GradingStats> for GradingStatsData {
+    fn from(s: GradingStats) -> Self {
+        Self {
+            accuracy: s.accuracy(),
+            decided_count: s.decided_count,
+            agreed_count: s.agreed_count,
+            pairs: s
+                .pairs
+                .into_iter()
+                .map(|(suggested, final_result, count)| GradingPairData {
+                    suggested: suggested.as_str().to_string(),
+                    final_result: final_result.as_str().to_string(),
+                    count,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 给复习时输入的作答判分，按设置中的规范化规则给出建议
+#[tauri::command]
+pub fn grade_answer_comm(
+    state: tauri::State<AppState>,
+    question_id: i64,
+    response: String,
+) -> Result<AnswerGradingData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let config = crate::app::config::load_settings().answer_grading;
+    answer_grading::grade_response(conn, QuestionId::from(question_id), &response, &config, now_ts())
+        .map(AnswerGradingData::from)
+        .map_err(|e| format!("failed to grade answer: {}", e))
+}
+
+/// 记下用户最终给出的结果（采纳建议或改判）
+#[tauri::command]
+pub fn decide_grading_comm(state: tauri::State<AppState>, grading_id: i64, result: String) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let result = ReviewResult::from_str(&result).ok_or_else(|| "Invalid review result".to_string())?;
+    answer_grading::decide(conn, grading_id, result, now_ts()).map_err(|e| format!("failed to record grading: {}", e))
+}
+
+/// 判分建议与最终结果的对照
+#[tauri::command]
+pub fn get_grading_stats_comm(state: tauri::State<AppState>) -> Result<GradingStatsData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    answer_grading::grading_stats(conn)
+        .map(GradingStatsData::from)
+        .map_err(|e| format!("failed to load grading stats: {}", e))
+}
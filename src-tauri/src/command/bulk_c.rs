@@ -0,0 +1,22 @@
+//! 批量操作命令层
+
+use crate::app::AppState;
+use crate::server::bulk::{self, BulkAction, BulkFilter, BulkOutcome};
+
+/// 对一组题目执行批量操作；`question_ids` 与 `filter` 二选一，返回每道题的结果
+#[tauri::command]
+pub fn bulk_questions_comm(
+    state: tauri::State<AppState>,
+    question_ids: Option<Vec<i64>>,
+    filter: Option<BulkFilter>,
+    action: BulkAction,
+) -> Result<Vec<BulkOutcome>, String> {
+    let guard = state.inner.lock().unwrap();
+    let (conn, store) = match &*guard {
+        Some(inner) => (&inner.db, &inner.asset_store),
+        None => return Err("App not initialized".to_string()),
+    };
+    let ids = bulk::resolve_selection(conn, question_ids, filter, &action)
+        .map_err(|e| format!("failed to resolve selection: {}", e))?;
+    bulk::run_bulk(conn, store, &ids, &action).map_err(|e| format!("failed to run bulk operation: {}", e))
+}
@@ -0,0 +1,116 @@
+//! 知识点前置关系命令层
+
+use serde::Serialize;
+
+use crate::app::AppState;
+use crate::dao::prerequisite_dao::PrerequisiteDao;
+use crate::dao::review_dao::ReviewDao;
+use crate::server::knowledge_graph::{self, KnowledgeGraph};
+
+/// 一条前置关系
+#[derive(Serialize)]
+pub struct PrerequisiteEdge {
+    pub knowledge_point: String,
+    pub prerequisite: String,
+}
+
+/// 添加前置关系：学 `knowledge_point` 之前需要先掌握 `prerequisite`；成环时报错
+#[tauri::command]
+pub fn add_prerequisite_comm(
+    state: tauri::State<AppState>,
+    knowledge_point: String,
+    prerequisite: String,
+) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    knowledge_graph::add_prerequisite(conn, &knowledge_point, &prerequisite, crate::util::time::now_ts())
+        .map_err(|e| format!("failed to add prerequisite: {}", e))
+}
+
+/// 删除前置关系
+#[tauri::command]
+pub fn remove_prerequisite_comm(
+    state: tauri::State<AppState>,
+    knowledge_point: String,
+    prerequisite: String,
+) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    knowledge_graph::remove_prerequisite(conn, &knowledge_point, &prerequisite)
+        .map_err(|e| format!("failed to remove prerequisite: {}", e))
+}
+
+/// 列出全部前置关系
+#[tauri::command]
+pub fn list_prerequisites_comm(
+    state: tauri::State<AppState>,
+) -> Result<Vec<PrerequisiteEdge>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let edges = PrerequisiteDao::new(conn)
+        .list_all()
+        .map_err(|e| format!("failed to list prerequisites: {}", e))?;
+    Ok(edges
+        .into_iter()
+        .map(|(knowledge_point, prerequisite)| PrerequisiteEdge { knowledge_point, prerequisite })
+        .collect())
+}
+
+/// 知识点的全部前置（直接与间接），由近及远
+#[tauri::command]
+pub fn get_knowledge_point_ancestors_comm(
+    state: tauri::State<AppState>,
+    knowledge_point: String,
+) -> Result<Vec<String>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let graph = KnowledgeGraph::load(conn).map_err(|e| format!("failed to load graph: {}", e))?;
+    Ok(graph.ancestors(&knowledge_point))
+}
+
+/// 依赖该知识点的全部知识点（直接与间接），由近及远
+#[tauri::command]
+pub fn get_knowledge_point_descendants_comm(
+    state: tauri::State<AppState>,
+    knowledge_point: String,
+) -> Result<Vec<String>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let graph = KnowledgeGraph::load(conn).map_err(|e| format!("failed to load graph: {}", e))?;
+    Ok(graph.descendants(&knowledge_point))
+}
+
+/// 知识点最薄弱的前置（近 `days` 天错误率最高，缺省 14 天）；没有前置时返回 None
+#[tauri::command]
+pub fn get_weakest_prerequisite_comm(
+    state: tauri::State<AppState>,
+    knowledge_point: String,
+    days: Option<i64>,
+) -> Result<Option<String>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let graph = KnowledgeGraph::load(conn).map_err(|e| format!("failed to load graph: {}", e))?;
+    let since = crate::util::time::now_ts().as_i64() - days.unwrap_or(14) * 24 * 60 * 60;
+    let error_rates = ReviewDao::new(conn)
+        .get_knowledge_point_error_rates(since)
+        .map_err(|e| format!("failed to load error rates: {}", e))?;
+    Ok(graph.weakest_prerequisite(&knowledge_point, &error_rates, 1))
+}
@@ -0,0 +1,139 @@
+//! 知识点树管理命令层
+
+use serde::Serialize;
+
+use crate::app::AppState;
+use crate::domain::knowledge_point::KnowledgePoint;
+use crate::server::knowledge_point::{self, KnowledgePointStats};
+
+/// 知识点节点
+#[derive(Serialize)]
+pub struct KnowledgePointData {
+    pub id: i64,
+    pub name: String,
+    pub subject_id: Option<i64>,
+    pub parent_id: Option<i64>,
+    pub sort_order: i64,
+}
+
+impl From<KnowledgePoint> for KnowledgePointData {
+    fn from(kp: KnowledgePoint) -> Self {
+        Self {
+            id: kp.id,
+            name: kp.name,
+            subject_id: kp.subject_id,
+            parent_id: kp.parent_id,
+            sort_order: kp.sort_order,
+        }
+    }
+}
+
+/// 全部知识点节点（按显示顺序，前端按 parent_id 组装成树）
+#[tauri::command]
+pub fn get_knowledge_point_tree_comm(state: tauri::State<AppState>) -> Result<Vec<KnowledgePointData>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    crate::dao::KnowledgePointDao::new(conn)
+        .list()
+        .map(|nodes| nodes.into_iter().map(KnowledgePointData::from).collect())
+        .map_err(|e| format!("failed to list knowledge points: {}", e))
+}
+
+/// 新建知识点；有父节点时科目取父节点的科目
+#[tauri::command]
+pub fn create_knowledge_point_comm(
+    state: tauri::State<AppState>,
+    name: String,
+    subject_id: Option<i64>,
+    parent_id: Option<i64>,
+) -> Result<KnowledgePointData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    knowledge_point::create_knowledge_point(conn, &name, subject_id, parent_id, crate::util::time::now_ts())
+        .map(KnowledgePointData::from)
+        .map_err(|e| format!("failed to create knowledge point: {}", e))
+}
+
+/// 知识点改名（级联更新题目与前置关系）
+#[tauri::command]
+pub fn rename_knowledge_point_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+    name: String,
+) -> Result<KnowledgePointData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    knowledge_point::rename_knowledge_point(conn, id, &name)
+        .map(KnowledgePointData::from)
+        .map_err(|e| format!("failed to rename knowledge point: {}", e))
+}
+
+/// 移动知识点到新的父节点下（`parent_id` 为空则移为根节点）
+#[tauri::command]
+pub fn move_knowledge_point_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+    parent_id: Option<i64>,
+) -> Result<KnowledgePointData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    knowledge_point::move_knowledge_point(conn, id, parent_id)
+        .map(KnowledgePointData::from)
+        .map_err(|e| format!("failed to move knowledge point: {}", e))
+}
+
+/// 把知识点 `from_id` 合并进 `into_id`
+#[tauri::command]
+pub fn merge_knowledge_points_comm(
+    state: tauri::State<AppState>,
+    from_id: i64,
+    into_id: i64,
+) -> Result<KnowledgePointData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    knowledge_point::merge_knowledge_points(conn, from_id, into_id)
+        .map(KnowledgePointData::from)
+        .map_err(|e| format!("failed to merge knowledge points: {}", e))
+}
+
+/// 删除知识点（从题目上移除，子节点挂到其父节点下）
+#[tauri::command]
+pub fn delete_knowledge_point_comm(state: tauri::State<AppState>, id: i64) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    knowledge_point::delete_knowledge_point(conn, id)
+        .map_err(|e| format!("failed to delete knowledge point: {}", e))
+}
+
+/// 各知识点子树的题目数与错误率；`subject_id` 给定时只列该科
+#[tauri::command]
+pub fn get_knowledge_point_stats_comm(
+    state: tauri::State<AppState>,
+    subject_id: Option<i64>,
+) -> Result<Vec<KnowledgePointStats>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    knowledge_point::knowledge_point_stats(conn, subject_id)
+        .map_err(|e| format!("failed to load knowledge point stats: {}", e))
+}
@@ -1,6 +1,17 @@
+pub mod answer_grading_c;
+pub mod bulk_c;
 pub mod init_c;
+pub mod knowledge_graph_c;
+pub mod knowledge_point_c;
+pub mod occlusion_c;
+pub mod paper_c;
 pub mod question_c;
+pub mod question_link_c;
+pub mod question_part_c;
 pub mod recommendation_c;
 pub mod review_c;
 pub mod settings_c;
 pub mod show_list_c;
+pub mod subject_c;
+pub mod tag_c;
+pub mod user_field_c;
@@ -0,0 +1,108 @@
+//! 图片遮罩命令层
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::AppState;
+use crate::domain::ids::AssetId;
+use crate::domain::occlusion::{MaskRect, OcclusionMask};
+use crate::server::occlusion::{self, MaskInput};
+use crate::util::time::now_ts;
+
+/// 图片上的一个遮罩；坐标与宽高按图片尺寸归一化到 0..1
+#[derive(Serialize, Deserialize)]
+pub struct OcclusionMaskData {
+    pub id: i64,
+    /// 复习这一遮罩的卡片
+    pub card_id: i64,
+    /// 遮罩所在图片（与 QuestionImageData.asset_id 相同的 UUID 字符串）
+    pub asset_id: String,
+    /// 换图一次升一版
+    pub version: i64,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl From<OcclusionMask> for OcclusionMaskData {
+    fn from(m: OcclusionMask) -> Self {
+        Self {
+            id: m.id,
+            card_id: m.card_id,
+            asset_id: AssetId::from(m.asset_id).0.to_string(),
+            version: m.version,
+            x: m.rect.x,
+            y: m.rect.y,
+            width: m.rect.width,
+            height: m.rect.height,
+        }
+    }
+}
+
+/// 编辑遮罩时提交的矩形；`card_id` 为空表示新画的遮罩
+#[derive(Deserialize)]
+pub struct MaskInputData {
+    pub card_id: Option<i64>,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+fn parse_asset_id(asset_id: &str) -> Result<AssetId, String> {
+    uuid::Uuid::parse_str(asset_id).map(AssetId).map_err(|e| format!("Invalid asset id: {}", e))
+}
+
+/// 图片上当前的遮罩
+#[tauri::command]
+pub fn list_image_masks_comm(
+    state: tauri::State<AppState>,
+    asset_id: String,
+) -> Result<Vec<OcclusionMaskData>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let masks = occlusion::list_masks(conn, parse_asset_id(&asset_id)?)
+        .map_err(|e| format!("failed to list masks: {}", e))?;
+    Ok(masks.into_iter().map(OcclusionMaskData::from).collect())
+}
+
+/// 整体设置图片上的遮罩：新画的生成卡片，没有列出的移除
+#[tauri::command]
+pub fn set_image_masks_comm(
+    state: tauri::State<AppState>,
+    asset_id: String,
+    masks: Vec<MaskInputData>,
+) -> Result<Vec<OcclusionMaskData>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let masks = masks
+        .into_iter()
+        .map(|m| MaskInput { card_id: m.card_id, rect: MaskRect { x: m.x, y: m.y, width: m.width, height: m.height } })
+        .collect();
+    let saved = occlusion::set_masks(conn, parse_asset_id(&asset_id)?, masks, now_ts())
+        .map_err(|e| format!("failed to save masks: {}", e))?;
+    Ok(saved.into_iter().map(OcclusionMaskData::from).collect())
+}
+
+/// 换图：遮罩挪到新图并升版本，返回新图的 asset_id
+#[tauri::command]
+pub fn replace_question_image_comm(
+    state: tauri::State<AppState>,
+    asset_id: String,
+    image_path: String,
+) -> Result<String, String> {
+    let guard = state.inner.lock().unwrap();
+    let (conn, store) = match &*guard {
+        Some(inner) => (&inner.db, &inner.asset_store),
+        None => return Err("App not initialized".to_string()),
+    };
+    let new_id = occlusion::replace_image(conn, store, parse_asset_id(&asset_id)?, image_path, now_ts())
+        .map_err(|e| format!("failed to replace image: {}", e))?;
+    Ok(new_id.0.to_string())
+}
@@ -0,0 +1,148 @@
+//! 试卷命令层
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::AppState;
+use crate::domain::paper::{Paper, PaperStats};
+use crate::server::paper::{self, PaperInput};
+use crate::util::time::now_ts;
+
+/// 试卷及其题目的掌握情况
+#[derive(Serialize, Deserialize)]
+pub struct PaperData {
+    pub id: i64,
+    pub name: String,
+    /// 考试日期（YYYY-MM-DD）
+    pub exam_date: Option<String>,
+    pub subject: Option<String>,
+    pub total_score: Option<f64>,
+    pub stats: PaperStatsData,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PaperStatsData {
+    pub question_count: i64,
+    pub new_count: i64,
+    pub learning_count: i64,
+    pub stable_count: i64,
+    pub suspended_count: i64,
+    pub review_count: i64,
+    pub wrong_review_count: i64,
+    /// 已掌握（STABLE）的比例，0..1
+    pub stable_rate: f64,
+}
+
+impl From<PaperStats> for PaperStatsData {
+    fn from(s: PaperStats) -> Self {
+        Self {
+            stable_rate: s.stable_rate(),
+            question_count: s.question_count,
+            new_count: s.new_count,
+            learning_count: s.learning_count,
+            stable_count: s.stable_count,
+            suspended_count: s.suspended_count,
+            review_count: s.review_count,
+            wrong_review_count: s.wrong_review_count,
+        }
+    }
+}
+
+fn paper_data(conn: &crate::db::connection::Connection, p: Paper) -> Result<PaperData, String> {
+    let stats = paper::paper_stats(conn, p.id).map_err(|e| format!("failed to load paper stats: {}", e))?;
+    Ok(PaperData {
+        id: p.id,
+        name: p.name,
+        exam_date: p.exam_date,
+        subject: p.subject,
+        total_score: p.total_score,
+        stats: stats.into(),
+    })
+}
+
+/// 全部试卷（含统计），考试日期新的在前
+#[tauri::command]
+pub fn list_papers_comm(state: tauri::State<AppState>) -> Result<Vec<PaperData>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let papers = paper::list_papers(conn).map_err(|e| format!("failed to list papers: {}", e))?;
+    papers.into_iter().map(|p| paper_data(conn, p)).collect()
+}
+
+/// 新建试卷
+#[tauri::command]
+pub fn create_paper_comm(
+    state: tauri::State<AppState>,
+    name: String,
+    exam_date: Option<String>,
+    subject: Option<String>,
+    total_score: Option<f64>,
+) -> Result<PaperData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let input = PaperInput { name, exam_date, subject, total_score };
+    let created = paper::create_paper(conn, input, now_ts()).map_err(|e| format!("failed to create paper: {}", e))?;
+    paper_data(conn, created)
+}
+
+/// 修改试卷；改名时题目上的来源试卷一并更新
+#[tauri::command]
+pub fn update_paper_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+    name: String,
+    exam_date: Option<String>,
+    subject: Option<String>,
+    total_score: Option<f64>,
+) -> Result<PaperData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let input = PaperInput { name, exam_date, subject, total_score };
+    let updated = paper::update_paper(conn, id, input).map_err(|e| format!("failed to update paper: {}", e))?;
+    paper_data(conn, updated)
+}
+
+/// 删除试卷（题目保留，只清除来源试卷）
+#[tauri::command]
+pub fn delete_paper_comm(state: tauri::State<AppState>, id: i64) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    paper::delete_paper(conn, id).map_err(|e| format!("failed to delete paper: {}", e))
+}
+
+/// 把一组题目归到试卷下；`paper_id` 为空时清除来源试卷
+#[tauri::command]
+pub fn assign_paper_comm(
+    state: tauri::State<AppState>,
+    question_ids: Vec<i64>,
+    paper_id: Option<i64>,
+) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    paper::assign_questions(conn, &question_ids, paper_id).map_err(|e| format!("failed to assign paper: {}", e))
+}
+
+/// 整套复习一张试卷时要做的题目 ID（大题换成小题，暂停的跳过）
+#[tauri::command]
+pub fn get_paper_session_comm(state: tauri::State<AppState>, id: i64) -> Result<Vec<i64>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    paper::session_question_ids(conn, id).map_err(|e| format!("failed to load paper questions: {}", e))
+}
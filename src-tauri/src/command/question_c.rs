@@ -2,12 +2,20 @@ use serde::{Deserialize, Serialize};
 use tauri::State;
 
 use crate::app::{AppInner, AppState};
-use crate::domain::{enums::AssetType, enums::MetaKey, enums::SystemMetaKey, ids::QuestionId};
+use crate::command::occlusion_c::OcclusionMaskData;
+use crate::command::question_link_c::QuestionLinkData;
+use crate::command::question_part_c::QuestionPartData;
+use crate::domain::{
+    enums::AssetType, enums::ExtensionMetaKey, enums::MetaKey, enums::SystemMetaKey, enums::TextField,
+    ids::AssetId, ids::QuestionId, question_text::QuestionText,
+};
 use crate::server::question_manager::{
     add_question_images, cleanup_old_deleted_questions, create_question, delete_question,
-    delete_question_image, get_question_detail, permanently_delete_question, rename_question,
-    restore_question, update_question_meta,
+    delete_question_image, get_question_detail, get_question_text_history,
+    permanently_delete_question, reorder_question_assets, restore_question, update_question,
 };
+use crate::domain::question_revision::QuestionSnapshot;
+use crate::server::revision::{self, RevertOutcome, RevisionChange};
 use crate::util::time::{ClockConfig, LogicalDay};
 
 #[derive(Serialize, Deserialize)]
@@ -26,19 +34,55 @@ pub struct QuestionInfoData {
     pub deleted_at: Option<String>,
     pub subject: Option<String>,
     pub knowledge_points: Vec<String>,
+    /// 自由标签
+    pub tags: Vec<String>,
+    /// 来源试卷名称
+    pub source_paper: Option<String>,
     pub question_images: Vec<QuestionImageData>,
     pub answer_images: Vec<QuestionImageData>,
+    pub explain_images: Vec<QuestionImageData>,
+    /// 其他附件（不一定是图片）
+    pub other_assets: Vec<QuestionImageData>,
     pub last_reviewed_at: Option<String>,
+    /// 题干 / 答案 / 解析文本（Markdown，行内 LaTeX）
+    pub prompt: Option<String>,
+    pub answer: Option<String>,
+    pub explanation: Option<String>,
+    /// 与其他题目的关联
+    pub links: Vec<QuestionLinkData>,
+    /// 小题（按顺序）
+    pub parts: Vec<QuestionPartData>,
+    /// 本题是小题时所属的大题
+    pub parent_id: Option<i64>,
+    /// 本题是填空卡时的填空编号（文本由笔记题干渲染）
+    pub cloze_index: Option<i64>,
+    /// 本题是遮罩卡片时要遮住的区域（画在 question_images 中的那张图上）
+    pub occlusion: Option<OcclusionMaskData>,
+}
+
+/// 文本字段的一个历史版本
+#[derive(Serialize)]
+pub struct TextVersionData {
+    pub version: i64,
+    pub content: String,
+    pub created_at: String,
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn create_question_comm(
     state: tauri::State<AppState>,
     name: String,
     question_image_paths: Vec<String>,
     answer_image_paths: Vec<String>,
+    explain_image_paths: Option<Vec<String>>,
+    other_paths: Option<Vec<String>>,
     subject: Option<String>,
     knowledge_points: Vec<String>,
+    prompt: Option<String>,
+    answer: Option<String>,
+    explanation: Option<String>,
+    paper_id: Option<i64>,
 ) -> Result<String, String> {
     // Debug: Print received image paths
     println!("[DEBUG] create_question_comm received:");
@@ -61,14 +105,23 @@ pub fn create_question_comm(
         Some(inner) => &inner.asset_store,
         None => return Err("App not initialized".to_string()),
     };
+    // 先确认来源试卷存在，避免题目建好后才发现试卷不存在
+    let paper = match paper_id {
+        Some(id) => Some(crate::server::paper::get_paper(conn, id).map_err(|e| e.to_string())?),
+        None => None,
+    };
     match create_question(
         &conn,
         &store,
         name,
         question_image_paths,
         answer_image_paths,
+        explain_image_paths.unwrap_or_default(),
+        other_paths.unwrap_or_default(),
         subject,
         knowledge_points,
+        QuestionText { prompt, answer, explanation },
+        paper.as_ref(),
     ) {
         Ok(qid) => {
             println!("Question created with ID: {}", qid.0);
@@ -205,39 +258,43 @@ pub fn get_question_detail_comm(
                 .filter(|m| matches!(m.key, MetaKey::System(SystemMetaKey::KnowledgePoint)))
                 .map(|m| m.value.clone())
                 .collect();
-
-            // Extract question images from assets (convert to full path)
-            // 使用正斜杠以兼容 asset 协议
-            let question_images: Vec<QuestionImageData> = q_info
-                .assets
+            let tags: Vec<String> = q_info
+                .metas
                 .iter()
-                .filter(|a| a.asset_type == AssetType::QUESTION && a.deleted_at.is_none())
-                .map(|a| {
-                    let full_path = store_root.join(a.path.as_str());
-                    // 转换为正斜杠格式，兼容 asset 协议
-                    QuestionImageData {
-                        path: full_path.to_string_lossy().replace('\\', "/"),
-                        asset_id: Some(a.id.0.to_string()),
-                        sort_order: Some(a.sort_order),
-                    }
-                })
+                .filter(|m| matches!(m.key, MetaKey::System(SystemMetaKey::Tag)))
+                .map(|m| m.value.clone())
                 .collect();
-
-            // Extract answer images from assets (convert to full path)
-            let answer_images: Vec<QuestionImageData> = q_info
-                .assets
+            let source_paper = q_info
+                .metas
                 .iter()
-                .filter(|a| a.asset_type == AssetType::ANSWER && a.deleted_at.is_none())
-                .map(|a| {
-                    let full_path = store_root.join(a.path.as_str());
-                    // 转换为正斜杠格式，兼容 asset 协议
-                    QuestionImageData {
-                        path: full_path.to_string_lossy().replace('\\', "/"),
-                        asset_id: Some(a.id.0.to_string()),
-                        sort_order: Some(a.sort_order),
-                    }
-                })
-                .collect();
+                .find(|m| matches!(m.key, MetaKey::Extension(ExtensionMetaKey::SourcePaper)))
+                .map(|m| m.value.clone());
+
+            // 按类型提取资源（转换为完整路径），按 sort_order 排列
+            // 使用正斜杠以兼容 asset 协议
+            let assets_of = |asset_type: AssetType| -> Vec<QuestionImageData> {
+                let mut assets: Vec<_> = q_info
+                    .assets
+                    .iter()
+                    .filter(|a| a.asset_type == asset_type && a.deleted_at.is_none())
+                    .collect();
+                assets.sort_by_key(|a| a.sort_order);
+                assets
+                    .into_iter()
+                    .map(|a| {
+                        let full_path = store_root.join(a.path.as_str());
+                        QuestionImageData {
+                            path: full_path.to_string_lossy().replace('\\', "/"),
+                            asset_id: Some(a.id.0.to_string()),
+                            sort_order: Some(a.sort_order),
+                        }
+                    })
+                    .collect()
+            };
+            let question_images = assets_of(AssetType::QUESTION);
+            let answer_images = assets_of(AssetType::ANSWER);
+            let explain_images = assets_of(AssetType::EXPLAIN);
+            let other_assets = assets_of(AssetType::OTHER);
 
             // Get last reviewed date from reviews
             let cfg = ClockConfig::default();
@@ -253,9 +310,21 @@ pub fn get_question_detail_comm(
                 deleted_at: question.deleted_at.map(|ts| LogicalDay::from_timestamp(ts, &cfg).to_string(&cfg)),
                 subject,
                 knowledge_points,
+                tags,
+                source_paper,
                 question_images,
                 answer_images,
+                explain_images,
+                other_assets,
                 last_reviewed_at,
+                prompt: q_info.text.prompt,
+                answer: q_info.text.answer,
+                explanation: q_info.text.explanation,
+                links: q_info.links.into_iter().map(QuestionLinkData::from).collect(),
+                parts: q_info.parts.into_iter().map(QuestionPartData::from).collect(),
+                parent_id: q_info.parent_id.map(i64::from),
+                cloze_index: q_info.cloze_index,
+                occlusion: q_info.occlusion.map(OcclusionMaskData::from),
             })
         }
         Err(e) => {
@@ -270,6 +339,10 @@ pub struct UpdateQuestionInput {
     pub name: Option<String>,
     pub subject: Option<String>,
     pub knowledge_points: Option<Vec<String>>,
+    /// 文本字段：None 表示不修改，空字符串表示清空
+    pub prompt: Option<String>,
+    pub answer: Option<String>,
+    pub explanation: Option<String>,
 }
 
 #[tauri::command]
@@ -284,32 +357,30 @@ pub fn update_question_comm(
         None => return Err("App not initialized".to_string()),
     };
 
-    // 更新题目名称
-    if let Some(name) = &data.name {
-        if let Err(e) = rename_question(conn, QuestionId::from(id), name.clone()) {
-            eprintln!("Failed to update question name: {:?}", e);
-            return Err(e.to_string());
-        }
-    }
-
-    // 更新科目和知识点
-    if data.subject.is_some() || data.knowledge_points.is_some() {
-        if let Err(e) = update_question_meta(
-            conn,
-            QuestionId::from(id),
-            data.subject.clone(),
-            data.knowledge_points.clone(),
-        ) {
-            eprintln!("Failed to update question meta: {:?}", e);
-            return Err(e.to_string());
-        }
+    // 题名、科目、知识点与文本一起保存，记一条修订；每个有变化的文本字段追加一个新版本
+    let text = QuestionText {
+        prompt: data.prompt,
+        answer: data.answer,
+        explanation: data.explanation,
+    };
+    if let Err(e) = update_question(
+        conn,
+        QuestionId::from(id),
+        data.name,
+        data.subject,
+        data.knowledge_points,
+        text,
+        crate::util::time::now_ts(),
+    ) {
+        eprintln!("Failed to update question: {:?}", e);
+        return Err(e.to_string());
     }
 
     println!("Question updated with ID: {}", id);
     Ok(id.to_string())
 }
 
-/// 添加题目的图片
+/// 添加题目的图片或附件；`image_type` 为 question / answer / explain / other
 #[tauri::command]
 pub fn add_question_images_comm(
     state: tauri::State<AppState>,
@@ -327,10 +398,9 @@ pub fn add_question_images_comm(
         None => return Err("App not initialized".to_string()),
     };
 
-    let asset_type = match image_type.as_str() {
-        "question" => AssetType::QUESTION,
-        "answer" => AssetType::ANSWER,
-        _ => return Err("Invalid image type".to_string()),
+    let asset_type = match AssetType::from_str(&image_type) {
+        Some(t) => t,
+        None => return Err("Invalid image type".to_string()),
     };
 
     match add_question_images(
@@ -412,7 +482,7 @@ pub struct SortOrderUpdate {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BatchSortOrderUpdate {
     pub question_id: i64,
-    pub type_: String, // "QUESTION" or "ANSWER"
+    pub type_: String, // "QUESTION" / "ANSWER" / "EXPLAIN" / "OTHER"
     pub updates: Vec<SortOrderUpdate>,
 }
 
@@ -430,19 +500,108 @@ pub fn update_image_sort_order_comm(
         None => return Err("App not initialized".to_string()),
     };
 
-    // 批量更新排序 - asset_id 是 UUID 字符串，需要转换为 i64
-    let asset_orders: Vec<(i64, i64)> = updates
+    let asset_type = AssetType::from_str(&type_).ok_or_else(|| format!("Invalid asset type: {}", type_))?;
+
+    // 批量更新排序 - asset_id 是 UUID 字符串
+    let asset_orders: Vec<(AssetId, i64)> = updates
         .iter()
         .map(|u| {
             let uuid = uuid::Uuid::parse_str(&u.asset_id)
                 .map_err(|e| format!("Invalid asset_id UUID: {}", e))?;
-            let asset_id_i64: i64 = uuid.as_u128() as i64;
-            Ok((asset_id_i64, u.sort_order))
+            Ok((AssetId(uuid), u.sort_order))
         })
         .collect::<Result<Vec<_>, String>>()?;
 
-    match crate::db::batch_update_asset_sort_order(conn, question_id, &type_, asset_orders) {
+    match reorder_question_assets(conn, QuestionId::from(question_id), asset_type, asset_orders) {
         Ok(_) => Ok(format!("Updated {} items", updates.len())),
         Err(e) => Err(e.to_string())
     }
 }
+
+/// 文本字段的历史版本，新版本在前；`field` 为 prompt / answer / explanation
+#[tauri::command]
+pub fn get_question_text_history_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+    field: String,
+) -> Result<Vec<TextVersionData>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let field = TextField::from_str(&field).ok_or_else(|| format!("unknown text field: {}", field))?;
+    let cfg = ClockConfig::default();
+    let versions = get_question_text_history(conn, QuestionId::from(id), field)
+        .map_err(|e| format!("failed to load text history: {}", e))?;
+    Ok(versions
+        .into_iter()
+        .map(|v| TextVersionData {
+            version: v.version,
+            content: v.content,
+            created_at: LogicalDay::from_timestamp(crate::util::time::Timestamp(v.created_at), &cfg)
+                .to_string(&cfg),
+        })
+        .collect())
+}
+
+/// 题目的一条修订
+#[derive(Serialize)]
+pub struct QuestionRevisionData {
+    pub id: i64,
+    /// Unix 秒
+    pub created_at: i64,
+    pub snapshot: QuestionSnapshot,
+}
+
+/// 题目的修订历史，新的在前
+#[tauri::command]
+pub fn list_question_revisions_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+) -> Result<Vec<QuestionRevisionData>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let revisions = revision::list_revisions(conn, QuestionId::from(id))
+        .map_err(|e| format!("failed to load revisions: {}", e))?;
+    Ok(revisions
+        .into_iter()
+        .map(|r| QuestionRevisionData { id: r.id, created_at: r.created_at.as_i64(), snapshot: r.snapshot })
+        .collect())
+}
+
+/// 比较两条修订：从 `from_revision` 到 `to_revision` 的变化
+#[tauri::command]
+pub fn diff_question_revisions_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+    from_revision: i64,
+    to_revision: i64,
+) -> Result<Vec<RevisionChange>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    revision::diff_revisions(conn, QuestionId::from(id), from_revision, to_revision)
+        .map_err(|e| format!("failed to diff revisions: {}", e))
+}
+
+/// 把题目恢复到某条修订（含仍在回收区的已移除资源）
+#[tauri::command]
+pub fn revert_question_revision_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+    revision_id: i64,
+) -> Result<RevertOutcome, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    revision::revert_to_revision(conn, QuestionId::from(id), revision_id, crate::util::time::now_ts())
+        .map_err(|e| format!("failed to revert question: {}", e))
+}
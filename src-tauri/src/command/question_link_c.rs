@@ -0,0 +1,76 @@
+//! 题目关联命令层
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::AppState;
+use crate::domain::question_link::{LinkType, QuestionLink};
+use crate::server::question_link;
+use crate::util::time::now_ts;
+
+/// 从某道题看出去的一条关联
+#[derive(Serialize, Deserialize)]
+pub struct QuestionLinkData {
+    pub id: i64,
+    /// VARIANT_OF / FOLLOWS / CONTRASTS_WITH / SAME_SOURCE
+    pub link_type: String,
+    pub question_id: i64,
+    pub question_name: Option<String>,
+    /// 仅对 FOLLOWS 有意义：true 表示本题承接对方
+    pub outgoing: bool,
+    /// Unix 秒
+    pub created_at: i64,
+}
+
+impl From<QuestionLink> for QuestionLinkData {
+    fn from(link: QuestionLink) -> Self {
+        Self {
+            id: link.id,
+            link_type: link.link_type.as_str().to_string(),
+            question_id: link.question_id,
+            question_name: link.question_name,
+            outgoing: link.outgoing,
+            created_at: link.created_at.as_i64(),
+        }
+    }
+}
+
+/// 题目的全部关联
+#[tauri::command]
+pub fn list_question_links_comm(state: tauri::State<AppState>, id: i64) -> Result<Vec<QuestionLinkData>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let links = question_link::list_links(conn, id).map_err(|e| format!("failed to list links: {}", e))?;
+    Ok(links.into_iter().map(QuestionLinkData::from).collect())
+}
+
+/// 关联两道题；FOLLOWS 表示 `from_question_id` 承接 `to_question_id`。返回关联 id
+#[tauri::command]
+pub fn link_questions_comm(
+    state: tauri::State<AppState>,
+    from_question_id: i64,
+    to_question_id: i64,
+    link_type: String,
+) -> Result<i64, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let link_type = LinkType::from_str(&link_type).ok_or_else(|| format!("invalid link type: {}", link_type))?;
+    question_link::link_questions(conn, from_question_id, to_question_id, link_type, now_ts())
+        .map_err(|e| format!("failed to link questions: {}", e))
+}
+
+/// 删除一条关联
+#[tauri::command]
+pub fn unlink_questions_comm(state: tauri::State<AppState>, link_id: i64) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    question_link::unlink(conn, link_id).map_err(|e| format!("failed to unlink questions: {}", e))
+}
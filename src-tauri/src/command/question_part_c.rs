@@ -0,0 +1,72 @@
+//! 小题命令层
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::AppState;
+use crate::domain::ids::QuestionId;
+use crate::domain::question::Question;
+use crate::domain::question_text::QuestionText;
+use crate::server::question_part;
+use crate::util::time::{now_ts, ClockConfig, LogicalDay};
+
+/// 大题下的一道小题
+#[derive(Serialize, Deserialize)]
+pub struct QuestionPartData {
+    pub id: i64,
+    pub name: Option<String>,
+    pub state: String,
+    pub last_result: Option<String>,
+    /// 下次到期的逻辑日（YYYY-MM-DD）
+    pub due_at: Option<String>,
+}
+
+impl From<Question> for QuestionPartData {
+    fn from(part: Question) -> Self {
+        let cfg = ClockConfig::default();
+        Self {
+            id: i64::from(part.id),
+            name: part.name,
+            state: part.state.as_str().to_string(),
+            last_result: part.last_result.map(|r| r.as_str().to_string()),
+            due_at: part.due_at.map(|ts| LogicalDay::from_timestamp(ts, &cfg).to_string(&cfg)),
+        }
+    }
+}
+
+/// 给大题追加一道小题（沿用大题的科目与知识点）；返回小题 ID。
+/// 小题的图片与附件随后在小题自己的详情页添加。
+#[tauri::command]
+pub fn add_question_part_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+    name: Option<String>,
+    prompt: Option<String>,
+    answer: Option<String>,
+    explanation: Option<String>,
+) -> Result<i64, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let text = QuestionText { prompt, answer, explanation };
+    question_part::add_part(conn, QuestionId::from(id), name, text, now_ts())
+        .map(i64::from)
+        .map_err(|e| format!("failed to add part: {}", e))
+}
+
+/// 按给定顺序重排大题的小题
+#[tauri::command]
+pub fn reorder_question_parts_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+    part_ids: Vec<i64>,
+) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    question_part::reorder_parts(conn, QuestionId::from(id), &part_ids)
+        .map_err(|e| format!("failed to reorder parts: {}", e))
+}
@@ -6,9 +6,12 @@ use crate::dao::meta_dao::MetaDao;
 use crate::dao::question_dao::QuestionDao;
 use crate::dao::recommendation_dao::{DailyReviewStatus, RecommendationDao, ReviewRecord};
 use crate::dao::review_dao::ReviewDao;
+use crate::domain::enums::RecommendationTier;
+use crate::domain::review_order::ReviewOrder;
+use crate::server::subject;
 use crate::server::recommendation::{
-    DailyRecommendation, PreviewRecommendationItem, RecommendationStats, RecommendationSystem,
-    RecommendedQuestion,
+    DailyRecommendation, PreviewRecommendationItem, QuestionExplanation, RecommendationStats,
+    RecommendationSystem, RecommendedQuestion,
 };
 
 /// 获取每日推荐
@@ -22,30 +25,35 @@ pub fn get_daily_recommendation_comm(
         None => return Err("App not initialized".to_string()),
     };
     let rs = RecommendationSystem::new(conn);
-    let settings = config::load_settings();
-    rs.get_daily_recommendation(&settings.subjects, settings.per_subject_daily_limit, settings.new_question_guarantee_ratio)
+    let settings = subject::load_settings(conn).map_err(|e| e.to_string())?;
+    rs.get_daily_recommendation(&settings)
         .map_err(|e| e.to_string())
 }
 
 /// 获取推荐题目列表（用于复习会话）
 ///
-/// 走 `RecommendationSystem::recommend_for_review` 完整评分 + reason 词条 + 分池
-/// 流程，每条 `RecommendedQuestion.reason` 字段携带新版的标签列表
-/// （"新题" / "到期" / "超期 N 天" / "上次出错"）。
+/// 走 `RecommendationSystem::recommend_for_review` 完整评分 + reason 编码 + 分池
+/// 流程，每条 `RecommendedQuestion.reason` 字段携带结构化的理由编码
+/// （如 `{"code":"overdue","days":5}`），文案由前端渲染。
+///
+/// `order` / `seed` 覆盖设置中的默认出题顺序，取值同 `recommend_questions_comm`。
 #[tauri::command]
 pub fn get_recommendation_list_comm(
     state: tauri::State<AppState>,
     limit: Option<i64>,
     subject: Option<String>,
+    order: Option<String>,
+    seed: Option<u64>,
 ) -> Result<Vec<RecommendedQuestion>, String> {
     let guard = state.inner.lock().unwrap();
     let conn = match &*guard {
         Some(inner) => &inner.db,
         None => return Err("App not initialized".to_string()),
     };
+    let order = order.map(|o| o.parse::<ReviewOrder>()).transpose()?;
     let rs = RecommendationSystem::new(conn);
     let lim = limit.map(|n| n as usize);
-    rs.recommend_for_review(lim, subject.as_deref())
+    rs.recommend_for_review(lim, subject.as_deref(), order, seed)
         .map_err(|e| e.to_string())
 }
 
@@ -62,15 +70,26 @@ pub fn preview_recommendation_comm(
         None => return Err("App not initialized".to_string()),
     };
     let rs = RecommendationSystem::new(conn);
-    let settings = config::load_settings();
-    rs.preview_recommendation(
-        show_score_detail,
-        show_exclusion_reason,
-        &settings.subjects,
-        settings.per_subject_daily_limit,
-        settings.new_question_guarantee_ratio,
-    )
-    .map_err(|e| e.to_string())
+    let settings = subject::load_settings(conn).map_err(|e| e.to_string())?;
+    rs.preview_recommendation(show_score_detail, show_exclusion_reason, &settings)
+        .map_err(|e| e.to_string())
+}
+
+/// 解释单题为什么入选 / 没入选（开发者模式）
+#[tauri::command]
+pub fn explain_question_comm(
+    state: tauri::State<AppState>,
+    question_id: i64,
+) -> Result<QuestionExplanation, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let rs = RecommendationSystem::new(conn);
+    let settings = subject::load_settings(conn).map_err(|e| e.to_string())?;
+    rs.explain_question(question_id, &settings)
+        .map_err(|e| e.to_string())
 }
 
 /// 重新生成今日推荐（删除缓存后重新生成，记录操作日志）
@@ -97,16 +116,16 @@ pub fn regenerate_daily_recommendation_comm(
         .map(|qs| qs.len())
         .unwrap_or(0);
 
-    // 删除今日缓存
+    // 删除今日缓存（只删每日推荐层，保留答错后追加的巩固题）
     recommendation_dao
-        .delete_by_day(day)
+        .delete_by_day_tier(day, RecommendationTier::DAILY)
         .map_err(|e| e.to_string())?;
 
     // 重新生成
     let rs = RecommendationSystem::new(conn);
-    let settings = config::load_settings();
+    let settings = subject::load_settings(conn).map_err(|e| e.to_string())?;
     let daily = rs
-        .get_daily_recommendation(&settings.subjects, settings.per_subject_daily_limit, settings.new_question_guarantee_ratio)
+        .get_daily_recommendation(&settings)
         .map_err(|e| e.to_string())?;
 
     let new_count = daily.questions.len();
@@ -117,6 +136,80 @@ pub fn regenerate_daily_recommendation_comm(
     Ok(daily)
 }
 
+/// 手动置顶：题目在 `days_ahead` 天后（缺省 0 = 今天）必定出现在推荐中
+#[tauri::command]
+pub fn pin_question_comm(
+    state: tauri::State<AppState>,
+    question_id: i64,
+    days_ahead: Option<i64>,
+) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let days_ahead = days_ahead.unwrap_or(0);
+    if days_ahead < 0 {
+        return Err("days_ahead must not be negative".to_string());
+    }
+    let rs = RecommendationSystem::new(conn);
+    rs.pin_question(question_id, days_ahead)
+        .map_err(|e| format!("failed to pin question: {}", e))
+}
+
+/// 今日搁置：题目今天不再推荐，不记录复习
+#[tauri::command]
+pub fn bury_question_comm(
+    state: tauri::State<AppState>,
+    question_id: i64,
+) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let rs = RecommendationSystem::new(conn);
+    rs.bury_question(question_id)
+        .map_err(|e| format!("failed to bury question: {}", e))
+}
+
+/// 推迟 `days` 天：期间不推荐，到期时间顺延，不记录复习
+#[tauri::command]
+pub fn postpone_question_comm(
+    state: tauri::State<AppState>,
+    question_id: i64,
+    days: i64,
+) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    if days < 1 {
+        return Err("days must be at least 1".to_string());
+    }
+    let rs = RecommendationSystem::new(conn);
+    rs.postpone_question(question_id, days)
+        .map_err(|e| format!("failed to postpone question: {}", e))
+}
+
+/// 取消 `days_ahead` 天后（缺省 0 = 今天）的置顶 / 搁置
+#[tauri::command]
+pub fn clear_recommendation_override_comm(
+    state: tauri::State<AppState>,
+    question_id: i64,
+    days_ahead: Option<i64>,
+) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let rs = RecommendationSystem::new(conn);
+    rs.clear_override(question_id, days_ahead.unwrap_or(0))
+        .map_err(|e| format!("failed to clear override: {}", e))
+}
+
 /// 获取推荐统计概览
 #[tauri::command]
 pub fn get_recommendation_stats_comm(
@@ -128,8 +221,8 @@ pub fn get_recommendation_stats_comm(
         None => return Err("App not initialized".to_string()),
     };
     let rs = RecommendationSystem::new(conn);
-    let settings = config::load_settings();
-    rs.get_recommendation_stats(&settings.subjects)
+    let settings = subject::load_settings(conn).map_err(|e| e.to_string())?;
+    rs.get_recommendation_stats(&settings)
         .map_err(|e| e.to_string())
 }
 
@@ -185,6 +278,21 @@ pub fn get_today_review_records_comm(
     dao.get_today_review_records().map_err(|e| e.to_string())
 }
 
+/// 获取今日待做的巩固题（答错后按知识点追加，尚未复习的部分）
+#[tauri::command]
+pub fn get_remedial_queue_comm(
+    state: tauri::State<AppState>,
+) -> Result<Vec<RecommendedQuestion>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let dao = RecommendationDao::new(conn);
+    dao.get_pending_recommendations(i64::MAX, None, Some(RecommendationTier::REMEDIAL))
+        .map_err(|e| e.to_string())
+}
+
 /// 根据题目ID列表获取题目（用于练习模式）
 #[tauri::command]
 pub fn get_questions_by_ids_comm(
@@ -228,10 +336,12 @@ pub fn get_questions_by_ids_comm(
             last_result: q.last_result.map(|r| r.as_str().to_string()),
             error_rate: None,
             subject,
+            tier: RecommendationTier::DAILY.as_str().to_string(),
             reason: None,
             score_detail: None,
             review_count: 0,
             created_at: 0,
+            knowledge_points: vec![],
         });
     }
 
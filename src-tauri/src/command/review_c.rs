@@ -6,8 +6,11 @@ use crate::db::{DailySeriesRow, SubjectStatRow};
 use crate::dao::review_dao::ReviewDao;
 use crate::domain::ids::QuestionId;
 use crate::domain::question::Question;
+use crate::domain::review_order::ReviewOrder;
+use crate::server::forget::{self, ForgetScope};
+use crate::server::recommendation::RecommendationSystem;
 use crate::server::ReviewManager;
-use crate::util::time::ClockConfig;
+use crate::util::time::{ClockConfig, LogicalDay};
 
 /// 推荐结果数据
 #[derive(Serialize, Deserialize)]
@@ -54,16 +57,36 @@ pub fn process_review_comm(
         _ => return Err("Invalid review result".to_string()),
     };
 
-    let question = manager.process_review(qid, review_result, crate::util::time::now_ts())?;
+    let now = crate::util::time::now_ts();
+    let is_wrong = review_result == crate::domain::enums::ReviewResult::WRONG;
+    let question = manager.process_review(qid, review_result, now)?;
+
+    // 答错后追加同知识点巩固题；复习记录已写入，追加失败不影响本次结果
+    if is_wrong {
+        let queued = crate::server::subject::load_settings(conn).and_then(|settings| {
+            RecommendationSystem::new(conn).queue_remedial_follow_ups(question_id, now, &settings)
+        });
+        if let Err(e) = queued {
+            println!("failed to queue remedial follow-ups: {}", e);
+        }
+    }
+
     Ok(question_to_data(question))
 }
 
 /// 获取推荐的复习题目
+///
+/// `order`：出题顺序（"score" / "subject_round_robin" / "shuffle" / "hardest_first" /
+/// "easiest_first"），不传则用设置中的默认值；`seed` 仅对 "shuffle" 有效，缺省取当天逻辑日。
+/// `tags`：标签范围，非空时只推荐同时带有这些标签的题目。
 #[tauri::command]
 pub fn recommend_questions_comm(
     state: tauri::State<AppState>,
     limit: Option<usize>,
     subject: Option<String>,
+    tags: Option<Vec<String>>,
+    order: Option<String>,
+    seed: Option<u64>,
 ) -> Result<RecommendResultData, String> {
     let guard = state.inner.lock().unwrap();
     let conn = match &*guard {
@@ -71,8 +94,17 @@ pub fn recommend_questions_comm(
         None => return Err("App not initialized".to_string()),
     };
 
+    let order = match order {
+        Some(o) => o.parse::<ReviewOrder>()?,
+        None => crate::server::subject::load_settings(conn).map_err(|e| e.to_string())?.review_order,
+    };
+    let seed = seed.unwrap_or_else(|| {
+        LogicalDay::from_timestamp(crate::util::time::now_ts(), &ClockConfig::default()).0 as u64
+    });
+
     let manager = ReviewManager::new(conn);
-    let result = manager.recommend(limit, subject.as_deref())?;
+    let result = manager.recommend(limit, subject.as_deref(), &tags.unwrap_or_default())?;
+    let result = manager.arrange(result, order, seed)?;
 
     let questions: Vec<QuestionData> = result.questions.into_iter().map(question_to_data).collect();
     let reasons: Vec<String> = result.reasons.into_iter().map(|r| format!("{:?}", r)).collect();
@@ -84,6 +116,22 @@ pub fn recommend_questions_comm(
     })
 }
 
+/// 跳过题目：只记录跳过，不改题目状态、不计入错误率
+#[tauri::command]
+pub fn skip_question_comm(
+    state: tauri::State<AppState>,
+    question_id: i64,
+) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+
+    let manager = ReviewManager::new(conn);
+    manager.skip(QuestionId::from(question_id), crate::util::time::now_ts())
+}
+
 /// 暂停题目
 #[tauri::command]
 pub fn suspend_question_comm(
@@ -120,6 +168,24 @@ pub fn recover_question_comm(
     Ok(question_to_data(question))
 }
 
+/// 重置学习进度（题目 / 知识点 / 科目），返回重置的题目数；
+/// `archive_reviews` 为 true 时归档原有复习记录，否则删除
+#[tauri::command]
+pub fn forget_progress_comm(
+    state: tauri::State<AppState>,
+    scope: ForgetScope,
+    archive_reviews: bool,
+) -> Result<usize, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+
+    forget::forget(conn, &scope, archive_reviews, crate::util::time::now_ts())
+        .map_err(|e| format!("failed to reset progress: {}", e))
+}
+
 /// 获取所有科目列表
 #[tauri::command]
 pub fn list_subjects_comm(
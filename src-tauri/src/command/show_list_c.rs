@@ -4,6 +4,7 @@ use tauri::State;
 use crate::app::appstate::AppState;
 use crate::domain::View;
 use crate::server::show_question_view;
+use crate::server::user_field::UserFieldFilter;
 use crate::util::time::{ClockConfig, LogicalDay};
 
 #[derive(Serialize)]
@@ -13,6 +14,7 @@ pub struct ActiveQuestion {
     pub title: String,
     pub status: String,
     pub knowledge_points: Vec<String>,
+    pub tags: Vec<String>,
     pub created_at: String,
     pub last_review: String,
     /// 累计答错次数（用于"高频错题"过滤/标记）。
@@ -31,6 +33,7 @@ impl ActiveQuestion {
                 title: v.name.unwrap_or_default(),
                 status: v.state.as_str().to_string(),
                 knowledge_points: v.knowledge_points,
+                tags: v.tags,
                 created_at: LogicalDay::from_timestamp(v.created_at, &cfg).to_string(&cfg),
                 last_review: if v.last_reviewed_at.0 == 0 {
                     String::new()
@@ -51,6 +54,7 @@ pub struct DeleteQuestion {
     pub title: String,
     pub status: String,
     pub knowledge_points: Vec<String>,
+    pub tags: Vec<String>,
     pub deleted_at: String,
 }
 impl DeleteQuestion {
@@ -64,6 +68,7 @@ impl DeleteQuestion {
                 title: v.name.unwrap_or_default(),
                 status: v.state.as_str().to_string(),
                 knowledge_points: v.knowledge_points,
+                tags: v.tags,
                 deleted_at: v
                     .deleted_at
                     .map(|ts| LogicalDay::from_timestamp(ts, &cfg).to_string(&cfg))
@@ -95,12 +100,16 @@ pub fn show_subjects(state: tauri::State<AppState>) -> Result<Vec<String>, Strin
     show_question_view::list_subjects(conn).map_err(|e| e.to_string())
 }
 
-/// 分类查询：按科目 / 状态分页过滤；`page` 0-indexed。
+/// 分类查询：按科目 / 状态分页过滤，可叠加知识点（含后代节点）、标签与用户自定义字段过滤；`page` 0-indexed。
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn classify_questions(
     state: tauri::State<AppState>,
     subject: Option<String>,
     question_state: Option<String>,
+    knowledge_point: Option<String>,
+    tags: Option<Vec<String>>,
+    fields: Option<Vec<UserFieldFilter>>,
     page: usize,
     page_size: usize,
 ) -> Result<Vec<ActiveQuestion>, String> {
@@ -113,6 +122,9 @@ pub fn classify_questions(
         conn,
         subject,
         question_state,
+        knowledge_point,
+        &tags.unwrap_or_default(),
+        &fields.unwrap_or_default(),
         page,
         page_size,
     )
@@ -120,13 +132,14 @@ pub fn classify_questions(
     Ok(ActiveQuestion::new(views))
 }
 
-/// 搜索查询：单字符串输入，后端按"纯数字 → ID 精确"或"模糊"分发；可叠加 subject/state。
+/// 搜索查询：单字符串输入，后端按"纯数字 → ID 精确"或"模糊"分发；可叠加 subject/state/tags。
 #[tauri::command]
 pub fn search_questions(
     state: tauri::State<AppState>,
     query: String,
     subject: Option<String>,
     question_state: Option<String>,
+    tags: Option<Vec<String>>,
     page: usize,
     page_size: usize,
 ) -> Result<Vec<ActiveQuestion>, String> {
@@ -140,6 +153,7 @@ pub fn search_questions(
         query,
         subject,
         question_state,
+        &tags.unwrap_or_default(),
         page,
         page_size,
     )
@@ -0,0 +1,114 @@
+//! 科目管理命令层
+
+use serde::Serialize;
+
+use crate::app::AppState;
+use crate::domain::subject::{Subject, SubjectConfig};
+use crate::server::subject;
+
+/// 科目
+#[derive(Serialize)]
+pub struct SubjectData {
+    pub id: i64,
+    pub name: String,
+    pub color: Option<String>,
+    pub sort_order: i64,
+    pub config: SubjectConfig,
+}
+
+impl From<Subject> for SubjectData {
+    fn from(s: Subject) -> Self {
+        Self {
+            id: s.id,
+            name: s.name,
+            color: s.color,
+            sort_order: s.sort_order,
+            config: s.config,
+        }
+    }
+}
+
+/// 全部科目（含配置），按显示顺序
+#[tauri::command]
+pub fn get_subjects_comm(state: tauri::State<AppState>) -> Result<Vec<SubjectData>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    subject::list_subjects(conn)
+        .map(|subjects| subjects.into_iter().map(SubjectData::from).collect())
+        .map_err(|e| format!("failed to list subjects: {}", e))
+}
+
+/// 新建科目
+#[tauri::command]
+pub fn create_subject_comm(state: tauri::State<AppState>, name: String) -> Result<SubjectData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    subject::create_subject(conn, &name, crate::util::time::now_ts())
+        .map(SubjectData::from)
+        .map_err(|e| format!("failed to create subject: {}", e))
+}
+
+/// 修改科目颜色与配置
+#[tauri::command]
+pub fn update_subject_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+    color: Option<String>,
+    config: SubjectConfig,
+) -> Result<SubjectData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    subject::update_subject(conn, id, color, config)
+        .map(SubjectData::from)
+        .map_err(|e| format!("failed to update subject: {}", e))
+}
+
+/// 科目改名（级联更新题目、推荐缓存与顺延名额）
+#[tauri::command]
+pub fn rename_subject_comm(state: tauri::State<AppState>, id: i64, name: String) -> Result<SubjectData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    subject::rename_subject(conn, id, &name)
+        .map(SubjectData::from)
+        .map_err(|e| format!("failed to rename subject: {}", e))
+}
+
+/// 把科目 `from_id` 合并进 `into_id`，返回合并后的目标科目
+#[tauri::command]
+pub fn merge_subjects_comm(
+    state: tauri::State<AppState>,
+    from_id: i64,
+    into_id: i64,
+) -> Result<SubjectData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    subject::merge_subjects(conn, from_id, into_id)
+        .map(SubjectData::from)
+        .map_err(|e| format!("failed to merge subjects: {}", e))
+}
+
+/// 按给定 id 顺序重排科目
+#[tauri::command]
+pub fn reorder_subjects_comm(state: tauri::State<AppState>, ids: Vec<i64>) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    subject::reorder_subjects(conn, &ids).map_err(|e| format!("failed to reorder subjects: {}", e))
+}
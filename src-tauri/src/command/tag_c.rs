@@ -0,0 +1,45 @@
+//! 题目标签命令层
+
+use crate::app::AppState;
+use crate::server::tag::{self, TagCount};
+
+/// 全部标签及题目数，按名称排序
+#[tauri::command]
+pub fn list_tags_comm(state: tauri::State<AppState>) -> Result<Vec<TagCount>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    tag::list_tags(conn).map_err(|e| format!("failed to list tags: {}", e))
+}
+
+/// 批量打标签；返回新增的（题目, 标签）对数
+#[tauri::command]
+pub fn tag_questions_comm(
+    state: tauri::State<AppState>,
+    question_ids: Vec<i64>,
+    tags: Vec<String>,
+) -> Result<usize, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    tag::tag_questions(conn, &question_ids, &tags).map_err(|e| format!("failed to tag questions: {}", e))
+}
+
+/// 批量去标签；返回去掉的（题目, 标签）对数
+#[tauri::command]
+pub fn untag_questions_comm(
+    state: tauri::State<AppState>,
+    question_ids: Vec<i64>,
+    tags: Vec<String>,
+) -> Result<usize, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    tag::untag_questions(conn, &question_ids, &tags).map_err(|e| format!("failed to untag questions: {}", e))
+}
@@ -0,0 +1,140 @@
+//! 用户自定义字段命令层
+
+use serde::Serialize;
+
+use crate::app::AppState;
+use crate::domain::enums::{Cardinality, UserFieldType};
+use crate::domain::ids::QuestionId;
+use crate::domain::user_field::UserField;
+use crate::server::user_field::{self, UserFieldValues};
+
+/// 字段定义
+#[derive(Serialize)]
+pub struct UserFieldData {
+    pub name: String,
+    /// TEXT / NUMBER / DATE / ENUM / BOOL
+    pub field_type: String,
+    pub allowed_values: Vec<String>,
+    /// SINGLE / MULTIPLE
+    pub cardinality: String,
+}
+
+impl From<UserField> for UserFieldData {
+    fn from(f: UserField) -> Self {
+        Self {
+            name: f.name,
+            field_type: f.field_type.as_str().to_string(),
+            allowed_values: f.allowed_values,
+            cardinality: f.cardinality.as_str().to_string(),
+        }
+    }
+}
+
+fn parse_definition(field_type: &str, cardinality: &str) -> Result<(UserFieldType, Cardinality), String> {
+    let field_type = UserFieldType::from_str(field_type).ok_or_else(|| format!("unknown field type: {}", field_type))?;
+    let cardinality = Cardinality::from_str(cardinality).ok_or_else(|| format!("unknown cardinality: {}", cardinality))?;
+    Ok((field_type, cardinality))
+}
+
+/// 新建字段
+#[tauri::command]
+pub fn define_user_field_comm(
+    state: tauri::State<AppState>,
+    name: String,
+    field_type: String,
+    allowed_values: Option<Vec<String>>,
+    cardinality: String,
+) -> Result<UserFieldData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let (field_type, cardinality) = parse_definition(&field_type, &cardinality)?;
+    user_field::define_user_field(
+        conn,
+        &name,
+        field_type,
+        allowed_values.unwrap_or_default(),
+        cardinality,
+        crate::util::time::now_ts(),
+    )
+    .map(UserFieldData::from)
+    .map_err(|e| format!("failed to define field: {}", e))
+}
+
+/// 修改字段类型、可选值与基数；现有值不符合新定义时报错
+#[tauri::command]
+pub fn update_user_field_comm(
+    state: tauri::State<AppState>,
+    name: String,
+    field_type: String,
+    allowed_values: Option<Vec<String>>,
+    cardinality: String,
+) -> Result<UserFieldData, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    let (field_type, cardinality) = parse_definition(&field_type, &cardinality)?;
+    user_field::update_user_field(conn, &name, field_type, allowed_values.unwrap_or_default(), cardinality)
+        .map(UserFieldData::from)
+        .map_err(|e| format!("failed to update field: {}", e))
+}
+
+/// 删除字段及其全部值
+#[tauri::command]
+pub fn delete_user_field_comm(state: tauri::State<AppState>, name: String) -> Result<(), String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    user_field::delete_user_field(conn, &name).map_err(|e| format!("failed to delete field: {}", e))
+}
+
+/// 全部字段定义
+#[tauri::command]
+pub fn list_user_fields_comm(state: tauri::State<AppState>) -> Result<Vec<UserFieldData>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    user_field::list_user_fields(conn)
+        .map(|fields| fields.into_iter().map(UserFieldData::from).collect())
+        .map_err(|e| format!("failed to list fields: {}", e))
+}
+
+/// 设置某题某字段的值（整体替换，空列表清空），返回规范化后的值
+#[tauri::command]
+pub fn set_question_field_values_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+    name: String,
+    values: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    user_field::set_user_field_values(conn, QuestionId::from(id), &name, values)
+        .map_err(|e| format!("failed to set field values: {}", e))
+}
+
+/// 某题全部字段的值
+#[tauri::command]
+pub fn get_question_field_values_comm(
+    state: tauri::State<AppState>,
+    id: i64,
+) -> Result<Vec<UserFieldValues>, String> {
+    let guard = state.inner.lock().unwrap();
+    let conn = match &*guard {
+        Some(inner) => &inner.db,
+        None => return Err("App not initialized".to_string()),
+    };
+    user_field::get_user_field_values(conn, QuestionId::from(id))
+        .map_err(|e| format!("failed to load field values: {}", e))
+}
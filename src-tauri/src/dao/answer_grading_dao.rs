@@ -0,0 +1,99 @@
+//! 简答题判分数据访问层
+
+use crate::db::error::DbError;
+use crate::domain::answer_grading::{AnswerGrading, GradingStats};
+use crate::domain::enums::ReviewResult;
+use crate::util::time::Timestamp;
+use rusqlite::{Connection, OptionalExtension};
+
+const GRADING_COLUMNS: &str =
+    "id, question_id, response, expected, suggested, final_result, created_at, decided_at";
+
+/// DAO for answer_grading table
+pub struct AnswerGradingDao<'a> {
+    conn: &'a Connection,
+}
+
+fn result_of(value: String, column: usize) -> rusqlite::Result<ReviewResult> {
+    ReviewResult::from_str(&value).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(
+            column,
+            rusqlite::types::Type::Text,
+            format!("invalid review result: {}", value).into(),
+        )
+    })
+}
+
+fn grading_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<AnswerGrading> {
+    Ok(AnswerGrading {
+        id: row.get(0)?,
+        question_id: row.get(1)?,
+        response: row.get(2)?,
+        expected: row.get(3)?,
+        suggested: result_of(row.get(4)?, 4)?,
+        final_result: row.get::<_, Option<String>>(5)?.map(|v| result_of(v, 5)).transpose()?,
+        created_at: Timestamp::from(row.get::<_, i64>(6)?),
+        decided_at: row.get::<_, Option<i64>>(7)?.map(Timestamp::from),
+    })
+}
+
+impl<'a> AnswerGradingDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 记下一次判分建议，返回新记录 id
+    pub fn insert(
+        &self,
+        question_id: i64,
+        response: &str,
+        expected: &str,
+        suggested: &ReviewResult,
+        created_at: Timestamp,
+    ) -> Result<i64, DbError> {
+        self.conn.execute(
+            "INSERT INTO answer_grading (question_id, response, expected, suggested, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![question_id, response, expected, suggested.as_str(), created_at.as_i64()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 按 id 取判分记录
+    pub fn get(&self, id: i64) -> Result<Option<AnswerGrading>, DbError> {
+        let sql = format!("SELECT {} FROM answer_grading WHERE id = ?1", GRADING_COLUMNS);
+        self.conn.query_row(&sql, [id], grading_from_row).optional().map_err(Into::into)
+    }
+
+    /// 写入用户最终给出的结果
+    pub fn decide(&self, id: i64, result: &ReviewResult, decided_at: Timestamp) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE answer_grading SET final_result = ?2, decided_at = ?3 WHERE id = ?1",
+            rusqlite::params![id, result.as_str(), decided_at.as_i64()],
+        )?;
+        Ok(())
+    }
+
+    /// 已评分记录中建议与最终结果的对照
+    pub fn stats(&self) -> Result<GradingStats, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT suggested, final_result, COUNT(*) FROM answer_grading
+             WHERE final_result IS NOT NULL
+             GROUP BY suggested, final_result
+             ORDER BY suggested, final_result",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((result_of(row.get(0)?, 0)?, result_of(row.get(1)?, 1)?, row.get(2)?)))?;
+        let pairs = rows.collect::<Result<Vec<(ReviewResult, ReviewResult, i64)>, _>>()?;
+        Ok(GradingStats {
+            decided_count: pairs.iter().map(|(_, _, n)| n).sum(),
+            agreed_count: pairs.iter().filter(|(s, f, _)| s == f).map(|(_, _, n)| n).sum(),
+            pairs,
+        })
+    }
+
+    /// 删除题目的全部判分记录（彻底删除题目时）
+    pub fn delete_by_question(&self, question_id: i64) -> Result<(), DbError> {
+        self.conn.execute("DELETE FROM answer_grading WHERE question_id = ?1", [question_id])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+//! 科目顺延名额数据访问层
+
+use std::collections::HashMap;
+
+use crate::db::error::DbError;
+use rusqlite::Connection;
+
+/// DAO for subject_carry_over table
+pub struct CarryOverDao<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> CarryOverDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 全部科目的顺延名额：科目 → 题数
+    pub fn list(&self) -> Result<HashMap<String, i64>, DbError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT subject, amount FROM subject_carry_over")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<HashMap<_, _>, _>>().map_err(Into::into)
+    }
+
+    /// 设置某科的顺延名额（`day` 为结算的逻辑日）
+    pub fn set(&self, subject: &str, amount: i64, day: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO subject_carry_over (subject, amount, updated_day) VALUES (?1, ?2, ?3)",
+            rusqlite::params![subject, amount, day],
+        )?;
+        Ok(())
+    }
+
+    /// 清除某科的顺延名额
+    pub fn delete(&self, subject: &str) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM subject_carry_over WHERE subject = ?1",
+            [subject],
+        )?;
+        Ok(())
+    }
+}
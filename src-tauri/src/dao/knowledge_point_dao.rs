@@ -0,0 +1,192 @@
+//! 知识点树数据访问层
+//!
+//! 节点存放在 `knowledge_point` 表；题目与知识点的关联仍在 `meta` 表（key 为 `system.KnowledgePoint`）。
+
+use crate::db::error::DbError;
+use crate::domain::knowledge_point::KnowledgePoint;
+use crate::util::time::Timestamp;
+use rusqlite::{Connection, OptionalExtension};
+
+const KP_META_KEY: &str = "system.KnowledgePoint";
+
+/// DAO for knowledge_point table
+pub struct KnowledgePointDao<'a> {
+    conn: &'a Connection,
+}
+
+fn node_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<KnowledgePoint> {
+    Ok(KnowledgePoint {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        subject_id: row.get(2)?,
+        parent_id: row.get(3)?,
+        sort_order: row.get(4)?,
+        created_at: Timestamp::from(row.get::<_, i64>(5)?),
+    })
+}
+
+impl<'a> KnowledgePointDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 全部节点，按显示顺序
+    pub fn list(&self) -> Result<Vec<KnowledgePoint>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, subject_id, parent_id, sort_order, created_at
+             FROM knowledge_point ORDER BY sort_order, id",
+        )?;
+        let rows = stmt.query_map([], node_from_row)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// 按 id 取节点
+    pub fn get(&self, id: i64) -> Result<Option<KnowledgePoint>, DbError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT id, name, subject_id, parent_id, sort_order, created_at
+                 FROM knowledge_point WHERE id = ?1",
+                [id],
+                node_from_row,
+            )
+            .optional()?)
+    }
+
+    /// 按名称取节点
+    pub fn get_by_name(&self, name: &str) -> Result<Option<KnowledgePoint>, DbError> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT id, name, subject_id, parent_id, sort_order, created_at
+                 FROM knowledge_point WHERE name = ?1",
+                [name],
+                node_from_row,
+            )
+            .optional()?)
+    }
+
+    /// 新增节点，排在最后；返回新节点 id
+    pub fn insert(
+        &self,
+        name: &str,
+        subject_id: Option<i64>,
+        parent_id: Option<i64>,
+        now: Timestamp,
+    ) -> Result<i64, DbError> {
+        self.conn.execute(
+            "INSERT INTO knowledge_point (name, subject_id, parent_id, sort_order, created_at)
+             VALUES (?1, ?2, ?3, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM knowledge_point), ?4)",
+            rusqlite::params![name, subject_id, parent_id, now.as_i64()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 只改节点名称；题目引用由调用方通过 `rename_references` 一并更新
+    pub fn rename(&self, id: i64, name: &str) -> Result<(), DbError> {
+        self.conn
+            .execute("UPDATE knowledge_point SET name = ?2 WHERE id = ?1", rusqlite::params![id, name])?;
+        Ok(())
+    }
+
+    /// 设置父节点（None 为根节点）
+    pub fn set_parent(&self, id: i64, parent_id: Option<i64>) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE knowledge_point SET parent_id = ?2 WHERE id = ?1",
+            rusqlite::params![id, parent_id],
+        )?;
+        Ok(())
+    }
+
+    /// 把 `from` 的子节点全部挂到 `to` 下
+    pub fn reparent_children(&self, from: i64, to: Option<i64>) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE knowledge_point SET parent_id = ?2 WHERE parent_id = ?1",
+            rusqlite::params![from, to],
+        )?;
+        Ok(())
+    }
+
+    /// 科目合并时把源科目的知识点归入目标科目
+    pub fn move_subject(&self, from_subject: i64, into_subject: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE knowledge_point SET subject_id = ?2 WHERE subject_id = ?1",
+            rusqlite::params![from_subject, into_subject],
+        )?;
+        Ok(())
+    }
+
+    /// 删除节点记录
+    pub fn delete(&self, id: i64) -> Result<(), DbError> {
+        self.conn.execute("DELETE FROM knowledge_point WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// 题目与前置关系中的知识点名称 `old` -> `new`。
+    /// 题目已同时带有 `new` 时只删掉 `old`；前置关系中因此重复或自环的边一并删除。
+    pub fn rename_references(&self, old: &str, new: &str) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM meta WHERE key = ?1 AND value = ?2
+               AND question_id IN (SELECT question_id FROM meta WHERE key = ?1 AND value = ?3)",
+            rusqlite::params![KP_META_KEY, old, new],
+        )?;
+        self.conn.execute(
+            "UPDATE meta SET value = ?3 WHERE key = ?1 AND value = ?2",
+            rusqlite::params![KP_META_KEY, old, new],
+        )?;
+        self.conn.execute(
+            "UPDATE OR IGNORE knowledge_point_prerequisite SET knowledge_point = ?2 WHERE knowledge_point = ?1",
+            [old, new],
+        )?;
+        self.conn.execute(
+            "UPDATE OR IGNORE knowledge_point_prerequisite SET prerequisite = ?2 WHERE prerequisite = ?1",
+            [old, new],
+        )?;
+        self.delete_references_in_graph(old)
+    }
+
+    /// 删除所有题目上的该知识点及其前置关系
+    pub fn delete_references(&self, name: &str) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM meta WHERE key = ?1 AND value = ?2",
+            rusqlite::params![KP_META_KEY, name],
+        )?;
+        self.delete_references_in_graph(name)
+    }
+
+    fn delete_references_in_graph(&self, name: &str) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM knowledge_point_prerequisite WHERE knowledge_point = ?1 OR prerequisite = ?1",
+            [name],
+        )?;
+        Ok(())
+    }
+
+    /// 带有任一给定知识点的未删除题目的统计：(题目数, 复习次数, 答错次数)。
+    /// 一道题同时带有多个给定知识点时只算一次。
+    pub fn question_stats(&self, names: &[String]) -> Result<(i64, i64, i64), DbError> {
+        if names.is_empty() {
+            return Ok((0, 0, 0));
+        }
+        let placeholders = vec!["?"; names.len()].join(",");
+        let sql = format!(
+            "WITH qs AS (
+                 SELECT DISTINCT m.question_id AS id
+                 FROM meta m JOIN question q ON q.id = m.question_id AND q.deleted_at IS NULL
+                 WHERE m.key = '{}' AND m.value IN ({})
+             )
+             SELECT
+                 (SELECT COUNT(*) FROM qs),
+                 COUNT(r.id),
+                 COALESCE(SUM(CASE WHEN LOWER(r.result) != 'correct' THEN 1 ELSE 0 END), 0)
+             FROM review r WHERE r.question_id IN (SELECT id FROM qs)",
+            KP_META_KEY, placeholders
+        );
+        let row = self
+            .conn
+            .query_row(&sql, rusqlite::params_from_iter(names), |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?;
+        Ok(row)
+    }
+}
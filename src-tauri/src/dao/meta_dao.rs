@@ -1,7 +1,7 @@
 use crate::db::connection::Connection;
 use crate::db::error::DbError;
 use crate::domain::{
-    enums::MetaKey,
+    enums::{Cardinality, MetaKey},
     ids::{MetaId, QuestionId},
     meta::Meta,
 };
@@ -33,11 +33,39 @@ impl<'a> MetaDao<'a> {
     }
 
     /// 插入元信息记录，返回新记录的自增 ID。
+    /// 按 key 的基数校验：单值 key 已有值、或同一值重复写入时返回 `DbError::Constraint`，
+    /// 需要替换时先调用 `delete_by_question_and_key`。
     pub fn insert(&self, qid: QuestionId, key: MetaKey, value: &str) -> Result<MetaId, DbError> {
-        let id = crate::db::insert_meta(self.conn, i64::from(qid), &String::from(key), value)?;
+        let qid_i64 = i64::from(qid);
+        let key_str = key.as_str();
+        let existing = crate::db::select_meta_values_by_question_key(self.conn, qid_i64, &key_str)?;
+        if existing.iter().any(|v| v == value) {
+            return Err(DbError::Constraint(format!(
+                "question {} already has {} = '{}'",
+                qid_i64, key_str, value
+            )));
+        }
+        if !existing.is_empty() && self.cardinality_of(&key)? == Cardinality::SINGLE {
+            return Err(DbError::Constraint(format!(
+                "{} is single-valued and question {} already has a value",
+                key_str, qid_i64
+            )));
+        }
+        let id = crate::db::insert_meta(self.conn, qid_i64, &key_str, value)?;
         Ok(MetaId::from(id))
     }
 
+    /// key 的基数；用户自定义 key 查 `user_field` 表，未登记的按多值处理。
+    fn cardinality_of(&self, key: &MetaKey) -> Result<Cardinality, DbError> {
+        let declared = match key {
+            MetaKey::User(name) => crate::dao::UserFieldDao::new(self.conn)
+                .get(name)?
+                .map(|f| f.cardinality),
+            _ => key.cardinality(),
+        };
+        Ok(declared.unwrap_or(Cardinality::MULTIPLE))
+    }
+
     /// 按 ID 删除元信息（物理删除）。
     pub fn delete(&self, id: MetaId) -> Result<(), DbError> {
         let id_i64: i64 = i64::from(id);
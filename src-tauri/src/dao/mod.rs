@@ -1,13 +1,43 @@
+pub mod answer_grading_dao;
 pub mod asset_dao;
+pub mod carry_over_dao;
+pub mod knowledge_point_dao;
 pub mod meta_dao;
+pub mod occlusion_dao;
+pub mod override_dao;
+pub mod paper_dao;
+pub mod prerequisite_dao;
 pub mod question_dao;
+pub mod question_link_dao;
+pub mod question_part_dao;
+pub mod question_revision_dao;
+pub mod question_text_dao;
 pub mod recommendation_dao;
 pub mod review_dao;
+pub mod skip_dao;
+pub mod subject_dao;
+pub mod tag_dao;
+pub mod user_field_dao;
 pub mod view_dao;
 
+pub use answer_grading_dao::*;
 pub use asset_dao::*;
+pub use carry_over_dao::*;
+pub use knowledge_point_dao::*;
 pub use meta_dao::*;
+pub use occlusion_dao::*;
+pub use override_dao::*;
+pub use paper_dao::*;
+pub use prerequisite_dao::*;
 pub use question_dao::*;
+pub use question_link_dao::*;
+pub use question_part_dao::*;
+pub use question_revision_dao::*;
+pub use question_text_dao::*;
 pub use recommendation_dao::*;
 pub use review_dao::*;
+pub use skip_dao::*;
+pub use subject_dao::*;
+pub use tag_dao::*;
+pub use user_field_dao::*;
 pub use view_dao::*;
@@ -0,0 +1,133 @@
+//! 图片遮罩数据访问层
+//!
+//! `occlusion_mask` 的每一行是某张卡片在某张图片上的一个遮罩版本，当前版本的 `superseded_at` 为空。
+
+use crate::db::error::DbError;
+use crate::domain::occlusion::{MaskRect, OcclusionMask};
+use crate::util::time::Timestamp;
+use rusqlite::{Connection, OptionalExtension};
+
+const MASK_COLUMNS: &str =
+    "m.id, m.card_id, m.asset_id, m.version, m.x, m.y, m.width, m.height, m.created_at, m.superseded_at";
+
+/// DAO for occlusion_mask table
+pub struct OcclusionDao<'a> {
+    conn: &'a Connection,
+}
+
+fn mask_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<OcclusionMask> {
+    Ok(OcclusionMask {
+        id: row.get(0)?,
+        card_id: row.get(1)?,
+        asset_id: row.get(2)?,
+        version: row.get(3)?,
+        rect: MaskRect { x: row.get(4)?, y: row.get(5)?, width: row.get(6)?, height: row.get(7)? },
+        created_at: Timestamp::from(row.get::<_, i64>(8)?),
+        superseded_at: row.get::<_, Option<i64>>(9)?.map(Timestamp::from),
+    })
+}
+
+impl<'a> OcclusionDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    fn query(&self, filter: &str, param: i64) -> Result<Vec<OcclusionMask>, DbError> {
+        let sql = format!("SELECT {} FROM occlusion_mask m {}", MASK_COLUMNS, filter);
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map([param], mask_from_row)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// 新增一个遮罩版本，返回新行 id
+    pub fn insert(
+        &self,
+        card_id: i64,
+        asset_id: i64,
+        version: i64,
+        rect: MaskRect,
+        created_at: Timestamp,
+    ) -> Result<i64, DbError> {
+        self.conn.execute(
+            "INSERT INTO occlusion_mask (card_id, asset_id, version, x, y, width, height, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            rusqlite::params![card_id, asset_id, version, rect.x, rect.y, rect.width, rect.height, created_at.as_i64()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 卡片当前的遮罩；不是遮罩卡片或遮罩已移除时为 None
+    pub fn current_by_card(&self, card_id: i64) -> Result<Option<OcclusionMask>, DbError> {
+        let sql = format!(
+            "SELECT {} FROM occlusion_mask m WHERE m.card_id = ?1 AND m.superseded_at IS NULL",
+            MASK_COLUMNS
+        );
+        self.conn.query_row(&sql, [card_id], mask_from_row).optional().map_err(Into::into)
+    }
+
+    /// 卡片的全部遮罩版本，旧的在前
+    pub fn list_by_card(&self, card_id: i64) -> Result<Vec<OcclusionMask>, DbError> {
+        self.query("WHERE m.card_id = ?1 ORDER BY m.version, m.id", card_id)
+    }
+
+    /// 图片上当前的遮罩，按创建顺序
+    pub fn list_current_by_asset(&self, asset_id: i64) -> Result<Vec<OcclusionMask>, DbError> {
+        self.query("WHERE m.asset_id = ?1 AND m.superseded_at IS NULL ORDER BY m.id", asset_id)
+    }
+
+    /// 画在题目 `note_id` 的图片上的全部遮罩版本（含已取代的）
+    pub fn list_by_note(&self, note_id: i64) -> Result<Vec<OcclusionMask>, DbError> {
+        self.query(
+            "JOIN asset a ON a.id = m.asset_id WHERE a.question_id = ?1 ORDER BY m.card_id, m.version",
+            note_id,
+        )
+    }
+
+    /// 修改当前版本的矩形（同一张图上调整位置，不升版本）
+    pub fn update_rect(&self, id: i64, rect: MaskRect) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE occlusion_mask SET x = ?2, y = ?3, width = ?4, height = ?5 WHERE id = ?1",
+            rusqlite::params![id, rect.x, rect.y, rect.width, rect.height],
+        )?;
+        Ok(())
+    }
+
+    /// 把遮罩版本标记为已取代
+    pub fn supersede(&self, id: i64, at: Timestamp) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE occlusion_mask SET superseded_at = ?2 WHERE id = ?1",
+            rusqlite::params![id, at.as_i64()],
+        )?;
+        Ok(())
+    }
+
+    /// 图片的版本（换过几次图）；没有换过图时为 None
+    pub fn image_version(&self, asset_id: i64) -> Result<Option<i64>, DbError> {
+        self.conn
+            .query_row("SELECT version FROM occlusion_image WHERE asset_id = ?1", [asset_id], |row| row.get(0))
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// 记下图片的版本
+    pub fn set_image_version(&self, asset_id: i64, version: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT INTO occlusion_image (asset_id, version) VALUES (?1, ?2)
+             ON CONFLICT(asset_id) DO UPDATE SET version = excluded.version",
+            [asset_id, version],
+        )?;
+        Ok(())
+    }
+
+    /// 删除图片的版本记录（彻底删除图片时）
+    pub fn delete_image_version(&self, asset_id: i64) -> Result<(), DbError> {
+        self.conn.execute("DELETE FROM occlusion_image WHERE asset_id = ?1", [asset_id])?;
+        Ok(())
+    }
+
+    /// 删除卡片的全部遮罩版本（彻底删除卡片时）
+    pub fn delete_by_card(&self, card_id: i64) -> Result<(), DbError> {
+        self.conn.execute("DELETE FROM occlusion_mask WHERE card_id = ?1", [card_id])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+//! 推荐手动干预数据访问层
+
+use std::collections::HashMap;
+
+use crate::db::error::DbError;
+use crate::domain::enums::OverrideAction;
+use rusqlite::Connection;
+
+/// DAO for recommendation_override table
+pub struct OverrideDao<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> OverrideDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 设置某题在某逻辑日的干预（同一天同一题只保留最后一次操作）
+    pub fn set(
+        &self,
+        day: i64,
+        question_id: i64,
+        action: OverrideAction,
+        now: i64,
+    ) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO recommendation_override (day, question_id, action, created_at) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![day, question_id, action.as_str(), now],
+        )?;
+        Ok(())
+    }
+
+    /// 取消某题在某逻辑日的干预
+    pub fn delete(&self, day: i64, question_id: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM recommendation_override WHERE day = ?1 AND question_id = ?2",
+            rusqlite::params![day, question_id],
+        )?;
+        Ok(())
+    }
+
+    /// 获取某逻辑日的全部干预：question_id → 操作
+    pub fn list_by_day(&self, day: i64) -> Result<HashMap<i64, OverrideAction>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT question_id, action FROM recommendation_override WHERE day = ?1",
+        )?;
+        let rows = stmt.query_map([day], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut map = HashMap::new();
+        for row in rows {
+            let (qid, action) = row?;
+            if let Some(action) = OverrideAction::from_str(&action) {
+                map.insert(qid, action);
+            }
+        }
+        Ok(map)
+    }
+
+    /// 清理早于指定逻辑日的干预记录
+    pub fn cleanup_before(&self, day: i64) -> Result<usize, DbError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM recommendation_override WHERE day < ?1",
+            [day],
+        )?;
+        Ok(deleted)
+    }
+
+    /// 删除某题的全部干预记录（永久删除题目时调用）
+    pub fn delete_by_question(&self, question_id: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM recommendation_override WHERE question_id = ?1",
+            [question_id],
+        )?;
+        Ok(())
+    }
+}
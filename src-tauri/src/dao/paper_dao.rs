@@ -0,0 +1,167 @@
+//! 试卷数据访问层
+//!
+//! 试卷登记在 `paper` 表，读取经 `paper_view`（科目存 id，视图给出名称）；
+//! 题目通过 `extension.SourcePaper` 元信息引用试卷名称。
+
+use crate::db::error::DbError;
+use crate::domain::paper::{Paper, PaperStats};
+use crate::util::time::Timestamp;
+use rusqlite::{Connection, OptionalExtension};
+
+const PAPER_COLUMNS: &str = "id, name, exam_date, subject, subject_id, total_score, created_at";
+const PAPER_META_KEY: &str = "extension.SourcePaper";
+
+/// 试卷上未删除的题目（大题与独立题，不含小题）
+const PAPER_QUESTIONS: &str = "SELECT q.id FROM question q
+     JOIN meta m ON m.question_id = q.id AND m.key = ?1 AND m.value = ?2
+     WHERE q.deleted_at IS NULL";
+
+/// DAO for paper table
+pub struct PaperDao<'a> {
+    conn: &'a Connection,
+}
+
+fn paper_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Paper> {
+    Ok(Paper {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        exam_date: row.get(2)?,
+        subject: row.get(3)?,
+        subject_id: row.get(4)?,
+        total_score: row.get(5)?,
+        created_at: Timestamp::from(row.get::<_, i64>(6)?),
+    })
+}
+
+impl<'a> PaperDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 全部试卷，考试日期新的在前，没有日期的排在最后
+    pub fn list(&self) -> Result<Vec<Paper>, DbError> {
+        let sql = format!(
+            "SELECT {} FROM paper_view ORDER BY exam_date IS NULL, exam_date DESC, id DESC",
+            PAPER_COLUMNS
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map([], paper_from_row)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// 按 id 取试卷
+    pub fn get(&self, id: i64) -> Result<Option<Paper>, DbError> {
+        let sql = format!("SELECT {} FROM paper_view WHERE id = ?1", PAPER_COLUMNS);
+        self.conn.query_row(&sql, [id], paper_from_row).optional().map_err(Into::into)
+    }
+
+    /// 按名称取试卷
+    pub fn get_by_name(&self, name: &str) -> Result<Option<Paper>, DbError> {
+        let sql = format!("SELECT {} FROM paper_view WHERE name = ?1", PAPER_COLUMNS);
+        self.conn.query_row(&sql, [name], paper_from_row).optional().map_err(Into::into)
+    }
+
+    /// 新增试卷，返回新试卷 id；科目按 `subject_id` 写入
+    pub fn insert(&self, paper: &Paper) -> Result<i64, DbError> {
+        self.conn.execute(
+            "INSERT INTO paper (name, exam_date, subject, total_score, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                paper.name,
+                paper.exam_date,
+                paper.subject_id.map(|id| id.to_string()),
+                paper.total_score,
+                paper.created_at.as_i64()
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 更新名称、日期、科目与总分；名称的引用处由 `server::paper::update_paper` 一并更新
+    pub fn update(&self, paper: &Paper) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE paper SET name = ?2, exam_date = ?3, subject = ?4, total_score = ?5 WHERE id = ?1",
+            rusqlite::params![
+                paper.id,
+                paper.name,
+                paper.exam_date,
+                paper.subject_id.map(|id| id.to_string()),
+                paper.total_score
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 科目合并时把源科目的试卷归入目标科目
+    pub fn move_subject(&self, from_subject: i64, into_subject: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE paper SET subject = ?2 WHERE subject = ?1",
+            [from_subject.to_string(), into_subject.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// 删除试卷记录
+    pub fn delete(&self, id: i64) -> Result<(), DbError> {
+        self.conn.execute("DELETE FROM paper WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    /// 试卷上未删除的题目 ID，按 ID 排序
+    pub fn question_ids(&self, name: &str) -> Result<Vec<i64>, DbError> {
+        let sql = format!("{} ORDER BY q.id", PAPER_QUESTIONS);
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map([PAPER_META_KEY, name], |row| row.get(0))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// 题目状态分布与复习次数。大题的复习记录在小题上，一并计入。
+    pub fn stats(&self, name: &str) -> Result<PaperStats, DbError> {
+        let mut stats = self.conn.query_row(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(q.state = 'NEW'), 0),
+                    COALESCE(SUM(q.state = 'LEARNING'), 0),
+                    COALESCE(SUM(q.state = 'STABLE'), 0),
+                    COALESCE(SUM(q.state = 'SUSPENDED'), 0)
+             FROM question q
+             JOIN meta m ON m.question_id = q.id AND m.key = ?1 AND m.value = ?2
+             WHERE q.deleted_at IS NULL",
+            [PAPER_META_KEY, name],
+            |row| {
+                Ok(PaperStats {
+                    question_count: row.get(0)?,
+                    new_count: row.get(1)?,
+                    learning_count: row.get(2)?,
+                    stable_count: row.get(3)?,
+                    suspended_count: row.get(4)?,
+                    ..PaperStats::default()
+                })
+            },
+        )?;
+        let sql = format!(
+            "SELECT COUNT(*), COALESCE(SUM(CASE WHEN LOWER(r.result) = 'wrong' THEN 1 ELSE 0 END), 0)
+             FROM review r
+             WHERE r.question_id IN ({0})
+                OR r.question_id IN (SELECT p.question_id FROM question_part p WHERE p.parent_id IN ({0}))",
+            PAPER_QUESTIONS
+        );
+        (stats.review_count, stats.wrong_review_count) =
+            self.conn.query_row(&sql, [PAPER_META_KEY, name], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        Ok(stats)
+    }
+
+    /// 把引用 `old` 的题目改为引用 `new`
+    pub fn rename_references(&self, old: &str, new: &str) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE meta SET value = ?3 WHERE key = ?1 AND value = ?2",
+            [PAPER_META_KEY, old, new],
+        )?;
+        Ok(())
+    }
+
+    /// 去掉全部题目上对 `name` 的引用
+    pub fn delete_references(&self, name: &str) -> Result<(), DbError> {
+        self.conn
+            .execute("DELETE FROM meta WHERE key = ?1 AND value = ?2", [PAPER_META_KEY, name])?;
+        Ok(())
+    }
+}
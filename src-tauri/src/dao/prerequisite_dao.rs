@@ -0,0 +1,42 @@
+//! 知识点前置关系数据访问层
+
+use crate::db::error::DbError;
+use rusqlite::Connection;
+
+/// DAO for knowledge_point_prerequisite table
+pub struct PrerequisiteDao<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> PrerequisiteDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 添加一条前置关系（已存在则忽略）
+    pub fn insert(&self, knowledge_point: &str, prerequisite: &str, now: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO knowledge_point_prerequisite (knowledge_point, prerequisite, created_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![knowledge_point, prerequisite, now],
+        )?;
+        Ok(())
+    }
+
+    /// 删除一条前置关系，返回是否删除了记录
+    pub fn delete(&self, knowledge_point: &str, prerequisite: &str) -> Result<bool, DbError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM knowledge_point_prerequisite WHERE knowledge_point = ?1 AND prerequisite = ?2",
+            rusqlite::params![knowledge_point, prerequisite],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// 列出全部前置关系：(knowledge_point, prerequisite)
+    pub fn list_all(&self) -> Result<Vec<(String, String)>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT knowledge_point, prerequisite FROM knowledge_point_prerequisite ORDER BY knowledge_point, prerequisite",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+}
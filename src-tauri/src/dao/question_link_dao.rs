@@ -0,0 +1,114 @@
+//! 题目关联数据访问层
+//!
+//! 不分方向的关联存成 `from_question_id < to_question_id` 的一行，调用方负责规范化。
+
+use crate::db::error::DbError;
+use crate::domain::question_link::{LinkType, QuestionLink};
+use crate::util::time::Timestamp;
+use rusqlite::{Connection, OptionalExtension};
+
+/// DAO for question_link table
+pub struct QuestionLinkDao<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> QuestionLinkDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 添加一条关联，返回其 id；已存在时返回已有记录的 id
+    pub fn insert(&self, from: i64, to: i64, link_type: LinkType, now: Timestamp) -> Result<i64, DbError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO question_link (from_question_id, to_question_id, link_type, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![from, to, link_type.as_str(), now.as_i64()],
+        )?;
+        let id = self.conn.query_row(
+            "SELECT id FROM question_link WHERE from_question_id = ?1 AND to_question_id = ?2 AND link_type = ?3",
+            rusqlite::params![from, to, link_type.as_str()],
+            |row| row.get(0),
+        )?;
+        Ok(id)
+    }
+
+    /// 按 id 查询：(from, to, 类型)
+    pub fn get(&self, id: i64) -> Result<Option<(i64, i64, LinkType)>, DbError> {
+        let row: Option<(i64, i64, String)> = self
+            .conn
+            .query_row(
+                "SELECT from_question_id, to_question_id, link_type FROM question_link WHERE id = ?1",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        row.map(|(from, to, t)| Ok((from, to, parse_link_type(&t)?))).transpose()
+    }
+
+    /// 删除一条关联，返回是否删除了记录
+    pub fn delete(&self, id: i64) -> Result<bool, DbError> {
+        let deleted = self.conn.execute("DELETE FROM question_link WHERE id = ?1", [id])?;
+        Ok(deleted > 0)
+    }
+
+    /// 删除题目参与的全部关联（两端任一）
+    pub fn delete_by_question(&self, question_id: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM question_link WHERE from_question_id = ?1 OR to_question_id = ?1",
+            [question_id],
+        )?;
+        Ok(())
+    }
+
+    /// 从题目一侧看的全部关联，另一端在回收站中的不列出；按类型、创建顺序排列
+    pub fn list_by_question(&self, question_id: i64) -> Result<Vec<QuestionLink>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT l.id, l.link_type, q.id, q.name, l.from_question_id = ?1, l.created_at
+             FROM question_link l
+             JOIN question q ON q.id = CASE WHEN l.from_question_id = ?1 THEN l.to_question_id ELSE l.from_question_id END
+             WHERE (l.from_question_id = ?1 OR l.to_question_id = ?1) AND q.deleted_at IS NULL
+             ORDER BY l.link_type, l.id",
+        )?;
+        let rows = stmt.query_map([question_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, bool>(4)?,
+                row.get::<_, i64>(5)?,
+            ))
+        })?;
+        let mut links = Vec::new();
+        for row in rows {
+            let (id, link_type, other, name, outgoing, created_at) = row?;
+            links.push(QuestionLink {
+                id,
+                link_type: parse_link_type(&link_type)?,
+                question_id: other,
+                question_name: name,
+                outgoing,
+                created_at: Timestamp::from(created_at),
+            });
+        }
+        Ok(links)
+    }
+
+    /// 指定类型的全部关联 (from, to)，两端都未删除
+    pub fn list_pairs(&self, link_type: LinkType) -> Result<Vec<(i64, i64)>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT l.from_question_id, l.to_question_id
+             FROM question_link l
+             JOIN question a ON a.id = l.from_question_id AND a.deleted_at IS NULL
+             JOIN question b ON b.id = l.to_question_id AND b.deleted_at IS NULL
+             WHERE l.link_type = ?1
+             ORDER BY l.id",
+        )?;
+        let rows = stmt.query_map([link_type.as_str()], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+}
+
+fn parse_link_type(s: &str) -> Result<LinkType, DbError> {
+    LinkType::from_str(s).ok_or_else(|| DbError::Migration(format!("invalid link type: {}", s)))
+}
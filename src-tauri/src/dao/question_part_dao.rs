@@ -0,0 +1,140 @@
+//! 小题数据访问层
+//!
+//! `question_part` 只记录小题与大题的从属关系和顺序，小题本身是 `question` 表中的普通题目。
+//! 由题干填空生成的小题（填空卡）另在 `cloze_card` 记录填空编号。
+
+use std::collections::{HashMap, HashSet};
+
+use crate::db::error::DbError;
+use rusqlite::{Connection, OptionalExtension};
+
+/// DAO for question_part table
+pub struct QuestionPartDao<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> QuestionPartDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 把 `question_id` 挂到 `parent_id` 下，排在现有小题之后；返回序号
+    pub fn append(&self, parent_id: i64, question_id: i64) -> Result<i64, DbError> {
+        self.insert(parent_id, question_id, None)
+    }
+
+    /// 把填空卡 `question_id` 挂到笔记 `parent_id` 下；返回序号
+    pub fn append_cloze(&self, parent_id: i64, question_id: i64, cloze_index: i64) -> Result<i64, DbError> {
+        self.insert(parent_id, question_id, Some(cloze_index))
+    }
+
+    fn insert(&self, parent_id: i64, question_id: i64, cloze_index: Option<i64>) -> Result<i64, DbError> {
+        let part_order: i64 = self.conn.query_row(
+            "SELECT COALESCE(MAX(part_order), 0) + 1 FROM question_part WHERE parent_id = ?1",
+            [parent_id],
+            |row| row.get(0),
+        )?;
+        self.conn.execute(
+            "INSERT INTO question_part (question_id, parent_id, part_order) VALUES (?1, ?2, ?3)",
+            rusqlite::params![question_id, parent_id, part_order],
+        )?;
+        if let Some(cloze_index) = cloze_index {
+            self.conn.execute(
+                "INSERT INTO cloze_card (question_id, note_id, cloze_index) VALUES (?1, ?2, ?3)",
+                rusqlite::params![question_id, parent_id, cloze_index],
+            )?;
+        }
+        Ok(part_order)
+    }
+
+    /// 填空卡的 (笔记 ID, 填空编号)；不是填空卡时为 None
+    pub fn cloze_of(&self, question_id: i64) -> Result<Option<(i64, i64)>, DbError> {
+        self.conn
+            .query_row(
+                "SELECT note_id, cloze_index FROM cloze_card WHERE question_id = ?1",
+                [question_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// 笔记的全部填空卡 (卡片 ID, 填空编号)，含回收站中的
+    pub fn list_cloze_cards(&self, parent_id: i64) -> Result<Vec<(i64, i64)>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT question_id, cloze_index FROM cloze_card WHERE note_id = ?1 ORDER BY cloze_index",
+        )?;
+        let rows = stmt.query_map([parent_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// 未删除的填空卡 → 所属笔记
+    pub fn cloze_groups(&self) -> Result<HashMap<i64, i64>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.question_id, c.note_id
+             FROM cloze_card c
+             JOIN question q ON q.id = c.question_id
+             WHERE q.deleted_at IS NULL",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<HashMap<_, _>, _>>().map_err(Into::into)
+    }
+
+    /// 小题所属的大题
+    pub fn parent_of(&self, question_id: i64) -> Result<Option<i64>, DbError> {
+        self.conn
+            .query_row(
+                "SELECT parent_id FROM question_part WHERE question_id = ?1",
+                [question_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// 大题下的小题 ID，按顺序；`include_deleted` 为 false 时不含回收站中的小题
+    pub fn list_part_ids(&self, parent_id: i64, include_deleted: bool) -> Result<Vec<i64>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT p.question_id
+             FROM question_part p
+             JOIN question q ON q.id = p.question_id
+             WHERE p.parent_id = ?1 AND (?2 OR q.deleted_at IS NULL)
+             ORDER BY p.part_order, p.question_id",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![parent_id, include_deleted], |row| row.get(0))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// 至少有一道未删除小题的大题 ID
+    pub fn parent_ids(&self) -> Result<HashSet<i64>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT p.parent_id
+             FROM question_part p
+             JOIN question q ON q.id = p.question_id
+             WHERE q.deleted_at IS NULL",
+        )?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect::<Result<HashSet<_>, _>>().map_err(Into::into)
+    }
+
+    pub fn update_order(&self, question_id: i64, part_order: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE question_part SET part_order = ?1 WHERE question_id = ?2",
+            rusqlite::params![part_order, question_id],
+        )?;
+        Ok(())
+    }
+
+    /// 删除题目作为小题或大题的全部从属记录（含填空卡编号）
+    pub fn delete_by_question(&self, question_id: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM question_part WHERE question_id = ?1 OR parent_id = ?1",
+            [question_id],
+        )?;
+        self.conn.execute(
+            "DELETE FROM cloze_card WHERE question_id = ?1 OR note_id = ?1",
+            [question_id],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+//! 题目修订数据访问层
+//!
+//! `question_revision` 只追加：每条保存一份题目快照（JSON），同一题目按 id 递增即时间顺序。
+
+use crate::db::error::DbError;
+use crate::domain::question_revision::{QuestionRevision, QuestionSnapshot};
+use crate::util::time::Timestamp;
+use rusqlite::{Connection, OptionalExtension};
+
+/// DAO for question_revision table
+pub struct QuestionRevisionDao<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> QuestionRevisionDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 追加一条修订，返回其 id
+    pub fn insert(&self, question_id: i64, snapshot: &QuestionSnapshot, created_at: Timestamp) -> Result<i64, DbError> {
+        let json = serde_json::to_string(snapshot)
+            .map_err(|e| DbError::Migration(format!("convert error: {:?}", e)))?;
+        self.conn.execute(
+            "INSERT INTO question_revision (question_id, snapshot, created_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![question_id, json, created_at.as_i64()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<QuestionRevision>, DbError> {
+        self.conn
+            .query_row(
+                "SELECT id, question_id, snapshot, created_at FROM question_revision WHERE id = ?1",
+                [id],
+                row_to_raw,
+            )
+            .optional()?
+            .map(into_revision)
+            .transpose()
+    }
+
+    /// 题目最近的一条修订；从未记录过时返回 None
+    pub fn latest(&self, question_id: i64) -> Result<Option<QuestionRevision>, DbError> {
+        self.conn
+            .query_row(
+                "SELECT id, question_id, snapshot, created_at FROM question_revision
+                 WHERE question_id = ?1 ORDER BY id DESC LIMIT 1",
+                [question_id],
+                row_to_raw,
+            )
+            .optional()?
+            .map(into_revision)
+            .transpose()
+    }
+
+    /// 题目的全部修订，新的在前
+    pub fn list(&self, question_id: i64) -> Result<Vec<QuestionRevision>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, question_id, snapshot, created_at FROM question_revision
+             WHERE question_id = ?1 ORDER BY id DESC",
+        )?;
+        let rows = stmt.query_map([question_id], row_to_raw)?;
+        rows.map(|r| into_revision(r?)).collect()
+    }
+
+    pub fn delete_by_question(&self, question_id: i64) -> Result<(), DbError> {
+        self.conn
+            .execute("DELETE FROM question_revision WHERE question_id = ?1", [question_id])?;
+        Ok(())
+    }
+}
+
+type RawRevision = (i64, i64, String, i64);
+
+fn row_to_raw(row: &rusqlite::Row<'_>) -> rusqlite::Result<RawRevision> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+}
+
+fn into_revision((id, question_id, json, created_at): RawRevision) -> Result<QuestionRevision, DbError> {
+    let snapshot = serde_json::from_str(&json)
+        .map_err(|e| DbError::Migration(format!("convert error: {:?}", e)))?;
+    Ok(QuestionRevision { id, question_id, snapshot, created_at: Timestamp::from(created_at) })
+}
@@ -0,0 +1,103 @@
+//! 题目文本数据访问层
+//!
+//! `question_text` 只追加不修改：每次保存写入新版本，读取时取各字段 version 最大的一行。
+
+use crate::db::error::DbError;
+use crate::domain::enums::TextField;
+use crate::domain::question_text::QuestionText;
+use rusqlite::{Connection, OptionalExtension};
+
+/// 文本的一个历史版本
+#[derive(Debug, Clone)]
+pub struct TextVersion {
+    pub version: i64,
+    pub content: String,
+    pub created_at: i64,
+}
+
+/// DAO for question_text table
+pub struct QuestionTextDao<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> QuestionTextDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 某字段的当前内容；从未写过时返回 None
+    pub fn latest(&self, question_id: i64, field: TextField) -> Result<Option<TextVersion>, DbError> {
+        self.conn
+            .query_row(
+                "SELECT version, content, created_at FROM question_text
+                 WHERE question_id = ?1 AND field = ?2
+                 ORDER BY version DESC LIMIT 1",
+                rusqlite::params![question_id, field.as_str()],
+                |row| {
+                    Ok(TextVersion {
+                        version: row.get(0)?,
+                        content: row.get(1)?,
+                        created_at: row.get(2)?,
+                    })
+                },
+            )
+            .optional()
+            .map_err(Into::into)
+    }
+
+    /// 三个字段的当前内容；空内容视为未填写
+    pub fn get_current(&self, question_id: i64) -> Result<QuestionText, DbError> {
+        let mut text = QuestionText::default();
+        for field in TextField::ALL {
+            let content = self
+                .latest(question_id, field)?
+                .map(|v| v.content)
+                .filter(|c| !c.is_empty());
+            text.set(field, content);
+        }
+        Ok(text)
+    }
+
+    /// 追加一个新版本，返回版本号（从 1 开始）
+    pub fn insert_version(
+        &self,
+        question_id: i64,
+        field: TextField,
+        content: &str,
+        created_at: i64,
+    ) -> Result<i64, DbError> {
+        let version = self.latest(question_id, field)?.map_or(1, |v| v.version + 1);
+        self.conn.execute(
+            "INSERT INTO question_text (question_id, field, content, version, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![question_id, field.as_str(), content, version, created_at],
+        )?;
+        Ok(version)
+    }
+
+    /// 某字段的全部历史版本，新版本在前
+    pub fn history(&self, question_id: i64, field: TextField) -> Result<Vec<TextVersion>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT version, content, created_at FROM question_text
+             WHERE question_id = ?1 AND field = ?2
+             ORDER BY version DESC",
+        )?;
+        let rows = stmt.query_map(rusqlite::params![question_id, field.as_str()], |row| {
+            Ok(TextVersion {
+                version: row.get(0)?,
+                content: row.get(1)?,
+                created_at: row.get(2)?,
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// 删除某题的全部文本版本（永久删除题目时调用）
+    pub fn delete_by_question(&self, question_id: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM question_text WHERE question_id = ?1",
+            [question_id],
+        )?;
+        Ok(())
+    }
+}
@@ -1,9 +1,12 @@
 //! 推荐数据访问层
 
 use crate::db::error::DbError;
+use crate::domain::enums::RecommendationTier;
+use crate::server::reason_code::parse_stored_reasons;
 use crate::server::recommendation::RecommendedQuestion;
 use crate::util::time::{ClockConfig, LogicalDay};
 use rusqlite::Connection;
+use std::collections::{HashMap, HashSet};
 
 /// 每日复习状态
 #[derive(Debug, Clone, serde::Serialize)]
@@ -38,21 +41,20 @@ impl<'a> RecommendationDao<'a> {
                 q.last_result,
                 rs.error_rate,
                 r.subject,
-                r.reason
+                r.reason,
+                r.tier
             FROM recommendation r
             JOIN question q ON r.question_id = q.id
             LEFT JOIN review_summary rs ON r.question_id = rs.question_id
             WHERE r.day = ?1
               AND q.deleted_at IS NULL
-            ORDER BY r.score DESC
+            ORDER BY CASE r.tier WHEN 'REMEDIAL' THEN 0 ELSE 1 END, r.score DESC
             "#
         )?;
 
         let rows = stmt.query_map([day], |row| {
             let reason_text: Option<String> = row.get(10)?;
-            let reason = reason_text
-                .filter(|t| t != "null")
-                .and_then(|t| serde_json::from_str(&t).ok());
+            let reason = reason_text.and_then(|t| parse_stored_reasons(&t));
             Ok(RecommendedQuestion {
                 question_id: row.get(0)?,
                 name: row.get(1)?,
@@ -64,10 +66,12 @@ impl<'a> RecommendationDao<'a> {
                 last_result: row.get(7)?,
                 error_rate: row.get(8)?,
                 subject: row.get(9)?,
+                tier: row.get(11)?,
                 reason,
                 score_detail: None,
                 review_count: 0,
                 created_at: 0,
+                knowledge_points: vec![],
             })
         })?;
 
@@ -83,34 +87,163 @@ impl<'a> RecommendationDao<'a> {
         }
     }
 
-    /// 批量插入推荐记录
+    /// 批量插入每日推荐记录
+    ///
+    /// 只替换当天的 DAILY 层；已追加的巩固题保留，重复的题目以巩固题为准。
     pub fn insert_batch(
         &self,
         day: i64,
         questions: &[RecommendedQuestion],
     ) -> Result<(), DbError> {
         // 先删除当天的旧推荐
-        self.conn.execute("DELETE FROM recommendation WHERE day = ?1", [day])?;
+        self.delete_by_day_tier(day, RecommendationTier::DAILY)?;
 
         // 插入新推荐
+        self.insert_tier(day, questions, RecommendationTier::DAILY)
+    }
+
+    /// 追加巩固题（已在当天推荐中的题目跳过）
+    pub fn insert_remedial(
+        &self,
+        day: i64,
+        questions: &[RecommendedQuestion],
+    ) -> Result<(), DbError> {
+        self.insert_tier(day, questions, RecommendationTier::REMEDIAL)
+    }
+
+    fn insert_tier(
+        &self,
+        day: i64,
+        questions: &[RecommendedQuestion],
+        tier: RecommendationTier,
+    ) -> Result<(), DbError> {
         let mut stmt = self.conn.prepare(
-            "INSERT INTO recommendation (day, question_id, score, subject, reason) VALUES (?1, ?2, ?3, ?4, ?5)"
+            "INSERT OR IGNORE INTO recommendation (day, question_id, score, subject, reason, tier) VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
         )?;
 
         for q in questions {
             let reason_json = serde_json::to_string(&q.reason).unwrap_or_else(|_| "null".to_string());
-            stmt.execute(rusqlite::params![day, q.question_id, q.score, q.subject, reason_json])?;
+            stmt.execute(rusqlite::params![day, q.question_id, q.score, q.subject, reason_json, tier.as_str()])?;
         }
 
         Ok(())
     }
 
+    /// 向指定日期的推荐中加入（或覆盖）一道题，归入 DAILY 层
+    pub fn insert_one(&self, day: i64, q: &RecommendedQuestion) -> Result<(), DbError> {
+        let reason_json = serde_json::to_string(&q.reason).unwrap_or_else(|_| "null".to_string());
+        self.conn.execute(
+            "INSERT OR REPLACE INTO recommendation (day, question_id, score, subject, reason, tier) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![day, q.question_id, q.score, q.subject, reason_json, RecommendationTier::DAILY.as_str()],
+        )?;
+        Ok(())
+    }
+
+    /// 指定日期某一层的推荐数量
+    pub fn count_by_tier(&self, day: i64, tier: RecommendationTier) -> Result<i64, DbError> {
+        let count = self.conn.query_row(
+            "SELECT COUNT(*) FROM recommendation WHERE day = ?1 AND tier = ?2",
+            rusqlite::params![day, tier.as_str()],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// 指定日期已在推荐中的题目 ID（不分层）
+    pub fn question_ids_by_day(&self, day: i64) -> Result<HashSet<i64>, DbError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT question_id FROM recommendation WHERE day = ?1")?;
+        let ids = stmt
+            .query_map([day], |row| row.get(0))?
+            .collect::<Result<HashSet<i64>, _>>()?;
+        Ok(ids)
+    }
+
+    /// 从指定日期的推荐中移除一道题
+    pub fn delete_one(&self, day: i64, question_id: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM recommendation WHERE day = ?1 AND question_id = ?2",
+            rusqlite::params![day, question_id],
+        )?;
+        Ok(())
+    }
+
+    /// 统计指定日期推荐中各理由编码出现的次数，按次数降序
+    pub fn count_reason_codes(&self, day: i64) -> Result<Vec<(String, i64)>, DbError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT code, COUNT(*) AS cnt
+            FROM (
+                SELECT CASE WHEN j.type = 'object' THEN json_extract(j.value, '$.code') END AS code
+                FROM recommendation r,
+                     json_each(CASE WHEN json_valid(r.reason) THEN r.reason ELSE '[]' END) j
+                WHERE r.day = ?1
+            )
+            WHERE code IS NOT NULL
+            GROUP BY code
+            ORDER BY cnt DESC, code
+            "#,
+        )?;
+        let rows = stmt.query_map([day], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// 早于指定日期、仍有推荐记录的最近一天
+    pub fn latest_day_before(&self, day: i64) -> Result<Option<i64>, DbError> {
+        let latest = self.conn.query_row(
+            "SELECT MAX(day) FROM recommendation WHERE day < ?1",
+            [day],
+            |row| row.get(0),
+        )?;
+        Ok(latest)
+    }
+
+    /// 指定日期每日推荐（DAILY 层）按科目统计：科目 → (推荐题数, 其中在 [start, end) 内复习过的题数)
+    pub fn subject_progress(
+        &self,
+        day: i64,
+        start: i64,
+        end: i64,
+    ) -> Result<HashMap<Option<String>, (i64, i64)>, DbError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT
+                r.subject,
+                COUNT(*),
+                SUM(CASE WHEN EXISTS (
+                    SELECT 1 FROM review rev
+                    WHERE rev.question_id = r.question_id
+                      AND rev.reviewed_at >= ?3
+                      AND rev.reviewed_at < ?4
+                ) THEN 1 ELSE 0 END)
+            FROM recommendation r
+            WHERE r.day = ?1 AND r.tier = ?2
+            GROUP BY r.subject
+            "#,
+        )?;
+        let rows = stmt.query_map(
+            rusqlite::params![day, RecommendationTier::DAILY.as_str(), start, end],
+            |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))),
+        )?;
+        rows.collect::<Result<HashMap<_, _>, _>>().map_err(Into::into)
+    }
+
     /// 删除指定日期的推荐
     pub fn delete_by_day(&self, day: i64) -> Result<(), DbError> {
         self.conn.execute("DELETE FROM recommendation WHERE day = ?1", [day])?;
         Ok(())
     }
 
+    /// 删除指定日期某一层的推荐
+    pub fn delete_by_day_tier(&self, day: i64, tier: RecommendationTier) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM recommendation WHERE day = ?1 AND tier = ?2",
+            rusqlite::params![day, tier.as_str()],
+        )?;
+        Ok(())
+    }
+
     /// 清理旧推荐记录，只保留当前逻辑天的记录
     /// 每次生成新推荐时调用，确保推荐表不会无限增长
     pub fn cleanup_old_recommendations(&self, current_day: i64) -> Result<usize, DbError> {
@@ -216,10 +349,13 @@ impl<'a> RecommendationDao<'a> {
     /// - `None` 不过滤，返回所有科目。
     /// - `Some("未分类")` 只返回未标注科目的题目。
     /// - `Some("其他")` 只返回该科目。
+    ///
+    /// `tier`：可选的推荐层筛选，`None` 返回全部层（巩固题排在前面）。
     pub fn get_pending_recommendations(
         &self,
         limit: i64,
         subject: Option<&str>,
+        tier: Option<RecommendationTier>,
     ) -> Result<Vec<RecommendedQuestion>, DbError> {
         let now = crate::util::time::now_ts();
         let cfg = ClockConfig::default();
@@ -239,31 +375,31 @@ impl<'a> RecommendationDao<'a> {
                 q.last_result,
                 rs.error_rate,
                 r.subject,
-                r.reason
+                r.reason,
+                r.tier
             FROM recommendation r
             JOIN question q ON r.question_id = q.id
             LEFT JOIN review_summary rs ON r.question_id = rs.question_id
             WHERE r.day = ?1
               AND q.deleted_at IS NULL
               AND (?5 IS NULL OR r.subject = ?5)
+              AND (?6 IS NULL OR r.tier = ?6)
               AND NOT EXISTS (
                   SELECT 1 FROM review rev
                   WHERE rev.question_id = r.question_id
                     AND rev.reviewed_at >= ?2
                     AND rev.reviewed_at < ?3
               )
-            ORDER BY r.score DESC
+            ORDER BY CASE r.tier WHEN 'REMEDIAL' THEN 0 ELSE 1 END, r.score DESC
             LIMIT ?4
             "#
         )?;
 
         let rows = stmt.query_map(
-            rusqlite::params![day, day_start.0, day_end.0, limit, subject],
+            rusqlite::params![day, day_start.0, day_end.0, limit, subject, tier.map(|t| t.as_str())],
             |row| {
             let reason_text: Option<String> = row.get(10)?;
-            let reason = reason_text
-                .filter(|t| t != "null")
-                .and_then(|t| serde_json::from_str(&t).ok());
+            let reason = reason_text.and_then(|t| parse_stored_reasons(&t));
             Ok(RecommendedQuestion {
                 question_id: row.get(0)?,
                 name: row.get(1)?,
@@ -275,10 +411,12 @@ impl<'a> RecommendationDao<'a> {
                 last_result: row.get(7)?,
                 error_rate: row.get(8)?,
                 subject: row.get(9)?,
+                tier: row.get(11)?,
                 reason,
                 score_detail: None,
                 review_count: 0,
                 created_at: 0,
+                knowledge_points: vec![],
             })
         })?;
 
@@ -57,6 +57,16 @@ impl<'a> ReviewDao<'a> {
         Ok(ReviewId::from(id))
     }
 
+    /// 把某题目的复习记录移入归档表，返回归档条数。
+    pub fn archive_by_question(&self, question_id: QuestionId, archived_at: Timestamp) -> Result<usize, DbError> {
+        crate::db::archive_reviews_by_question(self.conn, i64::from(question_id), archived_at.into())
+    }
+
+    /// 删除某题目的全部复习记录。
+    pub fn delete_by_question(&self, question_id: QuestionId) -> Result<(), DbError> {
+        crate::db::delete_reviews_by_question(self.conn, i64::from(question_id))
+    }
+
     /// 统计总复习次数
     pub fn count_all(&self) -> Result<i64, DbError> {
         crate::db::count_reviews(self.conn)
@@ -112,6 +122,39 @@ impl<'a> ReviewDao<'a> {
         Ok(map)
     }
 
+    /// 按知识点统计 `since` 之后的错误率和复习次数（口径与 review_summary 一致，非 correct 即算错）。
+    /// 返回 HashMap<knowledge_point, (error_rate, review_count)>
+    pub fn get_knowledge_point_error_rates(
+        &self,
+        since: i64,
+    ) -> Result<std::collections::HashMap<String, (f64, i64)>, DbError> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT
+                m.value,
+                SUM(CASE WHEN LOWER(r.result) != 'correct' THEN 1 ELSE 0 END) * 1.0 / COUNT(*),
+                COUNT(*)
+            FROM review r
+            JOIN meta m ON m.question_id = r.question_id AND m.key = 'system.KnowledgePoint'
+            JOIN question q ON q.id = r.question_id AND q.deleted_at IS NULL
+            WHERE r.reviewed_at >= ?1
+            GROUP BY m.value
+            "#
+        )?;
+
+        let rows = stmt.query_map([since], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?, row.get::<_, i64>(2)?))
+        })?;
+
+        let mut map = std::collections::HashMap::new();
+        for row in rows {
+            let (kp, rate, count) = row?;
+            map.insert(kp, (rate, count));
+        }
+
+        Ok(map)
+    }
+
     /// 每个科目的错误率统计（不分时间）。`subject_filter = None` 即全部。
     pub fn subject_error_stats(
         &self,
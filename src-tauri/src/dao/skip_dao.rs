@@ -0,0 +1,44 @@
+//! 跳过记录数据访问层
+
+use std::collections::HashMap;
+
+use crate::db::error::DbError;
+use rusqlite::Connection;
+
+/// DAO for review_skip table
+pub struct SkipDao<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> SkipDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 记录一次跳过
+    pub fn insert(&self, question_id: i64, skipped_at: i64) -> Result<i64, DbError> {
+        self.conn.execute(
+            "INSERT INTO review_skip (question_id, skipped_at) VALUES (?1, ?2)",
+            rusqlite::params![question_id, skipped_at],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// `since` 之后每道题的跳过次数：question_id → 次数
+    pub fn count_since(&self, since: i64) -> Result<HashMap<i64, i64>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT question_id, COUNT(*) FROM review_skip WHERE skipped_at >= ?1 GROUP BY question_id",
+        )?;
+        let rows = stmt.query_map([since], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<HashMap<_, _>, _>>().map_err(Into::into)
+    }
+
+    /// 删除某题的全部跳过记录（永久删除题目时调用）
+    pub fn delete_by_question(&self, question_id: i64) -> Result<(), DbError> {
+        self.conn.execute(
+            "DELETE FROM review_skip WHERE question_id = ?1",
+            [question_id],
+        )?;
+        Ok(())
+    }
+}
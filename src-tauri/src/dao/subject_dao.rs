@@ -0,0 +1,132 @@
+//! 科目数据访问层
+//!
+//! 科目登记在 `subject` 表；题目通过 `system.Subject` 元信息引用科目 id。
+
+use crate::db::error::DbError;
+use crate::domain::subject::{Subject, SubjectConfig};
+use crate::util::time::Timestamp;
+use rusqlite::{Connection, OptionalExtension};
+
+const SUBJECT_COLUMNS: &str = "id, name, color, sort_order, archived, recommendation_limit,
+     max_per_knowledge_point, study_days, carry_over_unused, created_at";
+
+/// DAO for subject table
+pub struct SubjectDao<'a> {
+    conn: &'a Connection,
+}
+
+fn subject_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<(Subject, Option<String>)> {
+    let study_days: Option<String> = row.get(7)?;
+    let subject = Subject {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        sort_order: row.get(3)?,
+        config: SubjectConfig {
+            archived: row.get(4)?,
+            recommendation_limit: row.get(5)?,
+            max_per_knowledge_point: row.get(6)?,
+            study_days: None,
+            carry_over_unused: row.get(8)?,
+        },
+        created_at: Timestamp::from(row.get::<_, i64>(9)?),
+    };
+    Ok((subject, study_days))
+}
+
+/// 学习日以 JSON 数组存放，NULL 表示每天
+fn subject_to_domain((mut subject, study_days): (Subject, Option<String>)) -> Result<Subject, DbError> {
+    subject.config.study_days = match study_days {
+        Some(json) => Some(
+            serde_json::from_str(&json)
+                .map_err(|e| DbError::Migration(format!("invalid study_days: {}", e)))?,
+        ),
+        None => None,
+    };
+    Ok(subject)
+}
+
+fn study_days_json(config: &SubjectConfig) -> Option<String> {
+    config
+        .study_days
+        .as_ref()
+        .and_then(|days| serde_json::to_string(days).ok())
+}
+
+impl<'a> SubjectDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 全部科目，按显示顺序
+    pub fn list(&self) -> Result<Vec<Subject>, DbError> {
+        let sql = format!("SELECT {} FROM subject ORDER BY sort_order, id", SUBJECT_COLUMNS);
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map([], subject_from_row)?;
+        rows.map(|r| subject_to_domain(r?)).collect()
+    }
+
+    /// 按 id 取科目
+    pub fn get(&self, id: i64) -> Result<Option<Subject>, DbError> {
+        let sql = format!("SELECT {} FROM subject WHERE id = ?1", SUBJECT_COLUMNS);
+        let row = self.conn.query_row(&sql, [id], subject_from_row).optional()?;
+        row.map(subject_to_domain).transpose()
+    }
+
+    /// 按名称取科目
+    pub fn get_by_name(&self, name: &str) -> Result<Option<Subject>, DbError> {
+        let sql = format!("SELECT {} FROM subject WHERE name = ?1", SUBJECT_COLUMNS);
+        let row = self.conn.query_row(&sql, [name], subject_from_row).optional()?;
+        row.map(subject_to_domain).transpose()
+    }
+
+    /// 新增科目，排在最后；返回新科目 id
+    pub fn insert(&self, name: &str, now: Timestamp) -> Result<i64, DbError> {
+        self.conn.execute(
+            "INSERT INTO subject (name, sort_order, created_at)
+             VALUES (?1, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM subject), ?2)",
+            rusqlite::params![name, now.as_i64()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// 更新颜色与科目配置
+    pub fn update(&self, subject: &Subject) -> Result<(), DbError> {
+        let c = &subject.config;
+        self.conn.execute(
+            "UPDATE subject SET color = ?2, archived = ?3, recommendation_limit = ?4,
+                 max_per_knowledge_point = ?5, study_days = ?6, carry_over_unused = ?7
+             WHERE id = ?1",
+            rusqlite::params![
+                subject.id,
+                subject.color,
+                c.archived,
+                c.recommendation_limit,
+                c.max_per_knowledge_point,
+                study_days_json(c),
+                c.carry_over_unused
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 设置显示顺序
+    pub fn set_sort_order(&self, id: i64, sort_order: i64) -> Result<(), DbError> {
+        self.conn
+            .execute("UPDATE subject SET sort_order = ?2 WHERE id = ?1", rusqlite::params![id, sort_order])?;
+        Ok(())
+    }
+
+    /// 只改科目表中的名称；引用处由 `server::subject::rename_subject` 一并更新
+    pub fn rename(&self, id: i64, name: &str) -> Result<(), DbError> {
+        self.conn
+            .execute("UPDATE subject SET name = ?2 WHERE id = ?1", rusqlite::params![id, name])?;
+        Ok(())
+    }
+
+    /// 删除科目记录
+    pub fn delete(&self, id: i64) -> Result<(), DbError> {
+        self.conn.execute("DELETE FROM subject WHERE id = ?1", [id])?;
+        Ok(())
+    }
+}
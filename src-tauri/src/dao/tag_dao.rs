@@ -0,0 +1,74 @@
+//! 标签数据访问层
+//!
+//! 标签是题目上的自由标记（如 期中、老师讲过、易混），以 `system.Tag` 元信息存放，每题可有多个，
+//! 与知识点树无关，也不单独登记。
+
+use std::collections::HashSet;
+
+use crate::db::error::DbError;
+use rusqlite::Connection;
+
+const TAG_META_KEY: &str = "system.Tag";
+
+/// DAO for question tags (`meta` rows with key `system.Tag`)
+pub struct TagDao<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> TagDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 给未删除题目打标签；题目不存在、已删除或已带有该标签时返回 false
+    pub fn add(&self, question_id: i64, tag: &str) -> Result<bool, DbError> {
+        let n = self.conn.execute(
+            "INSERT OR IGNORE INTO meta (question_id, key, value)
+             SELECT id, ?2, ?3 FROM question WHERE id = ?1 AND deleted_at IS NULL",
+            rusqlite::params![question_id, TAG_META_KEY, tag],
+        )?;
+        Ok(n > 0)
+    }
+
+    /// 去掉题目上的标签；原本没有时返回 false
+    pub fn remove(&self, question_id: i64, tag: &str) -> Result<bool, DbError> {
+        let n = self.conn.execute(
+            "DELETE FROM meta WHERE question_id = ?1 AND key = ?2 AND value = ?3",
+            rusqlite::params![question_id, TAG_META_KEY, tag],
+        )?;
+        Ok(n > 0)
+    }
+
+    /// 全部标签及带有该标签的未删除题目数，按名称排序
+    pub fn list_with_counts(&self) -> Result<Vec<(String, i64)>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT m.value, COUNT(*)
+             FROM meta m JOIN question q ON q.id = m.question_id AND q.deleted_at IS NULL
+             WHERE m.key = ?1
+             GROUP BY m.value
+             ORDER BY m.value",
+        )?;
+        let rows = stmt.query_map([TAG_META_KEY], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// 同时带有 `tags` 中每一个标签的题目 id；`tags` 不得有重复项
+    pub fn question_ids_with_all(&self, tags: &[String]) -> Result<HashSet<i64>, DbError> {
+        if tags.is_empty() {
+            return Ok(HashSet::new());
+        }
+        let placeholders = vec!["?"; tags.len()].join(",");
+        let sql = format!(
+            "SELECT question_id FROM meta
+             WHERE key = '{}' AND value IN ({})
+             GROUP BY question_id
+             HAVING COUNT(DISTINCT value) = {}",
+            TAG_META_KEY,
+            placeholders,
+            tags.len()
+        );
+        let mut stmt = self.conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(tags), |row| row.get(0))?;
+        rows.collect::<Result<HashSet<_>, _>>().map_err(Into::into)
+    }
+}
@@ -0,0 +1,125 @@
+//! 用户自定义字段数据访问层
+//!
+//! 字段定义存放在 `user_field` 表；字段值仍在 `meta` 表中，key 为 `user.<name>`。
+
+use crate::db::error::DbError;
+use crate::domain::enums::{Cardinality, MetaKey, UserFieldType};
+use crate::domain::user_field::UserField;
+use crate::util::time::Timestamp;
+use rusqlite::{Connection, OptionalExtension};
+
+/// DAO for user_field table
+pub struct UserFieldDao<'a> {
+    conn: &'a Connection,
+}
+
+fn field_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<(String, String, Option<String>, String, i64)> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+}
+
+fn field_to_domain(
+    (name, field_type, allowed_values, cardinality, created_at): (String, String, Option<String>, String, i64),
+) -> Result<UserField, DbError> {
+    let field_type = UserFieldType::from_str(&field_type)
+        .ok_or_else(|| DbError::Migration(format!("invalid user field type: {}", field_type)))?;
+    let cardinality = Cardinality::from_str(&cardinality)
+        .ok_or_else(|| DbError::Migration(format!("invalid cardinality: {}", cardinality)))?;
+    let allowed_values = match allowed_values {
+        Some(json) => serde_json::from_str(&json)
+            .map_err(|e| DbError::Migration(format!("invalid allowed_values: {}", e)))?,
+        None => Vec::new(),
+    };
+    Ok(UserField {
+        name,
+        field_type,
+        allowed_values,
+        cardinality,
+        created_at: Timestamp::from(created_at),
+    })
+}
+
+fn allowed_values_json(field: &UserField) -> Option<String> {
+    if field.allowed_values.is_empty() {
+        None
+    } else {
+        serde_json::to_string(&field.allowed_values).ok()
+    }
+}
+
+impl<'a> UserFieldDao<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// 全部字段定义，按创建先后
+    pub fn list(&self) -> Result<Vec<UserField>, DbError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT name, field_type, allowed_values, cardinality, created_at
+             FROM user_field ORDER BY created_at, rowid",
+        )?;
+        let rows = stmt.query_map([], field_from_row)?;
+        rows.map(|r| field_to_domain(r?)).collect()
+    }
+
+    /// 按名称取字段定义
+    pub fn get(&self, name: &str) -> Result<Option<UserField>, DbError> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT name, field_type, allowed_values, cardinality, created_at
+                 FROM user_field WHERE name = ?1",
+                [name],
+                field_from_row,
+            )
+            .optional()?;
+        row.map(field_to_domain).transpose()
+    }
+
+    /// 新增字段定义
+    pub fn insert(&self, field: &UserField) -> Result<(), DbError> {
+        self.conn.execute(
+            "INSERT INTO user_field (name, field_type, allowed_values, cardinality, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            rusqlite::params![
+                field.name,
+                field.field_type.as_str(),
+                allowed_values_json(field),
+                field.cardinality.as_str(),
+                field.created_at.as_i64()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 按名称更新类型、可选值与基数
+    pub fn update(&self, field: &UserField) -> Result<(), DbError> {
+        self.conn.execute(
+            "UPDATE user_field SET field_type = ?2, allowed_values = ?3, cardinality = ?4 WHERE name = ?1",
+            rusqlite::params![
+                field.name,
+                field.field_type.as_str(),
+                allowed_values_json(field),
+                field.cardinality.as_str()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 删除字段定义及其全部值；返回是否存在该字段
+    pub fn delete(&self, name: &str) -> Result<bool, DbError> {
+        let key = MetaKey::User(name.to_string()).as_str();
+        self.conn.execute("DELETE FROM meta WHERE key = ?1", [&key])?;
+        let n = self.conn.execute("DELETE FROM user_field WHERE name = ?1", [name])?;
+        Ok(n > 0)
+    }
+
+    /// 某字段在所有题目上的值：(question_id, value)，按题目分组排列
+    pub fn list_values(&self, name: &str) -> Result<Vec<(i64, String)>, DbError> {
+        let key = MetaKey::User(name.to_string()).as_str();
+        let mut stmt = self
+            .conn
+            .prepare("SELECT question_id, value FROM meta WHERE key = ?1 ORDER BY question_id, id")?;
+        let rows = stmt.query_map([&key], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+}
@@ -1,8 +1,8 @@
 use crate::dao::meta_dao::MetaDao;
 use crate::db::error::DbError;
 use crate::db::schema::view_schema::{
-    select_deleted_views_page, select_view_active_by_id, select_view_by_id, select_views_by_name,
-    select_views_classified, select_views_search_fuzzy,
+    select_deleted_views_page, select_question_ids_filtered, select_view_active_by_id, select_view_by_id,
+    select_views_by_name, select_views_classified, select_views_search_fuzzy, MetaFilter,
 };
 use crate::domain::enums::QuestionState;
 use crate::domain::ids::QuestionId;
@@ -17,6 +17,7 @@ pub struct ViewDao<'a> {
 }
 
 const KP_META_KEY: &str = "system.KnowledgePoint";
+const TAG_META_KEY: &str = "system.Tag";
 
 impl<'a> ViewDao<'a> {
     pub fn new(conn: &'a Connection) -> Self {
@@ -27,40 +28,46 @@ impl<'a> ViewDao<'a> {
     pub fn get_by_id(&self, id: QuestionId) -> Result<View, DbError> {
         let id_i64: i64 = i64::from(id);
         let row = select_view_by_id(self.conn, id_i64)?;
-        let md = MetaDao::new(self.conn);
-        let knowledge_points = md.get_values_by_question_key(id, KP_META_KEY)?;
-        view_from_row(row, &knowledge_points)
+        self.attach_one(row)
     }
 
     /// 根据名称精确匹配题目。
     pub fn get_by_name(&self, name: &str) -> Result<Vec<View>, DbError> {
         let rows = select_views_by_name(self.conn, name)?;
-        let md = MetaDao::new(self.conn);
-        let mut views = Vec::new();
-        for row in rows {
-            let knowledge_points =
-                md.get_values_by_question_key(QuestionId::from(row.id), KP_META_KEY)?;
-            views.push(view_from_row(row, &knowledge_points)?);
-        }
-        Ok(views)
+        self.attach_meta(rows)
     }
 
     /// 分页输出已删除题目。
     pub fn list_deleted(&self, offset: i64, limit: i64) -> Result<Vec<View>, DbError> {
         let rows = select_deleted_views_page(self.conn, offset, limit)?;
-        self.attach_kps(rows)
+        self.attach_meta(rows)
     }
 
-    /// 分类查询：`subject` 和 `state` 都可选；同时为 `None` 时返回全部分页结果。
+    /// 分类查询：`subject` 和 `state` 都可选；`tags` 非空时须全部带有；
+    /// 均未指定且无 `meta_filters` 时返回全部分页结果。
     pub fn list_classified(
         &self,
         subject: Option<&str>,
         state: Option<&str>,
+        tags: &[String],
+        meta_filters: &[MetaFilter],
         offset: i64,
         limit: i64,
     ) -> Result<Vec<View>, DbError> {
-        let rows = select_views_classified(self.conn, subject, state, limit, offset)?;
-        self.attach_kps(rows)
+        let rows = select_views_classified(self.conn, subject, state, tags, meta_filters, limit, offset)?;
+        self.attach_meta(rows)
+    }
+
+    /// 批量操作用：按条件取全部命中题目的 id；`deleted` 为 true 时只在回收站中查找。
+    pub fn list_ids_filtered(
+        &self,
+        deleted: bool,
+        subject: Option<&str>,
+        state: Option<&str>,
+        tags: &[String],
+        meta_filters: &[MetaFilter],
+    ) -> Result<Vec<i64>, DbError> {
+        select_question_ids_filtered(self.conn, deleted, subject, state, tags, meta_filters)
     }
 
     /// 按 ID 精确搜索未删除题目，返回 0 或 1 条。
@@ -68,28 +75,36 @@ impl<'a> ViewDao<'a> {
         let Some(row) = select_view_active_by_id(self.conn, id)? else {
             return Ok(None);
         };
-        let md = MetaDao::new(self.conn);
-        let kps = md.get_values_by_question_key(QuestionId::from(row.id), KP_META_KEY)?;
-        Ok(Some(view_from_row(row, &kps)?))
+        Ok(Some(self.attach_one(row)?))
     }
 
     /// 模糊搜索：`query` 任意字符串，按 `%query%` 匹配 `name` 或知识点；
-    /// 可叠加 `subject` / `state` 过滤（Mode B）。
+    /// 可叠加 `subject` / `state` / 标签过滤（Mode B）。
     pub fn search_fuzzy(
         &self,
         query: &str,
         subject: Option<&str>,
         state: Option<&str>,
+        tags: &[String],
         offset: i64,
         limit: i64,
     ) -> Result<Vec<View>, DbError> {
         let pattern = format!("%{}%", query);
-        let rows = select_views_search_fuzzy(self.conn, &pattern, subject, state, limit, offset)?;
-        self.attach_kps(rows)
+        let rows = select_views_search_fuzzy(self.conn, &pattern, subject, state, tags, limit, offset)?;
+        self.attach_meta(rows)
+    }
+
+    /// 为单个 `ViewRow` 补充 `knowledge_points` 与 `tags`。
+    fn attach_one(&self, row: crate::db::schema::view_schema::ViewRow) -> Result<View, DbError> {
+        let md = MetaDao::new(self.conn);
+        let qid = QuestionId::from(row.id);
+        let knowledge_points = md.get_values_by_question_key(qid, KP_META_KEY)?;
+        let tags = md.get_values_by_question_key(qid, TAG_META_KEY)?;
+        view_from_row(row, knowledge_points, tags)
     }
 
-    /// 批量为 `ViewRow` 列表补充 `knowledge_points`，避免 N+1。
-    fn attach_kps(
+    /// 批量为 `ViewRow` 列表补充 `knowledge_points` 与 `tags`，避免 N+1。
+    fn attach_meta(
         &self,
         rows: Vec<crate::db::schema::view_schema::ViewRow>,
     ) -> Result<Vec<View>, DbError> {
@@ -99,10 +114,12 @@ impl<'a> ViewDao<'a> {
         let qids: Vec<i64> = rows.iter().map(|r| r.id).collect();
         let md = MetaDao::new(self.conn);
         let mut kp_map = md.list_values_by_question_ids(&qids, KP_META_KEY)?;
+        let mut tag_map = md.list_values_by_question_ids(&qids, TAG_META_KEY)?;
         let mut out = Vec::with_capacity(rows.len());
         for row in rows {
             let kps = kp_map.remove(&row.id).unwrap_or_default();
-            out.push(view_from_row(row, &kps)?);
+            let tags = tag_map.remove(&row.id).unwrap_or_default();
+            out.push(view_from_row(row, kps, tags)?);
         }
         Ok(out)
     }
@@ -110,7 +127,8 @@ impl<'a> ViewDao<'a> {
 
 fn view_from_row(
     row: crate::db::schema::view_schema::ViewRow,
-    knowledge_points: &[String],
+    knowledge_points: Vec<String>,
+    tags: Vec<String>,
 ) -> Result<View, DbError> {
     let state = QuestionState::try_from(row.state.clone())
         .map_err(|e| DbError::Migration(format!("convert error: {:?}", e)))?;
@@ -121,7 +139,8 @@ fn view_from_row(
         created_at: Timestamp::from(row.created_at),
         deleted_at: row.deleted_at.map(Timestamp::from),
         subject: row.subject.clone().unwrap_or_default(),
-        knowledge_points: knowledge_points.to_vec(),
+        knowledge_points,
+        tags,
         last_reviewed_at: Timestamp::from(row.last_reviewed_at.unwrap_or(0)),
         wrong_count: row.wrong_count,
         error_rate: row.error_rate,
@@ -10,6 +10,10 @@ pub enum DbError {
     #[error("migration failed: {0}")]
     Migration(String),
 
+    //违反数据约束（如单值 key 重复写入）
+    #[error("constraint violation: {0}")]
+    Constraint(String),
+
     //记录未找到
     #[error("record not found")]
     NotFound,
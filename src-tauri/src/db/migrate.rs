@@ -7,6 +7,8 @@ pub struct Migration {
     pub version: i32,
     pub name: &'static str,
     pub sql: &'static str,
+    // 迁移 SQL 执行后的附加步骤（如报告清理结果），大多数迁移为 None
+    pub after: Option<fn(&Connection) -> Result<()>>,
 }
 
 // 所有迁移，按 version 升序排列
@@ -57,6 +59,7 @@ const MIGRATIONS: &[Migration] = &[
             FOREIGN KEY(question_id) REFERENCES question(id)
         );
         "#,
+        after: None,
     },
     Migration {
         version: 2,
@@ -81,6 +84,7 @@ const MIGRATIONS: &[Migration] = &[
                 GROUP BY question_id
             ) r ON r.question_id = q.id
         "#,
+        after: None,
     },
     Migration {
         version: 3,
@@ -92,6 +96,7 @@ const MIGRATIONS: &[Migration] = &[
         ALTER TABLE question ADD COLUMN wrong_count INTEGER NOT NULL DEFAULT 0;
         ALTER TABLE question ADD COLUMN due_at INTEGER;
         "#,
+        after: None,
     },
     Migration {
         version: 4,
@@ -114,6 +119,7 @@ const MIGRATIONS: &[Migration] = &[
             FROM review
             GROUP BY question_id;
         "#,
+        after: None,
     },
     Migration {
         version: 5,
@@ -121,6 +127,7 @@ const MIGRATIONS: &[Migration] = &[
         sql: r#"
         ALTER TABLE recommendation ADD COLUMN subject TEXT;
         "#,
+        after: None,
     },
     Migration {
         version: 6,
@@ -128,6 +135,7 @@ const MIGRATIONS: &[Migration] = &[
         sql: r#"
         ALTER TABLE asset ADD COLUMN sort_order INTEGER;
         "#,
+        after: None,
     },
     Migration {
         version: 7,
@@ -146,6 +154,7 @@ const MIGRATIONS: &[Migration] = &[
             AND a.id < asset.id
         ) + 1;
         "#,
+        after: None,
     },
     Migration {
         version: 8,
@@ -153,6 +162,7 @@ const MIGRATIONS: &[Migration] = &[
         sql: r#"
         ALTER TABLE recommendation ADD COLUMN reason TEXT;
         "#,
+        after: None,
     },
     Migration {
         version: 9,
@@ -168,6 +178,7 @@ const MIGRATIONS: &[Migration] = &[
             FROM review
             GROUP BY question_id;
         "#,
+        after: None,
     },
     Migration {
         version: 10,
@@ -177,6 +188,7 @@ const MIGRATIONS: &[Migration] = &[
         -- 将存量 state='DUE' 转为 'STABLE'，与新行为保持一致。
         UPDATE question SET state = 'STABLE' WHERE state = 'DUE';
         "#,
+        after: None,
     },
     Migration {
         version: 11,
@@ -208,6 +220,632 @@ const MIGRATIONS: &[Migration] = &[
             ) r ON r.question_id = q.id
             LEFT JOIN review_summary rs ON rs.question_id = q.id;
         "#,
+        after: None,
+    },
+    Migration {
+        version: 12,
+        name: "recommendation_override",
+        sql: r#"
+        -- 用户对每日推荐的手动干预，按逻辑日存储：
+        -- PIN = 当天必推，BURY = 当天不推（推迟 N 天即连续 N 天的 BURY）。
+        CREATE TABLE IF NOT EXISTS recommendation_override (
+            day INTEGER NOT NULL,
+            question_id INTEGER NOT NULL,
+            action TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY(day, question_id),
+            FOREIGN KEY(question_id) REFERENCES question(id)
+        );
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 13,
+        name: "add_tier_to_recommendation",
+        sql: r#"
+        -- DAILY = 每日推荐；REMEDIAL = 答错后追加的同知识点巩固题
+        ALTER TABLE recommendation ADD COLUMN tier TEXT NOT NULL DEFAULT 'DAILY';
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 14,
+        name: "knowledge_point_prerequisite",
+        sql: r#"
+        -- 知识点前置关系：学 knowledge_point 之前需要先掌握 prerequisite。
+        -- 图必须无环，环检测在写入前由 server 层完成。
+        CREATE TABLE IF NOT EXISTS knowledge_point_prerequisite (
+            knowledge_point TEXT NOT NULL,
+            prerequisite TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY(knowledge_point, prerequisite),
+            CHECK(knowledge_point <> prerequisite)
+        );
+        CREATE INDEX IF NOT EXISTS idx_kp_prerequisite_prerequisite
+            ON knowledge_point_prerequisite(prerequisite);
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 15,
+        name: "structured_recommendation_reason",
+        sql: r#"
+        -- recommendation.reason 由中文文案数组改为编码对象数组，
+        -- 例如 ["超期5天"] → [{"code":"overdue","days":5}]；认不出的文案保留为 legacy。
+        UPDATE recommendation
+        SET reason = (
+            SELECT json_group_array(json(
+                CASE
+                    WHEN j.type != 'text' THEN json_object('code', 'legacy', 'text', CAST(j.value AS TEXT))
+                    WHEN j.value = '新题' THEN json_object('code', 'new_question')
+                    WHEN j.value = '到期' THEN json_object('code', 'due')
+                    WHEN j.value = '上次出错' THEN json_object('code', 'last_wrong')
+                    WHEN j.value = '新题保送' THEN json_object('code', 'new_guaranteed')
+                    WHEN j.value = '手动置顶' THEN json_object('code', 'pinned')
+                    WHEN j.value = '同知识点巩固' THEN json_object('code', 'remedial')
+                    WHEN j.value GLOB '超期[0-9]*天' THEN
+                        json_object('code', 'overdue', 'days', CAST(substr(j.value, 3, length(j.value) - 3) AS INTEGER))
+                    WHEN j.value GLOB '错误率[0-9]*%' THEN
+                        json_object('code', 'high_error_rate', 'percent', CAST(substr(j.value, 4, length(j.value) - 4) AS INTEGER))
+                    WHEN j.value LIKE '补前置：%' THEN
+                        json_object('code', 'prerequisite', 'knowledge_point', substr(j.value, 5))
+                    ELSE json_object('code', 'legacy', 'text', j.value)
+                END
+            ))
+            FROM json_each(recommendation.reason) j
+        )
+        WHERE reason IS NOT NULL
+          AND json_valid(reason)
+          AND json_type(reason) = 'array';
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 16,
+        name: "subject_carry_over",
+        sql: r#"
+        -- 科目学习日未用完的推荐名额，顺延到下一个学习日；
+        -- updated_day 为最近一次结算的逻辑日。
+        CREATE TABLE IF NOT EXISTS subject_carry_over (
+            subject TEXT PRIMARY KEY,
+            amount INTEGER NOT NULL,
+            updated_day INTEGER NOT NULL
+        );
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 17,
+        name: "review_skip",
+        sql: r#"
+        -- 复习会话中跳过的题目。与 review 分表存放，跳过不是作答，不计入错误率。
+        CREATE TABLE IF NOT EXISTS review_skip (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            question_id INTEGER NOT NULL,
+            skipped_at INTEGER NOT NULL,
+            FOREIGN KEY(question_id) REFERENCES question(id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_review_skip_question ON review_skip(question_id, skipped_at);
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 18,
+        name: "question_text",
+        sql: r#"
+        -- 题干 / 答案 / 解析的文本内容（Markdown，行内 LaTeX）。
+        -- 每次修改追加一个新版本，version 最大的一行为当前内容；空字符串表示已清空。
+        CREATE TABLE IF NOT EXISTS question_text (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            question_id INTEGER NOT NULL,
+            field TEXT NOT NULL CHECK(field IN ('PROMPT', 'ANSWER', 'EXPLANATION')),
+            content TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            created_at INTEGER NOT NULL,
+            UNIQUE(question_id, field, version),
+            FOREIGN KEY(question_id) REFERENCES question(id)
+        );
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 19,
+        name: "user_field",
+        sql: r#"
+        -- 用户自定义元信息字段的登记表；字段值存放在 meta 表，key 为 `user.<name>`。
+        -- allowed_values 为 ENUM 字段的可选值（JSON 数组），其余类型为 NULL。
+        CREATE TABLE IF NOT EXISTS user_field (
+            name TEXT PRIMARY KEY,
+            field_type TEXT NOT NULL CHECK(field_type IN ('TEXT', 'NUMBER', 'DATE', 'ENUM', 'BOOL')),
+            allowed_values TEXT,
+            cardinality TEXT NOT NULL DEFAULT 'SINGLE' CHECK(cardinality IN ('SINGLE', 'MULTIPLE')),
+            created_at INTEGER NOT NULL
+        );
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 20,
+        name: "meta_cardinality",
+        sql: r#"
+        -- 清理 meta 中的重复数据，被删除的行记入 meta_dedupe_log 以便核对。
+        -- DUPLICATE_VALUE：同一题同一 key 的相同值，保留 id 最小的一行；
+        -- SINGLE_VALUED：单值 key 的多个值，保留 id 最大（最后写入）的一行。
+        CREATE TABLE IF NOT EXISTS meta_dedupe_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            meta_id INTEGER NOT NULL,
+            question_id INTEGER NOT NULL,
+            key TEXT NOT NULL,
+            value TEXT NOT NULL,
+            kept_meta_id INTEGER NOT NULL,
+            reason TEXT NOT NULL CHECK(reason IN ('DUPLICATE_VALUE', 'SINGLE_VALUED'))
+        );
+
+        INSERT INTO meta_dedupe_log (meta_id, question_id, key, value, kept_meta_id, reason)
+        SELECT m.id, m.question_id, m.key, m.value, k.kept_id, 'DUPLICATE_VALUE'
+        FROM meta m
+        JOIN (
+            SELECT question_id, key, value, MIN(id) AS kept_id
+            FROM meta
+            GROUP BY question_id, key, value
+            HAVING COUNT(*) > 1
+        ) k ON k.question_id = m.question_id AND k.key = m.key AND k.value = m.value
+        WHERE m.id <> k.kept_id;
+
+        DELETE FROM meta
+        WHERE id NOT IN (SELECT MIN(id) FROM meta GROUP BY question_id, key, value);
+
+        INSERT INTO meta_dedupe_log (meta_id, question_id, key, value, kept_meta_id, reason)
+        SELECT m.id, m.question_id, m.key, m.value, k.kept_id, 'SINGLE_VALUED'
+        FROM meta m
+        JOIN (
+            SELECT question_id, key, MAX(id) AS kept_id
+            FROM meta
+            WHERE key IN ('system.Subject', 'system.PreSuspendState', 'extension.SourcePaper', 'extension.Difficulty')
+               OR key IN (SELECT 'user.' || name FROM user_field WHERE cardinality = 'SINGLE')
+            GROUP BY question_id, key
+            HAVING COUNT(*) > 1
+        ) k ON k.question_id = m.question_id AND k.key = m.key
+        WHERE m.id <> k.kept_id;
+
+        DELETE FROM meta
+        WHERE (key IN ('system.Subject', 'system.PreSuspendState', 'extension.SourcePaper', 'extension.Difficulty')
+               OR key IN (SELECT 'user.' || name FROM user_field WHERE cardinality = 'SINGLE'))
+          AND id NOT IN (
+            SELECT MAX(id) FROM meta
+            WHERE key IN ('system.Subject', 'system.PreSuspendState', 'extension.SourcePaper', 'extension.Difficulty')
+               OR key IN (SELECT 'user.' || name FROM user_field WHERE cardinality = 'SINGLE')
+            GROUP BY question_id, key
+          );
+
+        -- 约束：同一题同一 key 不能有相同值；系统单值 key 每题至多一行；
+        -- 用户单值字段的基数登记在 user_field 表，用触发器检查。
+        CREATE UNIQUE INDEX IF NOT EXISTS uq_meta_question_key_value ON meta(question_id, key, value);
+        CREATE UNIQUE INDEX IF NOT EXISTS uq_meta_single_valued ON meta(question_id, key)
+            WHERE key IN ('system.Subject', 'system.PreSuspendState', 'extension.SourcePaper', 'extension.Difficulty');
+        CREATE TRIGGER IF NOT EXISTS trg_meta_user_single_valued
+        BEFORE INSERT ON meta
+        WHEN NEW.key IN (SELECT 'user.' || name FROM user_field WHERE cardinality = 'SINGLE')
+         AND EXISTS (SELECT 1 FROM meta WHERE question_id = NEW.question_id AND key = NEW.key)
+        BEGIN
+            SELECT RAISE(ABORT, 'meta cardinality violation: single-valued user field');
+        END;
+        "#,
+        after: Some(report_meta_dedupe),
+    },
+    Migration {
+        version: 21,
+        name: "subject",
+        sql: r#"
+        -- 科目表：稳定 id、显示名、颜色、排序，以及原先存放在配置文件中的科目配置。
+        -- 题目仍以 `system.Subject` 元信息引用科目名称，新名称写入时由触发器自动登记
+        -- （v31 起改为引用科目 id，见 subject_ref）。
+        CREATE TABLE IF NOT EXISTS subject (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            color TEXT,
+            sort_order INTEGER NOT NULL DEFAULT 0,
+            archived INTEGER NOT NULL DEFAULT 0,
+            recommendation_limit INTEGER,
+            max_per_knowledge_point INTEGER,
+            study_days TEXT,
+            carry_over_unused INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL
+        );
+
+        INSERT OR IGNORE INTO subject (name, created_at)
+        SELECT DISTINCT value, CAST(strftime('%s', 'now') AS INTEGER)
+        FROM meta
+        WHERE key = 'system.Subject' AND value <> ''
+          -- v31 之后元信息中存的是科目 id，回滚重跑时不再按名称登记
+          AND NOT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'view' AND name = 'meta_view')
+        ORDER BY value;
+
+        UPDATE subject SET sort_order = id WHERE sort_order = 0;
+
+        CREATE TRIGGER IF NOT EXISTS trg_meta_subject_insert
+        AFTER INSERT ON meta
+        WHEN NEW.key = 'system.Subject' AND NEW.value <> ''
+        BEGIN
+            INSERT OR IGNORE INTO subject (name, sort_order, created_at)
+            VALUES (NEW.value, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM subject), CAST(strftime('%s', 'now') AS INTEGER));
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS trg_meta_subject_update
+        AFTER UPDATE OF value ON meta
+        WHEN NEW.key = 'system.Subject' AND NEW.value <> ''
+        BEGIN
+            INSERT OR IGNORE INTO subject (name, sort_order, created_at)
+            VALUES (NEW.value, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM subject), CAST(strftime('%s', 'now') AS INTEGER));
+        END;
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 22,
+        name: "knowledge_point_tree",
+        sql: r#"
+        -- 知识点树：每科一棵（subject_id 为 NULL 的归入未分类），parent_id 为 NULL 的是根节点。
+        -- 名称全库唯一，题目仍以 `system.KnowledgePoint` 元信息引用名称。
+        CREATE TABLE IF NOT EXISTS knowledge_point (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            subject_id INTEGER,
+            parent_id INTEGER,
+            sort_order INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY(subject_id) REFERENCES subject(id),
+            FOREIGN KEY(parent_id) REFERENCES knowledge_point(id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_knowledge_point_parent ON knowledge_point(parent_id);
+
+        -- 已有知识点登记为根节点；同一知识点出现在多个科目的题目上时，归到题目最多的科目
+        INSERT OR IGNORE INTO knowledge_point (name, subject_id, created_at)
+        SELECT kp.value,
+               (SELECT s.id
+                FROM meta km
+                JOIN meta sm ON sm.question_id = km.question_id AND sm.key = 'system.Subject'
+                JOIN subject s ON s.name = sm.value
+                WHERE km.key = 'system.KnowledgePoint' AND km.value = kp.value
+                GROUP BY s.id
+                ORDER BY COUNT(*) DESC, s.id
+                LIMIT 1),
+               CAST(strftime('%s', 'now') AS INTEGER)
+        FROM (SELECT DISTINCT value FROM meta WHERE key = 'system.KnowledgePoint' AND value <> '') kp
+        ORDER BY kp.value;
+
+        UPDATE knowledge_point SET sort_order = id WHERE sort_order = 0;
+
+        -- 题目上出现新的知识点名称时，登记为该题所属科目的根节点
+        CREATE TRIGGER IF NOT EXISTS trg_meta_knowledge_point_insert
+        AFTER INSERT ON meta
+        WHEN NEW.key = 'system.KnowledgePoint' AND NEW.value <> ''
+        BEGIN
+            INSERT OR IGNORE INTO knowledge_point (name, subject_id, sort_order, created_at)
+            VALUES (
+                NEW.value,
+                (SELECT s.id FROM meta sm JOIN subject s ON s.name = sm.value
+                 WHERE sm.question_id = NEW.question_id AND sm.key = 'system.Subject'),
+                (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM knowledge_point),
+                CAST(strftime('%s', 'now') AS INTEGER)
+            );
+        END;
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 23,
+        name: "review_archive",
+        sql: r#"
+        -- 重置学习进度时归档的复习记录：不再参与排程与错误率，复习历史仍可统计
+        CREATE TABLE IF NOT EXISTS review_archive (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            review_id INTEGER NOT NULL,
+            question_id INTEGER NOT NULL,
+            reviewed_at INTEGER NOT NULL,
+            result TEXT NOT NULL,
+            archived_at INTEGER NOT NULL,
+            FOREIGN KEY(question_id) REFERENCES question(id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_review_archive_question ON review_archive(question_id);
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 24,
+        name: "question_revision",
+        sql: r#"
+        -- 题目修订历史：每次编辑后题目可编辑内容的完整快照（JSON）
+        CREATE TABLE IF NOT EXISTS question_revision (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            question_id INTEGER NOT NULL,
+            snapshot TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY(question_id) REFERENCES question(id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_question_revision_question ON question_revision(question_id);
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 25,
+        name: "question_link",
+        sql: r#"
+        -- 题目之间的关联：VARIANT_OF / FOLLOWS / CONTRASTS_WITH / SAME_SOURCE。
+        -- 不分方向的类型存成 from < to 的一行；FOLLOWS 表示 from 承接 to。
+        CREATE TABLE IF NOT EXISTS question_link (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            from_question_id INTEGER NOT NULL,
+            to_question_id INTEGER NOT NULL,
+            link_type TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            UNIQUE(from_question_id, to_question_id, link_type),
+            CHECK(from_question_id <> to_question_id),
+            FOREIGN KEY(from_question_id) REFERENCES question(id),
+            FOREIGN KEY(to_question_id) REFERENCES question(id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_question_link_to ON question_link(to_question_id);
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 26,
+        name: "question_part",
+        sql: r#"
+        -- 多小题：小题本身是一道完整的题目（自有资源、文本与复习状态），在这里挂到大题下。
+        -- 大题的状态由小题推导；小题只有一层，不再拆分。
+        CREATE TABLE IF NOT EXISTS question_part (
+            question_id INTEGER PRIMARY KEY,
+            parent_id INTEGER NOT NULL,
+            part_order INTEGER NOT NULL,
+            FOREIGN KEY(question_id) REFERENCES question(id),
+            FOREIGN KEY(parent_id) REFERENCES question(id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_question_part_parent ON question_part(parent_id, part_order);
+
+        -- 题目列表只列大题和独立的题：小题随大题一起显示；单独删除的小题仍出现在回收站
+        DROP VIEW IF EXISTS show_view;
+        CREATE VIEW show_view AS
+            SELECT
+                q.id,
+                q.name,
+                q.state,
+                q.created_at,
+                q.deleted_at,
+                m.value AS subject,
+                r.last_reviewed_at AS last_reviewed_at,
+                q.wrong_count AS wrong_count,
+                rs.error_rate AS error_rate
+            FROM question q
+            LEFT JOIN meta m
+                ON m.question_id = q.id
+                AND m.key = 'system.Subject'
+            LEFT JOIN (
+                SELECT question_id, MAX(reviewed_at) AS last_reviewed_at
+                FROM review
+                GROUP BY question_id
+            ) r ON r.question_id = q.id
+            LEFT JOIN review_summary rs ON rs.question_id = q.id
+            WHERE NOT EXISTS (
+                SELECT 1
+                FROM question_part p
+                JOIN question pq ON pq.id = p.parent_id
+                WHERE p.question_id = q.id
+                  AND (q.deleted_at IS NULL OR pq.deleted_at IS NOT NULL)
+            );
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 27,
+        name: "paper",
+        sql: r#"
+        -- 试卷：题目以 extension.SourcePaper 元信息引用试卷名称，改名时由 server 层级联更新。
+        -- exam_date 为考试日期（YYYY-MM-DD），total_score 为卷面总分。
+        CREATE TABLE IF NOT EXISTS paper (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            exam_date TEXT,
+            subject TEXT,
+            total_score REAL,
+            created_at INTEGER NOT NULL
+        );
+
+        -- 已经填过来源试卷的题目，按名称登记
+        INSERT OR IGNORE INTO paper (name, created_at)
+        SELECT DISTINCT value, CAST(strftime('%s', 'now') AS INTEGER)
+        FROM meta
+        WHERE key = 'extension.SourcePaper' AND value <> ''
+        ORDER BY value;
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 28,
+        name: "cloze_card",
+        sql: r#"
+        -- 填空卡：题干中的 {{c1::...}} 为笔记生成的小题（仍挂在 question_part 下），这里记录填空编号。
+        -- 卡片随题干编辑生成 / 移入回收站，同一笔记同一编号只有一张卡。
+        CREATE TABLE IF NOT EXISTS cloze_card (
+            question_id INTEGER PRIMARY KEY,
+            note_id INTEGER NOT NULL,
+            cloze_index INTEGER NOT NULL,
+            UNIQUE(note_id, cloze_index),
+            FOREIGN KEY(question_id) REFERENCES question(id),
+            FOREIGN KEY(note_id) REFERENCES question(id)
+        );
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 29,
+        name: "occlusion_mask",
+        sql: r#"
+        -- 图片遮罩：题目图 / 答案图上框出的矩形，坐标与宽高按图片尺寸归一化到 0..1。
+        -- 每个遮罩对应笔记下的一张卡片（question_part 的小题，card_id），复习时遮住该区域。
+        -- 换图时遮罩挪到新图上并升一个版本，旧版本保留并记 superseded_at；同一卡片只有一个当前版本。
+        CREATE TABLE IF NOT EXISTS occlusion_mask (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            card_id INTEGER NOT NULL,
+            asset_id INTEGER NOT NULL,
+            version INTEGER NOT NULL,
+            x REAL NOT NULL,
+            y REAL NOT NULL,
+            width REAL NOT NULL,
+            height REAL NOT NULL,
+            created_at INTEGER NOT NULL,
+            superseded_at INTEGER,
+            FOREIGN KEY(card_id) REFERENCES question(id),
+            FOREIGN KEY(asset_id) REFERENCES asset(id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_occlusion_mask_asset ON occlusion_mask(asset_id);
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_occlusion_mask_current
+            ON occlusion_mask(card_id) WHERE superseded_at IS NULL;
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 30,
+        name: "answer_grading",
+        sql: r#"
+        -- 简答题自动判分：复习时输入的作答、当时的标准答案、判分建议（suggested）与用户最终的结果（final_result）。
+        -- final_result 在用户采纳或改判后写入，两者对比即判分准确率。
+        CREATE TABLE IF NOT EXISTS answer_grading (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            question_id INTEGER NOT NULL,
+            response TEXT NOT NULL,
+            expected TEXT NOT NULL,
+            suggested TEXT NOT NULL,
+            final_result TEXT,
+            created_at INTEGER NOT NULL,
+            decided_at INTEGER,
+            FOREIGN KEY(question_id) REFERENCES question(id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_answer_grading_question ON answer_grading(question_id);
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 31,
+        name: "subject_ref",
+        sql: r#"
+        -- 题目的 `system.Subject` 元信息改存科目 id，名称经 meta_view 联查 subject 表得到，
+        -- 科目改名只需改 subject 表。新科目在写入元信息时登记，不再依赖触发器。
+        DROP TRIGGER IF EXISTS trg_meta_subject_insert;
+        DROP TRIGGER IF EXISTS trg_meta_subject_update;
+
+        -- 名称 -> id。meta_view 在本迁移末尾创建，回滚重跑时值已经是 id，不再转换
+        UPDATE meta
+        SET value = CAST((SELECT s.id FROM subject s WHERE s.name = meta.value) AS TEXT)
+        WHERE key = 'system.Subject'
+          AND EXISTS (SELECT 1 FROM subject s WHERE s.name = meta.value)
+          AND NOT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'view' AND name = 'meta_view');
+
+        -- 读取元信息统一经过该视图：科目给出名称，其他 key 原样
+        CREATE VIEW IF NOT EXISTS meta_view AS
+            SELECT
+                m.id,
+                m.question_id,
+                m.key,
+                CASE WHEN m.key = 'system.Subject' THEN COALESCE(s.name, m.value) ELSE m.value END AS value
+            FROM meta m
+            LEFT JOIN subject s
+                ON m.key = 'system.Subject'
+                AND s.id = CAST(m.value AS INTEGER);
+
+        DROP VIEW IF EXISTS show_view;
+        CREATE VIEW show_view AS
+            SELECT
+                q.id,
+                q.name,
+                q.state,
+                q.created_at,
+                q.deleted_at,
+                m.value AS subject,
+                r.last_reviewed_at AS last_reviewed_at,
+                q.wrong_count AS wrong_count,
+                rs.error_rate AS error_rate
+            FROM question q
+            LEFT JOIN meta_view m
+                ON m.question_id = q.id
+                AND m.key = 'system.Subject'
+            LEFT JOIN (
+                SELECT question_id, MAX(reviewed_at) AS last_reviewed_at
+                FROM review
+                GROUP BY question_id
+            ) r ON r.question_id = q.id
+            LEFT JOIN review_summary rs ON rs.question_id = q.id
+            WHERE NOT EXISTS (
+                SELECT 1
+                FROM question_part p
+                JOIN question pq ON pq.id = p.parent_id
+                WHERE p.question_id = q.id
+                  AND (q.deleted_at IS NULL OR pq.deleted_at IS NOT NULL)
+            );
+
+        -- 新知识点登记到题目所属科目：按 id 找科目
+        DROP TRIGGER IF EXISTS trg_meta_knowledge_point_insert;
+        CREATE TRIGGER trg_meta_knowledge_point_insert
+        AFTER INSERT ON meta
+        WHEN NEW.key = 'system.KnowledgePoint' AND NEW.value <> ''
+        BEGIN
+            INSERT OR IGNORE INTO knowledge_point (name, subject_id, sort_order, created_at)
+            VALUES (
+                NEW.value,
+                (SELECT s.id FROM meta sm JOIN subject s ON s.id = CAST(sm.value AS INTEGER)
+                 WHERE sm.question_id = NEW.question_id AND sm.key = 'system.Subject'),
+                (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM knowledge_point),
+                CAST(strftime('%s', 'now') AS INTEGER)
+            );
+        END;
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 32,
+        name: "occlusion_image_version",
+        sql: r#"
+        -- 画遮罩的图片的版本：换过几次图就是第几版，遮罩全部移除后再画新遮罩也沿用。
+        -- 没有记录的图片为第 1 版。
+        CREATE TABLE IF NOT EXISTS occlusion_image (
+            asset_id INTEGER PRIMARY KEY,
+            version INTEGER NOT NULL,
+            FOREIGN KEY(asset_id) REFERENCES asset(id)
+        );
+
+        INSERT OR IGNORE INTO occlusion_image (asset_id, version)
+        SELECT asset_id, MAX(version) FROM occlusion_mask GROUP BY asset_id HAVING MAX(version) > 1;
+        "#,
+        after: None,
+    },
+    Migration {
+        version: 33,
+        name: "paper_subject_ref",
+        sql: r#"
+        -- 试卷的科目与题目的 `system.Subject` 一样改存科目 id，名称经 paper_view 联查 subject 表得到，
+        -- 科目改名无需改动试卷，合并时由 server 层改指向目标科目。
+        -- paper_view 在本迁移末尾创建，回滚重跑时值已经是 id，不再登记、转换。
+        -- 试卷上还没登记的科目名称先登记为科目
+        INSERT OR IGNORE INTO subject (name, sort_order, created_at)
+        SELECT DISTINCT p.subject,
+               (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM subject),
+               CAST(strftime('%s', 'now') AS INTEGER)
+        FROM paper p
+        WHERE p.subject IS NOT NULL AND p.subject <> ''
+          AND NOT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'view' AND name = 'paper_view');
+
+        UPDATE paper
+        SET subject = CAST((SELECT s.id FROM subject s WHERE s.name = paper.subject) AS TEXT)
+        WHERE EXISTS (SELECT 1 FROM subject s WHERE s.name = paper.subject)
+          AND NOT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'view' AND name = 'paper_view');
+
+        -- 读取试卷统一经过该视图：subject 为科目名称，subject_id 为科目 id
+        CREATE VIEW IF NOT EXISTS paper_view AS
+            SELECT p.id, p.name, p.exam_date, s.name AS subject, s.id AS subject_id, p.total_score, p.created_at
+            FROM paper p
+            LEFT JOIN subject s ON s.id = CAST(p.subject AS INTEGER);
+        "#,
+        after: None,
     },
 ];
 
@@ -224,12 +862,30 @@ pub fn migrate(conn: &mut Connection) -> Result<()> {
     for migration in MIGRATIONS {
         if migration.version > current_version {
             apply_migration(conn, migration)?;
+            if let Some(after) = migration.after {
+                after(conn)?;
+            }
         }
     }
 
     Ok(())
 }
 
+// 报告 meta_cardinality 迁移清理掉的重复 meta 行（明细见 meta_dedupe_log 表）
+fn report_meta_dedupe(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT reason, key, COUNT(*) FROM meta_dedupe_log GROUP BY reason, key ORDER BY reason, key",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+    })?;
+    for row in rows {
+        let (reason, key, count) = row?;
+        eprintln!("meta_cardinality: removed {} row(s) of {} ({})", count, key, reason);
+    }
+    Ok(())
+}
+
 // 工具函数，确保 schema_version 表存在
 fn ensure_schema_version_table(conn: &Connection) -> Result<()> {
     conn.execute(
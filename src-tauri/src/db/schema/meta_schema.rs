@@ -3,6 +3,9 @@ use rusqlite::Result;
 use crate::db::error::DbError;
 use crate::db::Connection;
 
+/// 科目元信息的 key：表中存科目 id，读取时经 `meta_view` 换成科目名称
+const SUBJECT_KEY: &str = "system.Subject";
+
 #[derive(Debug, Clone)]
 pub struct MetaRow {
     pub id: i64,
@@ -10,13 +13,20 @@ pub struct MetaRow {
     pub key: String,
     pub value: String,
 }
-/* 增加一条记录 */
+/* 增加一条记录；科目按名称传入，存为科目 id（新名称先登记到 subject 表） */
 pub fn insert_meta(
     conn: &Connection,
     question_id: i64,
     key: &str,
     value: &str,
 ) -> Result<i64, DbError> {
+    let subject_ref;
+    let value = if key == SUBJECT_KEY && !value.is_empty() {
+        subject_ref = subject_id_of(conn, value)?.to_string();
+        subject_ref.as_str()
+    } else {
+        value
+    };
     conn.execute(
         r#"
         INSERT INTO meta (question_id, key, value)
@@ -27,6 +37,18 @@ pub fn insert_meta(
 
     Ok(conn.last_insert_rowid())
 }
+
+/* 科目名称对应的 id，不存在时登记为排在最后的新科目 */
+fn subject_id_of(conn: &Connection, name: &str) -> Result<i64, DbError> {
+    conn.execute(
+        r#"
+        INSERT OR IGNORE INTO subject (name, sort_order, created_at)
+        VALUES (?1, (SELECT COALESCE(MAX(sort_order), 0) + 1 FROM subject), CAST(strftime('%s', 'now') AS INTEGER))
+        "#,
+        (name,),
+    )?;
+    Ok(conn.query_row("SELECT id FROM subject WHERE name = ?1", (name,), |row| row.get(0))?)
+}
 /* 删除一条记录 */
 pub fn delete_meta(conn: &Connection, meta_id: i64) -> Result<(), DbError> {
     conn.execute(
@@ -64,7 +86,7 @@ pub fn select_meta_by_id(conn: &Connection, id: i64) -> Result<Option<MetaRow>,
     let mut stmt = conn.prepare(
         r#"
         SELECT id, question_id, key, value
-        FROM meta
+        FROM meta_view
         WHERE id = ?1
         "#,
     )?;
@@ -93,8 +115,9 @@ pub fn select_meta_by_question(
     let mut stmt = conn.prepare(
         r#"
         SELECT id, question_id, key, value
-        FROM meta
+        FROM meta_view
         WHERE question_id = ?1
+        ORDER BY id
         "#,
     )?;
 
@@ -119,7 +142,7 @@ pub fn select_meta_by_question_key(
     let mut stmt = conn.prepare(
         r#"
         SELECT id, question_id, key, value
-        FROM meta
+        FROM meta_view
         WHERE question_id = ?1 AND key = ?2
         "#,
     )?;
@@ -148,8 +171,9 @@ pub fn select_meta_values_by_question_key(
     let mut stmt = conn.prepare(
         r#"
         SELECT value
-        FROM meta
+        FROM meta_view
         WHERE question_id = ?1 AND key = ?2
+        ORDER BY id
         "#,
     )?;
 
@@ -184,7 +208,7 @@ pub fn select_meta_values_by_question_ids(
         .collect::<Vec<_>>()
         .join(",");
     let sql = format!(
-        "SELECT question_id, value FROM meta WHERE question_id IN ({}) AND key = ?",
+        "SELECT question_id, value FROM meta_view WHERE question_id IN ({}) AND key = ? ORDER BY id",
         placeholders
     );
 
@@ -226,7 +250,7 @@ pub fn select_distinct_values_by_key(
     let mut stmt = conn.prepare(
         r#"
         SELECT DISTINCT value
-        FROM meta
+        FROM meta_view
         WHERE key = ?1
         ORDER BY value
         "#,
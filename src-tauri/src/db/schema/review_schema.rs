@@ -198,7 +198,55 @@ pub fn delete_reviews_by_question(conn: &Connection, question_id: i64) -> Result
     Ok(())
 }
 
+/*
+    把指定题目的复习记录移入 review_archive
+    输入：
+        question_id: 题目ID
+        archived_at: 归档时间
+    输出：
+        归档的记录条数
+*/
+pub fn archive_reviews_by_question(
+    conn: &Connection,
+    question_id: i64,
+    archived_at: i64,
+) -> Result<usize, DbError> {
+    conn.execute(
+        r#"
+        INSERT INTO review_archive (review_id, question_id, reviewed_at, result, archived_at)
+        SELECT id, question_id, reviewed_at, result, ?2 FROM review WHERE question_id = ?1
+        ORDER BY id
+        "#,
+        (question_id, archived_at),
+    )?;
+    let n = conn.execute(
+        r#"
+        DELETE FROM review WHERE question_id = ?1
+        "#,
+        (question_id,),
+    )?;
+    Ok(n)
+}
+
+/*
+    删除指定题目的全部归档复习记录
+    输入：
+        question_id: 题目ID
+    输出：
+        若删除成功，返回空值
+*/
+pub fn delete_archived_reviews_by_question(conn: &Connection, question_id: i64) -> Result<(), DbError> {
+    conn.execute(
+        r#"
+        DELETE FROM review_archive WHERE question_id = ?1
+        "#,
+        (question_id,),
+    )?;
+    Ok(())
+}
+
 /// 每科目错误率统计行（不分时间）。
+/// `skip_rate` = 跳过次数 / (复习次数 + 跳过次数)。
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct SubjectStatRow {
     pub subject: String,
@@ -206,6 +254,8 @@ pub struct SubjectStatRow {
     pub correct_count: i64,
     pub wrong_count: i64,
     pub fuzzy_count: i64,
+    pub skip_count: i64,
+    pub skip_rate: f64,
 }
 
 /// 每日 × 科目 复习行为时间序列。
@@ -219,9 +269,10 @@ pub struct DailySeriesRow {
     pub wrong_count: i64,
 }
 
-/// 每个科目的错误率统计。
+/// 每个科目的错误率与跳过率统计。
 /// `subject_filter = None` 即"全部科目"；`Some(name)` 即只取给定科目。
 /// 没有 subject meta 的题记为 `"__未分类__"`（前端用 settings 隐藏）。
+/// 跳过记录来自 `review_skip`，只计入 skip_count，不影响各作答计数。
 pub fn select_subject_error_stats(
     conn: &Connection,
     subject_filter: Option<&str>,
@@ -230,12 +281,18 @@ pub fn select_subject_error_stats(
         r#"
         SELECT
             COALESCE(m.value, '__未分类__') AS subject,
-            COUNT(*) AS review_count,
+            SUM(r.is_review) AS review_count,
             SUM(CASE WHEN LOWER(r.result) = 'correct' THEN 1 ELSE 0 END) AS correct_count,
             SUM(CASE WHEN LOWER(r.result) = 'wrong'   THEN 1 ELSE 0 END) AS wrong_count,
-            SUM(CASE WHEN LOWER(r.result) = 'fuzzy'   THEN 1 ELSE 0 END) AS fuzzy_count
-        FROM review r
-        LEFT JOIN meta m
+            SUM(CASE WHEN LOWER(r.result) = 'fuzzy'   THEN 1 ELSE 0 END) AS fuzzy_count,
+            SUM(1 - r.is_review) AS skip_count,
+            SUM(1 - r.is_review) * 1.0 / COUNT(*) AS skip_rate
+        FROM (
+            SELECT question_id, result, 1 AS is_review FROM review
+            UNION ALL
+            SELECT question_id, NULL, 0 FROM review_skip
+        ) r
+        LEFT JOIN meta_view m
             ON m.question_id = r.question_id
             AND m.key = 'system.Subject'
         WHERE (?1 IS NULL OR m.value = ?1)
@@ -251,6 +308,8 @@ pub fn select_subject_error_stats(
             correct_count: row.get(2)?,
             wrong_count: row.get(3)?,
             fuzzy_count: row.get(4)?,
+            skip_count: row.get(5)?,
+            skip_rate: row.get(6)?,
         })
     })?;
 
@@ -263,6 +322,7 @@ pub fn select_subject_error_stats(
 /// 这与 `LogicalDay` 抽象保持一致；offset_sec = 时区偏移秒，cutoff_sec = 切日秒。
 /// 前端按日历月过滤时也按同一公式算 start_day_bucket / end_day_bucket，
 /// 后端只要 `WHERE day_bucket BETWEEN ? AND ?` 即可。
+/// 复习历史包括重置进度时归档的记录（`review_archive`）。
 pub fn select_review_daily_series(
     conn: &Connection,
     subject_filter: Option<&str>,
@@ -279,8 +339,12 @@ pub fn select_review_daily_series(
             COUNT(*) AS review_count,
             SUM(CASE WHEN LOWER(r.result) = 'correct' THEN 1 ELSE 0 END) AS correct_count,
             SUM(CASE WHEN LOWER(r.result) = 'wrong'   THEN 1 ELSE 0 END) AS wrong_count
-        FROM review r
-        LEFT JOIN meta m
+        FROM (
+            SELECT question_id, reviewed_at, result FROM review
+            UNION ALL
+            SELECT question_id, reviewed_at, result FROM review_archive
+        ) r
+        LEFT JOIN meta_view m
             ON m.question_id = r.question_id
             AND m.key = 'system.Subject'
         WHERE (?1 IS NULL OR m.value = ?1)
@@ -1,3 +1,4 @@
+use rusqlite::types::Value;
 use rusqlite::Result;
 
 use crate::db::error::DbError;
@@ -17,6 +18,7 @@ pub struct ViewRow {
 }
 
 const KP_META_KEY: &str = "system.KnowledgePoint";
+const TAG_META_KEY: &str = "system.Tag";
 
 /// 从 show_view 的 SELECT 行构建 ViewRow。
 /// 调用方必须按以下顺序列出列：id, name, state, created_at, deleted_at,
@@ -120,56 +122,154 @@ pub fn select_deleted_views_page(
     iter.collect::<Result<Vec<_>, _>>().map_err(Into::into)
 }
 
-/// 分类查询：按 `subject` 与 `state` 过滤未删除题目，两者均可选。
+/// 按某个元信息 key 的值过滤：题目只要有一个值满足条件即命中。
+#[derive(Debug, Clone)]
+pub struct MetaFilter {
+    pub key: String,
+    pub cond: MetaCond,
+}
+
+#[derive(Debug, Clone)]
+pub enum MetaCond {
+    /// 值相等
+    Equals(String),
+    /// 值包含子串
+    Contains(String),
+    /// 值等于其中任一项（如知识点及其后代节点）
+    OneOf(Vec<String>),
+    /// 按数值比较的闭区间，端点可缺省
+    NumberRange { min: Option<f64>, max: Option<f64> },
+    /// 按字符串比较的闭区间，端点可缺省（`YYYY-MM-DD` 日期的字典序即时间序）
+    TextRange { min: Option<String>, max: Option<String> },
+}
+
+impl MetaFilter {
+    /// 追加 `AND EXISTS (...)` 子句及其参数
+    fn push_sql(&self, sql: &mut String, params: &mut Vec<Value>) {
+        sql.push_str(" AND EXISTS (SELECT 1 FROM meta_view m WHERE m.question_id = show_view.id AND m.key = ?");
+        params.push(Value::Text(self.key.clone()));
+        match &self.cond {
+            MetaCond::Equals(v) => {
+                sql.push_str(" AND m.value = ?");
+                params.push(Value::Text(v.clone()));
+            }
+            MetaCond::Contains(v) => {
+                sql.push_str(" AND m.value LIKE ?");
+                params.push(Value::Text(format!("%{}%", v)));
+            }
+            MetaCond::OneOf(values) => {
+                sql.push_str(" AND m.value IN (");
+                sql.push_str(&vec!["?"; values.len()].join(","));
+                sql.push(')');
+                params.extend(values.iter().cloned().map(Value::Text));
+            }
+            MetaCond::NumberRange { min, max } => {
+                if let Some(min) = min {
+                    sql.push_str(" AND CAST(m.value AS REAL) >= ?");
+                    params.push(Value::Real(*min));
+                }
+                if let Some(max) = max {
+                    sql.push_str(" AND CAST(m.value AS REAL) <= ?");
+                    params.push(Value::Real(*max));
+                }
+            }
+            MetaCond::TextRange { min, max } => {
+                if let Some(min) = min {
+                    sql.push_str(" AND m.value >= ?");
+                    params.push(Value::Text(min.clone()));
+                }
+                if let Some(max) = max {
+                    sql.push_str(" AND m.value <= ?");
+                    params.push(Value::Text(max.clone()));
+                }
+            }
+        }
+        sql.push(')');
+    }
+}
+
+/// 追加标签过滤：题目须带有 `tags` 中的每一个标签。`view` 为外层视图的名称或别名。
+fn push_tag_sql(sql: &mut String, params: &mut Vec<Value>, tags: &[String], view: &str) {
+    for tag in tags {
+        sql.push_str(&format!(
+            " AND EXISTS (SELECT 1 FROM meta tg WHERE tg.question_id = {}.id AND tg.key = '{}' AND tg.value = ?)",
+            view, TAG_META_KEY
+        ));
+        params.push(Value::Text(tag.clone()));
+    }
+}
+
+/// 分类查询：按 `subject` 与 `state` 过滤未删除题目，两者均可选；
+/// `tags` 非空时须带有其中每一个标签；
+/// `meta_filters` 为附加的元信息过滤条件（知识点、用户自定义字段），全部满足才命中。
 ///
 /// 0-indexed 分页：`offset = page * page_size`。
 pub fn select_views_classified(
     conn: &Connection,
     subject: Option<&str>,
     state: Option<&str>,
+    tags: &[String],
+    meta_filters: &[MetaFilter],
     limit: i64,
     offset: i64,
 ) -> Result<Vec<ViewRow>, DbError> {
-    let subject_present = subject.is_some();
-    let state_present = state.is_some();
-
     let mut sql = String::from(
         "SELECT id, name, state, created_at, deleted_at, subject, last_reviewed_at, \
                 wrong_count, error_rate \
          FROM show_view \
          WHERE deleted_at IS NULL",
     );
-    if subject_present {
+    let mut params: Vec<Value> = Vec::new();
+    if let Some(subject) = subject {
         sql.push_str(" AND subject = ?");
+        params.push(Value::Text(subject.to_string()));
     }
-    if state_present {
+    if let Some(state) = state {
         sql.push_str(" AND state = ?");
+        params.push(Value::Text(state.to_string()));
+    }
+    push_tag_sql(&mut sql, &mut params, tags, "show_view");
+    for filter in meta_filters {
+        filter.push_sql(&mut sql, &mut params);
     }
     sql.push_str(" ORDER BY created_at DESC LIMIT ? OFFSET ?");
+    params.push(Value::Integer(limit));
+    params.push(Value::Integer(offset));
 
     let mut stmt = conn.prepare(&sql)?;
+    let iter = stmt.query_map(rusqlite::params_from_iter(params), row_from)?;
+    iter.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+}
 
-    // 用具体类型让 rusqlite 能正确绑定 NULL
-    let subject_param: Option<String> = subject.map(String::from);
-    let state_param: Option<String> = state.map(String::from);
-
-    // 占位符顺序：subject?, state?, limit, offset
-    let iter = match (subject_present, state_present) {
-        (true, true) => stmt.query_map(
-            rusqlite::params![subject_param, state_param, limit, offset],
-            row_from,
-        )?,
-        (true, false) => stmt.query_map(
-            rusqlite::params![subject_param, limit, offset],
-            row_from,
-        )?,
-        (false, true) => stmt.query_map(
-            rusqlite::params![state_param, limit, offset],
-            row_from,
-        )?,
-        (false, false) => stmt.query_map(rusqlite::params![limit, offset], row_from)?,
-    };
+/// 批量操作用：按条件取全部命中题目的 id（不分页），条件同 `select_views_classified`；
+/// `deleted` 为 true 时在回收站（已删除题目）中查找。
+pub fn select_question_ids_filtered(
+    conn: &Connection,
+    deleted: bool,
+    subject: Option<&str>,
+    state: Option<&str>,
+    tags: &[String],
+    meta_filters: &[MetaFilter],
+) -> Result<Vec<i64>, DbError> {
+    let mut sql = String::from("SELECT id FROM show_view WHERE deleted_at IS ");
+    sql.push_str(if deleted { "NOT NULL" } else { "NULL" });
+    let mut params: Vec<Value> = Vec::new();
+    if let Some(subject) = subject {
+        sql.push_str(" AND subject = ?");
+        params.push(Value::Text(subject.to_string()));
+    }
+    if let Some(state) = state {
+        sql.push_str(" AND state = ?");
+        params.push(Value::Text(state.to_string()));
+    }
+    push_tag_sql(&mut sql, &mut params, tags, "show_view");
+    for filter in meta_filters {
+        filter.push_sql(&mut sql, &mut params);
+    }
+    sql.push_str(" ORDER BY id");
 
+    let mut stmt = conn.prepare(&sql)?;
+    let iter = stmt.query_map(rusqlite::params_from_iter(params), |row| row.get(0))?;
     iter.collect::<Result<Vec<_>, _>>().map_err(Into::into)
 }
 
@@ -196,19 +296,18 @@ pub fn select_view_active_by_id(
 }
 
 /// 模糊搜索：`pattern` 必须已经包含 `%` 通配符。
-/// 匹配 `name` 或知识点（`meta.key = 'system.KnowledgePoint'` 的 `value`），用 EXISTS 子查询。
-/// 可叠加 `subject` / `state` 过滤（Mode B）。
+/// 匹配 `name`、知识点（`meta.key = 'system.KnowledgePoint'` 的 `value`）
+/// 或题干 / 答案 / 解析的当前版本文本（`question_text`），后两者用 EXISTS 子查询。
+/// 可叠加 `subject` / `state` / 标签过滤（Mode B），标签须全部带有。
 pub fn select_views_search_fuzzy(
     conn: &Connection,
     pattern: &str,
     subject: Option<&str>,
     state: Option<&str>,
+    tags: &[String],
     limit: i64,
     offset: i64,
 ) -> Result<Vec<ViewRow>, DbError> {
-    let subject_present = subject.is_some();
-    let state_present = state.is_some();
-
     let mut sql = String::from(
         "SELECT v.id, v.name, v.state, v.created_at, v.deleted_at, v.subject, v.last_reviewed_at, \
                 v.wrong_count, v.error_rate \
@@ -220,39 +319,31 @@ pub fn select_views_search_fuzzy(
                           AND m.key = '",
     );
     sql.push_str(KP_META_KEY);
-    sql.push_str("' AND m.value LIKE ?))");
-    if subject_present {
+    sql.push_str(
+        "' AND m.value LIKE ?) \
+             OR EXISTS (SELECT 1 FROM question_text t \
+                        WHERE t.question_id = v.id \
+                          AND t.content LIKE ? \
+                          AND t.version = (SELECT MAX(t2.version) FROM question_text t2 \
+                                           WHERE t2.question_id = t.question_id \
+                                             AND t2.field = t.field)))",
+    );
+    // 占位符顺序：pattern(name), pattern(kp), pattern(text), subject?, state?, tags..., limit, offset
+    let mut params: Vec<Value> = vec![Value::Text(pattern.to_string()); 3];
+    if let Some(subject) = subject {
         sql.push_str(" AND v.subject = ?");
+        params.push(Value::Text(subject.to_string()));
     }
-    if state_present {
+    if let Some(state) = state {
         sql.push_str(" AND v.state = ?");
+        params.push(Value::Text(state.to_string()));
     }
+    push_tag_sql(&mut sql, &mut params, tags, "v");
     sql.push_str(" ORDER BY v.created_at DESC LIMIT ? OFFSET ?");
+    params.push(Value::Integer(limit));
+    params.push(Value::Integer(offset));
 
     let mut stmt = conn.prepare(&sql)?;
-
-    let subject_param: Option<String> = subject.map(String::from);
-    let state_param: Option<String> = state.map(String::from);
-
-    // 占位符顺序：pattern(name), pattern(kp), subject?, state?, limit, offset
-    let iter = match (subject_present, state_present) {
-        (true, true) => stmt.query_map(
-            rusqlite::params![pattern, pattern, subject_param, state_param, limit, offset],
-            row_from,
-        )?,
-        (true, false) => stmt.query_map(
-            rusqlite::params![pattern, pattern, subject_param, limit, offset],
-            row_from,
-        )?,
-        (false, true) => stmt.query_map(
-            rusqlite::params![pattern, pattern, state_param, limit, offset],
-            row_from,
-        )?,
-        (false, false) => stmt.query_map(
-            rusqlite::params![pattern, pattern, limit, offset],
-            row_from,
-        )?,
-    };
-
+    let iter = stmt.query_map(rusqlite::params_from_iter(params), row_from)?;
     iter.collect::<Result<Vec<_>, _>>().map_err(Into::into)
 }
\ No newline at end of file
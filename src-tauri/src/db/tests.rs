@@ -402,7 +402,7 @@ fn seed_view_data() -> (Connection, i64, i64, i64) {
 #[test]
 fn test_classify_all_none_returns_all() {
     let (conn, q1, q2, q3) = seed_view_data();
-    let rows = select_views_classified(&conn, None, None, 10, 0).unwrap();
+    let rows = select_views_classified(&conn, None, None, &[], &[], 10, 0).unwrap();
     let ids: Vec<i64> = rows.iter().map(|r| r.id).collect();
     assert_eq!(ids.len(), 3);
     assert!(ids.contains(&q1) && ids.contains(&q2) && ids.contains(&q3));
@@ -411,7 +411,7 @@ fn test_classify_all_none_returns_all() {
 #[test]
 fn test_classify_subject_only() {
     let (conn, q1, _, q3) = seed_view_data();
-    let rows = select_views_classified(&conn, Some("数学"), None, 10, 0).unwrap();
+    let rows = select_views_classified(&conn, Some("数学"), None, &[], &[], 10, 0).unwrap();
     let ids: Vec<i64> = rows.iter().map(|r| r.id).collect();
     assert_eq!(ids.len(), 2);
     assert!(ids.contains(&q1) && ids.contains(&q3));
@@ -420,7 +420,7 @@ fn test_classify_subject_only() {
 #[test]
 fn test_classify_state_only() {
     let (conn, q1, _, _) = seed_view_data();
-    let rows = select_views_classified(&conn, None, Some("NEW"), 10, 0).unwrap();
+    let rows = select_views_classified(&conn, None, Some("NEW"), &[], &[], 10, 0).unwrap();
     let ids: Vec<i64> = rows.iter().map(|r| r.id).collect();
     assert_eq!(ids.len(), 1);
     assert_eq!(ids[0], q1);
@@ -429,7 +429,7 @@ fn test_classify_state_only() {
 #[test]
 fn test_classify_subject_and_state() {
     let (conn, q1, _, _) = seed_view_data();
-    let rows = select_views_classified(&conn, Some("数学"), Some("NEW"), 10, 0).unwrap();
+    let rows = select_views_classified(&conn, Some("数学"), Some("NEW"), &[], &[], 10, 0).unwrap();
     let ids: Vec<i64> = rows.iter().map(|r| r.id).collect();
     assert_eq!(ids.len(), 1);
     assert_eq!(ids[0], q1);
@@ -442,7 +442,7 @@ fn test_classify_excludes_deleted() {
     let qid = insert_question(&conn, Some("被删除"), "NEW", now).unwrap();
     insert_meta(&conn, qid, "system.Subject", "数学").unwrap();
     update_question_deleted_at(&conn, qid, Some(now + 100)).unwrap();
-    let rows = select_views_classified(&conn, None, None, 10, 0).unwrap();
+    let rows = select_views_classified(&conn, None, None, &[], &[], 10, 0).unwrap();
     assert!(rows.iter().all(|r| r.id != qid));
 }
 
@@ -463,7 +463,7 @@ fn test_v11_show_view_includes_wrong_count_and_error_rate() {
     insert_review(&conn, qid, "correct", now + 4).unwrap();
     update_question_review_fields(&conn, qid, Some(now + 4), Some("correct"), 2, 3, Some(now + 86400)).unwrap();
 
-    let rows = select_views_classified(&conn, None, None, 10, 0).unwrap();
+    let rows = select_views_classified(&conn, None, None, &[], &[], 10, 0).unwrap();
     let row = rows.iter().find(|r| r.id == qid).expect("row for qid");
     assert_eq!(row.wrong_count, 3);
     let rate = row.error_rate.expect("error_rate 应有值");
@@ -472,7 +472,7 @@ fn test_v11_show_view_includes_wrong_count_and_error_rate() {
     // 新题零 review: error_rate 应为 None, wrong_count 为 0
     let qid2 = insert_question(&conn, Some("新题"), "NEW", now + 100).unwrap();
     insert_meta(&conn, qid2, "system.Subject", "数学").unwrap();
-    let row2 = select_views_classified(&conn, None, None, 10, 0).unwrap()
+    let row2 = select_views_classified(&conn, None, None, &[], &[], 10, 0).unwrap()
         .into_iter()
         .find(|r| r.id == qid2)
         .expect("row for qid2");
@@ -491,7 +491,7 @@ fn test_search_by_id_exact() {
 #[test]
 fn test_search_fuzzy_by_name() {
     let (conn, q1, _, _) = seed_view_data();
-    let rows = select_views_search_fuzzy(&conn, "%导数%", None, None, 10, 0).unwrap();
+    let rows = select_views_search_fuzzy(&conn, "%导数%", None, None, &[], 10, 0).unwrap();
     let ids: Vec<i64> = rows.iter().map(|r| r.id).collect();
     assert_eq!(ids.len(), 1);
     assert_eq!(ids[0], q1);
@@ -501,7 +501,7 @@ fn test_search_fuzzy_by_name() {
 fn test_search_fuzzy_by_kp_via_exists() {
     let (conn, _, q2, _) = seed_view_data();
     // 题目名不含"牛顿"，但知识点含"牛顿"——EXISTS 子查询应匹配
-    let rows = select_views_search_fuzzy(&conn, "%牛顿%", None, None, 10, 0).unwrap();
+    let rows = select_views_search_fuzzy(&conn, "%牛顿%", None, None, &[], 10, 0).unwrap();
     let ids: Vec<i64> = rows.iter().map(|r| r.id).collect();
     assert_eq!(ids.len(), 1);
     assert_eq!(ids[0], q2);
@@ -512,16 +512,58 @@ fn test_search_fuzzy_with_subject_filter() {
     let (conn, _, _, q3) = seed_view_data();
     // "极限" 只在题目 3 (q3) 的 name 里；subject=数学 应当匹配 q3
     let rows =
-        select_views_search_fuzzy(&conn, "%极限%", Some("数学"), None, 10, 0).unwrap();
+        select_views_search_fuzzy(&conn, "%极限%", Some("数学"), None, &[], 10, 0).unwrap();
     let ids: Vec<i64> = rows.iter().map(|r| r.id).collect();
     assert_eq!(ids.len(), 1);
     assert!(ids.contains(&q3));
 }
 
+#[test]
+fn test_search_fuzzy_by_current_text_only() {
+    use crate::dao::QuestionTextDao;
+    use crate::domain::enums::TextField;
+
+    let (conn, _, _, q3) = seed_view_data();
+    let td = QuestionTextDao::new(&conn);
+    td.insert_version(q3, TextField::PROMPT, "求 $\\lim_{x \\to 0} \\frac{\\sin x}{x}$", 0).unwrap();
+    td.insert_version(q3, TextField::ANSWER, "洛必达法则", 0).unwrap();
+
+    let rows = select_views_search_fuzzy(&conn, "%sin x%", None, None, &[], 10, 0).unwrap();
+    assert_eq!(rows.iter().map(|r| r.id).collect::<Vec<_>>(), vec![q3]);
+
+    // 改写答案后，旧版本文本不再参与搜索
+    td.insert_version(q3, TextField::ANSWER, "重要极限", 1).unwrap();
+    assert!(select_views_search_fuzzy(&conn, "%洛必达%", None, None, &[], 10, 0).unwrap().is_empty());
+    assert_eq!(select_views_search_fuzzy(&conn, "%重要极限%", None, None, &[], 10, 0).unwrap().len(), 1);
+}
+
+#[test]
+fn test_question_text_versions() {
+    use crate::dao::QuestionTextDao;
+    use crate::domain::enums::TextField;
+
+    let conn = setup_test_db();
+    let qid = insert_question(&conn, Some("单词"), "NEW", 0).unwrap();
+    let td = QuestionTextDao::new(&conn);
+    assert_eq!(td.insert_version(qid, TextField::PROMPT, "abandon", 0).unwrap(), 1);
+    assert_eq!(td.insert_version(qid, TextField::PROMPT, "abandon (v.)", 1).unwrap(), 2);
+    td.insert_version(qid, TextField::EXPLANATION, "", 2).unwrap();
+
+    let text = td.get_current(qid).unwrap();
+    assert_eq!(text.prompt.as_deref(), Some("abandon (v.)"));
+    assert_eq!(text.answer, None);
+    // 空字符串表示已清空
+    assert_eq!(text.explanation, None);
+
+    let history = td.history(qid, TextField::PROMPT).unwrap();
+    assert_eq!(history.iter().map(|v| v.version).collect::<Vec<_>>(), vec![2, 1]);
+    assert_eq!(history[1].content, "abandon");
+}
+
 #[test]
 fn test_search_fuzzy_no_match() {
     let (conn, _, _, _) = seed_view_data();
-    let rows = select_views_search_fuzzy(&conn, "%不存在的关键词%", None, None, 10, 0).unwrap();
+    let rows = select_views_search_fuzzy(&conn, "%不存在的关键词%", None, None, &[], 10, 0).unwrap();
     assert!(rows.is_empty());
 }
 
@@ -591,3 +633,199 @@ fn test_v10_migration_leaves_other_states_untouched() {
         .collect();
     assert_eq!(states, vec!["NEW", "LEARNING", "STABLE"]);
 }
+
+#[test]
+fn test_v15_migration_converts_reason_text_to_codes() {
+    use super::migrate;
+    use rusqlite::Connection;
+
+    let mut conn = Connection::open_in_memory().unwrap();
+    migrate(&mut conn).unwrap();
+
+    // 模拟 v15 之前写入的中文文案
+    let qid = insert_question(&conn, Some("q"), "NEW", 1).unwrap();
+    conn.execute(
+        "INSERT INTO recommendation (day, question_id, score, reason) VALUES (1, ?1, 1.0, ?2)",
+        rusqlite::params![qid, r#"["新题","超期5天","错误率60%","补前置：一次函数","奇怪的文案"]"#],
+    )
+    .unwrap();
+    let q2 = insert_question(&conn, Some("q2"), "NEW", 2).unwrap();
+    conn.execute(
+        "INSERT INTO recommendation (day, question_id, score, reason) VALUES (1, ?1, 1.0, 'null')",
+        [q2],
+    )
+    .unwrap();
+
+    // 回退版本号后重新迁移，只会重跑 v15
+    conn.execute("UPDATE schema_version SET version = 14", []).unwrap();
+    migrate(&mut conn).unwrap();
+
+    let reason: String = conn
+        .query_row("SELECT reason FROM recommendation WHERE question_id = ?1", [qid], |r| r.get(0))
+        .unwrap();
+    let value: serde_json::Value = serde_json::from_str(&reason).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!([
+            {"code": "new_question"},
+            {"code": "overdue", "days": 5},
+            {"code": "high_error_rate", "percent": 60},
+            {"code": "prerequisite", "knowledge_point": "一次函数"},
+            {"code": "legacy", "text": "奇怪的文案"},
+        ])
+    );
+
+    let untouched: String = conn
+        .query_row("SELECT reason FROM recommendation WHERE question_id = ?1", [q2], |r| r.get(0))
+        .unwrap();
+    assert_eq!(untouched, "null");
+}
+
+#[test]
+fn test_subject_error_stats_counts_skips_separately() {
+    let conn = setup_test_db();
+    let now = Utc::now().timestamp();
+    let qid = insert_question(&conn, Some("题目A"), "LEARNING", now).unwrap();
+    insert_meta(&conn, qid, "system.Subject", "数学").unwrap();
+    insert_review(&conn, qid, "CORRECT", now).unwrap();
+    insert_review(&conn, qid, "WRONG", now).unwrap();
+    conn.execute(
+        "INSERT INTO review_skip (question_id, skipped_at) VALUES (?1, ?2), (?1, ?2)",
+        rusqlite::params![qid, now],
+    )
+    .unwrap();
+
+    let rows = select_subject_error_stats(&conn, None).unwrap();
+    assert_eq!(rows.len(), 1);
+    let row = &rows[0];
+    assert_eq!(row.subject, "数学");
+    assert_eq!((row.review_count, row.correct_count, row.wrong_count), (2, 1, 1));
+    assert_eq!(row.skip_count, 2);
+    assert!((row.skip_rate - 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_v20_migration_dedupes_meta_and_enforces_cardinality() {
+    let mut conn = setup_test_db();
+    let qid = insert_question(&conn, Some("q"), "NEW", 1).unwrap();
+
+    // 模拟约束出现之前写入的重复数据
+    conn.execute_batch(
+        "DROP INDEX uq_meta_question_key_value;
+         DROP INDEX uq_meta_single_valued;",
+    )
+    .unwrap();
+    insert_meta(&conn, qid, "system.Subject", "数学").unwrap();
+    let kept_subject = insert_meta(&conn, qid, "system.Subject", "物理").unwrap();
+    let kept_kp = insert_meta(&conn, qid, "system.KnowledgePoint", "导数").unwrap();
+    insert_meta(&conn, qid, "system.KnowledgePoint", "导数").unwrap();
+    insert_meta(&conn, qid, "system.KnowledgePoint", "极限").unwrap();
+
+    conn.execute("UPDATE schema_version SET version = 19", []).unwrap();
+    migrate(&mut conn).unwrap();
+
+    let metas = select_meta_by_question(&conn, qid).unwrap();
+    let mut pairs: Vec<(i64, &str, &str)> =
+        metas.iter().map(|m| (m.id, m.key.as_str(), m.value.as_str())).collect();
+    pairs.sort();
+    assert_eq!(pairs.len(), 3);
+    assert!(pairs.contains(&(kept_subject, "system.Subject", "物理")));
+    assert!(pairs.contains(&(kept_kp, "system.KnowledgePoint", "导数")));
+
+    let reasons: Vec<(String, String)> = conn
+        .prepare("SELECT key, reason FROM meta_dedupe_log ORDER BY id")
+        .unwrap()
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        reasons,
+        vec![
+            ("system.KnowledgePoint".to_string(), "DUPLICATE_VALUE".to_string()),
+            ("system.Subject".to_string(), "SINGLE_VALUED".to_string()),
+        ]
+    );
+
+    // 约束生效：单值 key 与重复值都写不进去，多值 key 的新值可以
+    assert!(insert_meta(&conn, qid, "system.Subject", "化学").is_err());
+    assert!(insert_meta(&conn, qid, "system.KnowledgePoint", "极限").is_err());
+    insert_meta(&conn, qid, "system.KnowledgePoint", "积分").unwrap();
+
+    conn.execute(
+        "INSERT INTO user_field (name, field_type, cardinality, created_at) VALUES ('页码', 'NUMBER', 'SINGLE', 0)",
+        [],
+    )
+    .unwrap();
+    insert_meta(&conn, qid, "user.页码", "12").unwrap();
+    assert!(insert_meta(&conn, qid, "user.页码", "13").is_err());
+}
+
+#[test]
+fn test_v33_migration_stores_paper_subject_ids() {
+    let mut conn = setup_test_db();
+
+    // 模拟 v33 之前的数据：试卷中存科目名称，其中一个还没登记为科目
+    conn.execute_batch(
+        "DROP VIEW paper_view;
+         INSERT INTO subject (name, created_at) VALUES ('化学', 0);
+         INSERT INTO paper (name, subject, created_at) VALUES ('期中', '化学', 0), ('月考', '生物', 0), ('周测', NULL, 0);
+         UPDATE schema_version SET version = 32;",
+    )
+    .unwrap();
+    migrate(&mut conn).unwrap();
+
+    let subject_of = |conn: &rusqlite::Connection, name: &str| -> (Option<String>, Option<String>) {
+        conn.query_row(
+            "SELECT p.subject, v.subject FROM paper p JOIN paper_view v ON v.id = p.id WHERE p.name = ?1",
+            [name],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .unwrap()
+    };
+    let chem: i64 = conn.query_row("SELECT id FROM subject WHERE name = '化学'", [], |r| r.get(0)).unwrap();
+    let bio: i64 = conn.query_row("SELECT id FROM subject WHERE name = '生物'", [], |r| r.get(0)).unwrap();
+    assert_eq!(subject_of(&conn, "期中"), (Some(chem.to_string()), Some("化学".to_string())));
+    assert_eq!(subject_of(&conn, "月考"), (Some(bio.to_string()), Some("生物".to_string())));
+    assert_eq!(subject_of(&conn, "周测"), (None, None));
+
+    // 重跑不会把 id 当作名称再登记、转换一次
+    conn.execute("UPDATE schema_version SET version = 20", []).unwrap();
+    migrate(&mut conn).unwrap();
+    assert_eq!(subject_of(&conn, "期中"), (Some(chem.to_string()), Some("化学".to_string())));
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM subject", [], |r| r.get(0)).unwrap();
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn test_v31_migration_stores_subject_ids() {
+    let mut conn = setup_test_db();
+    let qid = insert_question(&conn, Some("q"), "NEW", 1).unwrap();
+
+    // 模拟 v31 之前的数据：元信息中存科目名称
+    conn.execute_batch(
+        "DROP VIEW meta_view;
+         INSERT INTO subject (name, created_at) VALUES ('生物', 0), ('化学', 0);
+         UPDATE schema_version SET version = 30;",
+    )
+    .unwrap();
+    conn.execute("INSERT INTO meta (question_id, key, value) VALUES (?1, 'system.Subject', '化学')", [qid])
+        .unwrap();
+    migrate(&mut conn).unwrap();
+
+    let chem: i64 = conn.query_row("SELECT id FROM subject WHERE name = '化学'", [], |r| r.get(0)).unwrap();
+    let stored: String = conn
+        .query_row("SELECT value FROM meta WHERE question_id = ?1 AND key = 'system.Subject'", [qid], |r| r.get(0))
+        .unwrap();
+    assert_eq!(stored, chem.to_string());
+    assert_eq!(select_meta_values_by_question_key(&conn, qid, "system.Subject").unwrap(), vec!["化学"]);
+    let row = select_view_active_by_id(&conn, qid).unwrap().unwrap();
+    assert_eq!(row.subject.as_deref(), Some("化学"));
+
+    // 重跑不会把 id 当作名称再转换一次
+    conn.execute("UPDATE schema_version SET version = 20", []).unwrap();
+    migrate(&mut conn).unwrap();
+    assert_eq!(select_meta_values_by_question_key(&conn, qid, "system.Subject").unwrap(), vec!["化学"]);
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM subject", [], |r| r.get(0)).unwrap();
+    assert_eq!(count, 2);
+}
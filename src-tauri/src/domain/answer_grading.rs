@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::enums::ReviewResult;
+use crate::util::time::Timestamp;
+
+/// 一次简答题判分：作答、标准答案、判分建议，以及用户采纳或改判后的最终结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnswerGrading {
+    pub id: i64,
+    pub question_id: i64,
+    pub response: String,
+    /// 判分时的标准答案
+    pub expected: String,
+    pub suggested: ReviewResult,
+    /// 用户最终给出的结果；还没评分时为 None
+    pub final_result: Option<ReviewResult>,
+    pub created_at: Timestamp,
+    pub decided_at: Option<Timestamp>,
+}
+
+/// 已评分的判分记录中，建议与最终结果的对照
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GradingStats {
+    /// 用户已评分的记录数
+    pub decided_count: i64,
+    /// 其中建议被采纳的记录数
+    pub agreed_count: i64,
+    /// (建议, 最终结果, 次数)，只列出现过的组合
+    pub pairs: Vec<(ReviewResult, ReviewResult, i64)>,
+}
+
+impl GradingStats {
+    /// 建议被采纳的比例；没有记录时为 0
+    pub fn accuracy(&self) -> f64 {
+        if self.decided_count == 0 {
+            0.0
+        } else {
+            self.agreed_count as f64 / self.decided_count as f64
+        }
+    }
+}
+
+/// 判分时的规范化规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GradingConfig {
+    /// 忽略全部空白；关闭时连续空白视为一个空格
+    pub ignore_whitespace: bool,
+    /// 全角字母、数字、标点折叠为半角（如 "ＡＢ１２，" → "AB12,"）
+    pub fold_width: bool,
+    /// 忽略大小写
+    pub ignore_case: bool,
+    /// 数值答案的相对误差容限（0 = 必须相等）；标准答案绝对值小于 1 时按绝对误差
+    pub numeric_tolerance: f64,
+    /// 文本相似度（0..1）不低于该值时建议 FUZZY
+    pub fuzzy_threshold: f64,
+}
+
+impl Default for GradingConfig {
+    fn default() -> Self {
+        Self {
+            ignore_whitespace: true,
+            fold_width: true,
+            ignore_case: true,
+            numeric_tolerance: 0.001,
+            fuzzy_threshold: 0.8,
+        }
+    }
+}
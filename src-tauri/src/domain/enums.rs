@@ -53,6 +53,52 @@ impl ReviewResult {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverrideAction {
+    PIN,  //当天必推
+    BURY, //当天不推
+}
+
+impl OverrideAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverrideAction::PIN => "PIN",
+            OverrideAction::BURY => "BURY",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "PIN" | "pin" => Some(OverrideAction::PIN),
+            "BURY" | "bury" => Some(OverrideAction::BURY),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecommendationTier {
+    DAILY,    //每日推荐
+    REMEDIAL, //答错后追加的同知识点巩固
+}
+
+impl RecommendationTier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecommendationTier::DAILY => "DAILY",
+            RecommendationTier::REMEDIAL => "REMEDIAL",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "DAILY" | "daily" => Some(RecommendationTier::DAILY),
+            "REMEDIAL" | "remedial" => Some(RecommendationTier::REMEDIAL),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AssetType {
     QUESTION, //题干图片、音频等
@@ -82,6 +128,89 @@ impl AssetType {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextField {
+    PROMPT,      //题干
+    ANSWER,      //答案
+    EXPLANATION, //解析
+}
+
+impl TextField {
+    pub const ALL: [TextField; 3] = [TextField::PROMPT, TextField::ANSWER, TextField::EXPLANATION];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TextField::PROMPT => "PROMPT",
+            TextField::ANSWER => "ANSWER",
+            TextField::EXPLANATION => "EXPLANATION",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "PROMPT" | "prompt" => Some(TextField::PROMPT),
+            "ANSWER" | "answer" => Some(TextField::ANSWER),
+            "EXPLANATION" | "explanation" => Some(TextField::EXPLANATION),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserFieldType {
+    TEXT,   //任意文本
+    NUMBER, //数字
+    DATE,   //日期，YYYY-MM-DD
+    ENUM,   //从可选值中选取
+    BOOL,   //是 / 否
+}
+
+impl UserFieldType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserFieldType::TEXT => "TEXT",
+            UserFieldType::NUMBER => "NUMBER",
+            UserFieldType::DATE => "DATE",
+            UserFieldType::ENUM => "ENUM",
+            UserFieldType::BOOL => "BOOL",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "TEXT" | "text" => Some(UserFieldType::TEXT),
+            "NUMBER" | "number" => Some(UserFieldType::NUMBER),
+            "DATE" | "date" => Some(UserFieldType::DATE),
+            "ENUM" | "enum" => Some(UserFieldType::ENUM),
+            "BOOL" | "bool" => Some(UserFieldType::BOOL),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cardinality {
+    SINGLE,   //每题至多一个值
+    MULTIPLE, //每题可有多个值
+}
+
+impl Cardinality {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Cardinality::SINGLE => "SINGLE",
+            Cardinality::MULTIPLE => "MULTIPLE",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "SINGLE" | "single" => Some(Cardinality::SINGLE),
+            "MULTIPLE" | "multiple" => Some(Cardinality::MULTIPLE),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MetaKey {
     System(SystemMetaKey),       // 系统定义且必要的元信息
@@ -111,6 +240,15 @@ impl MetaKey {
             None
         }
     }
+
+    /// 每题可有几个值。用户自定义 key 的基数登记在 `user_field` 表，这里返回 `None`。
+    pub fn cardinality(&self) -> Option<Cardinality> {
+        match self {
+            MetaKey::System(k) => Some(k.cardinality()),
+            MetaKey::Extension(k) => Some(k.cardinality()),
+            MetaKey::User(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -118,6 +256,7 @@ pub enum SystemMetaKey {
     Subject,          // 科目
     KnowledgePoint,   // 知识点
     PreSuspendState,  // 暂停前保存的状态，恢复时还原
+    Tag,              // 自由标签（如 期中、老师讲过、易混），与知识点体系无关
 }
 
 impl SystemMetaKey {
@@ -126,6 +265,7 @@ impl SystemMetaKey {
             SystemMetaKey::Subject => "Subject",
             SystemMetaKey::KnowledgePoint => "KnowledgePoint",
             SystemMetaKey::PreSuspendState => "PreSuspendState",
+            SystemMetaKey::Tag => "Tag",
         }
     }
 
@@ -138,9 +278,19 @@ impl SystemMetaKey {
             "PreSuspendState" | "presuspendstate" | "pre_suspend_state" => {
                 Some(SystemMetaKey::PreSuspendState)
             }
+            "Tag" | "tag" => Some(SystemMetaKey::Tag),
             _ => None,
         }
     }
+
+    pub fn cardinality(&self) -> Cardinality {
+        match self {
+            SystemMetaKey::Subject => Cardinality::SINGLE,
+            SystemMetaKey::KnowledgePoint => Cardinality::MULTIPLE,
+            SystemMetaKey::PreSuspendState => Cardinality::SINGLE,
+            SystemMetaKey::Tag => Cardinality::MULTIPLE,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -166,4 +316,11 @@ impl ExtensionMetaKey {
             _ => None,
         }
     }
+
+    pub fn cardinality(&self) -> Cardinality {
+        match self {
+            ExtensionMetaKey::SourcePaper => Cardinality::SINGLE,
+            ExtensionMetaKey::Difficulty => Cardinality::SINGLE,
+        }
+    }
 }
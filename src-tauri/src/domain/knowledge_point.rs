@@ -0,0 +1,16 @@
+use crate::util::time::Timestamp;
+
+/// 知识点树中的一个节点，例如 函数 › 二次函数 › 顶点式。
+/// 名称全库唯一，题目通过 `system.KnowledgePoint` 元信息引用名称。
+#[derive(Debug, Clone)]
+pub struct KnowledgePoint {
+    pub id: i64,
+    pub name: String,
+    /// 所属科目，None=未分类
+    pub subject_id: Option<i64>,
+    /// 父节点，None=该科的根节点
+    pub parent_id: Option<i64>,
+    /// 同级节点间的显示顺序
+    pub sort_order: i64,
+    pub created_at: Timestamp,
+}
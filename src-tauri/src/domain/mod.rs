@@ -1,19 +1,39 @@
+pub mod answer_grading;
 pub mod asset;
 pub mod enums;
 pub mod ids;
+pub mod knowledge_point;
 pub mod meta;
+pub mod occlusion;
+pub mod paper;
 pub mod question;
 pub mod question_info;
+pub mod question_link;
+pub mod question_revision;
+pub mod question_text;
 pub mod review;
+pub mod review_order;
 pub mod state_machine;
+pub mod subject;
+pub mod user_field;
 pub mod view;
 
+pub use answer_grading::*;
 pub use asset::*;
 pub use enums::*;
 pub use ids::*;
+pub use knowledge_point::*;
 pub use meta::*;
+pub use occlusion::*;
+pub use paper::*;
 pub use question::*;
 pub use question_info::*;
+pub use question_link::*;
+pub use question_revision::*;
+pub use question_text::*;
 pub use review::*;
+pub use review_order::*;
 pub use state_machine::*;
+pub use subject::*;
+pub use user_field::*;
 pub use view::*;
@@ -0,0 +1,37 @@
+use crate::util::time::Timestamp;
+
+/// 遮罩矩形，坐标与宽高按图片尺寸归一化到 0..1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MaskRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl MaskRect {
+    /// 宽高为正且整个矩形落在图片内
+    pub fn is_valid(&self) -> bool {
+        let finite = [self.x, self.y, self.width, self.height].iter().all(|v| v.is_finite());
+        finite
+            && self.x >= 0.0
+            && self.y >= 0.0
+            && self.width > 0.0
+            && self.height > 0.0
+            && self.x + self.width <= 1.0 + f64::EPSILON
+            && self.y + self.height <= 1.0 + f64::EPSILON
+    }
+}
+
+/// 图片遮罩的一个版本。`card_id` 是复习这一遮罩的卡片（笔记的小题），换图时升版本、卡片不变。
+#[derive(Debug, Clone, PartialEq)]
+pub struct OcclusionMask {
+    pub id: i64,
+    pub card_id: i64,
+    pub asset_id: i64,
+    pub version: i64,
+    pub rect: MaskRect,
+    pub created_at: Timestamp,
+    /// 被新版本取代（换图）或遮罩被移除的时间；当前版本为 None
+    pub superseded_at: Option<Timestamp>,
+}
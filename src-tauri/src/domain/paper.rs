@@ -0,0 +1,41 @@
+use crate::util::time::Timestamp;
+
+/// 试卷。题目通过 `extension.SourcePaper` 元信息引用试卷名称，
+/// 改名 / 删除由 `server::paper` 级联更新所有引用处。
+#[derive(Debug, Clone)]
+pub struct Paper {
+    pub id: i64,
+    pub name: String,
+    /// 考试日期（YYYY-MM-DD）
+    pub exam_date: Option<String>,
+    /// 科目名称；表中只存科目 id（`subject_id`），名称经 `paper_view` 得到
+    pub subject: Option<String>,
+    pub subject_id: Option<i64>,
+    /// 卷面总分
+    pub total_score: Option<f64>,
+    pub created_at: Timestamp,
+}
+
+/// 一张试卷上错题的掌握情况（只统计未删除的题目）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PaperStats {
+    pub question_count: i64,
+    pub new_count: i64,
+    pub learning_count: i64,
+    pub stable_count: i64,
+    pub suspended_count: i64,
+    /// 这些题目的复习次数与答错次数
+    pub review_count: i64,
+    pub wrong_review_count: i64,
+}
+
+impl PaperStats {
+    /// 已掌握（STABLE）的比例；没有题目时为 0
+    pub fn stable_rate(&self) -> f64 {
+        if self.question_count == 0 {
+            0.0
+        } else {
+            self.stable_count as f64 / self.question_count as f64
+        }
+    }
+}
@@ -5,20 +5,44 @@ pub struct QuestionInfo {
     pub assets: Vec<Asset>,
     pub metas: Vec<Meta>,
     pub reviews: Vec<Review>,
+    pub text: QuestionText,
+    /// 与其他题目的关联（对方在回收站中的除外）
+    pub links: Vec<QuestionLink>,
+    /// 小题（按顺序，不含回收站中的）
+    pub parts: Vec<Question>,
+    /// 本题是小题时所属的大题
+    pub parent_id: Option<QuestionId>,
+    /// 本题是填空卡时的填空编号
+    pub cloze_index: Option<i64>,
+    /// 本题是遮罩卡片时要遮住的区域
+    pub occlusion: Option<OcclusionMask>,
 }
 
 impl QuestionInfo {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         question: Question,
         assets: Vec<Asset>,
         metas: Vec<Meta>,
         reviews: Vec<Review>,
+        text: QuestionText,
+        links: Vec<QuestionLink>,
+        parts: Vec<Question>,
+        parent_id: Option<QuestionId>,
+        cloze_index: Option<i64>,
+        occlusion: Option<OcclusionMask>,
     ) -> Self {
         Self {
             question,
             assets,
             metas,
             reviews,
+            text,
+            links,
+            parts,
+            parent_id,
+            cloze_index,
+            occlusion,
         }
     }
 }
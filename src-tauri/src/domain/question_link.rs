@@ -0,0 +1,50 @@
+use crate::util::time::Timestamp;
+
+/// 题目之间的关联类型
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkType {
+    VARIANT_OF,     //同一陷阱的变式题
+    FOLLOWS,        //承接另一题（先做被承接的那道）
+    CONTRASTS_WITH, //对比辨析
+    SAME_SOURCE,    //出自同一来源（同一套卷子、同一道大题等）
+}
+
+impl LinkType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkType::VARIANT_OF => "VARIANT_OF",
+            LinkType::FOLLOWS => "FOLLOWS",
+            LinkType::CONTRASTS_WITH => "CONTRASTS_WITH",
+            LinkType::SAME_SOURCE => "SAME_SOURCE",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "VARIANT_OF" | "variant_of" => Some(LinkType::VARIANT_OF),
+            "FOLLOWS" | "follows" => Some(LinkType::FOLLOWS),
+            "CONTRASTS_WITH" | "contrasts_with" => Some(LinkType::CONTRASTS_WITH),
+            "SAME_SOURCE" | "same_source" => Some(LinkType::SAME_SOURCE),
+            _ => None,
+        }
+    }
+
+    /// 是否不分方向。只有 FOLLOWS 有方向：from 承接 to
+    pub fn is_symmetric(&self) -> bool {
+        !matches!(self, LinkType::FOLLOWS)
+    }
+}
+
+/// 从某道题看出去的一条关联
+#[derive(Debug, Clone)]
+pub struct QuestionLink {
+    pub id: i64,
+    pub link_type: LinkType,
+    /// 关联的另一道题
+    pub question_id: i64,
+    pub question_name: Option<String>,
+    /// 仅对 FOLLOWS 有意义：true 表示本题承接对方，false 表示对方承接本题
+    pub outgoing: bool,
+    pub created_at: Timestamp,
+}
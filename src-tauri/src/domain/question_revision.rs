@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+use crate::util::time::Timestamp;
+
+/// 快照中的一个资源
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AssetSnapshot {
+    pub id: i64,
+    /// QUESTION / ANSWER / EXPLAIN / OTHER
+    pub asset_type: String,
+    pub path: String,
+    pub sort_order: i64,
+}
+
+/// 题目可编辑内容的完整快照：题名、科目、知识点、资源集合与顺序、文本字段
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuestionSnapshot {
+    pub name: Option<String>,
+    pub subject: Option<String>,
+    /// 科目 id；恢复时按 id 找科目，改名、合并后仍能对上。早期修订没有该字段
+    #[serde(default)]
+    pub subject_id: Option<i64>,
+    pub knowledge_points: Vec<String>,
+    /// 与 `knowledge_points` 一一对应的节点 id，作用同 `subject_id`
+    #[serde(default)]
+    pub knowledge_point_ids: Vec<Option<i64>>,
+    /// 未删除的资源，按类型、序号排列
+    pub assets: Vec<AssetSnapshot>,
+    pub prompt: Option<String>,
+    pub answer: Option<String>,
+    pub explanation: Option<String>,
+}
+
+/// 题目的一条修订：某次编辑之后的快照。
+/// 题目第一次被编辑时，编辑前的状态也会记为一条修订。
+#[derive(Debug, Clone)]
+pub struct QuestionRevision {
+    pub id: i64,
+    pub question_id: i64,
+    pub snapshot: QuestionSnapshot,
+    pub created_at: Timestamp,
+}
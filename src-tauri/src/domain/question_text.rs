@@ -0,0 +1,27 @@
+use crate::domain::enums::TextField;
+
+/// 题目的文本内容（Markdown，行内 LaTeX 用 `$...$`），只含各字段的当前版本
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QuestionText {
+    pub prompt: Option<String>,
+    pub answer: Option<String>,
+    pub explanation: Option<String>,
+}
+
+impl QuestionText {
+    pub fn get(&self, field: TextField) -> Option<&String> {
+        match field {
+            TextField::PROMPT => self.prompt.as_ref(),
+            TextField::ANSWER => self.answer.as_ref(),
+            TextField::EXPLANATION => self.explanation.as_ref(),
+        }
+    }
+
+    pub fn set(&mut self, field: TextField, content: Option<String>) {
+        match field {
+            TextField::PROMPT => self.prompt = content,
+            TextField::ANSWER => self.answer = content,
+            TextField::EXPLANATION => self.explanation = content,
+        }
+    }
+}
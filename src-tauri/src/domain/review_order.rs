@@ -0,0 +1,48 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// 出题顺序策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewOrder {
+    /// 按推荐分数（保持输入顺序）
+    #[default]
+    Score,
+    /// 科目轮转：各科依次出一题，避免长时间停留在同一科
+    SubjectRoundRobin,
+    /// 按种子随机打乱，种子相同则顺序相同
+    Shuffle,
+    /// 难题优先（错误率高、错误次数多的在前）
+    HardestFirst,
+    /// 易题优先（热身用）
+    EasiestFirst,
+}
+
+impl ReviewOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewOrder::Score => "score",
+            ReviewOrder::SubjectRoundRobin => "subject_round_robin",
+            ReviewOrder::Shuffle => "shuffle",
+            ReviewOrder::HardestFirst => "hardest_first",
+            ReviewOrder::EasiestFirst => "easiest_first",
+        }
+    }
+}
+
+/// 解析命令参数中的出题顺序，取值同 `as_str`
+impl FromStr for ReviewOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "score" => Ok(ReviewOrder::Score),
+            "subject_round_robin" => Ok(ReviewOrder::SubjectRoundRobin),
+            "shuffle" => Ok(ReviewOrder::Shuffle),
+            "hardest_first" => Ok(ReviewOrder::HardestFirst),
+            "easiest_first" => Ok(ReviewOrder::EasiestFirst),
+            _ => Err(format!("invalid review order: {}", s)),
+        }
+    }
+}
@@ -8,6 +8,8 @@
 //! - STABLE → STABLE: 复习结果 = 正确
 //! - 任意状态 → SUSPENDED: 用户手动暂停
 //! - SUSPENDED → 原状态: 用户手动恢复（保存于 meta `system.PreSuspendState`）
+//! - 任意状态 → NEW: 用户重置学习进度
+//! - 有小题的大题：状态由小题推导，不单独复习
 
 use crate::domain::enums::{QuestionState, ReviewResult};
 use crate::domain::question::Question;
@@ -211,6 +213,47 @@ impl QuestionStateMachine {
         }
     }
 
+    /// 重置学习进度（任意状态 → NEW）：连续正确与错误次数清零，不设到期时间
+    pub fn reset() -> StateTransition {
+        StateTransition {
+            new_state: QuestionState::NEW,
+            correct_streak: 0,
+            wrong_count: 0,
+            due_at: None,
+        }
+    }
+
+    /// 由小题推导大题的状态：
+    /// - 小题全部暂停 → SUSPENDED
+    /// - 其余（未暂停的）小题全是 NEW → NEW，全是 STABLE → STABLE，否则 LEARNING
+    ///
+    /// 连续正确取未暂停小题中的最小值，错误次数为全部小题之和，到期时间取最早的一道。
+    pub fn derive_from_parts(parts: &[Question]) -> StateTransition {
+        let active: Vec<&Question> = parts.iter().filter(|p| p.state != QuestionState::SUSPENDED).collect();
+        let wrong_count = parts.iter().map(|p| p.wrong_count).sum();
+        if active.is_empty() {
+            return StateTransition {
+                new_state: QuestionState::SUSPENDED,
+                correct_streak: parts.iter().map(|p| p.correct_streak).min().unwrap_or(0),
+                wrong_count,
+                due_at: None,
+            };
+        }
+        let new_state = if active.iter().all(|p| p.state == QuestionState::NEW) {
+            QuestionState::NEW
+        } else if active.iter().all(|p| p.state == QuestionState::STABLE) {
+            QuestionState::STABLE
+        } else {
+            QuestionState::LEARNING
+        };
+        StateTransition {
+            new_state,
+            correct_streak: active.iter().map(|p| p.correct_streak).min().unwrap_or(0),
+            wrong_count,
+            due_at: active.iter().filter_map(|p| p.due_at).min(),
+        }
+    }
+
     /// 根据复习结果与更新后的 streak / wrong_count 计算下次复习间隔天数。
     /// 公式与推荐系统 (server/recommendation.rs) 保持一致，用于 due_at 字段。
     /// - CORRECT: ceil(stability² / difficulty)，stability = streak_after + 1
@@ -346,6 +389,51 @@ mod tests {
         assert_eq!(result.due_at, Some(now));
     }
 
+    #[test]
+    fn test_reset() {
+        let result = QuestionStateMachine::reset();
+
+        assert_eq!(result.new_state, QuestionState::NEW);
+        assert_eq!(result.correct_streak, 0);
+        assert_eq!(result.wrong_count, 0);
+        assert!(result.due_at.is_none());
+    }
+
+    #[test]
+    fn test_derive_from_parts() {
+        let part = |state: QuestionState, streak: i64, wrong: i64, due: Option<i64>| Question {
+            correct_streak: streak,
+            wrong_count: wrong,
+            due_at: due.map(Timestamp::from),
+            ..create_question(state)
+        };
+
+        let t = QuestionStateMachine::derive_from_parts(&[
+            part(QuestionState::STABLE, 4, 0, Some(900)),
+            part(QuestionState::LEARNING, 1, 2, Some(500)),
+            part(QuestionState::SUSPENDED, 0, 1, None),
+        ]);
+        assert_eq!(t.new_state, QuestionState::LEARNING);
+        assert_eq!((t.correct_streak, t.wrong_count), (1, 3));
+        assert_eq!(t.due_at, Some(Timestamp::from(500)));
+
+        // 暂停的小题不影响推导
+        let t = QuestionStateMachine::derive_from_parts(&[
+            part(QuestionState::STABLE, 3, 0, Some(900)),
+            part(QuestionState::SUSPENDED, 0, 0, None),
+        ]);
+        assert_eq!(t.new_state, QuestionState::STABLE);
+
+        let t = QuestionStateMachine::derive_from_parts(&[
+            part(QuestionState::NEW, 0, 0, None),
+            part(QuestionState::NEW, 0, 0, None),
+        ]);
+        assert_eq!(t.new_state, QuestionState::NEW);
+        let t = QuestionStateMachine::derive_from_parts(&[part(QuestionState::SUSPENDED, 0, 0, None)]);
+        assert_eq!(t.new_state, QuestionState::SUSPENDED);
+        assert!(t.due_at.is_none());
+    }
+
     // ===== due_at 公式测试（业务规则 5.3：连续答对 → 间隔逐渐拉长）=====
     //
     // 公式: stability = (streak_after + 1)
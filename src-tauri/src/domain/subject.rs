@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use crate::util::time::Timestamp;
+
+/// 科目配置
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SubjectConfig {
+    /// 是否归档（不参与推荐）
+    #[serde(default)]
+    pub archived: bool,
+    /// 该科每日推荐题数限制，None=使用全局值，Some(0)=不推荐
+    #[serde(default)]
+    pub recommendation_limit: Option<u32>,
+    /// 该科单个知识点每日最多入选题数，None=使用全局值，Some(0)=不限制
+    #[serde(default)]
+    pub max_per_knowledge_point: Option<u32>,
+    /// 每周学习日（ISO 星期：周一=1 … 周日=7），None=每天
+    #[serde(default)]
+    pub study_days: Option<Vec<u32>>,
+    /// 学习日未复习完的名额是否顺延到下一个学习日
+    #[serde(default)]
+    pub carry_over_unused: bool,
+}
+
+impl Default for SubjectConfig {
+    fn default() -> Self {
+        Self {
+            archived: false,
+            recommendation_limit: None,
+            max_per_knowledge_point: None,
+            study_days: None,
+            carry_over_unused: false,
+        }
+    }
+}
+
+impl SubjectConfig {
+    /// `weekday`（ISO 星期）是否为该科学习日
+    pub fn studies_on(&self, weekday: u32) -> bool {
+        match &self.study_days {
+            Some(days) => days.contains(&weekday),
+            None => true,
+        }
+    }
+}
+
+/// 科目。题目通过 `system.Subject` 元信息引用科目 id（读取时换成名称），
+/// 改名 / 合并由 `server::subject` 处理按名称记录的推荐缓存与顺延名额。
+#[derive(Debug, Clone)]
+pub struct Subject {
+    pub id: i64,
+    pub name: String,
+    /// 显示颜色（如 `#4CAF50`），None=默认
+    pub color: Option<String>,
+    /// 列表中的显示顺序，越小越靠前
+    pub sort_order: i64,
+    pub config: SubjectConfig,
+    pub created_at: Timestamp,
+}
@@ -0,0 +1,52 @@
+use crate::domain::enums::{Cardinality, MetaKey, UserFieldType};
+use crate::util::time::Timestamp;
+
+/// 用户自定义元信息字段的定义，例如"页码"（NUMBER）、"考试名称"（TEXT）
+#[derive(Debug, Clone)]
+pub struct UserField {
+    pub name: String,
+    pub field_type: UserFieldType,
+    /// ENUM 字段的可选值，其余类型为空
+    pub allowed_values: Vec<String>,
+    pub cardinality: Cardinality,
+    pub created_at: Timestamp,
+}
+
+impl UserField {
+    /// 字段值在 meta 表中使用的 key
+    pub fn meta_key(&self) -> MetaKey {
+        MetaKey::User(self.name.clone())
+    }
+
+    /// 校验并规范化一个字段值：
+    /// 数字统一为最短十进制写法，日期为 `YYYY-MM-DD`，布尔为 `true` / `false`。
+    /// 不合法时返回说明原因的错误文本。
+    pub fn normalize(&self, raw: &str) -> Result<String, String> {
+        let value = raw.trim();
+        if value.is_empty() {
+            return Err(format!("{}: value must not be empty", self.name));
+        }
+        match self.field_type {
+            UserFieldType::TEXT => Ok(value.to_string()),
+            UserFieldType::NUMBER => match value.parse::<f64>() {
+                Ok(n) if n.is_finite() => Ok(n.to_string()),
+                _ => Err(format!("{}: '{}' is not a number", self.name, value)),
+            },
+            UserFieldType::DATE => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .map_err(|_| format!("{}: '{}' is not a date (YYYY-MM-DD)", self.name, value)),
+            UserFieldType::ENUM => {
+                if self.allowed_values.iter().any(|v| v == value) {
+                    Ok(value.to_string())
+                } else {
+                    Err(format!("{}: '{}' is not one of {:?}", self.name, value, self.allowed_values))
+                }
+            }
+            UserFieldType::BOOL => match value.to_lowercase().as_str() {
+                "true" | "1" | "是" => Ok("true".to_string()),
+                "false" | "0" | "否" => Ok("false".to_string()),
+                _ => Err(format!("{}: '{}' is not a boolean", self.name, value)),
+            },
+        }
+    }
+}
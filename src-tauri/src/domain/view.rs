@@ -11,6 +11,8 @@ pub struct View {
     pub deleted_at: Option<Timestamp>,
     pub subject: String,
     pub knowledge_points: Vec<String>,
+    /// 自由标签
+    pub tags: Vec<String>,
     pub last_reviewed_at: Timestamp,
     /// 累计答错次数（来自 `question.wrong_count`）。
     pub wrong_count: i64,
@@ -27,6 +27,9 @@ fn main() {
                     println!("启动时自动加载 AppInner");
 
                     let conn = ePNote::db::init_db(&root).expect("open db failed");
+                    if let Err(e) = ePNote::server::subject::import_legacy_settings(&conn) {
+                        eprintln!("failed to import legacy subject configs: {}", e);
+                    }
 
                     let asset_store = AssetStore::new(root.clone());
 
@@ -55,27 +58,94 @@ fn main() {
             question_c::cleanup_recycle_bin_comm,
             question_c::update_question_comm,
             question_c::get_question_detail_comm,
+            question_c::get_question_text_history_comm,
+            question_c::list_question_revisions_comm,
+            question_c::diff_question_revisions_comm,
+            question_c::revert_question_revision_comm,
+            question_link_c::list_question_links_comm,
+            question_link_c::link_questions_comm,
+            question_link_c::unlink_questions_comm,
+            question_part_c::add_question_part_comm,
+            question_part_c::reorder_question_parts_comm,
             question_c::get_image_base64,
             question_c::add_question_images_comm,
             question_c::delete_question_image_comm,
             question_c::update_image_sort_order_comm,
             // 复习相关命令
             review_c::process_review_comm,
+            review_c::skip_question_comm,
             review_c::recommend_questions_comm,
             review_c::suspend_question_comm,
             review_c::recover_question_comm,
+            review_c::forget_progress_comm,
             review_c::list_subjects_comm,
             review_c::get_stats_comm,
             review_c::subject_error_stats_comm,
             review_c::review_daily_series_comm,
+            // 简答题判分命令
+            answer_grading_c::grade_answer_comm,
+            answer_grading_c::decide_grading_comm,
+            answer_grading_c::get_grading_stats_comm,
             // 推荐系统命令
             recommendation_c::get_daily_recommendation_comm,
             recommendation_c::get_recommendation_list_comm,
             recommendation_c::get_daily_review_status_comm,
             recommendation_c::get_today_review_records_comm,
             recommendation_c::get_questions_by_ids_comm,
+            recommendation_c::pin_question_comm,
+            recommendation_c::bury_question_comm,
+            recommendation_c::postpone_question_comm,
+            recommendation_c::clear_recommendation_override_comm,
+            recommendation_c::get_remedial_queue_comm,
+            // 知识点前置关系命令
+            knowledge_graph_c::add_prerequisite_comm,
+            knowledge_graph_c::remove_prerequisite_comm,
+            knowledge_graph_c::list_prerequisites_comm,
+            knowledge_graph_c::get_knowledge_point_ancestors_comm,
+            knowledge_graph_c::get_knowledge_point_descendants_comm,
+            knowledge_graph_c::get_weakest_prerequisite_comm,
+            // 用户自定义字段命令
+            user_field_c::define_user_field_comm,
+            user_field_c::update_user_field_comm,
+            user_field_c::delete_user_field_comm,
+            user_field_c::list_user_fields_comm,
+            user_field_c::set_question_field_values_comm,
+            user_field_c::get_question_field_values_comm,
+            // 科目管理命令
+            subject_c::get_subjects_comm,
+            subject_c::create_subject_comm,
+            subject_c::update_subject_comm,
+            subject_c::rename_subject_comm,
+            subject_c::merge_subjects_comm,
+            subject_c::reorder_subjects_comm,
+            // 知识点树管理命令
+            knowledge_point_c::get_knowledge_point_tree_comm,
+            knowledge_point_c::create_knowledge_point_comm,
+            knowledge_point_c::rename_knowledge_point_comm,
+            knowledge_point_c::move_knowledge_point_comm,
+            knowledge_point_c::merge_knowledge_points_comm,
+            knowledge_point_c::delete_knowledge_point_comm,
+            knowledge_point_c::get_knowledge_point_stats_comm,
+            // 标签命令
+            tag_c::list_tags_comm,
+            tag_c::tag_questions_comm,
+            tag_c::untag_questions_comm,
+            // 试卷命令
+            paper_c::list_papers_comm,
+            paper_c::create_paper_comm,
+            paper_c::update_paper_comm,
+            paper_c::delete_paper_comm,
+            paper_c::assign_paper_comm,
+            paper_c::get_paper_session_comm,
+            // 图片遮罩命令
+            occlusion_c::list_image_masks_comm,
+            occlusion_c::set_image_masks_comm,
+            occlusion_c::replace_question_image_comm,
+            // 批量操作命令
+            bulk_c::bulk_questions_comm,
             // 开发者模式命令
             recommendation_c::preview_recommendation_comm,
+            recommendation_c::explain_question_comm,
             recommendation_c::regenerate_daily_recommendation_comm,
             recommendation_c::get_recommendation_stats_comm,
             // 设置相关命令
@@ -0,0 +1,190 @@
+//! 简答题自动判分
+//!
+//! 复习有文本答案的题目时可以先输入作答：按设置中的规范化规则（空白、全角 / 半角、大小写、数值误差）
+//! 与标准答案比较，建议 CORRECT / FUZZY / WRONG。用户可以采纳或改判，建议与最终结果都记在
+//! `answer_grading` 表，用来统计判分建议的准确率。
+//!
+//! 填空卡的标准答案是本卡挖空处的答案，其他题目是答案文本。
+
+use crate::dao::answer_grading_dao::AnswerGradingDao;
+use crate::dao::question_dao::QuestionDao;
+use crate::dao::question_text_dao::QuestionTextDao;
+use crate::db::connection::Connection;
+use crate::domain::answer_grading::{AnswerGrading, GradingConfig, GradingStats};
+use crate::domain::enums::{ReviewResult, TextField};
+use crate::domain::ids::QuestionId;
+use crate::error::AppError;
+use crate::server::cloze;
+use crate::util::time::Timestamp;
+
+/// 按规则规范化一段作答或答案
+pub fn normalize(text: &str, config: &GradingConfig) -> String {
+    let folded: String = text
+        .chars()
+        .map(|c| match c {
+            '\u{3000}' if config.fold_width => ' ',
+            '\u{FF01}'..='\u{FF5E}' if config.fold_width => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .collect();
+    let cased = if config.ignore_case { folded.to_lowercase() } else { folded };
+    if config.ignore_whitespace {
+        cased.chars().filter(|c| !c.is_whitespace()).collect()
+    } else {
+        cased.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+}
+
+/// 两段文本的相似度：1 - 编辑距离 / 较长一段的字符数
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != cb);
+            row[j + 1] = substitute.min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+    1.0 - prev[b.len()] as f64 / longest as f64
+}
+
+/// 比较作答与标准答案，给出建议结果
+pub fn grade(expected: &str, response: &str, config: &GradingConfig) -> ReviewResult {
+    let expected = normalize(expected, config);
+    let response = normalize(response, config);
+    if response.is_empty() {
+        return ReviewResult::WRONG;
+    }
+    if response == expected {
+        return ReviewResult::CORRECT;
+    }
+    // 两边都是数值时只看误差，不看字面相似度
+    if let (Ok(want), Ok(got)) = (expected.parse::<f64>(), response.parse::<f64>()) {
+        let allowed = config.numeric_tolerance.max(0.0) * want.abs().max(1.0);
+        return if (got - want).abs() <= allowed { ReviewResult::CORRECT } else { ReviewResult::WRONG };
+    }
+    if similarity(&expected, &response) >= config.fuzzy_threshold {
+        ReviewResult::FUZZY
+    } else {
+        ReviewResult::WRONG
+    }
+}
+
+/// 题目的标准答案；没有文本答案时为 None
+pub fn expected_answer(conn: &Connection, qid: QuestionId) -> Result<Option<String>, AppError> {
+    let answer = match cloze::card_answer(conn, qid)? {
+        Some(answer) => Some(answer),
+        None => QuestionTextDao::new(conn).latest(i64::from(qid), TextField::ANSWER)?.map(|v| v.content),
+    };
+    Ok(answer.filter(|a| !a.trim().is_empty()))
+}
+
+/// 给一次作答判分并记下建议
+pub fn grade_response(
+    conn: &Connection,
+    qid: QuestionId,
+    response: &str,
+    config: &GradingConfig,
+    now: Timestamp,
+) -> Result<AnswerGrading, AppError> {
+    QuestionDao::new(conn)
+        .get_by_id(qid)?
+        .filter(|q| q.deleted_at.is_none())
+        .ok_or_else(|| AppError::NotFound(format!("question {}", i64::from(qid))))?;
+    let expected = expected_answer(conn, qid)?
+        .ok_or_else(|| AppError::InvalidInput("question has no text answer".to_string()))?;
+    let suggested = grade(&expected, response, config);
+
+    let dao = AnswerGradingDao::new(conn);
+    let id = dao.insert(i64::from(qid), response, &expected, &suggested, now)?;
+    dao.get(id)?.ok_or_else(|| AppError::NotFound(format!("grading {}", id)))
+}
+
+/// 记下用户最终给出的结果（采纳建议或改判）；每条判分只能评一次
+pub fn decide(conn: &Connection, grading_id: i64, result: ReviewResult, now: Timestamp) -> Result<(), AppError> {
+    let dao = AnswerGradingDao::new(conn);
+    let grading = dao.get(grading_id)?.ok_or_else(|| AppError::NotFound(format!("grading {}", grading_id)))?;
+    if grading.final_result.is_some() {
+        return Err(AppError::InvalidInput("grading already decided".to_string()));
+    }
+    dao.decide(grading_id, &result, now)?;
+    Ok(())
+}
+
+/// 判分建议与用户最终结果的对照
+pub fn grading_stats(conn: &Connection) -> Result<GradingStats, AppError> {
+    Ok(AnswerGradingDao::new(conn).stats()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::question_manager::write_question_text;
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_grade_normalizes_and_tolerates() {
+        let config = GradingConfig::default();
+        assert_eq!(grade("Mitochondria", " mito chondria ", &config), ReviewResult::CORRECT);
+        assert_eq!(grade("ATP", "ＡＴＰ", &config), ReviewResult::CORRECT);
+        assert_eq!(grade("3.1416", "3.1415", &config), ReviewResult::CORRECT);
+        assert_eq!(grade("3.1416", "3.2", &config), ReviewResult::WRONG);
+        assert_eq!(grade("photosynthesis", "photosynthesys", &config), ReviewResult::FUZZY);
+        assert_eq!(grade("光合作用", "呼吸作用", &config), ReviewResult::WRONG);
+        assert_eq!(grade("光合作用", "", &config), ReviewResult::WRONG);
+
+        // 规则可以逐项关闭
+        let strict = GradingConfig {
+            ignore_whitespace: false,
+            fold_width: false,
+            ignore_case: false,
+            numeric_tolerance: 0.0,
+            fuzzy_threshold: 1.0,
+        };
+        assert_eq!(grade("ATP", "atp", &strict), ReviewResult::WRONG);
+        assert_eq!(grade("ATP", "ＡＴＰ", &strict), ReviewResult::WRONG);
+        assert_eq!(grade("a  b", "a b", &strict), ReviewResult::CORRECT);
+        assert_eq!(grade("ab", "a b", &strict), ReviewResult::WRONG);
+        assert_eq!(grade("3.1416", "3.1415", &strict), ReviewResult::WRONG);
+    }
+
+    #[test]
+    fn test_suggestion_and_override_are_recorded() {
+        let conn = setup();
+        let qid = QuestionId::from(crate::db::insert_question(&conn, Some("q"), "NEW", 0).unwrap());
+        let config = GradingConfig::default();
+        assert!(grade_response(&conn, qid, "x", &config, Timestamp::from(10)).is_err());
+        write_question_text(&conn, qid, TextField::ANSWER, "线粒体", Timestamp::from(10)).unwrap();
+
+        let accepted = grade_response(&conn, qid, "线粒体", &config, Timestamp::from(20)).unwrap();
+        assert_eq!((accepted.suggested.clone(), accepted.expected.as_str()), (ReviewResult::CORRECT, "线粒体"));
+        decide(&conn, accepted.id, ReviewResult::CORRECT, Timestamp::from(21)).unwrap();
+        assert!(decide(&conn, accepted.id, ReviewResult::WRONG, Timestamp::from(22)).is_err());
+
+        let overridden = grade_response(&conn, qid, "叶绿体", &config, Timestamp::from(30)).unwrap();
+        assert_eq!(overridden.suggested, ReviewResult::WRONG);
+        decide(&conn, overridden.id, ReviewResult::FUZZY, Timestamp::from(31)).unwrap();
+        let stored = AnswerGradingDao::new(&conn).get(overridden.id).unwrap().unwrap();
+        assert_eq!(stored.final_result, Some(ReviewResult::FUZZY));
+        assert_eq!(stored.decided_at, Some(Timestamp::from(31)));
+
+        // 未评分的不计入
+        grade_response(&conn, qid, "线粒体", &config, Timestamp::from(40)).unwrap();
+        let stats = grading_stats(&conn).unwrap();
+        assert_eq!((stats.decided_count, stats.agreed_count), (2, 1));
+        assert_eq!(stats.accuracy(), 0.5);
+        assert!(stats.pairs.contains(&(ReviewResult::WRONG, ReviewResult::FUZZY, 1)));
+    }
+}
@@ -0,0 +1,321 @@
+//! 批量操作
+//!
+//! 对一组题目执行同一操作：题目可以直接给 id 列表，也可以给与列表页相同的过滤条件。
+//! 整批在一个 SQLite 事务里执行，每道题各占一个保存点：某道题失败只回滚它自己，
+//! 其余题目照常提交，并逐题返回结果。永久删除的文件在事务提交后才从存储中删除。
+
+use serde::{Deserialize, Serialize};
+
+use crate::asset::store::AssetStore;
+use crate::dao::meta_dao::MetaDao;
+use crate::dao::question_dao::QuestionDao;
+use crate::dao::view_dao::ViewDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::domain::enums::{MetaKey, SystemMetaKey};
+use crate::domain::ids::QuestionId;
+use crate::error::AppError;
+use crate::server::knowledge_point;
+use crate::server::paper;
+use crate::server::question_manager::{self, PurgedFile};
+use crate::server::review_manager::ReviewManager;
+use crate::server::revision;
+use crate::server::user_field::{self, UserFieldFilter};
+use crate::util::time::now_ts;
+
+/// 按条件选题，字段含义同列表页的分类查询；各条件都可缺省，全部缺省即全部题目。
+/// 恢复与永久删除在回收站中查找，其余操作在未删除题目中查找。
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BulkFilter {
+    pub subject: Option<String>,
+    pub question_state: Option<String>,
+    /// 包含该知识点的全部后代节点
+    pub knowledge_point: Option<String>,
+    /// 须全部带有
+    pub tags: Vec<String>,
+    pub fields: Vec<UserFieldFilter>,
+}
+
+/// 批量操作
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkAction {
+    /// 移入回收站
+    Delete,
+    /// 从回收站恢复
+    Restore,
+    /// 从回收站永久删除
+    PermanentlyDelete,
+    /// 暂停复习
+    Suspend,
+    /// 恢复复习
+    Recover,
+    /// 设置科目
+    SetSubject { subject: String },
+    /// 添加知识点（已有的跳过）
+    AddKnowledgePoints { knowledge_points: Vec<String> },
+    /// 移除知识点
+    RemoveKnowledgePoints { knowledge_points: Vec<String> },
+    /// 重置学习进度（回到新题）
+    ResetProgress,
+    /// 设置来源试卷，`paper_id` 为空时清除
+    SetPaper { paper_id: Option<i64> },
+}
+
+impl BulkAction {
+    /// 作用于回收站中的题目
+    fn on_deleted(&self) -> bool {
+        matches!(self, BulkAction::Restore | BulkAction::PermanentlyDelete)
+    }
+}
+
+/// 单道题的执行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkOutcome {
+    pub id: i64,
+    pub ok: bool,
+    /// 失败原因；成功时为 None
+    pub error: Option<String>,
+}
+
+/// 确定要操作的题目：`question_ids` 与 `filter` 二选一。id 列表去重并保持顺序。
+pub fn resolve_selection(
+    conn: &Connection,
+    question_ids: Option<Vec<i64>>,
+    filter: Option<BulkFilter>,
+    action: &BulkAction,
+) -> Result<Vec<i64>, AppError> {
+    match (question_ids, filter) {
+        (Some(ids), None) => {
+            let mut out: Vec<i64> = Vec::with_capacity(ids.len());
+            for id in ids {
+                if !out.contains(&id) {
+                    out.push(id);
+                }
+            }
+            Ok(out)
+        }
+        (None, Some(filter)) => {
+            let mut meta_filters = user_field::to_meta_filters(conn, &filter.fields)?;
+            if let Some(kp) = filter.knowledge_point.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+                meta_filters.push(knowledge_point::knowledge_point_filter(conn, kp)?);
+            }
+            let tags = crate::server::tag::normalize_tags(&filter.tags);
+            Ok(ViewDao::new(conn).list_ids_filtered(
+                action.on_deleted(),
+                filter.subject.as_deref().filter(|s| !s.is_empty()),
+                filter.question_state.as_deref().filter(|s| !s.is_empty()),
+                &tags,
+                &meta_filters,
+            )?)
+        }
+        _ => Err(AppError::InvalidInput(
+            "exactly one of question ids or filter must be given".to_string(),
+        )),
+    }
+}
+
+/// 对 `ids` 逐题执行 `action`，整批一个事务；返回每道题的结果（顺序同 `ids`）
+pub fn run_bulk(
+    conn: &Connection,
+    store: &AssetStore,
+    ids: &[i64],
+    action: &BulkAction,
+) -> Result<Vec<BulkOutcome>, AppError> {
+    check_action(conn, action)?;
+
+    let mut tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let mut outcomes = Vec::with_capacity(ids.len());
+    let mut purged: Vec<PurgedFile> = Vec::new();
+    for &id in ids {
+        let sp = tx.savepoint().map_err(DbError::from)?;
+        match apply(&sp, id, action) {
+            Ok(files) => {
+                sp.commit().map_err(DbError::from)?;
+                purged.extend(files);
+                outcomes.push(BulkOutcome { id, ok: true, error: None });
+            }
+            // 保存点在 drop 时回滚
+            Err(e) => outcomes.push(BulkOutcome { id, ok: false, error: Some(e.to_string()) }),
+        }
+    }
+    tx.commit().map_err(DbError::from)?;
+
+    question_manager::delete_purged_files(store, &purged)?;
+    Ok(outcomes)
+}
+
+/// 与具体题目无关的参数校验，不通过时整批不执行
+fn check_action(conn: &Connection, action: &BulkAction) -> Result<(), AppError> {
+    match action {
+        BulkAction::SetSubject { subject } if subject.trim().is_empty() => {
+            Err(AppError::InvalidInput("subject must not be empty".to_string()))
+        }
+        BulkAction::AddKnowledgePoints { knowledge_points }
+        | BulkAction::RemoveKnowledgePoints { knowledge_points }
+            if knowledge_points.iter().all(|kp| kp.trim().is_empty()) =>
+        {
+            Err(AppError::InvalidInput("knowledge points must not be empty".to_string()))
+        }
+        BulkAction::SetPaper { paper_id: Some(id) } => paper::get_paper(conn, *id).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+/// 对单道题执行操作；返回永久删除时需要随后删除的文件
+fn apply(conn: &Connection, id: i64, action: &BulkAction) -> Result<Vec<PurgedFile>, AppError> {
+    let qid = QuestionId::from(id);
+    let question = QuestionDao::new(conn)
+        .get_by_id(qid)?
+        .ok_or_else(|| AppError::NotFound(format!("question {}", id)))?;
+    match (action.on_deleted(), question.deleted_at.is_some()) {
+        (true, false) => return Err(AppError::InvalidInput("question is not in the recycle bin".to_string())),
+        (false, true) => return Err(AppError::InvalidInput("question is in the recycle bin".to_string())),
+        _ => {}
+    }
+
+    let kp_key = || MetaKey::System(SystemMetaKey::KnowledgePoint);
+    match action {
+        BulkAction::Delete => {
+            question_manager::move_to_recycle_bin(conn, qid)?;
+        }
+        BulkAction::Restore => {
+            question_manager::restore_from_recycle_bin(conn, qid)?;
+        }
+        BulkAction::PermanentlyDelete => return question_manager::purge_question(conn, qid),
+        BulkAction::Suspend => {
+            ReviewManager::new(conn).suspend(qid).map_err(AppError::InvalidInput)?;
+        }
+        BulkAction::Recover => {
+            ReviewManager::new(conn).recover(qid).map_err(AppError::InvalidInput)?;
+        }
+        BulkAction::ResetProgress => {
+            ReviewManager::new(conn).reset(qid).map_err(AppError::InvalidInput)?;
+        }
+        BulkAction::SetSubject { subject } => {
+            question_manager::apply_question_meta(conn, qid, Some(subject.trim().to_string()), None)?;
+        }
+        BulkAction::SetPaper { paper_id } => {
+            let paper = paper_id.map(|id| paper::get_paper(conn, id)).transpose()?;
+            paper::set_question_paper(conn, qid, paper.as_ref())?;
+        }
+        BulkAction::AddKnowledgePoints { knowledge_points } => revision::tracked(conn, qid, now_ts(), || {
+            let md = MetaDao::new(conn);
+            let mut current = md.get_values_by_question_key(qid, &kp_key().as_str())?;
+            for kp in knowledge_points.iter().map(|kp| kp.trim()).filter(|kp| !kp.is_empty()) {
+                if !current.iter().any(|c| c == kp) {
+                    md.insert(qid, kp_key(), kp)?;
+                    current.push(kp.to_string());
+                }
+            }
+            Ok(())
+        })?,
+        BulkAction::RemoveKnowledgePoints { knowledge_points } => revision::tracked(conn, qid, now_ts(), || {
+            let md = MetaDao::new(conn);
+            for meta in md.list_by_question(qid)? {
+                if meta.key == kp_key() && knowledge_points.iter().any(|kp| kp.trim() == meta.value) {
+                    md.delete(meta.id)?;
+                }
+            }
+            Ok(())
+        })?,
+    }
+    if matches!(action, BulkAction::AddKnowledgePoints { .. } | BulkAction::RemoveKnowledgePoints { .. }) {
+        crate::server::cloze::sync_cards(conn, qid, now_ts())?;
+        crate::server::occlusion::sync_masks(conn, qid, now_ts())?;
+        crate::server::question_part::sync_part_metas(conn, qid)?;
+    }
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn question(conn: &Connection, subject: &str, kps: &[&str]) -> i64 {
+        let qid = crate::db::insert_question(conn, Some("q"), "LEARNING", 0).unwrap();
+        crate::db::insert_meta(conn, qid, "system.Subject", subject).unwrap();
+        for kp in kps {
+            crate::db::insert_meta(conn, qid, "system.KnowledgePoint", kp).unwrap();
+        }
+        qid
+    }
+
+    fn kps_of(conn: &Connection, qid: i64) -> Vec<String> {
+        crate::db::select_meta_values_by_question_key(conn, qid, "system.KnowledgePoint").unwrap()
+    }
+
+    fn store() -> (tempfile::TempDir, AssetStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let store = AssetStore::new(dir.path().to_path_buf());
+        (dir, store)
+    }
+
+    #[test]
+    fn test_bulk_outcomes_are_per_question() {
+        let conn = setup();
+        let (_dir, store) = store();
+        let q1 = question(&conn, "数学", &["函数"]);
+        let q2 = question(&conn, "数学", &[]);
+        let missing = 999;
+
+        let outcomes = run_bulk(&conn, &store, &[q1, missing, q2], &BulkAction::Suspend).unwrap();
+        let ok: Vec<bool> = outcomes.iter().map(|o| o.ok).collect();
+        assert_eq!(ok, vec![true, false, true]);
+        // 已暂停的题目再暂停失败，不影响同批其他题目
+        let q3 = question(&conn, "数学", &[]);
+        let outcomes = run_bulk(&conn, &store, &[q1, q3], &BulkAction::Suspend).unwrap();
+        assert!(!outcomes[0].ok && outcomes[1].ok);
+
+        let add = BulkAction::AddKnowledgePoints { knowledge_points: vec!["函数".into(), "数列".into()] };
+        run_bulk(&conn, &store, &[q1, q2], &add).unwrap();
+        assert_eq!(kps_of(&conn, q1), vec!["函数", "数列"]);
+        assert_eq!(kps_of(&conn, q2), vec!["函数", "数列"]);
+        let remove = BulkAction::RemoveKnowledgePoints { knowledge_points: vec!["函数".into()] };
+        run_bulk(&conn, &store, &[q1, q2], &remove).unwrap();
+        assert_eq!(kps_of(&conn, q1), vec!["数列"]);
+
+        run_bulk(&conn, &store, &[q1], &BulkAction::ResetProgress).unwrap();
+        let q = QuestionDao::new(&conn).get_by_id(QuestionId::from(q1)).unwrap().unwrap();
+        assert_eq!(q.state, crate::domain::enums::QuestionState::NEW);
+        assert!(q.due_at.is_none() && q.last_result.is_none());
+    }
+
+    #[test]
+    fn test_bulk_by_filter_and_recycle_bin_flow() {
+        let conn = setup();
+        let (_dir, store) = store();
+        let q1 = question(&conn, "数学", &[]);
+        let q2 = question(&conn, "数学", &[]);
+        let q3 = question(&conn, "物理", &[]);
+
+        let math = BulkFilter { subject: Some("数学".into()), ..Default::default() };
+        let ids = resolve_selection(&conn, None, Some(math.clone()), &BulkAction::Delete).unwrap();
+        assert_eq!(ids, vec![q1, q2]);
+        run_bulk(&conn, &store, &ids, &BulkAction::Delete).unwrap();
+
+        // 恢复 / 永久删除在回收站中按条件查找
+        let ids = resolve_selection(&conn, None, Some(math.clone()), &BulkAction::PermanentlyDelete).unwrap();
+        assert_eq!(ids, vec![q1, q2]);
+        let outcomes = run_bulk(&conn, &store, &[q1, q3], &BulkAction::PermanentlyDelete).unwrap();
+        assert!(outcomes[0].ok && !outcomes[1].ok);
+        assert!(QuestionDao::new(&conn).get_by_id(QuestionId::from(q1)).unwrap().is_none());
+
+        run_bulk(&conn, &store, &[q2], &BulkAction::Restore).unwrap();
+        let set = BulkAction::SetSubject { subject: "物理".into() };
+        run_bulk(&conn, &store, &[q2], &set).unwrap();
+        let physics = BulkFilter { subject: Some("物理".into()), ..Default::default() };
+        assert_eq!(resolve_selection(&conn, None, Some(physics), &set).unwrap(), vec![q2, q3]);
+
+        assert!(resolve_selection(&conn, None, None, &set).is_err());
+        assert!(run_bulk(&conn, &store, &[q2], &BulkAction::SetSubject { subject: " ".into() }).is_err());
+        assert!(run_bulk(&conn, &store, &[q2], &BulkAction::SetPaper { paper_id: Some(999) }).is_err());
+    }
+}
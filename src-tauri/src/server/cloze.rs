@@ -0,0 +1,278 @@
+//! 填空卡
+//!
+//! 题干中的 `{{c1::答案}}` / `{{c1::答案::提示}}` 为题目（笔记）生成填空卡，编号相同的几处填空属于同一张卡。
+//! 填空卡是笔记的小题（见 `server::question_part`）：各自按状态机复习，笔记的状态由卡片推导。
+//! 卡片没有自己的文本，显示时由笔记题干渲染：本卡的填空挖空，其余填空照常显示。
+//!
+//! 卡片随笔记的编辑同步：新出现的编号生成新卡，消失的编号移入回收站（编号再出现时恢复，复习记录仍在），
+//! 名称、科目与知识点跟随笔记。同一笔记的卡片互为兄弟，每日推荐同一天只排其中一张。
+
+use crate::dao::question_dao::QuestionDao;
+use crate::dao::question_part_dao::QuestionPartDao;
+use crate::dao::question_text_dao::QuestionTextDao;
+use crate::db::connection::Connection;
+use crate::domain::enums::{QuestionState, TextField};
+use crate::domain::ids::QuestionId;
+use crate::domain::question_text::QuestionText;
+use crate::error::AppError;
+use crate::server::{question_part, ReviewManager};
+use crate::util::time::Timestamp;
+
+/// 题干中的一处填空
+#[derive(Debug, Clone, PartialEq)]
+struct Cloze {
+    index: i64,
+    answer: String,
+    hint: Option<String>,
+    /// 在原文中的字节范围（含 `{{` 与 `}}`）
+    start: usize,
+    end: usize,
+}
+
+/// 从 `start`（指向 `{{`）解析一处填空；答案中的花括号须成对（如 LaTeX 的 `x^{2}`）
+fn parse_at(text: &str, start: usize) -> Option<Cloze> {
+    let rest = text[start..].strip_prefix("{{c")?;
+    let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+    let index: i64 = rest[..digits].parse().ok().filter(|&i| i > 0)?;
+    let body_start = start + 3 + digits + 2;
+    if !rest[digits..].starts_with("::") {
+        return None;
+    }
+
+    let mut depth = 0;
+    let bytes = text.as_bytes();
+    let mut i = body_start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' if depth > 0 => depth -= 1,
+            b'}' if bytes.get(i + 1) == Some(&b'}') => {
+                let body = &text[body_start..i];
+                let (answer, hint) = match body.split_once("::") {
+                    Some((answer, hint)) => (answer, Some(hint.to_string())),
+                    None => (body, None),
+                };
+                return Some(Cloze { index, answer: answer.to_string(), hint, start, end: i + 2 });
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+fn parse(text: &str) -> Vec<Cloze> {
+    let mut clozes = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = text[pos..].find("{{c") {
+        let start = pos + offset;
+        match parse_at(text, start) {
+            Some(cloze) => {
+                pos = cloze.end;
+                clozes.push(cloze);
+            }
+            None => pos = start + 3,
+        }
+    }
+    clozes
+}
+
+/// 题干中出现的填空编号，升序去重
+pub fn cloze_indexes(text: &str) -> Vec<i64> {
+    let mut indexes: Vec<i64> = parse(text).into_iter().map(|c| c.index).collect();
+    indexes.sort();
+    indexes.dedup();
+    indexes
+}
+
+/// 把每处填空替换成 `show(填空)` 的结果
+fn render(text: &str, show: impl Fn(&Cloze) -> String) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0;
+    for cloze in parse(text) {
+        out.push_str(&text[pos..cloze.start]);
+        out.push_str(&show(&cloze));
+        pos = cloze.end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+/// 卡片正面：编号 `index` 的填空挖空（有提示时显示提示），其余填空显示答案
+pub fn render_front(text: &str, index: i64) -> String {
+    render(text, |c| {
+        if c.index == index {
+            format!("[{}]", c.hint.as_deref().unwrap_or("..."))
+        } else {
+            c.answer.clone()
+        }
+    })
+}
+
+/// 卡片背面：编号 `index` 的填空加粗显示答案，其余填空显示答案
+pub fn render_back(text: &str, index: i64) -> String {
+    render(text, |c| {
+        if c.index == index {
+            format!("**{}**", c.answer)
+        } else {
+            c.answer.clone()
+        }
+    })
+}
+
+fn prompt_of(conn: &Connection, question_id: i64) -> Result<String, AppError> {
+    Ok(QuestionTextDao::new(conn)
+        .latest(question_id, TextField::PROMPT)?
+        .map(|v| v.content)
+        .unwrap_or_default())
+}
+
+/// 填空卡显示用的文本：题干为正面，答案为背面（后接笔记原有的答案），解析沿用笔记的。
+/// 不是填空卡时返回 None。
+pub fn card_text(conn: &Connection, card_id: QuestionId) -> Result<Option<QuestionText>, AppError> {
+    let Some((note_id, index)) = QuestionPartDao::new(conn).cloze_of(i64::from(card_id))? else {
+        return Ok(None);
+    };
+    let td = QuestionTextDao::new(conn);
+    let latest = |field| -> Result<Option<String>, AppError> {
+        Ok(td.latest(note_id, field)?.map(|v| v.content).filter(|c| !c.is_empty()))
+    };
+    let prompt = prompt_of(conn, note_id)?;
+    let back = render_back(&prompt, index);
+    let answer = match latest(TextField::ANSWER)? {
+        Some(answer) => format!("{}\n\n{}", back, answer),
+        None => back,
+    };
+    Ok(Some(QuestionText {
+        prompt: Some(render_front(&prompt, index)),
+        answer: Some(answer),
+        explanation: latest(TextField::EXPLANATION)?,
+    }))
+}
+
+/// 填空卡的标准答案：本卡各处填空的答案，按出现顺序以空格连接。不是填空卡时返回 None。
+pub fn card_answer(conn: &Connection, card_id: QuestionId) -> Result<Option<String>, AppError> {
+    let Some((note_id, index)) = QuestionPartDao::new(conn).cloze_of(i64::from(card_id))? else {
+        return Ok(None);
+    };
+    let answers: Vec<String> =
+        parse(&prompt_of(conn, note_id)?).into_iter().filter(|c| c.index == index).map(|c| c.answer).collect();
+    Ok(Some(answers.join(" ")))
+}
+
+/// 按笔记当前的题干同步填空卡。笔记本身是小题、已删除或不存在时不处理。
+/// 不自带事务，由编辑题目的调用方包在事务里。
+pub fn sync_cards(conn: &Connection, note_id: QuestionId, now: Timestamp) -> Result<(), AppError> {
+    let qd = QuestionDao::new(conn);
+    let pd = QuestionPartDao::new(conn);
+    let note_i64 = i64::from(note_id);
+    let Some(note) = qd.get_by_id(note_id)? else {
+        return Ok(());
+    };
+    if note.deleted_at.is_some() || pd.parent_of(note_i64)?.is_some() {
+        return Ok(());
+    }
+
+    let wanted = cloze_indexes(&prompt_of(conn, note_i64)?);
+    let mut cards = pd.list_cloze_cards(note_i64)?;
+    if wanted.is_empty() && cards.is_empty() {
+        return Ok(());
+    }
+
+    // 编号消失的卡片移入回收站，重新出现的恢复
+    for &(card, index) in &cards {
+        let card = QuestionId::from(card);
+        let deleted = qd.get_by_id(card)?.is_some_and(|q| q.deleted_at.is_some());
+        match (wanted.contains(&index), deleted) {
+            (true, true) => qd.update_deleted_at(card, None)?,
+            (false, false) => qd.update_deleted_at(card, Some(now))?,
+            _ => {}
+        }
+    }
+    let missing: Vec<i64> =
+        wanted.iter().copied().filter(|i| !cards.iter().any(|(_, c)| c == i)).collect();
+    for index in missing {
+        let card = qd.insert(None, QuestionState::NEW, now)?;
+        pd.append_cloze(note_i64, i64::from(card), index)?;
+        cards.push((i64::from(card), index));
+    }
+
+    // 名称、科目与知识点跟随笔记
+    let note_name = note.name.as_deref().unwrap_or("未命名题目");
+    for &(card, index) in cards.iter().filter(|(_, i)| wanted.contains(i)) {
+        let card = QuestionId::from(card);
+        qd.update_name(card, Some(&format!("{} [c{}]", note_name, index)))?;
+        question_part::inherit_metas(conn, note_id, card)?;
+    }
+
+    ReviewManager::new(conn).sync_parent(note_id).map_err(AppError::InvalidInput)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::meta_dao::MetaDao;
+    use crate::domain::enums::ReviewResult;
+    use crate::server::question_manager::{get_question_detail, update_question};
+    use crate::server::question_part::list_parts;
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn edit_prompt(conn: &Connection, qid: QuestionId, prompt: &str, now: i64) {
+        let text = QuestionText { prompt: Some(prompt.to_string()), ..QuestionText::default() };
+        update_question(conn, qid, None, None, None, text, Timestamp::from(now)).unwrap();
+    }
+
+    #[test]
+    fn test_parse_and_render() {
+        let text = "{{c1::北京}}是{{c2::中国::国家}}的首都，{{c1::北京}}面积 $x^{2}$ 与 {{c3::$\\frac{a}{b}$}}，{{c0::x}} {{c4:y}}";
+        assert_eq!(cloze_indexes(text), vec![1, 2, 3]);
+        assert_eq!(
+            render_front(text, 1),
+            "[...]是中国的首都，[...]面积 $x^{2}$ 与 $\\frac{a}{b}$，{{c0::x}} {{c4:y}}"
+        );
+        assert!(render_front(text, 2).starts_with("北京是[国家]的首都"));
+        assert!(render_back(text, 3).contains("**$\\frac{a}{b}$**"));
+        assert_eq!(cloze_indexes("{{c1::没有结尾"), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn test_cards_follow_note_edits() {
+        let conn = setup();
+        let note = QuestionId::from(crate::db::insert_question(&conn, Some("首都"), "NEW", 0).unwrap());
+        crate::db::insert_meta(&conn, i64::from(note), "system.Subject", "地理").unwrap();
+
+        edit_prompt(&conn, note, "{{c1::北京}}是{{c2::中国}}的首都", 10);
+        let cards = list_parts(&conn, note).unwrap();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].name.as_deref(), Some("首都 [c1]"));
+        let (c1, c2) = (cards[0].id, cards[1].id);
+        let subject = MetaDao::new(&conn).get_by_question_key(c2, "system.Subject").unwrap().unwrap();
+        assert_eq!(subject.value, "地理");
+
+        // 卡片各自复习，笔记由卡片推导；卡片显示笔记题干渲染的文本
+        let manager = ReviewManager::new(&conn);
+        manager.process_review(c1, ReviewResult::WRONG, Timestamp::from(20)).unwrap();
+        assert_eq!(QuestionDao::new(&conn).get_by_id(note).unwrap().unwrap().state, QuestionState::LEARNING);
+        let detail = get_question_detail(&conn, c2).unwrap();
+        assert_eq!(detail.text.prompt.as_deref(), Some("北京是[...]的首都"));
+        assert_eq!(detail.text.answer.as_deref(), Some("北京是**中国**的首都"));
+
+        // 删掉 c1 的填空：c1 进回收站；加回来时原卡恢复，复习记录仍在
+        edit_prompt(&conn, note, "北京是{{c2::中国}}的首都", 30);
+        assert_eq!(list_parts(&conn, note).unwrap().iter().map(|q| q.id).collect::<Vec<_>>(), vec![c2]);
+        edit_prompt(&conn, note, "{{c1::北京}}是{{c2::中国}}的首都，{{c3::故宫}}在北京", 40);
+        let cards = list_parts(&conn, note).unwrap();
+        assert_eq!(cards.iter().map(|q| q.id).take(2).collect::<Vec<_>>(), vec![c1, c2]);
+        assert_eq!(cards.len(), 3);
+        assert_eq!(cards[0].wrong_count, 1);
+
+        // 卡片不再生成自己的卡片
+        edit_prompt(&conn, c1, "{{c1::x}}", 50);
+        assert!(list_parts(&conn, c1).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,168 @@
+//! 重置学习进度（遗忘）
+//!
+//! 把一道题、一个知识点子树或一整个科目下的题目退回新题，排程从头开始（如新学年重修某科）。
+//! 原有复习记录可以归档到 `review_archive`：不再影响排程与错误率，但复习历史统计仍然可见；
+//! 不归档则直接删除。跳过记录一并清除，推荐不再因之前的跳过压低这些题。整个操作在一个事务里完成。
+
+use serde::Deserialize;
+
+use crate::dao::knowledge_point_dao::KnowledgePointDao;
+use crate::dao::question_part_dao::QuestionPartDao;
+use crate::dao::review_dao::ReviewDao;
+use crate::dao::skip_dao::SkipDao;
+use crate::dao::subject_dao::SubjectDao;
+use crate::dao::view_dao::ViewDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::domain::ids::QuestionId;
+use crate::error::AppError;
+use crate::server::knowledge_point;
+use crate::server::review_manager::ReviewManager;
+use crate::util::time::Timestamp;
+
+/// 重置范围
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ForgetScope {
+    /// 指定题目
+    Questions { question_ids: Vec<i64> },
+    /// 知识点及其全部后代节点下的题目
+    KnowledgePoint { id: i64 },
+    /// 科目下的题目
+    Subject { id: i64 },
+}
+
+/// 重置范围内的题目；`archive_reviews` 为 true 时归档复习记录，否则删除。
+/// 回收站中的题目不在知识点 / 科目范围内。返回重置的题目数。
+pub fn forget(
+    conn: &Connection,
+    scope: &ForgetScope,
+    archive_reviews: bool,
+    now: Timestamp,
+) -> Result<usize, AppError> {
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    // 大题的复习记录在小题上，小题一起处理
+    let pd = QuestionPartDao::new(&tx);
+    let mut ids = Vec::new();
+    for id in resolve_scope(&tx, scope)? {
+        ids.extend(pd.list_part_ids(id, false)?);
+        ids.push(id);
+    }
+
+    let manager = ReviewManager::new(&tx);
+    let rd = ReviewDao::new(&tx);
+    let sd = SkipDao::new(&tx);
+    for &id in &ids {
+        let qid = QuestionId::from(id);
+        manager.reset(qid).map_err(AppError::InvalidInput)?;
+        if archive_reviews {
+            rd.archive_by_question(qid, now)?;
+        } else {
+            rd.delete_by_question(qid)?;
+        }
+        sd.delete_by_question(id)?;
+    }
+
+    tx.commit().map_err(DbError::from)?;
+    Ok(ids.len())
+}
+
+fn resolve_scope(conn: &Connection, scope: &ForgetScope) -> Result<Vec<i64>, AppError> {
+    let views = ViewDao::new(conn);
+    match scope {
+        ForgetScope::Questions { question_ids } => {
+            let mut ids: Vec<i64> = Vec::with_capacity(question_ids.len());
+            for &id in question_ids {
+                if !ids.contains(&id) {
+                    ids.push(id);
+                }
+            }
+            Ok(ids)
+        }
+        ForgetScope::KnowledgePoint { id } => {
+            let kp = KnowledgePointDao::new(conn)
+                .get(*id)?
+                .ok_or_else(|| AppError::NotFound(format!("knowledge point {}", id)))?;
+            let filter = knowledge_point::knowledge_point_filter(conn, &kp.name)?;
+            Ok(views.list_ids_filtered(false, None, None, &[], &[filter])?)
+        }
+        ForgetScope::Subject { id } => {
+            let subject = SubjectDao::new(conn)
+                .get(*id)?
+                .ok_or_else(|| AppError::NotFound(format!("subject {}", id)))?;
+            Ok(views.list_ids_filtered(false, Some(&subject.name), None, &[], &[])?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::question_dao::QuestionDao;
+    use crate::domain::enums::QuestionState;
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn reviewed(conn: &Connection, subject: &str, kp: &str) -> i64 {
+        let qid = crate::db::insert_question(conn, Some("q"), "LEARNING", 0).unwrap();
+        crate::db::insert_meta(conn, qid, "system.Subject", subject).unwrap();
+        crate::db::insert_meta(conn, qid, "system.KnowledgePoint", kp).unwrap();
+        crate::db::insert_review(conn, qid, "wrong", 100).unwrap();
+        crate::db::insert_review(conn, qid, "correct", 200).unwrap();
+        qid
+    }
+
+    fn count(conn: &Connection, table: &str, qid: i64) -> i64 {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {} WHERE question_id = ?1", table), [qid], |r| r.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_forget_by_subject_archives_reviews() {
+        let conn = setup();
+        let math = reviewed(&conn, "数学", "函数");
+        let physics = reviewed(&conn, "物理", "力学");
+        SkipDao::new(&conn).insert(math, 250).unwrap();
+        SkipDao::new(&conn).insert(physics, 250).unwrap();
+        let subject = SubjectDao::new(&conn).get_by_name("数学").unwrap().unwrap();
+
+        let n = forget(&conn, &ForgetScope::Subject { id: subject.id }, true, Timestamp::from(300)).unwrap();
+        assert_eq!(n, 1);
+        let q = QuestionDao::new(&conn).get_by_id(QuestionId::from(math)).unwrap().unwrap();
+        assert_eq!(q.state, QuestionState::NEW);
+        assert_eq!((q.correct_streak, q.wrong_count), (0, 0));
+        assert!(q.last_result.is_none() && q.due_at.is_none());
+        assert_eq!((count(&conn, "review", math), count(&conn, "review_archive", math)), (0, 2));
+        assert_eq!(count(&conn, "review_skip", math), 0);
+        // 其他科目不受影响
+        assert_eq!(count(&conn, "review", physics), 2);
+        assert_eq!(count(&conn, "review_skip", physics), 1);
+
+        // 归档的记录仍计入复习历史
+        let series = crate::db::select_review_daily_series(&conn, Some("数学"), None, None, 0, 0).unwrap();
+        assert_eq!(series.iter().map(|r| r.review_count).sum::<i64>(), 2);
+    }
+
+    #[test]
+    fn test_forget_by_knowledge_point_subtree_without_archive() {
+        let conn = setup();
+        let parent = crate::server::knowledge_point::create_knowledge_point(&conn, "函数", None, None, Timestamp::from(0))
+            .unwrap();
+        crate::server::knowledge_point::create_knowledge_point(&conn, "二次函数", None, Some(parent.id), Timestamp::from(0))
+            .unwrap();
+        let child = reviewed(&conn, "数学", "二次函数");
+        let other = reviewed(&conn, "数学", "数列");
+
+        let n = forget(&conn, &ForgetScope::KnowledgePoint { id: parent.id }, false, Timestamp::from(300)).unwrap();
+        assert_eq!(n, 1);
+        assert_eq!((count(&conn, "review", child), count(&conn, "review_archive", child)), (0, 0));
+        assert_eq!(count(&conn, "review", other), 2);
+
+        assert!(forget(&conn, &ForgetScope::Questions { question_ids: vec![999] }, true, Timestamp::from(300)).is_err());
+        assert!(forget(&conn, &ForgetScope::Subject { id: 999 }, true, Timestamp::from(300)).is_err());
+    }
+}
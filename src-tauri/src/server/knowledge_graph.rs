@@ -0,0 +1,239 @@
+//! 知识点前置关系图
+//!
+//! 边 `knowledge_point → prerequisite` 表示"学 knowledge_point 之前要先掌握 prerequisite"。
+//! 图在写入时保证无环；查询（祖先、后代、最薄弱前置）都在内存中完成，
+//! 推荐系统据此在某知识点近期错误率偏高时补推其前置知识点的题目。
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::dao::prerequisite_dao::PrerequisiteDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::error::AppError;
+use crate::util::time::Timestamp;
+
+/// 知识点前置关系图（内存表示）
+#[derive(Debug, Clone, Default)]
+pub struct KnowledgeGraph {
+    /// knowledge_point → 直接前置
+    prerequisites: HashMap<String, Vec<String>>,
+    /// prerequisite → 直接后继
+    dependents: HashMap<String, Vec<String>>,
+}
+
+impl KnowledgeGraph {
+    /// 由 (knowledge_point, prerequisite) 边列表构建
+    pub fn from_edges(edges: &[(String, String)]) -> Self {
+        let mut graph = Self::default();
+        for (kp, prereq) in edges {
+            graph.prerequisites.entry(kp.clone()).or_default().push(prereq.clone());
+            graph.dependents.entry(prereq.clone()).or_default().push(kp.clone());
+        }
+        graph
+    }
+
+    /// 从数据库加载整张图
+    pub fn load(conn: &Connection) -> Result<Self, DbError> {
+        let edges = PrerequisiteDao::new(conn).list_all()?;
+        Ok(Self::from_edges(&edges))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prerequisites.is_empty()
+    }
+
+    /// 全部祖先（直接与间接前置），按距离由近及远
+    pub fn ancestors(&self, knowledge_point: &str) -> Vec<String> {
+        Self::walk(&self.prerequisites, knowledge_point)
+    }
+
+    /// 全部后代（直接与间接依赖它的知识点），按距离由近及远
+    pub fn descendants(&self, knowledge_point: &str) -> Vec<String> {
+        Self::walk(&self.dependents, knowledge_point)
+    }
+
+    /// 添加边 `knowledge_point → prerequisite` 是否会成环：
+    /// 即 knowledge_point 已经是 prerequisite 的祖先（或二者相同）
+    pub fn creates_cycle(&self, knowledge_point: &str, prerequisite: &str) -> bool {
+        knowledge_point == prerequisite
+            || self.ancestors(prerequisite).iter().any(|kp| kp == knowledge_point)
+    }
+
+    /// 最薄弱的前置知识点：祖先中复习次数达到 `min_reviews` 且错误率最高的一个；
+    /// 同错误率取距离更近的。没有祖先有足够数据时退回最近的直接前置。
+    pub fn weakest_prerequisite(
+        &self,
+        knowledge_point: &str,
+        error_rates: &HashMap<String, (f64, i64)>,
+        min_reviews: i64,
+    ) -> Option<String> {
+        let ancestors = self.ancestors(knowledge_point);
+        let mut weakest: Option<(&String, f64)> = None;
+        for kp in &ancestors {
+            if let Some(&(rate, count)) = error_rates.get(kp) {
+                let weaker = match weakest {
+                    Some((_, best)) => rate > best,
+                    None => true,
+                };
+                if count >= min_reviews && weaker {
+                    weakest = Some((kp, rate));
+                }
+            }
+        }
+        weakest.map(|(kp, _)| kp.clone()).or_else(|| ancestors.into_iter().next())
+    }
+
+    /// 近期薄弱的知识点 → 需要补推的前置知识点。
+    ///
+    /// 错误率 ≥ `threshold` 且复习次数 ≥ `min_reviews` 的知识点视为薄弱，
+    /// 取其最薄弱的前置；返回 prerequisite → 薄弱知识点（多个薄弱点共用同一前置时取错误率更高的）。
+    pub fn prerequisite_focus(
+        &self,
+        error_rates: &HashMap<String, (f64, i64)>,
+        threshold: f64,
+        min_reviews: i64,
+    ) -> HashMap<String, String> {
+        let mut weak: Vec<(&String, f64)> = error_rates
+            .iter()
+            .filter(|(_, &(rate, count))| rate >= threshold && count >= min_reviews)
+            .map(|(kp, &(rate, _))| (kp, rate))
+            .collect();
+        // 错误率高的优先占用前置；同错误率按名称，保证结果稳定
+        weak.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then_with(|| a.0.cmp(b.0))
+        });
+
+        let mut focus = HashMap::new();
+        for (kp, _) in weak {
+            if let Some(prereq) = self.weakest_prerequisite(kp, error_rates, min_reviews) {
+                focus.entry(prereq).or_insert_with(|| kp.clone());
+            }
+        }
+        focus
+    }
+
+    fn walk(edges: &HashMap<String, Vec<String>>, start: &str) -> Vec<String> {
+        let mut visited: HashSet<&str> = HashSet::from([start]);
+        let mut queue: VecDeque<&str> = VecDeque::from([start]);
+        let mut result = Vec::new();
+        while let Some(kp) = queue.pop_front() {
+            for next in edges.get(kp).into_iter().flatten() {
+                if visited.insert(next.as_str()) {
+                    result.push(next.clone());
+                    queue.push_back(next.as_str());
+                }
+            }
+        }
+        result
+    }
+}
+
+/// 添加前置关系；两端不能为空或相同，且不能成环
+pub fn add_prerequisite(
+    conn: &Connection,
+    knowledge_point: &str,
+    prerequisite: &str,
+    now: Timestamp,
+) -> Result<(), AppError> {
+    let knowledge_point = knowledge_point.trim();
+    let prerequisite = prerequisite.trim();
+    if knowledge_point.is_empty() || prerequisite.is_empty() {
+        return Err(AppError::InvalidInput("knowledge point must not be empty".to_string()));
+    }
+
+    let graph = KnowledgeGraph::load(conn)?;
+    if graph.creates_cycle(knowledge_point, prerequisite) {
+        return Err(AppError::InvalidInput(format!(
+            "prerequisite cycle: {} already depends on {}",
+            prerequisite, knowledge_point
+        )));
+    }
+
+    PrerequisiteDao::new(conn).insert(knowledge_point, prerequisite, now.as_i64())?;
+    Ok(())
+}
+
+/// 删除前置关系
+pub fn remove_prerequisite(
+    conn: &Connection,
+    knowledge_point: &str,
+    prerequisite: &str,
+) -> Result<(), AppError> {
+    if !PrerequisiteDao::new(conn).delete(knowledge_point, prerequisite)? {
+        return Err(AppError::NotFound(format!("{} -> {}", knowledge_point, prerequisite)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(edges: &[(&str, &str)]) -> KnowledgeGraph {
+        let edges: Vec<(String, String)> =
+            edges.iter().map(|(a, b)| (a.to_string(), b.to_string())).collect();
+        KnowledgeGraph::from_edges(&edges)
+    }
+
+    fn rates(items: &[(&str, f64, i64)]) -> HashMap<String, (f64, i64)> {
+        items.iter().map(|(kp, r, c)| (kp.to_string(), (*r, *c))).collect()
+    }
+
+    #[test]
+    fn test_ancestors_and_descendants() {
+        // 二次函数 → 一次函数 → 代数式；二次函数 → 方程 → 代数式
+        let g = graph(&[("二次函数", "一次函数"), ("二次函数", "方程"), ("一次函数", "代数式"), ("方程", "代数式")]);
+        assert_eq!(g.ancestors("二次函数"), vec!["一次函数", "方程", "代数式"]);
+        assert_eq!(g.descendants("代数式"), vec!["一次函数", "方程", "二次函数"]);
+        assert!(g.ancestors("代数式").is_empty());
+    }
+
+    #[test]
+    fn test_creates_cycle() {
+        let g = graph(&[("二次函数", "一次函数"), ("一次函数", "代数式")]);
+        assert!(g.creates_cycle("代数式", "二次函数"));
+        assert!(g.creates_cycle("一次函数", "一次函数"));
+        assert!(!g.creates_cycle("二次函数", "代数式"));
+    }
+
+    #[test]
+    fn test_weakest_prerequisite() {
+        let g = graph(&[("二次函数", "一次函数"), ("一次函数", "代数式")]);
+        // 代数式错误率更高
+        let r = rates(&[("一次函数", 0.3, 5), ("代数式", 0.6, 5)]);
+        assert_eq!(g.weakest_prerequisite("二次函数", &r, 3).as_deref(), Some("代数式"));
+        // 样本不足时不参与比较，退回最近的直接前置
+        let r = rates(&[("代数式", 0.9, 1)]);
+        assert_eq!(g.weakest_prerequisite("二次函数", &r, 3).as_deref(), Some("一次函数"));
+        assert_eq!(g.weakest_prerequisite("代数式", &r, 3), None);
+    }
+
+    #[test]
+    fn test_prerequisite_focus_only_for_weak_points() {
+        let g = graph(&[("二次函数", "一次函数"), ("导数", "极限")]);
+        let r = rates(&[("二次函数", 0.8, 4), ("导数", 0.2, 10), ("一次函数", 0.1, 4)]);
+        let focus = g.prerequisite_focus(&r, 0.5, 3);
+        assert_eq!(focus, HashMap::from([("一次函数".to_string(), "二次函数".to_string())]));
+    }
+
+    #[test]
+    fn test_add_prerequisite_rejects_cycle() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        let now = Timestamp(0);
+
+        add_prerequisite(&conn, "二次函数", "一次函数", now).unwrap();
+        add_prerequisite(&conn, "一次函数", "代数式", now).unwrap();
+        assert!(matches!(
+            add_prerequisite(&conn, "代数式", "二次函数", now),
+            Err(AppError::InvalidInput(_))
+        ));
+
+        remove_prerequisite(&conn, "一次函数", "代数式").unwrap();
+        add_prerequisite(&conn, "代数式", "二次函数", now).unwrap();
+        assert!(matches!(
+            remove_prerequisite(&conn, "一次函数", "代数式"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,409 @@
+//! 知识点树
+//!
+//! 每个科目一棵知识点树（如 函数 › 二次函数 › 顶点式），节点名称全库唯一。
+//! 题目通过 `system.KnowledgePoint` 元信息引用节点名称，前置关系图（`knowledge_graph`）也按名称记录，
+//! 因此改名、合并、删除都在一个事务里同时更新节点、题目和前置关系。
+//! 列表过滤与统计选中某节点时包含其全部后代节点。
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::dao::knowledge_point_dao::KnowledgePointDao;
+use crate::dao::prerequisite_dao::PrerequisiteDao;
+use crate::dao::subject_dao::SubjectDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::db::schema::view_schema::{MetaCond, MetaFilter};
+use crate::domain::knowledge_point::KnowledgePoint;
+use crate::error::AppError;
+use crate::server::knowledge_graph::KnowledgeGraph;
+use crate::util::time::Timestamp;
+
+/// 知识点树（内存表示）
+#[derive(Debug, Clone, Default)]
+pub struct KnowledgeTree {
+    nodes: HashMap<i64, KnowledgePoint>,
+    /// parent_id → 子节点 id（按显示顺序）
+    children: HashMap<Option<i64>, Vec<i64>>,
+}
+
+impl KnowledgeTree {
+    /// 由节点列表构建；列表应已按显示顺序排列
+    pub fn from_nodes(nodes: Vec<KnowledgePoint>) -> Self {
+        let mut tree = Self::default();
+        for node in nodes {
+            tree.children.entry(node.parent_id).or_default().push(node.id);
+            tree.nodes.insert(node.id, node);
+        }
+        tree
+    }
+
+    /// 从数据库加载全部节点
+    pub fn load(conn: &Connection) -> Result<Self, DbError> {
+        Ok(Self::from_nodes(KnowledgePointDao::new(conn).list()?))
+    }
+
+    pub fn get(&self, id: i64) -> Option<&KnowledgePoint> {
+        self.nodes.get(&id)
+    }
+
+    /// 按名称查节点
+    pub fn find(&self, name: &str) -> Option<&KnowledgePoint> {
+        self.nodes.values().find(|n| n.name == name)
+    }
+
+    /// 全部后代 id（不含自身），先序
+    pub fn descendants(&self, id: i64) -> Vec<i64> {
+        let mut out = Vec::new();
+        let mut stack: Vec<i64> = self.children_of(Some(id)).iter().rev().copied().collect();
+        while let Some(next) = stack.pop() {
+            out.push(next);
+            stack.extend(self.children_of(Some(next)).iter().rev());
+        }
+        out
+    }
+
+    /// 自身与全部后代的名称
+    pub fn subtree_names(&self, id: i64) -> Vec<String> {
+        std::iter::once(id)
+            .chain(self.descendants(id))
+            .filter_map(|i| self.nodes.get(&i).map(|n| n.name.clone()))
+            .collect()
+    }
+
+    /// 从根到该节点的名称路径
+    pub fn path(&self, id: i64) -> Vec<String> {
+        let mut path = Vec::new();
+        let mut cur = self.nodes.get(&id);
+        while let Some(node) = cur {
+            path.push(node.name.clone());
+            cur = node.parent_id.and_then(|p| self.nodes.get(&p));
+        }
+        path.reverse();
+        path
+    }
+
+    fn children_of(&self, parent: Option<i64>) -> &[i64] {
+        self.children.get(&parent).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+}
+
+/// 知识点节点及其子树统计（含全部后代节点）
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgePointStats {
+    pub id: i64,
+    pub name: String,
+    pub subject_id: Option<i64>,
+    pub parent_id: Option<i64>,
+    /// 从根到该节点的名称路径
+    pub path: Vec<String>,
+    /// 子树内的未删除题目数（一道题只算一次）
+    pub question_count: i64,
+    pub review_count: i64,
+    /// 子树内题目的复习错误率；没有复习记录时为 None
+    pub error_rate: Option<f64>,
+}
+
+fn get_node(conn: &Connection, id: i64) -> Result<KnowledgePoint, AppError> {
+    KnowledgePointDao::new(conn)
+        .get(id)?
+        .ok_or_else(|| AppError::NotFound(format!("knowledge point {}", id)))
+}
+
+fn check_name(conn: &Connection, name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("knowledge point must not be empty".to_string()));
+    }
+    if KnowledgePointDao::new(conn).get_by_name(name)?.is_some() {
+        return Err(AppError::InvalidInput(format!("knowledge point already exists: {}", name)));
+    }
+    Ok(name.to_string())
+}
+
+/// 前置关系中的 `old` 改写为 `new` 后不能成环：以不涉及 `old` 的边为底，
+/// 逐条加入改写后的边，按 `add_prerequisite` 同样的规则检查。改写成自环的边会被删除，不检查。
+fn check_rewritten_edges(conn: &Connection, old: &str, new: &str) -> Result<(), AppError> {
+    let (rewritten, mut kept): (Vec<_>, Vec<_>) = PrerequisiteDao::new(conn)
+        .list_all()?
+        .into_iter()
+        .partition(|(kp, prereq)| kp == old || prereq == old);
+    let rewrite = |name: String| if name == old { new.to_string() } else { name };
+    for (kp, prereq) in rewritten {
+        let (kp, prereq) = (rewrite(kp), rewrite(prereq));
+        if kp == prereq {
+            continue;
+        }
+        if KnowledgeGraph::from_edges(&kept).creates_cycle(&kp, &prereq) {
+            return Err(AppError::InvalidInput(format!(
+                "prerequisite cycle: {} already depends on {}",
+                prereq, kp
+            )));
+        }
+        kept.push((kp, prereq));
+    }
+    Ok(())
+}
+
+/// 新建节点。有父节点时科目取父节点的科目。
+pub fn create_knowledge_point(
+    conn: &Connection,
+    name: &str,
+    subject_id: Option<i64>,
+    parent_id: Option<i64>,
+    now: Timestamp,
+) -> Result<KnowledgePoint, AppError> {
+    let name = check_name(conn, name)?;
+    let subject_id = match parent_id {
+        Some(p) => get_node(conn, p)?.subject_id,
+        None => {
+            if let Some(s) = subject_id {
+                SubjectDao::new(conn)
+                    .get(s)?
+                    .ok_or_else(|| AppError::NotFound(format!("subject {}", s)))?;
+            }
+            subject_id
+        }
+    };
+    let id = KnowledgePointDao::new(conn).insert(&name, subject_id, parent_id, now)?;
+    get_node(conn, id)
+}
+
+/// 节点改名，题目与前置关系中的名称一并更新。新名称已存在时拒绝，应改用合并。
+pub fn rename_knowledge_point(conn: &Connection, id: i64, new_name: &str) -> Result<KnowledgePoint, AppError> {
+    let node = get_node(conn, id)?;
+    if node.name == new_name.trim() {
+        return Ok(node);
+    }
+    let new_name = check_name(conn, new_name)?;
+
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    check_rewritten_edges(&tx, &node.name, &new_name)?;
+    let dao = KnowledgePointDao::new(&tx);
+    dao.rename(id, &new_name)?;
+    dao.rename_references(&node.name, &new_name)?;
+    tx.commit().map_err(DbError::from)?;
+
+    get_node(conn, id)
+}
+
+/// 移动节点到 `parent_id` 下（None 为根节点）。只能在同一科目的树内移动，且不能移到自己的子树下。
+pub fn move_knowledge_point(conn: &Connection, id: i64, parent_id: Option<i64>) -> Result<KnowledgePoint, AppError> {
+    let node = get_node(conn, id)?;
+    if let Some(p) = parent_id {
+        let parent = get_node(conn, p)?;
+        if parent.subject_id != node.subject_id {
+            return Err(AppError::InvalidInput("cannot move a knowledge point to another subject".to_string()));
+        }
+        let tree = KnowledgeTree::load(conn)?;
+        if p == id || tree.descendants(id).contains(&p) {
+            return Err(AppError::InvalidInput(format!(
+                "cannot move {} under its own subtree",
+                node.name
+            )));
+        }
+    }
+    KnowledgePointDao::new(conn).set_parent(id, parent_id)?;
+    get_node(conn, id)
+}
+
+/// 把节点 `from_id` 合并进 `into_id`：题目与前置关系改指向目标节点，
+/// 源节点的子节点挂到目标节点下，最后删除源节点。改指向后前置关系会成环时拒绝合并。
+/// 目标节点原在源节点子树内时，先把它提到源节点原来的位置。
+pub fn merge_knowledge_points(conn: &Connection, from_id: i64, into_id: i64) -> Result<KnowledgePoint, AppError> {
+    if from_id == into_id {
+        return Err(AppError::InvalidInput("cannot merge a knowledge point into itself".to_string()));
+    }
+    let from = get_node(conn, from_id)?;
+    let into = get_node(conn, into_id)?;
+    if from.subject_id != into.subject_id {
+        return Err(AppError::InvalidInput("cannot merge knowledge points of different subjects".to_string()));
+    }
+    let tree = KnowledgeTree::load(conn)?;
+
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    check_rewritten_edges(&tx, &from.name, &into.name)?;
+    let dao = KnowledgePointDao::new(&tx);
+    if tree.descendants(from_id).contains(&into_id) {
+        dao.set_parent(into_id, from.parent_id)?;
+    }
+    dao.reparent_children(from_id, Some(into_id))?;
+    dao.rename_references(&from.name, &into.name)?;
+    dao.delete(from_id)?;
+    tx.commit().map_err(DbError::from)?;
+
+    get_node(conn, into_id)
+}
+
+/// 删除节点：从所有题目上移除该知识点，删除其前置关系，子节点挂到它的父节点下
+pub fn delete_knowledge_point(conn: &Connection, id: i64) -> Result<(), AppError> {
+    let node = get_node(conn, id)?;
+
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let dao = KnowledgePointDao::new(&tx);
+    dao.reparent_children(id, node.parent_id)?;
+    dao.delete_references(&node.name)?;
+    dao.delete(id)?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(())
+}
+
+/// 列表过滤用：知识点及其全部后代的名称。不在树中的名称原样返回。
+pub fn expand_knowledge_point(conn: &Connection, name: &str) -> Result<Vec<String>, AppError> {
+    let tree = KnowledgeTree::load(conn)?;
+    Ok(match tree.find(name) {
+        Some(node) => tree.subtree_names(node.id),
+        None => vec![name.to_string()],
+    })
+}
+
+/// 按知识点过滤题目的条件：命中该知识点或其任一后代节点
+pub fn knowledge_point_filter(conn: &Connection, name: &str) -> Result<MetaFilter, AppError> {
+    Ok(MetaFilter {
+        key: "system.KnowledgePoint".to_string(),
+        cond: MetaCond::OneOf(expand_knowledge_point(conn, name)?),
+    })
+}
+
+/// 全部节点及其子树统计，按显示顺序；`subject_id` 给定时只列该科
+pub fn knowledge_point_stats(
+    conn: &Connection,
+    subject_id: Option<i64>,
+) -> Result<Vec<KnowledgePointStats>, AppError> {
+    let dao = KnowledgePointDao::new(conn);
+    let nodes = dao.list()?;
+    let tree = KnowledgeTree::from_nodes(nodes.clone());
+
+    let mut out = Vec::new();
+    for node in nodes {
+        if subject_id.is_some() && node.subject_id != subject_id {
+            continue;
+        }
+        let (question_count, review_count, wrong_count) = dao.question_stats(&tree.subtree_names(node.id))?;
+        out.push(KnowledgePointStats {
+            path: tree.path(node.id),
+            id: node.id,
+            name: node.name,
+            subject_id: node.subject_id,
+            parent_id: node.parent_id,
+            question_count,
+            review_count,
+            error_rate: (review_count > 0).then(|| wrong_count as f64 / review_count as f64),
+        });
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn question(conn: &Connection, subject: &str, kps: &[&str]) -> i64 {
+        let qid = crate::db::insert_question(conn, Some("q"), "NEW", 0).unwrap();
+        crate::db::insert_meta(conn, qid, "system.Subject", subject).unwrap();
+        for kp in kps {
+            crate::db::insert_meta(conn, qid, "system.KnowledgePoint", kp).unwrap();
+        }
+        qid
+    }
+
+    fn kps_of(conn: &Connection, qid: i64) -> Vec<String> {
+        crate::db::select_meta_values_by_question_key(conn, qid, "system.KnowledgePoint").unwrap()
+    }
+
+    fn node(conn: &Connection, name: &str) -> KnowledgePoint {
+        KnowledgePointDao::new(conn).get_by_name(name).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_tree_paths_and_subtree_filter() {
+        let conn = setup();
+        question(&conn, "数学", &["顶点式"]);
+        let math = SubjectDao::new(&conn).get_by_name("数学").unwrap().unwrap();
+        // 题目上出现的知识点自动登记为该科的根节点
+        assert_eq!(node(&conn, "顶点式").subject_id, Some(math.id));
+
+        let func = create_knowledge_point(&conn, "函数", Some(math.id), None, Timestamp(0)).unwrap();
+        let quad = create_knowledge_point(&conn, "二次函数", None, Some(func.id), Timestamp(0)).unwrap();
+        assert_eq!(quad.subject_id, Some(math.id));
+        move_knowledge_point(&conn, node(&conn, "顶点式").id, Some(quad.id)).unwrap();
+        assert!(move_knowledge_point(&conn, func.id, Some(quad.id)).is_err());
+
+        let tree = KnowledgeTree::load(&conn).unwrap();
+        assert_eq!(tree.path(node(&conn, "顶点式").id), vec!["函数", "二次函数", "顶点式"]);
+        assert_eq!(expand_knowledge_point(&conn, "函数").unwrap(), vec!["函数", "二次函数", "顶点式"]);
+        assert_eq!(expand_knowledge_point(&conn, "未登记").unwrap(), vec!["未登记"]);
+
+        let stats = knowledge_point_stats(&conn, Some(math.id)).unwrap();
+        let func_stats = stats.iter().find(|s| s.id == func.id).unwrap();
+        assert_eq!(func_stats.question_count, 1);
+    }
+
+    #[test]
+    fn test_rename_merge_delete_update_questions() {
+        let conn = setup();
+        let q1 = question(&conn, "数学", &["二次函数", "抛物线"]);
+        let q2 = question(&conn, "数学", &["抛物线"]);
+        crate::dao::PrerequisiteDao::new(&conn).insert("抛物线", "一次函数", 0).unwrap();
+
+        let renamed = rename_knowledge_point(&conn, node(&conn, "抛物线").id, "抛物线图像").unwrap();
+        assert_eq!(renamed.name, "抛物线图像");
+        assert_eq!(kps_of(&conn, q2), vec!["抛物线图像"]);
+        assert!(rename_knowledge_point(&conn, renamed.id, "二次函数").is_err());
+        let edges = crate::dao::PrerequisiteDao::new(&conn).list_all().unwrap();
+        assert_eq!(edges, vec![("抛物线图像".to_string(), "一次函数".to_string())]);
+
+        // q1 同时带有两个知识点，合并后只保留一个
+        merge_knowledge_points(&conn, renamed.id, node(&conn, "二次函数").id).unwrap();
+        assert_eq!(kps_of(&conn, q1), vec!["二次函数"]);
+        assert_eq!(kps_of(&conn, q2), vec!["二次函数"]);
+        assert!(KnowledgePointDao::new(&conn).get_by_name("抛物线图像").unwrap().is_none());
+
+        delete_knowledge_point(&conn, node(&conn, "二次函数").id).unwrap();
+        assert!(kps_of(&conn, q1).is_empty());
+        assert!(crate::dao::PrerequisiteDao::new(&conn).list_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_rejects_prerequisite_cycle() {
+        let conn = setup();
+        question(&conn, "数学", &["函数", "二次函数", "一次函数"]);
+        let prereqs = crate::dao::PrerequisiteDao::new(&conn);
+        // 二次函数 → 一次函数 → 函数；把函数合并进二次函数会得到 二次函数 ⇄ 一次函数
+        prereqs.insert("二次函数", "一次函数", 0).unwrap();
+        prereqs.insert("一次函数", "函数", 0).unwrap();
+        let before = prereqs.list_all().unwrap();
+
+        let err = merge_knowledge_points(&conn, node(&conn, "函数").id, node(&conn, "二次函数").id);
+        assert!(matches!(err, Err(AppError::InvalidInput(_))));
+        assert_eq!(prereqs.list_all().unwrap(), before);
+        assert!(KnowledgePointDao::new(&conn).get_by_name("函数").unwrap().is_some());
+
+        // 只会变成自环的边直接删除，不算成环
+        prereqs.delete("一次函数", "函数").unwrap();
+        prereqs.insert("二次函数", "函数", 0).unwrap();
+        merge_knowledge_points(&conn, node(&conn, "函数").id, node(&conn, "二次函数").id).unwrap();
+        assert_eq!(prereqs.list_all().unwrap(), vec![("二次函数".to_string(), "一次函数".to_string())]);
+    }
+
+    #[test]
+    fn test_merge_parent_into_child_keeps_tree_acyclic() {
+        let conn = setup();
+        question(&conn, "数学", &[]);
+        let math = SubjectDao::new(&conn).get_by_name("数学").unwrap().unwrap();
+        let a = create_knowledge_point(&conn, "函数", Some(math.id), None, Timestamp(0)).unwrap();
+        let b = create_knowledge_point(&conn, "二次函数", None, Some(a.id), Timestamp(0)).unwrap();
+        let c = create_knowledge_point(&conn, "一次函数", None, Some(a.id), Timestamp(0)).unwrap();
+
+        let merged = merge_knowledge_points(&conn, a.id, b.id).unwrap();
+        assert_eq!(merged.parent_id, None);
+        assert_eq!(KnowledgePointDao::new(&conn).get(c.id).unwrap().unwrap().parent_id, Some(b.id));
+    }
+}
@@ -1,7 +1,23 @@
+pub mod answer_grading;
+pub mod bulk;
+pub mod cloze;
+pub mod forget;
+pub mod knowledge_graph;
+pub mod knowledge_point;
+pub mod occlusion;
+pub mod paper;
+pub mod question_link;
 pub mod question_manager;
+pub mod question_part;
+pub mod reason_code;
 pub mod recommendation;
 pub mod review_manager;
+pub mod review_order;
+pub mod revision;
 pub mod show_question_view;
+pub mod subject;
+pub mod tag;
+pub mod user_field;
 
 pub use recommendation::{DailyRecommendation, RecommendationSystem, RecommendedQuestion};
 pub use review_manager::{RecommendReason, RecommendResult, ReviewManager};
@@ -0,0 +1,322 @@
+//! 图片遮罩
+//!
+//! 在题目图 / 答案图上框出矩形遮住一部分（如示意图上的标注），每个遮罩生成笔记下的一张卡片
+//! （`server::question_part` 的小题），各自按状态机复习；复习卡片时显示这张图并遮住对应区域。
+//!
+//! 遮罩坐标按图片尺寸归一化，换图时整体挪到新图上并升一个版本，卡片与复习记录不变。
+//! 卡片随遮罩与图片同步：遮罩被移除或图片被删除时卡片移入回收站，图片恢复时卡片一并恢复。
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+use crate::asset::store::{AssetMeta, AssetStore};
+use crate::dao::asset_dao::AssetDao;
+use crate::dao::occlusion_dao::OcclusionDao;
+use crate::dao::question_dao::QuestionDao;
+use crate::dao::question_part_dao::QuestionPartDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::domain::asset::Asset;
+use crate::domain::enums::{AssetType, QuestionState};
+use crate::domain::ids::{AssetId, QuestionId};
+use crate::domain::occlusion::{MaskRect, OcclusionMask};
+use crate::error::AppError;
+use crate::server::{question_part, revision, ReviewManager};
+use crate::util::time::Timestamp;
+
+/// 编辑遮罩时的一个矩形；`card_id` 为空表示新画的遮罩
+#[derive(Debug, Clone)]
+pub struct MaskInput {
+    pub card_id: Option<i64>,
+    pub rect: MaskRect,
+}
+
+/// 可以画遮罩的图片：未删除的题目图 / 答案图，所属题目未删除且不是小题
+fn maskable_image(conn: &Connection, asset_id: AssetId) -> Result<Asset, AppError> {
+    let asset = AssetDao::new(conn)
+        .get_by_id(asset_id)?
+        .filter(|a| a.deleted_at.is_none())
+        .ok_or_else(|| AppError::NotFound(format!("asset {}", i64::from(asset_id))))?;
+    if !matches!(asset.asset_type, AssetType::QUESTION | AssetType::ANSWER) {
+        return Err(AppError::InvalidInput("masks can only be drawn on question or answer images".to_string()));
+    }
+    let note = QuestionDao::new(conn)
+        .get_by_id(asset.question_id)?
+        .ok_or_else(|| AppError::NotFound(format!("question {}", i64::from(asset.question_id))))?;
+    if note.deleted_at.is_some() {
+        return Err(AppError::InvalidInput("question is in the recycle bin".to_string()));
+    }
+    if QuestionPartDao::new(conn).parent_of(i64::from(asset.question_id))?.is_some() {
+        return Err(AppError::InvalidInput("a part cannot have masks".to_string()));
+    }
+    Ok(asset)
+}
+
+/// 图片上当前的遮罩
+pub fn list_masks(conn: &Connection, asset_id: AssetId) -> Result<Vec<OcclusionMask>, AppError> {
+    Ok(OcclusionDao::new(conn).list_current_by_asset(i64::from(asset_id))?)
+}
+
+/// 遮罩卡片当前的遮罩（复习时遮住的区域）；不是遮罩卡片时为 None
+pub fn mask_of(conn: &Connection, card_id: QuestionId) -> Result<Option<OcclusionMask>, AppError> {
+    Ok(OcclusionDao::new(conn).current_by_card(i64::from(card_id))?)
+}
+
+/// 整体设置一张图片上的遮罩：带 `card_id` 的更新位置，新的生成卡片，没有列出的移除（卡片进回收站）。
+/// 返回设置后的遮罩。
+pub fn set_masks(
+    conn: &Connection,
+    asset_id: AssetId,
+    masks: Vec<MaskInput>,
+    now: Timestamp,
+) -> Result<Vec<OcclusionMask>, AppError> {
+    let asset = maskable_image(conn, asset_id)?;
+    let asset_i64 = i64::from(asset_id);
+    let current = OcclusionDao::new(conn).list_current_by_asset(asset_i64)?;
+    if masks.iter().any(|m| !m.rect.is_valid()) {
+        return Err(AppError::InvalidInput("mask must lie inside the image".to_string()));
+    }
+    let mut seen = HashSet::new();
+    for card_id in masks.iter().filter_map(|m| m.card_id) {
+        if !current.iter().any(|c| c.card_id == card_id) || !seen.insert(card_id) {
+            return Err(AppError::InvalidInput(format!("mask card {} is not on this image", card_id)));
+        }
+    }
+
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let od = OcclusionDao::new(&tx);
+    let qd = QuestionDao::new(&tx);
+    let pd = QuestionPartDao::new(&tx);
+    let note_id = asset.question_id;
+    // 同一张图上的遮罩版本一致：换过几次图就是第几版
+    let version = od.image_version(asset_i64)?.unwrap_or(1);
+
+    for mask in &current {
+        match masks.iter().find(|m| m.card_id == Some(mask.card_id)) {
+            Some(input) if input.rect != mask.rect => od.update_rect(mask.id, input.rect)?,
+            Some(_) => {}
+            None => od.supersede(mask.id, now)?,
+        }
+    }
+    for input in masks.iter().filter(|m| m.card_id.is_none()) {
+        let card = qd.insert(None, QuestionState::NEW, now)?;
+        pd.append(i64::from(note_id), i64::from(card))?;
+        od.insert(i64::from(card), asset_i64, version, input.rect, now)?;
+    }
+    sync_masks(&tx, note_id, now)?;
+
+    tx.commit().map_err(DbError::from)?;
+    list_masks(conn, asset_id)
+}
+
+/// 换图：新图接替旧图的类型与位置，旧图移入回收区，图上的遮罩挪到新图并升一个版本。返回新图 ID。
+pub fn replace_image(
+    conn: &Connection,
+    store: &AssetStore,
+    asset_id: AssetId,
+    image_path: String,
+    now: Timestamp,
+) -> Result<AssetId, AppError> {
+    let old = AssetDao::new(conn)
+        .get_by_id(asset_id)?
+        .filter(|a| a.deleted_at.is_none())
+        .ok_or_else(|| AppError::NotFound(format!("asset {}", i64::from(asset_id))))?;
+    let saved = store.save_many(&[PathBuf::from(image_path)])?;
+    let meta = saved
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::InvalidInput("no image to replace with".to_string()))?;
+
+    let result = attach_replacement(conn, &old, &meta, now);
+    if result.is_err() {
+        // 新图没有登记进数据库，删掉刚保存的文件；删除失败只会留下无主文件，不掩盖原来的错误
+        let _ = store.discard(std::slice::from_ref(&meta));
+    }
+    result
+}
+
+/// 在一个事务里登记新图、把旧图移入回收区，并把遮罩挪到新图
+fn attach_replacement(conn: &Connection, old: &Asset, meta: &AssetMeta, now: Timestamp) -> Result<AssetId, AppError> {
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let new_id = revision::tracked(&tx, old.question_id, now, || {
+        let ad = AssetDao::new(&tx);
+        let od = OcclusionDao::new(&tx);
+        let new_id = ad.insert(
+            old.question_id,
+            old.asset_type.clone(),
+            meta.relative_path.clone(),
+            Timestamp::from(meta.created_at),
+            old.sort_order,
+        )?;
+        ad.delete(old.id)?;
+        let version = od.image_version(i64::from(old.id))?.unwrap_or(1) + 1;
+        od.set_image_version(i64::from(new_id), version)?;
+        for mask in od.list_current_by_asset(i64::from(old.id))? {
+            od.supersede(mask.id, now)?;
+            od.insert(mask.card_id, i64::from(new_id), version, mask.rect, now)?;
+        }
+        sync_masks(&tx, old.question_id, now)?;
+        Ok(new_id)
+    })?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(new_id)
+}
+
+/// 按遮罩与图片的现状同步题目的遮罩卡片：遮罩仍在且图片未删除的卡片保持可用，其余移入回收站；
+/// 名称、科目与知识点跟随题目。题目是小题、已删除或不存在时不处理。
+/// 不自带事务，由调用方包在事务里。
+pub fn sync_masks(conn: &Connection, note_id: QuestionId, now: Timestamp) -> Result<(), AppError> {
+    let qd = QuestionDao::new(conn);
+    let note_i64 = i64::from(note_id);
+    let Some(note) = qd.get_by_id(note_id)? else {
+        return Ok(());
+    };
+    if note.deleted_at.is_some() || QuestionPartDao::new(conn).parent_of(note_i64)?.is_some() {
+        return Ok(());
+    }
+    let masks = OcclusionDao::new(conn).list_by_note(note_i64)?;
+    if masks.is_empty() {
+        return Ok(());
+    }
+
+    let live_assets: HashSet<i64> =
+        AssetDao::new(conn).list_by_question(note_id)?.into_iter().map(|a| i64::from(a.id)).collect();
+    let mut cards: BTreeMap<i64, bool> = BTreeMap::new();
+    for mask in &masks {
+        let active = mask.superseded_at.is_none() && live_assets.contains(&mask.asset_id);
+        *cards.entry(mask.card_id).or_default() |= active;
+    }
+
+    let note_name = note.name.as_deref().unwrap_or("未命名题目");
+    for (number, (&card, &active)) in cards.iter().enumerate() {
+        let card = QuestionId::from(card);
+        let deleted = qd.get_by_id(card)?.is_some_and(|q| q.deleted_at.is_some());
+        match (active, deleted) {
+            (true, true) => qd.update_deleted_at(card, None)?,
+            (false, false) => qd.update_deleted_at(card, Some(now))?,
+            _ => {}
+        }
+        if active {
+            qd.update_name(card, Some(&format!("{} [遮罩{}]", note_name, number + 1)))?;
+            question_part::inherit_metas(conn, note_id, card)?;
+        }
+    }
+
+    ReviewManager::new(conn).sync_parent(note_id).map_err(AppError::InvalidInput)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::meta_dao::MetaDao;
+    use crate::server::question_manager::{delete_question_image, get_question_detail};
+    use crate::server::question_part::list_parts;
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn rect(x: f64, y: f64) -> MaskRect {
+        MaskRect { x, y, width: 0.2, height: 0.1 }
+    }
+
+    /// 带一张题目图的题目，返回 (题目, 图片)
+    fn note_with_image(conn: &Connection) -> (QuestionId, AssetId) {
+        let qid = crate::db::insert_question(conn, Some("细胞结构图"), "NEW", 0).unwrap();
+        crate::db::insert_meta(conn, qid, "system.Subject", "生物").unwrap();
+        let asset = crate::db::insert_asset(conn, qid, "QUESTION", "cell.png", 0, 1).unwrap();
+        (QuestionId::from(qid), AssetId::from(asset))
+    }
+
+    #[test]
+    fn test_masks_become_cards() {
+        let conn = setup();
+        let (note, image) = note_with_image(&conn);
+        let input = |card_id, rect| MaskInput { card_id, rect };
+
+        let drawn = vec![input(None, rect(0.1, 0.1)), input(None, rect(0.5, 0.5))];
+        let masks = set_masks(&conn, image, drawn, Timestamp::from(10)).unwrap();
+        assert_eq!(masks.len(), 2);
+        let cards = list_parts(&conn, note).unwrap();
+        assert_eq!(cards.iter().map(|q| i64::from(q.id)).collect::<Vec<_>>(), vec![masks[0].card_id, masks[1].card_id]);
+        assert_eq!(cards[0].name.as_deref(), Some("细胞结构图 [遮罩1]"));
+        let subject = MetaDao::new(&conn).get_by_question_key(cards[0].id, "system.Subject").unwrap().unwrap();
+        assert_eq!(subject.value, "生物");
+
+        // 复习卡片时给出要遮住的区域
+        let detail = get_question_detail(&conn, cards[1].id).unwrap();
+        assert_eq!(detail.occlusion.map(|m| m.rect), Some(rect(0.5, 0.5)));
+
+        // 移动第一个、移除第二个：第二张卡片进回收站
+        let first = masks[0].card_id;
+        set_masks(&conn, image, vec![input(Some(first), rect(0.3, 0.3))], Timestamp::from(20)).unwrap();
+        assert_eq!(list_parts(&conn, note).unwrap().iter().map(|q| i64::from(q.id)).collect::<Vec<_>>(), vec![first]);
+        assert_eq!(mask_of(&conn, QuestionId::from(first)).unwrap().unwrap().rect, rect(0.3, 0.3));
+
+        // 超出图片的矩形、已移除的遮罩都不接受
+        let outside = MaskRect { x: 0.9, y: 0.0, width: 0.2, height: 0.1 };
+        assert!(set_masks(&conn, image, vec![input(None, outside)], Timestamp::from(30)).is_err());
+        let removed = vec![input(Some(masks[1].card_id), rect(0.1, 0.1))];
+        assert!(set_masks(&conn, image, removed, Timestamp::from(30)).is_err());
+
+        // 删除图片后卡片进回收站
+        delete_question_image(&conn, image.0.to_string()).unwrap();
+        assert!(list_parts(&conn, note).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_replacing_image_versions_masks() {
+        let conn = setup();
+        let (note, image) = note_with_image(&conn);
+        let drawn = vec![MaskInput { card_id: None, rect: rect(0.1, 0.2) }];
+        let masks = set_masks(&conn, image, drawn, Timestamp::from(10)).unwrap();
+        let card = masks[0].card_id;
+        ReviewManager::new(&conn)
+            .process_review(QuestionId::from(card), crate::domain::enums::ReviewResult::WRONG, Timestamp::from(15))
+            .unwrap();
+
+        let root = tempfile::tempdir().unwrap();
+        let store = AssetStore::new(root.path().to_path_buf());
+        let src = root.path().join("cell-v2.png");
+        std::fs::write(&src, b"png").unwrap();
+        let new_image =
+            replace_image(&conn, &store, image, src.to_string_lossy().to_string(), Timestamp::from(20)).unwrap();
+
+        let versions = OcclusionDao::new(&conn).list_by_card(card).unwrap();
+        assert_eq!(versions.iter().map(|m| m.version).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(versions[0].superseded_at, Some(Timestamp::from(20)));
+        assert_eq!(versions[1].asset_id, i64::from(new_image));
+        assert_eq!(versions[1].rect, rect(0.1, 0.2));
+        assert!(list_masks(&conn, image).unwrap().is_empty());
+
+        // 卡片与复习状态不变
+        let cards = list_parts(&conn, note).unwrap();
+        assert_eq!(cards.len(), 1);
+        assert_eq!(cards[0].state, QuestionState::LEARNING);
+
+        // 新图上的遮罩全部移除后再画，仍是第 2 版
+        set_masks(&conn, new_image, vec![], Timestamp::from(30)).unwrap();
+        let redrawn = vec![MaskInput { card_id: None, rect: rect(0.4, 0.4) }];
+        let masks = set_masks(&conn, new_image, redrawn, Timestamp::from(40)).unwrap();
+        assert_eq!(masks[0].version, 2);
+
+        // 登记失败时不留下刚保存的文件
+        let src = root.path().join("cell-v3.png");
+        std::fs::write(&src, b"png").unwrap();
+        let stored_files = || count_files(root.path());
+        let before = stored_files();
+        conn.execute_batch("DROP TABLE occlusion_image").unwrap();
+        assert!(replace_image(&conn, &store, new_image, src.to_string_lossy().to_string(), Timestamp::from(50)).is_err());
+        assert_eq!(stored_files(), before);
+    }
+
+    fn count_files(dir: &std::path::Path) -> usize {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .map(|p| if p.is_dir() { count_files(&p) } else { 1 })
+            .sum()
+    }
+}
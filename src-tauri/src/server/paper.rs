@@ -0,0 +1,316 @@
+//! 试卷
+//!
+//! 试卷登记在 `paper` 表，包含名称、考试日期、科目（须已登记，存科目 id）和卷面总分。题目以 `extension.SourcePaper`
+//! 元信息引用试卷名称（每题至多一张），录入时即可指定，之后也可批量改；试卷改名 / 删除时级联更新。
+//! 统计按试卷汇总题目的掌握情况，复习时可以把一张试卷上的题目整套拿出来做一遍。
+
+use crate::dao::meta_dao::MetaDao;
+use crate::dao::paper_dao::PaperDao;
+use crate::dao::question_dao::QuestionDao;
+use crate::dao::question_part_dao::QuestionPartDao;
+use crate::dao::subject_dao::SubjectDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::domain::enums::{ExtensionMetaKey, MetaKey, QuestionState};
+use crate::domain::ids::QuestionId;
+use crate::domain::paper::{Paper, PaperStats};
+use crate::error::AppError;
+use crate::util::time::Timestamp;
+
+/// 试卷的可编辑字段
+#[derive(Debug, Clone, Default)]
+pub struct PaperInput {
+    pub name: String,
+    /// 考试日期（YYYY-MM-DD）
+    pub exam_date: Option<String>,
+    pub subject: Option<String>,
+    pub total_score: Option<f64>,
+}
+
+fn paper_key() -> MetaKey {
+    MetaKey::Extension(ExtensionMetaKey::SourcePaper)
+}
+
+/// 按 id 取试卷
+pub fn get_paper(conn: &Connection, id: i64) -> Result<Paper, AppError> {
+    PaperDao::new(conn)
+        .get(id)?
+        .ok_or_else(|| AppError::NotFound(format!("paper {}", id)))
+}
+
+fn trimmed(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// 校验并整理输入：名称非空，日期为 YYYY-MM-DD，总分为非负数，科目已登记。
+/// 返回整理后的输入与科目 id。
+fn check_input(conn: &Connection, input: PaperInput) -> Result<(PaperInput, Option<i64>), AppError> {
+    let name = input.name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("paper name must not be empty".to_string()));
+    }
+    let exam_date = trimmed(input.exam_date);
+    if let Some(date) = &exam_date {
+        chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| AppError::InvalidInput(format!("invalid exam date: {}", date)))?;
+    }
+    if let Some(score) = input.total_score {
+        if !score.is_finite() || score < 0.0 {
+            return Err(AppError::InvalidInput(format!("invalid total score: {}", score)));
+        }
+    }
+    let subject = trimmed(input.subject);
+    let subject_id = match &subject {
+        Some(name) => Some(
+            SubjectDao::new(conn)
+                .get_by_name(name)?
+                .ok_or_else(|| AppError::NotFound(format!("subject {}", name)))?
+                .id,
+        ),
+        None => None,
+    };
+    Ok((PaperInput { name, exam_date, subject, total_score: input.total_score }, subject_id))
+}
+
+/// 全部试卷，考试日期新的在前
+pub fn list_papers(conn: &Connection) -> Result<Vec<Paper>, AppError> {
+    Ok(PaperDao::new(conn).list()?)
+}
+
+/// 新建试卷
+pub fn create_paper(conn: &Connection, input: PaperInput, now: Timestamp) -> Result<Paper, AppError> {
+    let (input, subject_id) = check_input(conn, input)?;
+    let dao = PaperDao::new(conn);
+    if dao.get_by_name(&input.name)?.is_some() {
+        return Err(AppError::InvalidInput(format!("paper already exists: {}", input.name)));
+    }
+    let id = dao.insert(&Paper {
+        id: 0,
+        name: input.name,
+        exam_date: input.exam_date,
+        subject: input.subject,
+        subject_id,
+        total_score: input.total_score,
+        created_at: now,
+    })?;
+    get_paper(conn, id)
+}
+
+/// 修改试卷；改名时题目上的引用一并更新
+pub fn update_paper(conn: &Connection, id: i64, input: PaperInput) -> Result<Paper, AppError> {
+    let (input, subject_id) = check_input(conn, input)?;
+    let old = get_paper(conn, id)?;
+    if old.name != input.name && PaperDao::new(conn).get_by_name(&input.name)?.is_some() {
+        return Err(AppError::InvalidInput(format!("paper already exists: {}", input.name)));
+    }
+    let paper = Paper {
+        name: input.name,
+        exam_date: input.exam_date,
+        subject: input.subject,
+        subject_id,
+        total_score: input.total_score,
+        ..old.clone()
+    };
+
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let dao = PaperDao::new(&tx);
+    dao.update(&paper)?;
+    if old.name != paper.name {
+        dao.rename_references(&old.name, &paper.name)?;
+    }
+    tx.commit().map_err(DbError::from)?;
+    Ok(paper)
+}
+
+/// 删除试卷；题目保留，只去掉来源试卷
+pub fn delete_paper(conn: &Connection, id: i64) -> Result<(), AppError> {
+    let paper = get_paper(conn, id)?;
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let dao = PaperDao::new(&tx);
+    dao.delete_references(&paper.name)?;
+    dao.delete(id)?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(())
+}
+
+/// 设置单道题的来源试卷，`None` 为清除。不自带事务。
+pub fn set_question_paper(conn: &Connection, qid: QuestionId, paper: Option<&Paper>) -> Result<(), AppError> {
+    let question = QuestionDao::new(conn)
+        .get_by_id(qid)?
+        .ok_or_else(|| AppError::NotFound(format!("question {}", i64::from(qid))))?;
+    if question.deleted_at.is_some() {
+        return Err(AppError::InvalidInput("question is in the recycle bin".to_string()));
+    }
+    let md = MetaDao::new(conn);
+    md.delete_by_question_and_key(qid, paper_key())?;
+    if let Some(paper) = paper {
+        md.insert(qid, paper_key(), &paper.name)?;
+    }
+    Ok(())
+}
+
+/// 把一组题目归到试卷 `paper_id` 下（`None` 为清除来源试卷），整批在一个事务里
+pub fn assign_questions(conn: &Connection, question_ids: &[i64], paper_id: Option<i64>) -> Result<(), AppError> {
+    let paper = paper_id.map(|id| get_paper(conn, id)).transpose()?;
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    for &id in question_ids {
+        set_question_paper(&tx, QuestionId::from(id), paper.as_ref())?;
+    }
+    tx.commit().map_err(DbError::from)?;
+    Ok(())
+}
+
+/// 试卷上题目的掌握情况
+pub fn paper_stats(conn: &Connection, id: i64) -> Result<PaperStats, AppError> {
+    let paper = get_paper(conn, id)?;
+    Ok(PaperDao::new(conn).stats(&paper.name)?)
+}
+
+/// 整套复习一张试卷时要做的题：有小题的大题换成各小题，暂停的题目跳过
+pub fn session_question_ids(conn: &Connection, id: i64) -> Result<Vec<i64>, AppError> {
+    let paper = get_paper(conn, id)?;
+    let pd = QuestionPartDao::new(conn);
+    let mut ids = Vec::new();
+    for qid in PaperDao::new(conn).question_ids(&paper.name)? {
+        let parts = pd.list_part_ids(qid, false)?;
+        if parts.is_empty() {
+            ids.push(qid);
+        } else {
+            ids.extend(parts);
+        }
+    }
+    let questions = QuestionDao::new(conn).get_by_ids(&ids)?;
+    ids.retain(|&id| {
+        questions
+            .iter()
+            .any(|q| i64::from(q.id) == id && q.state != QuestionState::SUSPENDED)
+    });
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::enums::ReviewResult;
+    use crate::domain::question_text::QuestionText;
+    use crate::server::question_part::add_part;
+    use crate::server::ReviewManager;
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn input(name: &str) -> PaperInput {
+        PaperInput { name: name.to_string(), ..PaperInput::default() }
+    }
+
+    fn question(conn: &Connection, state: &str) -> i64 {
+        crate::db::insert_question(conn, Some("q"), state, 0).unwrap()
+    }
+
+    #[test]
+    fn test_paper_crud_and_rename_cascades() {
+        let conn = setup();
+        let now = Timestamp::from(100);
+        assert!(create_paper(&conn, PaperInput { subject: Some("数学".to_string()), ..input("x") }, now).is_err());
+        crate::server::subject::create_subject(&conn, "数学", now).unwrap();
+        let mid = PaperInput {
+            name: " 期中考试 2026 ".to_string(),
+            exam_date: Some("2026-04-20".to_string()),
+            subject: Some("数学".to_string()),
+            total_score: Some(150.0),
+        };
+        let paper = create_paper(&conn, mid, now).unwrap();
+        assert_eq!(paper.name, "期中考试 2026");
+        assert_eq!(paper.subject.as_deref(), Some("数学"));
+        assert!(create_paper(&conn, input("期中考试 2026"), now).is_err());
+        assert!(create_paper(&conn, PaperInput { exam_date: Some("4/20".to_string()), ..input("x") }, now).is_err());
+        assert!(create_paper(&conn, PaperInput { total_score: Some(-1.0), ..input("x") }, now).is_err());
+
+        let q = question(&conn, "LEARNING");
+        assign_questions(&conn, &[q], Some(paper.id)).unwrap();
+        let renamed = update_paper(&conn, paper.id, PaperInput { name: "期中考试".to_string(), ..input("") }).unwrap();
+        assert_eq!(renamed.exam_date, None);
+        let meta = MetaDao::new(&conn)
+            .get_by_question_key(QuestionId::from(q), "extension.SourcePaper")
+            .unwrap()
+            .unwrap();
+        assert_eq!(meta.value, "期中考试");
+
+        delete_paper(&conn, paper.id).unwrap();
+        assert!(list_papers(&conn).unwrap().is_empty());
+        assert!(MetaDao::new(&conn)
+            .get_by_question_key(QuestionId::from(q), "extension.SourcePaper")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_paper_follows_subject_rename_and_merge() {
+        use crate::server::subject;
+
+        let conn = setup();
+        let now = Timestamp::from(100);
+        let math = subject::create_subject(&conn, "数学", now).unwrap();
+        let algebra = subject::create_subject(&conn, "代数", now).unwrap();
+        let paper = create_paper(&conn, PaperInput { subject: Some("数学".to_string()), ..input("期中") }, now).unwrap();
+        assert_eq!(paper.subject_id, Some(math.id));
+
+        subject::rename_subject(&conn, math.id, "高等数学").unwrap();
+        assert_eq!(get_paper(&conn, paper.id).unwrap().subject.as_deref(), Some("高等数学"));
+
+        subject::merge_subjects(&conn, math.id, algebra.id).unwrap();
+        let merged = get_paper(&conn, paper.id).unwrap();
+        assert_eq!((merged.subject.as_deref(), merged.subject_id), (Some("代数"), Some(algebra.id)));
+    }
+
+    #[test]
+    fn test_paper_stats_and_session() {
+        let conn = setup();
+        let paper = create_paper(&conn, input("期中"), Timestamp::from(100)).unwrap();
+        let other = create_paper(&conn, input("月考"), Timestamp::from(100)).unwrap();
+        let stable = question(&conn, "STABLE");
+        let learning = question(&conn, "LEARNING");
+        let suspended = question(&conn, "SUSPENDED");
+        let parent = question(&conn, "NEW");
+        let deleted = question(&conn, "STABLE");
+        let elsewhere = question(&conn, "STABLE");
+        assign_questions(&conn, &[stable, learning, suspended, parent, deleted], Some(paper.id)).unwrap();
+        assign_questions(&conn, &[elsewhere], Some(other.id)).unwrap();
+        // 改归另一张试卷时替换原来的来源
+        assign_questions(&conn, &[learning], Some(other.id)).unwrap();
+        assign_questions(&conn, &[learning], Some(paper.id)).unwrap();
+        crate::db::update_question_deleted_at(&conn, deleted, Some(200)).unwrap();
+        let part = add_part(&conn, QuestionId::from(parent), None, QuestionText::default(), Timestamp::from(100)).unwrap();
+        ReviewManager::new(&conn)
+            .process_review(part, ReviewResult::WRONG, Timestamp::from(300))
+            .unwrap();
+        ReviewManager::new(&conn)
+            .process_review(QuestionId::from(learning), ReviewResult::CORRECT, Timestamp::from(300))
+            .unwrap();
+
+        let stats = paper_stats(&conn, paper.id).unwrap();
+        assert_eq!(stats.question_count, 4);
+        assert_eq!(
+            (stats.new_count, stats.learning_count, stats.stable_count, stats.suspended_count),
+            (0, 2, 1, 1)
+        );
+        assert_eq!((stats.review_count, stats.wrong_review_count), (2, 1));
+        assert_eq!(stats.stable_rate(), 0.25);
+
+        // 旧数据中的小写结果同样计入错误次数
+        conn.execute(
+            "INSERT INTO review (question_id, result, reviewed_at) VALUES (?1, 'wrong', 400)",
+            [stable],
+        )
+        .unwrap();
+        let stats = paper_stats(&conn, paper.id).unwrap();
+        assert_eq!((stats.review_count, stats.wrong_review_count), (3, 2));
+
+        // 大题换成小题，暂停与已删除的跳过
+        assert_eq!(session_question_ids(&conn, paper.id).unwrap(), vec![stable, learning, i64::from(part)]);
+        assert!(assign_questions(&conn, &[deleted], Some(paper.id)).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! 题目关联
+//!
+//! 把同一陷阱的变式题、前后承接的题、需要对比辨析的题、同一来源的题连起来。
+//! 关联跟随题目：移入回收站后从对方的关联列表中隐藏，恢复后重新出现；彻底删除时一并删除。
+//! 推荐系统据 VARIANT_OF 把变式题归组，同一天的每日推荐每组只排一道。
+
+use std::collections::HashMap;
+
+use crate::dao::question_dao::QuestionDao;
+use crate::dao::question_link_dao::QuestionLinkDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::domain::ids::QuestionId;
+use crate::domain::question_link::{LinkType, QuestionLink};
+use crate::error::AppError;
+use crate::util::time::Timestamp;
+
+fn check_active(conn: &Connection, id: i64) -> Result<(), AppError> {
+    let question = QuestionDao::new(conn)
+        .get_by_id(QuestionId::from(id))?
+        .ok_or_else(|| AppError::NotFound(format!("question {}", id)))?;
+    if question.deleted_at.is_some() {
+        return Err(AppError::InvalidInput(format!("question {} is in the recycle bin", id)));
+    }
+    Ok(())
+}
+
+/// 关联两道题；FOLLOWS 表示 `from` 承接 `to`，其余类型不分方向。
+/// 重复关联不会产生新记录。返回关联 id。
+pub fn link_questions(
+    conn: &Connection,
+    from: i64,
+    to: i64,
+    link_type: LinkType,
+    now: Timestamp,
+) -> Result<i64, AppError> {
+    if from == to {
+        return Err(AppError::InvalidInput("cannot link a question to itself".to_string()));
+    }
+    check_active(conn, from)?;
+    check_active(conn, to)?;
+    let (from, to) = if link_type.is_symmetric() && from > to { (to, from) } else { (from, to) };
+    Ok(QuestionLinkDao::new(conn).insert(from, to, link_type, now)?)
+}
+
+/// 删除一条关联
+pub fn unlink(conn: &Connection, link_id: i64) -> Result<(), AppError> {
+    if !QuestionLinkDao::new(conn).delete(link_id)? {
+        return Err(AppError::NotFound(format!("question link {}", link_id)));
+    }
+    Ok(())
+}
+
+/// 题目的全部关联（对方在回收站中的除外）
+pub fn list_links(conn: &Connection, question_id: i64) -> Result<Vec<QuestionLink>, DbError> {
+    QuestionLinkDao::new(conn).list_by_question(question_id)
+}
+
+/// 变式题分组：题目 ID → 组号（组内最小的题目 ID）。
+/// VARIANT_OF 按传递闭包归组，A~B、B~C 则 A、B、C 同组；没有变式关联的题目不在表中。
+pub fn variant_groups(conn: &Connection) -> Result<HashMap<i64, i64>, DbError> {
+    let pairs = QuestionLinkDao::new(conn).list_pairs(LinkType::VARIANT_OF)?;
+    Ok(group_pairs(&pairs))
+}
+
+fn group_pairs(pairs: &[(i64, i64)]) -> HashMap<i64, i64> {
+    let mut parent: HashMap<i64, i64> = HashMap::new();
+    fn find(parent: &mut HashMap<i64, i64>, x: i64) -> i64 {
+        let p = *parent.entry(x).or_insert(x);
+        if p == x {
+            return x;
+        }
+        let root = find(parent, p);
+        parent.insert(x, root);
+        root
+    }
+    for &(a, b) in pairs {
+        let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+        if ra != rb {
+            // 以较小的 ID 作为组号
+            parent.insert(ra.max(rb), ra.min(rb));
+        }
+    }
+    let ids: Vec<i64> = parent.keys().copied().collect();
+    ids.into_iter().map(|id| (id, find(&mut parent, id))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn question(conn: &Connection, name: &str) -> i64 {
+        crate::db::insert_question(conn, Some(name), "NEW", 0).unwrap()
+    }
+
+    #[test]
+    fn test_link_list_and_referential_integrity() {
+        let conn = setup();
+        let a = question(&conn, "a");
+        let b = question(&conn, "b");
+        let c = question(&conn, "c");
+        let now = Timestamp::from(100);
+
+        let id = link_questions(&conn, b, a, LinkType::VARIANT_OF, now).unwrap();
+        // 不分方向的关联重复添加（反向）得到同一条记录
+        assert_eq!(link_questions(&conn, a, b, LinkType::VARIANT_OF, now).unwrap(), id);
+        link_questions(&conn, c, a, LinkType::FOLLOWS, now).unwrap();
+        assert!(link_questions(&conn, a, a, LinkType::SAME_SOURCE, now).is_err());
+        assert!(link_questions(&conn, a, 999, LinkType::SAME_SOURCE, now).is_err());
+
+        let links = list_links(&conn, a).unwrap();
+        let seen: Vec<(LinkType, i64, bool)> = links.iter().map(|l| (l.link_type, l.question_id, l.outgoing)).collect();
+        assert_eq!(seen, vec![(LinkType::FOLLOWS, c, false), (LinkType::VARIANT_OF, b, true)]);
+        assert!(list_links(&conn, c).unwrap()[0].outgoing);
+
+        // 回收站中的题目：从对方的列表中隐藏，也不能再被关联
+        crate::db::update_question_deleted_at(&conn, b, Some(200)).unwrap();
+        assert_eq!(list_links(&conn, a).unwrap().len(), 1);
+        assert!(link_questions(&conn, b, c, LinkType::CONTRASTS_WITH, now).is_err());
+        assert!(variant_groups(&conn).unwrap().is_empty());
+        crate::db::update_question_deleted_at(&conn, b, None).unwrap();
+        assert_eq!(list_links(&conn, a).unwrap().len(), 2);
+
+        // 彻底删除时关联一并删除
+        crate::server::question_manager::purge_question(&conn, QuestionId::from(b)).unwrap();
+        assert_eq!(list_links(&conn, a).unwrap().len(), 1);
+        let rows: i64 = conn.query_row("SELECT COUNT(*) FROM question_link", [], |r| r.get(0)).unwrap();
+        assert_eq!(rows, 1);
+
+        unlink(&conn, list_links(&conn, a).unwrap()[0].id).unwrap();
+        assert!(unlink(&conn, id).is_err());
+    }
+
+    #[test]
+    fn test_variant_groups_are_transitive() {
+        let groups = group_pairs(&[(5, 7), (1, 9), (7, 9), (2, 3)]);
+        for id in [1, 5, 7, 9] {
+            assert_eq!(groups[&id], 1);
+        }
+        assert_eq!((groups[&2], groups[&3]), (2, 2));
+        assert!(!groups.contains_key(&4));
+    }
+}
@@ -4,30 +4,45 @@
 //! 题目管理器还负责维护题目的元信息（`Meta`）和资源（`Asset`）的关联关系，确保数据的一致性。
 //! 题目管理器的设计目标是将题目的业务逻辑与数据访问层分离，使得代码更清晰、易于维护和测试。
 //!
-use crate::asset::store::AssetStore;
+use crate::asset::store::{AssetMeta, AssetStore};
 use crate::dao::{
-    asset_dao::AssetDao, meta_dao::MetaDao, question_dao::QuestionDao, review_dao::ReviewDao,
+    answer_grading_dao::AnswerGradingDao, asset_dao::AssetDao, meta_dao::MetaDao, override_dao::OverrideDao,
+    question_dao::QuestionDao, occlusion_dao::OcclusionDao, question_link_dao::QuestionLinkDao,
+    question_part_dao::QuestionPartDao,
+    question_revision_dao::QuestionRevisionDao, question_text_dao::{QuestionTextDao, TextVersion},
+    review_dao::ReviewDao, skip_dao::SkipDao,
 };
 use crate::db::connection::Connection;
-use crate::domain::enums::{AssetType, MetaKey, QuestionState, SystemMetaKey};
+use crate::db::error::DbError;
+use crate::domain::asset::Asset;
+use crate::domain::enums::{AssetType, MetaKey, QuestionState, SystemMetaKey, TextField};
 use crate::domain::ids::{AssetId, MetaId, QuestionId};
+use crate::domain::paper::Paper;
 use crate::domain::question_info::QuestionInfo;
+use crate::domain::question_text::QuestionText;
 use crate::error::AppError;
+use crate::server::{cloze, occlusion, paper, question_part, revision};
 use crate::util::time::Timestamp;
 use std::path::PathBuf;
 
 /// 录入题目
-/// 输入：题名，题目图路径列表，答案图路径列表，科目，知识点
-/// 先将图片资源复制到对应位置，再把题目信息和资源信息写入数据库，最后返回新建题目的ID或错误信息。
+/// 输入：题名，题目图 / 答案图 / 解析图 / 其他附件的路径列表，科目，知识点，文本内容（题干 / 答案 / 解析），来源试卷
+/// 先将资源文件复制到对应位置，再在一个事务里把题目信息和资源信息写入数据库，最后返回新建题目的ID或错误信息。
+/// 每种资源各自从 1 开始编排 `sort_order`。
 /// 输出：新建题目的ID或错误信息
+#[allow(clippy::too_many_arguments)]
 pub fn create_question(
     conn: &Connection,
     store: &AssetStore,
     name: String,
     question_image_paths: Vec<String>,
     answer_image_paths: Vec<String>,
+    explain_image_paths: Vec<String>,
+    other_paths: Vec<String>,
     subject: Option<String>,
     knowledge_points: Vec<String>,
+    text: QuestionText,
+    paper: Option<&Paper>,
 ) -> Result<QuestionId, AppError> {
     // Debug: Print received image paths
     println!("[DEBUG] create_question received:");
@@ -36,59 +51,41 @@ pub fn create_question(
         println!("    [{}]: {}", i, p);
     }
 
-    // 1. 将图片移动到 AssetStore 并获取相对路径列表
-
-    let q_srcs: Vec<PathBuf> = question_image_paths
-        .into_iter()
-        .map(PathBuf::from)
-        .collect();
-    let a_srcs: Vec<PathBuf> = answer_image_paths.into_iter().map(PathBuf::from).collect();
-
-    println!("  q_srcs count: {}", q_srcs.len());
-
-    let q_metas = store.save_many(&q_srcs)?;
-    let a_metas = store.save_many(&a_srcs)?;
+    // 1. 将资源文件移动到 AssetStore 并获取相对路径列表
+    let mut saved = Vec::new();
+    for (asset_type, paths) in [
+        (AssetType::QUESTION, question_image_paths),
+        (AssetType::ANSWER, answer_image_paths),
+        (AssetType::EXPLAIN, explain_image_paths),
+        (AssetType::OTHER, other_paths),
+    ] {
+        let srcs: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+        saved.push((asset_type, store.save_many(&srcs)?));
+    }
 
-    println!("  q_metas count: {}", q_metas.len());
-
-    // 2. 创建题目记录
-    let qd = QuestionDao::new(conn);
+    // 2. 创建题目记录；之后的数据库写入在同一个事务里，任一步失败都不会留下半建好的题目
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let qd = QuestionDao::new(&tx);
 
     let now = crate::util::time::now_ts();
     let qid = qd.insert(Some(&name), QuestionState::NEW, now)?;
 
     // 3. 插入资源记录
-    let ad = AssetDao::new(conn);
-    for (index, meta) in q_metas.into_iter().enumerate() {
-        let ts = Timestamp::from(meta.created_at.clone());
-        let sort_order = index as i64 + 1;
-        let aid = ad.insert(
-            qid.clone(),
-            AssetType::QUESTION,
-            meta.relative_path.clone(),
-            ts,
-            sort_order,
-        )?;
-    }
-    for (index, meta) in a_metas.into_iter().enumerate() {
-        let ts = Timestamp::from(meta.created_at.clone());
-        let sort_order = index as i64 + 1;
-        let aid = ad.insert(
-            qid.clone(),
-            AssetType::ANSWER,
-            meta.relative_path.clone(),
-            ts,
-            sort_order,
-        )?;
+    let ad = AssetDao::new(&tx);
+    for (asset_type, metas) in saved {
+        for (index, meta) in metas.into_iter().enumerate() {
+            let ts = Timestamp::from(meta.created_at);
+            ad.insert(qid, asset_type.clone(), meta.relative_path, ts, index as i64 + 1)?;
+        }
     }
 
     // 4. 插入元信息（科目与知识点）
-    let md = MetaDao::new(conn);
+    let md = MetaDao::new(&tx);
     if let Some(s) = subject {
         let mid = md.insert(qid.clone(), MetaKey::System(SystemMetaKey::Subject), &s)?;
     }
 
-    for kp in knowledge_points {
+    for kp in distinct(knowledge_points) {
         let mid = md.insert(
             qid.clone(),
             MetaKey::System(SystemMetaKey::KnowledgePoint),
@@ -96,84 +93,187 @@ pub fn create_question(
         )?;
     }
 
+    // 5. 写入文本内容
+    for field in TextField::ALL {
+        if let Some(content) = text.get(field) {
+            write_question_text(&tx, qid, field, content, now)?;
+        }
+    }
+
+    // 6. 题干中有填空时生成填空卡
+    cloze::sync_cards(&tx, qid, now)?;
+
+    // 7. 记下第一条修订
+    revision::record(&tx, qid, now)?;
+
+    // 8. 来源试卷
+    if paper.is_some() {
+        paper::set_question_paper(&tx, qid, paper)?;
+    }
+
+    tx.commit().map_err(DbError::from)?;
     Ok(qid)
 }
 
 /// 逻辑删除题目
 /// 输入：题目ID
 /// 向数据库中设置题目的删除时间戳（`deleted_at`），表示该题目已被删除，但数据仍保留在数据库中。
+/// 大题与小题在一个事务里一起删除。
 /// 输出：是否删除成功
 ///  - 成功返回 `Ok(true)`
 ///  - 失败返回 `Err(DbError)`
 pub fn delete_question(conn: &Connection, qid: QuestionId) -> Result<bool, AppError> {
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    move_to_recycle_bin(&tx, qid)?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(true)
+}
+
+/// 题目连同小题移入回收站。不自带事务，批量操作在自己的事务里调用。
+pub fn move_to_recycle_bin(conn: &Connection, qid: QuestionId) -> Result<(), AppError> {
     let qd = QuestionDao::new(conn);
+    let pd = QuestionPartDao::new(conn);
     let now = crate::util::time::now_ts();
+    // 小题与大题记同一个删除时间，恢复大题时据此一起恢复
+    for part in pd.list_part_ids(i64::from(qid), false)? {
+        qd.update_deleted_at(QuestionId::from(part), Some(now))?;
+    }
     qd.update_deleted_at(qid, Some(now))?;
-    Ok(true)
+    sync_parent_of(conn, qid)?;
+    Ok(())
+}
+
+/// 小题增删后重新推导其大题
+fn sync_parent_of(conn: &Connection, qid: QuestionId) -> Result<(), AppError> {
+    if let Some(parent) = QuestionPartDao::new(conn).parent_of(i64::from(qid))? {
+        crate::server::ReviewManager::new(conn)
+            .sync_parent(QuestionId::from(parent))
+            .map_err(AppError::InvalidInput)?;
+    }
+    Ok(())
 }
 
 /// 恢复已删除的题目
 /// 输入：题目ID
 /// 将数据库中该题目的删除时间戳（`deleted_at`）设置为 `NULL`，表示该题目已被恢复。
+/// 大题与小题在一个事务里一起恢复。
 /// 输出：是否恢复成功
 ///  - 成功返回 `Ok(true)`
 /// - 失败返回 `Err(DbError)`
 pub fn restore_question(conn: &Connection, qid: QuestionId) -> Result<bool, AppError> {
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    restore_from_recycle_bin(&tx, qid)?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(true)
+}
+
+/// 题目从回收站恢复。不自带事务，批量操作在自己的事务里调用。
+pub fn restore_from_recycle_bin(conn: &Connection, qid: QuestionId) -> Result<(), AppError> {
     let qd = QuestionDao::new(conn);
+    // 与大题一起删除的小题一起恢复，之前单独删除的留在回收站
+    if let Some(deleted_at) = qd.get_by_id(qid)?.and_then(|q| q.deleted_at) {
+        let part_ids = QuestionPartDao::new(conn).list_part_ids(i64::from(qid), true)?;
+        for part in part_ids.into_iter().map(QuestionId::from) {
+            if qd.get_by_id(part)?.and_then(|q| q.deleted_at) == Some(deleted_at) {
+                qd.update_deleted_at(part, None)?;
+            }
+        }
+    }
     qd.update_deleted_at(qid, None)?;
-    Ok(true)
+    sync_parent_of(conn, qid)?;
+    Ok(())
 }
 
 /// 永久删除题目（物理删除）
 /// 输入：题目ID, AssetStore
-/// 删除数据库中的题目、元信息、资源、复习记录，并删除存储中的文件
+/// 删除数据库中的题目、元信息、资源、复习记录、文本，并删除存储中的文件
+/// 数据库部分在一个事务里完成，提交后再删除文件
 /// 输出：是否删除成功
 pub fn permanently_delete_question(
     conn: &Connection,
     store: &AssetStore,
     qid: QuestionId,
 ) -> Result<bool, AppError> {
-    let qd = QuestionDao::new(conn);
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let entries = purge_question(&tx, qid)?;
+    tx.commit().map_err(DbError::from)?;
+    delete_purged_files(store, &entries)?;
+    Ok(true)
+}
+
+/// 存储中待删除的文件：(资源 id, 相对路径, 回收区相对路径)
+pub type PurgedFile = (uuid::Uuid, String, std::path::PathBuf);
+
+/// 从数据库中彻底删除题目及其全部关联记录，返回需要随后从存储中删除的文件。
+/// 只动数据库，调用方可以把它放进事务，提交后再调用 `delete_purged_files`。
+pub fn purge_question(conn: &Connection, qid: QuestionId) -> Result<Vec<PurgedFile>, AppError> {
     let md = MetaDao::new(conn);
     let ad = AssetDao::new(conn);
+    let pd = QuestionPartDao::new(conn);
 
-    // 1. 获取该题目的所有资源（包括软删除的），用于后续删除文件
-    let assets = ad.list_all_by_question(qid.clone())?;
+    // 0. 小题随大题一起删除
+    let mut entries: Vec<PurgedFile> = Vec::new();
+    for part in pd.list_part_ids(i64::from(qid), true)? {
+        entries.extend(purge_question(conn, QuestionId::from(part))?);
+    }
+    let parent = pd.parent_of(i64::from(qid))?;
 
-    // 2. 删除存储中的文件
-    let mut entries_to_delete: Vec<(uuid::Uuid, String, std::path::PathBuf)> = Vec::new();
+    // 1. 获取该题目的所有资源（包括软删除的），用于随后删除文件
+    let assets = ad.list_all_by_question(qid.clone())?;
     for asset in &assets {
         if let Ok(uuid) = uuid::Uuid::parse_str(&asset.id.0.to_string()) {
             let relative_path = asset.path.as_str();
             // 构造回收相对路径
             let recycle_relative = format!("recycle/{}/{}", uuid, relative_path);
-            entries_to_delete.push((uuid, relative_path, std::path::PathBuf::from(recycle_relative)));
+            entries.push((uuid, relative_path, std::path::PathBuf::from(recycle_relative)));
         }
     }
-    if !entries_to_delete.is_empty() {
-        store.delete_physical(&entries_to_delete)?;
-    }
 
-    // 3. 删除数据库中的复习记录
+    // 2. 删除数据库中的复习、跳过记录、推荐干预记录与题目关联
     let qid_i64: i64 = i64::from(qid.clone());
     crate::db::delete_reviews_by_question(conn, qid_i64)?;
-
-    // 4. 删除数据库中的元信息
+    crate::db::delete_archived_reviews_by_question(conn, qid_i64)?;
+    SkipDao::new(conn).delete_by_question(qid_i64)?;
+    OverrideDao::new(conn).delete_by_question(qid_i64)?;
+    QuestionTextDao::new(conn).delete_by_question(qid_i64)?;
+    QuestionRevisionDao::new(conn).delete_by_question(qid_i64)?;
+    QuestionLinkDao::new(conn).delete_by_question(qid_i64)?;
+    AnswerGradingDao::new(conn).delete_by_question(qid_i64)?;
+    OcclusionDao::new(conn).delete_by_card(qid_i64)?;
+    pd.delete_by_question(qid_i64)?;
+
+    // 3. 删除数据库中的元信息
     let metas = md.list_by_question(qid.clone())?;
     for meta in metas {
         md.delete(meta.id)?;
     }
 
-    // 5. 删除数据库中的资源记录（物理删除）
+    // 4. 删除数据库中的资源记录（物理删除）
+    let od = OcclusionDao::new(conn);
     for asset in &assets {
+        od.delete_image_version(i64::from(asset.id))?;
         ad.delete_physical(asset.id)?;
     }
 
-    // 6. 删除数据库中的题目记录
-    let qid_i64: i64 = i64::from(qid);
+    // 5. 删除数据库中的题目记录
     crate::db::delete_question_by_id(conn, qid_i64)?;
 
-    Ok(true)
+    // 6. 删除的是小题时重新推导大题
+    if let Some(parent) = parent {
+        crate::server::ReviewManager::new(conn)
+            .sync_parent(QuestionId::from(parent))
+            .map_err(AppError::InvalidInput)?;
+    }
+
+    Ok(entries)
+}
+
+/// 删除 `purge_question` 返回的存储文件
+pub fn delete_purged_files(store: &AssetStore, entries: &[PurgedFile]) -> Result<(), AppError> {
+    if !entries.is_empty() {
+        store.delete_physical(entries)?;
+    }
+    Ok(())
 }
 
 /// 清理回收站中超过指定天数的题目
@@ -205,7 +305,7 @@ pub fn cleanup_old_deleted_questions(
     Ok(deleted_count)
 }
 
-/// 题目改名
+/// 题目改名，与修订在一个事务里完成
 /// 输入：题目ID，新题目名
 /// 输出：是否改名成功
 pub fn rename_question(
@@ -213,12 +313,47 @@ pub fn rename_question(
     qid: QuestionId,
     new_name: String,
 ) -> Result<bool, AppError> {
-    let qd = QuestionDao::new(conn);
-    qd.update_name(qid, Some(&new_name))?;
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let renamed = revision::tracked(&tx, qid, crate::util::time::now_ts(), || {
+        QuestionDao::new(&tx).update_name(qid, Some(&new_name))?;
+        Ok(true)
+    })?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(renamed)
+}
+
+/// 一次保存题名、科目、知识点与文本字段，只记一条修订；`None` 表示不修改
+/// 文本字段为 `Some("")` 时清空。整个操作在一个事务里完成。
+pub fn update_question(
+    conn: &Connection,
+    qid: QuestionId,
+    new_name: Option<String>,
+    new_subject: Option<String>,
+    new_knowledge_points: Option<Vec<String>>,
+    text: QuestionText,
+    now: Timestamp,
+) -> Result<bool, AppError> {
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    revision::tracked(&tx, qid, now, || {
+        if let Some(name) = &new_name {
+            QuestionDao::new(&tx).update_name(qid, Some(name))?;
+        }
+        write_question_meta(&tx, qid, new_subject, new_knowledge_points)?;
+        for field in TextField::ALL {
+            if let Some(content) = text.get(field) {
+                write_question_text(&tx, qid, field, content, now)?;
+            }
+        }
+        Ok(())
+    })?;
+    cloze::sync_cards(&tx, qid, now)?;
+    occlusion::sync_masks(&tx, qid, now)?;
+    question_part::sync_part_metas(&tx, qid)?;
+    tx.commit().map_err(DbError::from)?;
     Ok(true)
 }
 
-/// 更新题目的科目和知识点
+/// 更新题目的科目和知识点，连同修订与卡片、小题的同步在一个事务里完成
 /// 输入：题目ID，新科目（可选），新知识点列表（可选）
 /// 输出：是否更新成功
 pub fn update_question_meta(
@@ -226,6 +361,35 @@ pub fn update_question_meta(
     qid: QuestionId,
     new_subject: Option<String>,
     new_knowledge_points: Option<Vec<String>>,
+) -> Result<bool, AppError> {
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let updated = apply_question_meta(&tx, qid, new_subject, new_knowledge_points)?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(updated)
+}
+
+/// 同 `update_question_meta`，但不自带事务，批量操作在自己的事务里调用
+pub fn apply_question_meta(
+    conn: &Connection,
+    qid: QuestionId,
+    new_subject: Option<String>,
+    new_knowledge_points: Option<Vec<String>>,
+) -> Result<bool, AppError> {
+    let now = crate::util::time::now_ts();
+    let updated = revision::tracked(conn, qid, now, || {
+        write_question_meta(conn, qid, new_subject, new_knowledge_points)
+    })?;
+    cloze::sync_cards(conn, qid, now)?;
+    occlusion::sync_masks(conn, qid, now)?;
+    question_part::sync_part_metas(conn, qid)?;
+    Ok(updated)
+}
+
+fn write_question_meta(
+    conn: &Connection,
+    qid: QuestionId,
+    new_subject: Option<String>,
+    new_knowledge_points: Option<Vec<String>>,
 ) -> Result<bool, AppError> {
     let md = MetaDao::new(conn);
 
@@ -242,7 +406,7 @@ pub fn update_question_meta(
         // 删除旧的知识点
         md.delete_by_question_and_key(qid.clone(), MetaKey::System(SystemMetaKey::KnowledgePoint))?;
         // 添加新的知识点
-        for kp in kps {
+        for kp in distinct(kps) {
             md.insert(qid.clone(), MetaKey::System(SystemMetaKey::KnowledgePoint), &kp)?;
         }
     }
@@ -250,7 +414,66 @@ pub fn update_question_meta(
     Ok(true)
 }
 
-/// 为题目添加图片
+/// 去掉重复的知识点（保留首次出现的顺序），同一值不能重复写入 meta
+fn distinct(values: Vec<String>) -> Vec<String> {
+    let mut out: Vec<String> = Vec::with_capacity(values.len());
+    for v in values {
+        if !out.contains(&v) {
+            out.push(v);
+        }
+    }
+    out
+}
+
+/// 保存题目的一个文本字段（Markdown，行内 LaTeX）
+/// 内容与当前版本相同时不写入；只含空白的内容视为清空，存为空字符串。
+/// 与修订、填空卡同步在一个事务里完成。
+/// 输出：是否写入了新版本
+pub fn set_question_text(
+    conn: &Connection,
+    qid: QuestionId,
+    field: TextField,
+    content: &str,
+    now: Timestamp,
+) -> Result<bool, AppError> {
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let written = revision::tracked(&tx, qid, now, || write_question_text(&tx, qid, field, content, now))?;
+    cloze::sync_cards(&tx, qid, now)?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(written)
+}
+
+/// 同 `set_question_text`，但不记修订（由调用方统一记）
+pub(crate) fn write_question_text(
+    conn: &Connection,
+    qid: QuestionId,
+    field: TextField,
+    content: &str,
+    now: Timestamp,
+) -> Result<bool, AppError> {
+    let td = QuestionTextDao::new(conn);
+    let qid_i64 = i64::from(qid);
+    let content = if content.trim().is_empty() { "" } else { content };
+    let current = td.latest(qid_i64, field)?.map(|v| v.content).unwrap_or_default();
+    if current == content {
+        return Ok(false);
+    }
+    td.insert_version(qid_i64, field, content, now.as_i64())?;
+    Ok(true)
+}
+
+/// 文本字段的历史版本，新版本在前
+pub fn get_question_text_history(
+    conn: &Connection,
+    qid: QuestionId,
+    field: TextField,
+) -> Result<Vec<TextVersion>, AppError> {
+    Ok(QuestionTextDao::new(conn).history(i64::from(qid), field)?)
+}
+
+/// 为题目添加资源（题目图 / 答案图 / 解析图 / 其他附件）
+/// 新资源接在同类型现有资源之后编排 `sort_order`，各类型的序列互不影响。
+/// 登记与修订在一个事务里完成，失败时删掉刚保存的文件。
 pub fn add_question_images(
     conn: &Connection,
     store: &AssetStore,
@@ -261,19 +484,63 @@ pub fn add_question_images(
     let srcs: Vec<PathBuf> = image_paths.into_iter().map(PathBuf::from).collect();
     let metas = store.save_many(&srcs)?;
 
-    let ad = AssetDao::new(conn);
-    for (index, meta) in metas.into_iter().enumerate() {
-        let ts = Timestamp::from(meta.created_at.clone());
-        let sort_order = index as i64 + 1;
-        ad.insert(
-            qid.clone(),
-            asset_type.clone(),
-            meta.relative_path.clone(),
-            ts,
-            sort_order,
-        )?;
+    let result = attach_assets(conn, qid, &metas, asset_type);
+    if result.is_err() {
+        // 删除失败只会留下无主文件，不掩盖原来的错误
+        let _ = store.discard(&metas);
     }
-    Ok(true)
+    result
+}
+
+fn attach_assets(
+    conn: &Connection,
+    qid: QuestionId,
+    metas: &[AssetMeta],
+    asset_type: AssetType,
+) -> Result<bool, AppError> {
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let added = revision::tracked(&tx, qid, crate::util::time::now_ts(), || {
+        let ad = AssetDao::new(&tx);
+        let last_order = ad
+            .list_by_question(qid)?
+            .iter()
+            .filter(|a| a.asset_type == asset_type)
+            .map(|a| a.sort_order)
+            .max()
+            .unwrap_or(0);
+        for (index, meta) in metas.iter().enumerate() {
+            let ts = Timestamp::from(meta.created_at.clone());
+            let sort_order = last_order + index as i64 + 1;
+            ad.insert(
+                qid.clone(),
+                asset_type.clone(),
+                meta.relative_path.clone(),
+                ts,
+                sort_order,
+            )?;
+        }
+        Ok(true)
+    })?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(added)
+}
+
+/// 调整某类资源的排序，与修订在一个事务里完成
+/// 输入：题目ID，资源类型，(资源ID, 新序号) 列表；只更新属于该题且类型一致的资源
+pub fn reorder_question_assets(
+    conn: &Connection,
+    qid: QuestionId,
+    asset_type: AssetType,
+    orders: Vec<(AssetId, i64)>,
+) -> Result<bool, AppError> {
+    let orders: Vec<(i64, i64)> = orders.into_iter().map(|(id, order)| (i64::from(id), order)).collect();
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let reordered = revision::tracked(&tx, qid, crate::util::time::now_ts(), || {
+        crate::db::batch_update_asset_sort_order(&tx, i64::from(qid), asset_type.as_str(), orders)?;
+        Ok(true)
+    })?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(reordered)
 }
 
 /// 删除题目的图片资源（逻辑删除）
@@ -283,8 +550,24 @@ pub fn delete_question_image(conn: &Connection, asset_id: String) -> Result<bool
     let uuid = uuid::Uuid::parse_str(&asset_id)
         .map_err(|e| AppError::NotFound(format!("Invalid asset id: {}", e)))?;
     let aid = AssetId(uuid);
-    ad.delete(aid)?;
-    Ok(true)
+    match ad.get_by_id(aid)? {
+        Some(asset) => {
+            let now = crate::util::time::now_ts();
+            let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+            let deleted = revision::tracked(&tx, asset.question_id, now, || {
+                AssetDao::new(&tx).delete(aid)?;
+                // 图上遮罩的卡片随图片移入回收站
+                occlusion::sync_masks(&tx, asset.question_id, now)?;
+                Ok(true)
+            })?;
+            tx.commit().map_err(DbError::from)?;
+            Ok(deleted)
+        }
+        None => {
+            ad.delete(aid)?;
+            Ok(true)
+        }
+    }
 }
 
 /// 提取单个题目的所有信息（包括元信息和资源）
@@ -310,6 +593,143 @@ pub fn get_question_detail(conn: &Connection, qid: QuestionId) -> Result<Questio
     // 获取复习记录信息
     let reviews = rd.list_by_question(qid.clone())?;
 
-    let q_info = QuestionInfo::new(question, assets, metas, reviews);
+    // 获取文本内容
+    let text = QuestionTextDao::new(conn).get_current(i64::from(qid))?;
+
+    // 获取题目关联
+    let links = QuestionLinkDao::new(conn).list_by_question(i64::from(qid))?;
+
+    // 获取小题与所属大题
+    let parts = crate::server::question_part::list_parts(conn, qid)?;
+    let parent_id = crate::server::question_part::parent_of(conn, qid)?;
+
+    // 填空卡没有自己的文本，由笔记题干渲染
+    let cloze_index = QuestionPartDao::new(conn).cloze_of(i64::from(qid))?.map(|(_, index)| index);
+    let text = cloze::card_text(conn, qid)?.unwrap_or(text);
+
+    // 遮罩卡片显示笔记上画遮罩的那张图（作为题目图），笔记的文本照常显示
+    let occlusion = occlusion::mask_of(conn, qid)?;
+    let (assets, text) = match (&occlusion, parent_id) {
+        (Some(mask), Some(note_id)) => {
+            let image = ad
+                .get_by_id(AssetId::from(mask.asset_id))?
+                .map(|a| Asset { asset_type: AssetType::QUESTION, ..a });
+            (image.into_iter().collect(), QuestionTextDao::new(conn).get_current(i64::from(note_id))?)
+        }
+        _ => (assets, text),
+    };
+
+    let q_info = QuestionInfo::new(
+        question,
+        assets,
+        metas,
+        reviews,
+        text,
+        links,
+        parts,
+        parent_id,
+        cloze_index,
+        occlusion,
+    );
     Ok(q_info)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn orders_of(conn: &Connection, qid: QuestionId, asset_type: AssetType) -> Vec<i64> {
+        let mut orders: Vec<i64> = AssetDao::new(conn)
+            .list_by_question(qid)
+            .unwrap()
+            .into_iter()
+            .filter(|a| a.asset_type == asset_type)
+            .map(|a| a.sort_order)
+            .collect();
+        orders.sort();
+        orders
+    }
+
+    #[test]
+    fn test_sort_order_sequences_are_per_type() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        let root = tempdir().unwrap();
+        let src = tempdir().unwrap();
+        let store = AssetStore::new(root.path().to_path_buf());
+        let file = |name: &str| {
+            let p = src.path().join(name);
+            fs::write(&p, b"x").unwrap();
+            p.to_string_lossy().into_owned()
+        };
+
+        let qid = create_question(
+            &conn,
+            &store,
+            "题目".to_string(),
+            vec![file("q1.png"), file("q2.png")],
+            vec![file("a1.png")],
+            vec![file("e1.png")],
+            vec![file("notes.pdf")],
+            None,
+            vec![],
+            QuestionText::default(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(orders_of(&conn, qid, AssetType::QUESTION), vec![1, 2]);
+        assert_eq!(orders_of(&conn, qid, AssetType::ANSWER), vec![1]);
+        assert_eq!(orders_of(&conn, qid, AssetType::OTHER), vec![1]);
+
+        // 追加的解析图接在已有解析图之后，不受题目图序号影响
+        add_question_images(&conn, &store, qid, vec![file("e2.png"), file("e3.png")], AssetType::EXPLAIN)
+            .unwrap();
+        assert_eq!(orders_of(&conn, qid, AssetType::EXPLAIN), vec![1, 2, 3]);
+
+        // 按类型重排：类型不符的资源不会被改动
+        let explain_ids: Vec<AssetId> = AssetDao::new(&conn)
+            .list_by_question(qid)
+            .unwrap()
+            .into_iter()
+            .filter(|a| a.asset_type == AssetType::EXPLAIN)
+            .map(|a| a.id)
+            .collect();
+        let orders = explain_ids.iter().map(|&id| (id, 10)).collect();
+        reorder_question_assets(&conn, qid, AssetType::QUESTION, orders).unwrap();
+        assert_eq!(orders_of(&conn, qid, AssetType::EXPLAIN), vec![1, 2, 3]);
+        let orders = explain_ids.iter().map(|&id| (id, 10)).collect();
+        reorder_question_assets(&conn, qid, AssetType::EXPLAIN, orders).unwrap();
+        assert_eq!(orders_of(&conn, qid, AssetType::EXPLAIN), vec![10, 10, 10]);
+    }
+
+    #[test]
+    fn test_meta_cardinality_enforced_in_dao() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        let qid = QuestionId::from(crate::db::insert_question(&conn, Some("q"), "NEW", 0).unwrap());
+        let subject = MetaKey::System(SystemMetaKey::Subject);
+        let kp = MetaKey::System(SystemMetaKey::KnowledgePoint);
+
+        let md = MetaDao::new(&conn);
+        md.insert(qid, subject.clone(), "数学").unwrap();
+        assert!(matches!(
+            md.insert(qid, subject.clone(), "物理"),
+            Err(crate::db::error::DbError::Constraint(_))
+        ));
+        md.insert(qid, kp.clone(), "导数").unwrap();
+        assert!(matches!(md.insert(qid, kp.clone(), "导数"), Err(crate::db::error::DbError::Constraint(_))));
+
+        // 业务层整体替换：重复的知识点只写一次
+        update_question_meta(
+            &conn,
+            qid,
+            Some("物理".to_string()),
+            Some(vec!["力学".to_string(), "光学".to_string(), "力学".to_string()]),
+        )
+        .unwrap();
+        assert_eq!(md.get_values_by_question_key(qid, &subject.as_str()).unwrap(), vec!["物理"]);
+        assert_eq!(md.get_values_by_question_key(qid, &kp.as_str()).unwrap(), vec!["力学", "光学"]);
+    }
+}
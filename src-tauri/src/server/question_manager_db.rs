@@ -128,6 +128,16 @@ pub fn get_question_detail(conn: &Connection, qid: QuestionId) -> Result<Questio
     // 获取复习记录信息
     let reviews = rd.list_by_question(qid.clone())?;
 
-    let q_info = QuestionInfo::new(question, assets, metas, reviews);
+    // 获取文本内容
+    let text = crate::dao::question_text_dao::QuestionTextDao::new(conn).get_current(i64::from(qid))?;
+
+    // 获取题目关联
+    let links = crate::dao::question_link_dao::QuestionLinkDao::new(conn).list_by_question(i64::from(qid))?;
+
+    // 获取小题与所属大题
+    let parts = crate::server::question_part::list_parts(conn, qid)?;
+    let parent_id = crate::server::question_part::parent_of(conn, qid)?;
+
+    let q_info = QuestionInfo::new(question, assets, metas, reviews, text, links, parts, parent_id, None, None);
     Ok(q_info)
 }
@@ -0,0 +1,231 @@
+//! 多小题
+//!
+//! 一道大题可以拆成有序的小题 (1)(2)(3)。小题是完整的题目：有自己的资源、文本与复习状态，
+//! 复习时逐道评分，推荐时也按小题挑选。大题不再单独复习，状态由小题推导
+//! （见 `QuestionStateMachine::derive_from_parts`）。
+//!
+//! 大题移入回收站 / 恢复 / 彻底删除时，小题随之一起处理；大题的科目与知识点改动时，小题随之更新。
+
+use crate::dao::meta_dao::MetaDao;
+use crate::dao::question_dao::QuestionDao;
+use crate::dao::question_part_dao::QuestionPartDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::domain::enums::{MetaKey, QuestionState, SystemMetaKey, TextField};
+use crate::domain::ids::QuestionId;
+use crate::domain::question::Question;
+use crate::domain::question_text::QuestionText;
+use crate::error::AppError;
+use crate::server::question_manager::write_question_text;
+use crate::server::revision;
+use crate::server::ReviewManager;
+use crate::util::time::Timestamp;
+
+/// 给大题追加一道小题，沿用大题的科目与知识点。
+/// 不给名称时取"大题名 (序号)"。返回小题 ID。
+pub fn add_part(
+    conn: &Connection,
+    parent_id: QuestionId,
+    name: Option<String>,
+    text: QuestionText,
+    now: Timestamp,
+) -> Result<QuestionId, AppError> {
+    let qd = QuestionDao::new(conn);
+    let pd = QuestionPartDao::new(conn);
+    let parent = qd
+        .get_by_id(parent_id)?
+        .ok_or_else(|| AppError::NotFound(format!("question {}", i64::from(parent_id))))?;
+    if parent.deleted_at.is_some() {
+        return Err(AppError::InvalidInput("question is in the recycle bin".to_string()));
+    }
+    if pd.parent_of(i64::from(parent_id))?.is_some() {
+        return Err(AppError::InvalidInput("a part cannot have parts".to_string()));
+    }
+
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let qd = QuestionDao::new(&tx);
+    let pd = QuestionPartDao::new(&tx);
+    let md = MetaDao::new(&tx);
+
+    let qid = qd.insert(None, QuestionState::NEW, now)?;
+    let part_order = pd.append(i64::from(parent_id), i64::from(qid))?;
+    let name = match name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()) {
+        Some(name) => name,
+        None => format!("{} ({})", parent.name.as_deref().unwrap_or("未命名题目"), part_order),
+    };
+    qd.update_name(qid, Some(&name))?;
+
+    for meta in md.list_by_question(parent_id)? {
+        if matches!(
+            meta.key,
+            MetaKey::System(SystemMetaKey::Subject) | MetaKey::System(SystemMetaKey::KnowledgePoint)
+        ) {
+            md.insert(qid, meta.key, &meta.value)?;
+        }
+    }
+    for field in TextField::ALL {
+        if let Some(content) = text.get(field) {
+            write_question_text(&tx, qid, field, content, now)?;
+        }
+    }
+    revision::record(&tx, qid, now)?;
+    ReviewManager::new(&tx).sync_parent(parent_id).map_err(AppError::InvalidInput)?;
+
+    tx.commit().map_err(DbError::from)?;
+    Ok(qid)
+}
+
+/// 由题目生成的卡片（填空卡、遮罩卡）跟随笔记的科目与知识点：先清掉卡片上的，再照笔记复制
+pub fn inherit_metas(conn: &Connection, note_id: QuestionId, card_id: QuestionId) -> Result<(), DbError> {
+    let md = MetaDao::new(conn);
+    let inherited = [MetaKey::System(SystemMetaKey::Subject), MetaKey::System(SystemMetaKey::KnowledgePoint)];
+    for key in &inherited {
+        md.delete_by_question_and_key(card_id, key.clone())?;
+    }
+    for meta in md.list_by_question(note_id)? {
+        if inherited.contains(&meta.key) {
+            md.insert(card_id, meta.key, &meta.value)?;
+        }
+    }
+    Ok(())
+}
+
+/// 大题的科目或知识点改动后，全部小题（含回收站中的）重新跟随大题。
+/// 填空卡、遮罩卡由 `sync_cards` / `sync_masks` 同步，这里也一并覆盖，结果相同。
+pub fn sync_part_metas(conn: &Connection, parent_id: QuestionId) -> Result<(), DbError> {
+    for part in QuestionPartDao::new(conn).list_part_ids(i64::from(parent_id), true)? {
+        inherit_metas(conn, parent_id, QuestionId::from(part))?;
+    }
+    Ok(())
+}
+
+/// 大题下未删除的小题，按顺序
+pub fn list_parts(conn: &Connection, parent_id: QuestionId) -> Result<Vec<Question>, DbError> {
+    let ids = QuestionPartDao::new(conn).list_part_ids(i64::from(parent_id), false)?;
+    let mut parts = QuestionDao::new(conn).get_by_ids(&ids)?;
+    parts.sort_by_key(|p| ids.iter().position(|&id| id == i64::from(p.id)));
+    Ok(parts)
+}
+
+/// 小题所属的大题
+pub fn parent_of(conn: &Connection, question_id: QuestionId) -> Result<Option<QuestionId>, DbError> {
+    Ok(QuestionPartDao::new(conn).parent_of(i64::from(question_id))?.map(QuestionId::from))
+}
+
+/// 按 `part_ids` 的顺序重排小题；必须正好是大题下全部未删除的小题
+pub fn reorder_parts(conn: &Connection, parent_id: QuestionId, part_ids: &[i64]) -> Result<(), AppError> {
+    let pd = QuestionPartDao::new(conn);
+    let mut current = pd.list_part_ids(i64::from(parent_id), false)?;
+    let mut given = part_ids.to_vec();
+    current.sort();
+    given.sort();
+    if current != given {
+        return Err(AppError::InvalidInput("part list does not match the question's parts".to_string()));
+    }
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let pd = QuestionPartDao::new(&tx);
+    for (index, &id) in part_ids.iter().enumerate() {
+        pd.update_order(id, index as i64 + 1)?;
+    }
+    tx.commit().map_err(DbError::from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::enums::ReviewResult;
+    use crate::server::question_manager::{delete_question, purge_question, restore_question};
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn parent(conn: &Connection) -> QuestionId {
+        let qid = crate::db::insert_question(conn, Some("导数大题"), "NEW", 0).unwrap();
+        crate::db::insert_meta(conn, qid, "system.Subject", "数学").unwrap();
+        crate::db::insert_meta(conn, qid, "system.KnowledgePoint", "导数").unwrap();
+        QuestionId::from(qid)
+    }
+
+    fn state(conn: &Connection, qid: QuestionId) -> QuestionState {
+        QuestionDao::new(conn).get_by_id(qid).unwrap().unwrap().state
+    }
+
+    #[test]
+    fn test_parts_are_graded_separately_and_drive_parent() {
+        let conn = setup();
+        let p = parent(&conn);
+        let text = QuestionText { prompt: Some("求单调区间".to_string()), ..QuestionText::default() };
+        let first = add_part(&conn, p, None, text, Timestamp::from(10)).unwrap();
+        let second = add_part(&conn, p, Some("(2) 求极值".to_string()), QuestionText::default(), Timestamp::from(10))
+            .unwrap();
+
+        let parts = list_parts(&conn, p).unwrap();
+        assert_eq!(parts.iter().map(|q| q.id).collect::<Vec<_>>(), vec![first, second]);
+        assert_eq!(parts[0].name.as_deref(), Some("导数大题 (1)"));
+        let subject = MetaDao::new(&conn).get_by_question_key(first, "system.Subject").unwrap().unwrap();
+        assert_eq!(subject.value, "数学");
+        // 小题不出现在题目列表中
+        let listed = crate::dao::ViewDao::new(&conn).list_ids_filtered(false, None, None, &[], &[]).unwrap();
+        assert_eq!(listed, vec![i64::from(p)]);
+
+        let manager = ReviewManager::new(&conn);
+        assert!(manager.process_review(p, ReviewResult::CORRECT, Timestamp::from(20)).is_err());
+        manager.process_review(second, ReviewResult::WRONG, Timestamp::from(20)).unwrap();
+        assert_eq!(state(&conn, first), QuestionState::NEW);
+        assert_eq!(state(&conn, p), QuestionState::LEARNING);
+        let parent_q = QuestionDao::new(&conn).get_by_id(p).unwrap().unwrap();
+        assert_eq!(parent_q.wrong_count, 1);
+        assert_eq!(parent_q.last_result, Some(ReviewResult::WRONG));
+
+        // 暂停大题即暂停全部小题
+        manager.suspend(p).unwrap();
+        assert_eq!((state(&conn, first), state(&conn, second)), (QuestionState::SUSPENDED, QuestionState::SUSPENDED));
+        assert_eq!(state(&conn, p), QuestionState::SUSPENDED);
+        manager.recover(p).unwrap();
+        assert_eq!(state(&conn, p), QuestionState::LEARNING);
+
+        reorder_parts(&conn, p, &[i64::from(second), i64::from(first)]).unwrap();
+        assert_eq!(list_parts(&conn, p).unwrap()[0].id, second);
+        assert!(reorder_parts(&conn, p, &[i64::from(first)]).is_err());
+        assert!(add_part(&conn, first, None, QuestionText::default(), Timestamp::from(30)).is_err());
+    }
+
+    #[test]
+    fn test_parts_follow_parent_through_recycle_bin() {
+        let conn = setup();
+        let p = parent(&conn);
+        let a = add_part(&conn, p, None, QuestionText::default(), Timestamp::from(10)).unwrap();
+        let b = add_part(&conn, p, None, QuestionText::default(), Timestamp::from(10)).unwrap();
+
+        // 单独删除（早于大题）的小题进回收站，恢复大题时不跟着恢复
+        crate::db::update_question_deleted_at(&conn, i64::from(b), Some(5)).unwrap();
+        let deleted = crate::dao::ViewDao::new(&conn).list_ids_filtered(true, None, None, &[], &[]).unwrap();
+        assert_eq!(deleted, vec![i64::from(b)]);
+        delete_question(&conn, p).unwrap();
+        assert!(list_parts(&conn, p).unwrap().is_empty());
+        restore_question(&conn, p).unwrap();
+        assert_eq!(list_parts(&conn, p).unwrap().iter().map(|q| q.id).collect::<Vec<_>>(), vec![a]);
+
+        purge_question(&conn, p).unwrap();
+        assert!(QuestionDao::new(&conn).get_by_id(a).unwrap().is_none());
+        let rows: i64 = conn.query_row("SELECT COUNT(*) FROM question_part", [], |r| r.get(0)).unwrap();
+        assert_eq!(rows, 0);
+    }
+
+    #[test]
+    fn test_parts_follow_parent_subject_and_knowledge_points() {
+        let conn = setup();
+        let p = parent(&conn);
+        let part = add_part(&conn, p, None, QuestionText::default(), Timestamp::from(10)).unwrap();
+
+        crate::server::question_manager::update_question_meta(&conn, p, Some("物理".into()), Some(vec!["力学".into()]))
+            .unwrap();
+        let md = MetaDao::new(&conn);
+        assert_eq!(md.get_by_question_key(part, "system.Subject").unwrap().unwrap().value, "物理");
+        assert_eq!(md.get_values_by_question_key(part, "system.KnowledgePoint").unwrap(), vec!["力学"]);
+    }
+}
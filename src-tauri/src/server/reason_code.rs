@@ -0,0 +1,164 @@
+//! 推荐理由 / 落选原因的结构化编码
+//!
+//! 后端只产出编码和参数，例如 `{"code": "overdue", "days": 5}`，显示文案由前端渲染。
+//! `recommendation.reason` 列存的也是这种 JSON 数组；旧版本存的中文文案由迁移转换，
+//! 无法识别的文案保留为 `legacy`。
+
+use serde::{Deserialize, Serialize};
+
+/// 推荐理由
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum ReasonCode {
+    /// 新题（复习次数 ≤ 3）
+    NewQuestion,
+    /// 今天到期
+    Due,
+    /// 超期 `days` 天
+    Overdue { days: i64 },
+    /// 上次答错或模糊
+    LastWrong,
+    /// 错误率偏高（百分比，取整）
+    HighErrorRate { percent: i64 },
+    /// 属于薄弱知识点 `knowledge_point` 的前置
+    Prerequisite { knowledge_point: String },
+    /// 新题保送名额
+    NewGuaranteed,
+    /// 手动置顶
+    Pinned,
+    /// 答错后追加的同知识点巩固
+    Remedial,
+    /// 迁移前的旧文案
+    Legacy { text: String },
+}
+
+/// 落选原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum ExclusionCode {
+    /// 暂停复习
+    Suspended,
+    /// 今日已搁置
+    Buried,
+    /// 科目已归档
+    SubjectArchived,
+    /// 今天不是该科的学习日
+    NotStudyDay,
+    /// 该科每日题数设为 0
+    SubjectNoQuota,
+    /// 该知识点当日入选数已达上限
+    KnowledgePointFull { knowledge_point: String },
+    /// 已掌握（STABLE 且未到期）
+    Mastered,
+    /// 遗忘风险低
+    LowForgetRisk,
+    /// 错误率低
+    LowErrorRate,
+    /// 近期被跳过
+    OftenSkipped,
+    /// 同科排名低于 `rank`
+    SubjectRankBelow { rank: usize },
+    /// 变式题 `question_id` 已排进今天
+    VariantScheduled { question_id: i64 },
+    /// 同一笔记的填空卡 `question_id` 已排进今天
+    SiblingScheduled { question_id: i64 },
+    /// 迁移前的旧文案
+    Legacy { text: String },
+}
+
+/// 科目今日不参与推荐的原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum SubjectOffReason {
+    /// 科目已归档
+    Archived,
+    /// 今天不在学习日 `study_days`（ISO 星期）内
+    NotStudyDay { study_days: Vec<u32> },
+    /// 每日题数设为 0
+    NoQuota,
+}
+
+impl SubjectOffReason {
+    /// 对应的题目落选原因
+    pub fn exclusion(&self) -> ExclusionCode {
+        match self {
+            SubjectOffReason::Archived => ExclusionCode::SubjectArchived,
+            SubjectOffReason::NotStudyDay { .. } => ExclusionCode::NotStudyDay,
+            SubjectOffReason::NoQuota => ExclusionCode::SubjectNoQuota,
+        }
+    }
+}
+
+impl ReasonCode {
+    /// 编码名（与序列化后的 `code` 字段一致），用于统计分组
+    pub fn code(&self) -> &'static str {
+        match self {
+            ReasonCode::NewQuestion => "new_question",
+            ReasonCode::Due => "due",
+            ReasonCode::Overdue { .. } => "overdue",
+            ReasonCode::LastWrong => "last_wrong",
+            ReasonCode::HighErrorRate { .. } => "high_error_rate",
+            ReasonCode::Prerequisite { .. } => "prerequisite",
+            ReasonCode::NewGuaranteed => "new_guaranteed",
+            ReasonCode::Pinned => "pinned",
+            ReasonCode::Remedial => "remedial",
+            ReasonCode::Legacy { .. } => "legacy",
+        }
+    }
+}
+
+/// 解析 `recommendation.reason` 列：新格式直接反序列化；
+/// 未迁移的旧字符串数组逐条保留为 `legacy`；`null` 或无法解析时返回 None
+pub fn parse_stored_reasons(text: &str) -> Option<Vec<ReasonCode>> {
+    if let Ok(codes) = serde_json::from_str::<Option<Vec<ReasonCode>>>(text) {
+        return codes;
+    }
+    serde_json::from_str::<Vec<String>>(text)
+        .ok()
+        .map(|texts| texts.into_iter().map(|text| ReasonCode::Legacy { text }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reason_serializes_with_code_tag() {
+        let json = serde_json::to_string(&ReasonCode::Overdue { days: 5 }).unwrap();
+        assert_eq!(json, r#"{"code":"overdue","days":5}"#);
+        let json = serde_json::to_string(&ReasonCode::NewQuestion).unwrap();
+        assert_eq!(json, r#"{"code":"new_question"}"#);
+        let json = serde_json::to_string(&ExclusionCode::SubjectRankBelow { rank: 12 }).unwrap();
+        assert_eq!(json, r#"{"code":"subject_rank_below","rank":12}"#);
+        let json = serde_json::to_string(&SubjectOffReason::NotStudyDay { study_days: vec![1, 3, 5] }).unwrap();
+        assert_eq!(json, r#"{"code":"not_study_day","study_days":[1,3,5]}"#);
+    }
+
+    #[test]
+    fn test_code_matches_serialized_tag() {
+        for r in [
+            ReasonCode::NewQuestion,
+            ReasonCode::Overdue { days: 1 },
+            ReasonCode::HighErrorRate { percent: 60 },
+            ReasonCode::Prerequisite { knowledge_point: "一次函数".to_string() },
+            ReasonCode::Legacy { text: "x".to_string() },
+        ] {
+            let value = serde_json::to_value(&r).unwrap();
+            assert_eq!(value["code"], r.code());
+        }
+    }
+
+    #[test]
+    fn test_parse_stored_reasons() {
+        assert_eq!(
+            parse_stored_reasons(r#"[{"code":"due"},{"code":"overdue","days":3}]"#),
+            Some(vec![ReasonCode::Due, ReasonCode::Overdue { days: 3 }])
+        );
+        assert_eq!(
+            parse_stored_reasons(r#"["新题"]"#),
+            Some(vec![ReasonCode::Legacy { text: "新题".to_string() }])
+        );
+        assert_eq!(parse_stored_reasons("null"), None);
+        assert_eq!(parse_stored_reasons("not json"), None);
+    }
+}
@@ -2,23 +2,59 @@
 //!
 //! 实现基于 Anki 改进的复习调度和推荐排序算法
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::app::config::SubjectConfig;
+use crate::app::config::{AppSettings, SubjectConfig};
+use crate::dao::carry_over_dao::CarryOverDao;
+use crate::dao::override_dao::OverrideDao;
 use crate::dao::question_dao::QuestionDao;
+use crate::dao::question_part_dao::QuestionPartDao;
 use crate::dao::recommendation_dao::RecommendationDao;
 use crate::dao::review_dao::ReviewDao;
+use crate::dao::skip_dao::SkipDao;
 use crate::db::error::DbError;
-use crate::domain::enums::{QuestionState, ReviewResult};
+use crate::domain::enums::{OverrideAction, QuestionState, RecommendationTier, ReviewResult};
 use crate::domain::ids::QuestionId;
 use crate::domain::question::Question;
-use crate::util::time::{now_ts, ClockConfig, LogicalDay, Timestamp};
+use crate::domain::review_order::ReviewOrder;
+use crate::server::knowledge_graph::KnowledgeGraph;
+use crate::server::question_link;
+use crate::server::reason_code::{ExclusionCode, ReasonCode, SubjectOffReason};
+use crate::server::review_order::{self, OrderKey};
+use crate::util::time::{now_ts, range_of_day, ClockConfig, LogicalDay, Timestamp};
 use rusqlite::Connection;
 use serde::Serialize;
 
 /// 一天对应的秒数
 const DAY_SECONDS: i64 = 24 * 60 * 60;
 
+/// 无科目题目的分组名
+const UNCATEGORIZED: &str = "未分类";
+
+/// 知识点近期错误率的统计窗口（天）
+const PREREQUISITE_WINDOW_DAYS: i64 = 14;
+
+/// 知识点近期错误率达到该值视为薄弱，需要补前置
+const PREREQUISITE_ERROR_THRESHOLD: f64 = 0.5;
+
+/// 统计错误率所需的最少复习次数，样本太少不判断
+const PREREQUISITE_MIN_REVIEWS: i64 = 3;
+
+/// 每个薄弱知识点额外补推的前置题数（不占科目名额）
+const PREREQUISITE_PULL_LIMIT: usize = 2;
+
+/// 统计跳过次数的窗口（天）
+const SKIP_WINDOW_DAYS: i64 = 7;
+
+/// 窗口内每跳过一次，评分乘以该系数
+const SKIP_PENALTY: f64 = 0.85;
+
+/// 跳过因子下限，避免反复跳过的题彻底沉底
+const SKIP_FACTOR_FLOOR: f64 = 0.5;
+
+/// 单题解释中预测入选日期的最远天数
+const EXPLAIN_PROJECTION_DAYS: i64 = 90;
+
 /// 评分明细（调试用）
 #[derive(Debug, Clone, Copy, Serialize)]
 pub struct ScoreDetail {
@@ -26,6 +62,8 @@ pub struct ScoreDetail {
     pub freshness_bonus: f64,
     pub last_wrong_bonus: f64,
     pub error_rate_bonus: f64,
+    /// 近期跳过惩罚（无跳过为 1）
+    pub skip_factor: f64,
     pub randomness: f64,
     pub final_score: f64,
 }
@@ -43,8 +81,10 @@ pub struct RecommendedQuestion {
     pub last_result: Option<String>,
     pub error_rate: Option<f64>,
     pub subject: Option<String>,
+    /// 推荐层级：DAILY 每日推荐，REMEDIAL 答错后追加的巩固题
+    pub tier: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub reason: Option<Vec<String>>,
+    pub reason: Option<Vec<ReasonCode>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub score_detail: Option<ScoreDetail>,
     /// 复习次数（内部使用，不序列化）
@@ -53,6 +93,9 @@ pub struct RecommendedQuestion {
     /// 创建时间戳（内部使用，不序列化）
     #[serde(skip)]
     pub created_at: i64,
+    /// 所属知识点（内部使用，不序列化）
+    #[serde(skip)]
+    pub knowledge_points: Vec<String>,
 }
 
 /// 每日推荐结果
@@ -70,11 +113,54 @@ pub struct PreviewRecommendationItem {
     pub subject: Option<String>,
     pub score: f64,
     pub selected: bool,
-    pub reason: Vec<String>,
-    pub exclusion_reason: Vec<String>,
+    pub reason: Vec<ReasonCode>,
+    pub exclusion_reason: Vec<ExclusionCode>,
     pub score_detail: Option<ScoreDetail>,
     pub subject_rank: usize,
     pub subject_limit: usize,
+    /// 是否经池B评分竞争入选（内部使用，不序列化）
+    #[serde(skip)]
+    pub by_score: bool,
+    /// 同科池B名额已满时的入选最低分（内部使用，不序列化）
+    #[serde(skip)]
+    pub score_cutoff: Option<f64>,
+}
+
+/// 评分因子
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreFactor {
+    ForgetRisk,
+    Freshness,
+    LastWrong,
+    ErrorRate,
+    Skip,
+    Randomness,
+}
+
+/// 单题"为什么入选 / 为什么没入选"的解释
+#[derive(Debug, Clone, Serialize)]
+pub struct QuestionExplanation {
+    pub question_id: i64,
+    pub name: Option<String>,
+    pub subject: Option<String>,
+    pub selected: bool,
+    pub score: f64,
+    pub score_detail: ScoreDetail,
+    pub reason: Vec<ReasonCode>,
+    pub exclusion_reason: Vec<ExclusionCode>,
+    /// 同科按分数的排名（从 1 开始）；暂停的题不参与排名，为 None
+    pub subject_rank: Option<usize>,
+    /// 同科参与排名的题数
+    pub subject_size: usize,
+    /// 入选所需分数：同科经评分竞争入选的最低分；名额未满（任意分数都能入选）时为 None
+    pub required_score: Option<f64>,
+    /// 与入选门槛题相比差距最大的因子；已入选或无门槛时为 None
+    pub main_deficit: Option<ScoreFactor>,
+    /// 状态不变时预计进入每日推荐的逻辑日（YYYY-MM-DD），预测期内进不了为 None
+    pub projected_date: Option<String>,
+    /// 距今天数（今天已入选为 0）
+    pub projected_in_days: Option<i64>,
 }
 
 /// 推荐统计信息
@@ -86,6 +172,24 @@ pub struct RecommendationStats {
     pub recommended_count: usize,
     pub new_questions: usize,
     pub pending_review: usize,
+    /// 今日推荐中各理由编码的出现次数（按次数降序）
+    pub reason_counts: Vec<ReasonCodeCount>,
+    /// 今日不参与推荐的科目及原因（按科目名排序）
+    pub off_subjects: Vec<OffSubject>,
+}
+
+/// 今日不参与推荐的科目
+#[derive(Debug, Clone, Serialize)]
+pub struct OffSubject {
+    pub subject: String,
+    pub reason: SubjectOffReason,
+}
+
+/// 理由编码计数
+#[derive(Debug, Clone, Serialize)]
+pub struct ReasonCodeCount {
+    pub code: String,
+    pub count: i64,
 }
 
 /// 推荐系统
@@ -93,7 +197,10 @@ pub struct RecommendationSystem<'a> {
     conn: &'a Connection,
     question_dao: QuestionDao<'a>,
     review_dao: ReviewDao<'a>,
+    skip_dao: SkipDao<'a>,
     recommendation_dao: RecommendationDao<'a>,
+    override_dao: OverrideDao<'a>,
+    carry_over_dao: CarryOverDao<'a>,
     meta_dao: crate::dao::MetaDao<'a>,
 }
 
@@ -103,7 +210,10 @@ impl<'a> RecommendationSystem<'a> {
             conn,
             question_dao: QuestionDao::new(conn),
             review_dao: ReviewDao::new(conn),
+            skip_dao: SkipDao::new(conn),
             recommendation_dao: RecommendationDao::new(conn),
+            override_dao: OverrideDao::new(conn),
+            carry_over_dao: CarryOverDao::new(conn),
             meta_dao: crate::dao::MetaDao::new(conn),
         }
     }
@@ -111,27 +221,31 @@ impl<'a> RecommendationSystem<'a> {
     /// 获取或生成每日推荐
     pub fn get_daily_recommendation(
         &self,
-        subject_configs: &HashMap<String, SubjectConfig>,
-        per_subject_default_limit: u32,
-        new_question_guarantee_ratio: f64,
+        settings: &AppSettings,
     ) -> Result<DailyRecommendation, DbError> {
         let now = now_ts();
         let cfg = ClockConfig::default();
         let day = LogicalDay::from_timestamp(now, &cfg).0 as i64;
 
-        // 尝试从数据库加载今日推荐
-        if let Some(questions) = self.recommendation_dao.get_by_day(day)? {
+        // 尝试从数据库加载今日推荐（只有巩固题时仍需生成每日推荐）
+        if self.recommendation_dao.count_by_tier(day, RecommendationTier::DAILY)? > 0 {
+            let questions = self.recommendation_dao.get_by_day(day)?.unwrap_or_default();
             return Ok(DailyRecommendation { day, questions });
         }
 
+        // 清理前先结算上一个推荐日未用完的名额
+        self.update_carry_over(day, settings)?;
+
         // 清理旧推荐记录，只保留当天的
         self.recommendation_dao.cleanup_old_recommendations(day)?;
+        self.override_dao.cleanup_before(day)?;
 
         // 生成新推荐
-        let questions = self.generate_recommendation(now, subject_configs, per_subject_default_limit, new_question_guarantee_ratio)?;
+        let questions = self.generate_recommendation(now, settings)?;
 
-        // 保存到数据库
+        // 保存到数据库，并与当天已追加的巩固题一起返回
         self.recommendation_dao.insert_batch(day, &questions)?;
+        let questions = self.recommendation_dao.get_by_day(day)?.unwrap_or_default();
 
         Ok(DailyRecommendation { day, questions })
     }
@@ -141,136 +255,143 @@ impl<'a> RecommendationSystem<'a> {
         &self,
         show_score_detail: bool,
         show_exclusion_reason: bool,
-        subject_configs: &HashMap<String, SubjectConfig>,
-        per_subject_default_limit: u32,
-        new_question_guarantee_ratio: f64,
+        settings: &AppSettings,
     ) -> Result<Vec<PreviewRecommendationItem>, DbError> {
         let now = now_ts();
-        let all_questions = self.get_all_active_questions()?;
 
-        if all_questions.is_empty() {
+        // Step 1: 为每道题计算评分（复用现有逻辑）；薄弱知识点的前置关系评分与补推共用
+        let focus = self.prerequisite_focus(now)?;
+        let scored_questions = self.score_all_questions(now, show_score_detail, &focus)?;
+        self.rank_preview(scored_questions, &focus, now, show_exclusion_reason, settings)
+    }
+
+    /// 预览的排名部分：对已评分的题目应用手动干预、分池与补推，标记入选/落选状态。
+    /// 只在内存中进行，同一份评分可以按不同设置重复排名。
+    fn rank_preview(
+        &self,
+        scored_questions: Vec<RecommendedQuestion>,
+        focus: &HashMap<String, String>,
+        now: Timestamp,
+        show_exclusion_reason: bool,
+        settings: &AppSettings,
+    ) -> Result<Vec<PreviewRecommendationItem>, DbError> {
+        if scored_questions.is_empty() {
             return Ok(vec![]);
         }
 
-        let review_summaries = self.review_dao.get_all_error_rates()?;
-        let subject_key = "system.Subject";
-
-        // Step 1: 为每道题计算评分（复用现有逻辑）
-        let mut scored_questions: Vec<RecommendedQuestion> = Vec::new();
-
-        for question in all_questions {
-            let qid = i64::from(question.id.clone());
-
-            let (review_count, error_rate) = review_summaries
-                .get(&qid)
-                .map(|&(err_rate, cnt)| (cnt, Some(err_rate)))
-                .unwrap_or((0, None));
-
-            let detail = self.calculate_score(&question, now, review_count, error_rate);
-
-            let overdue_days = question.due_at
-                .map(|d| ((now.as_i64() - d.as_i64()) as f64 / DAY_SECONDS as f64).max(0.0))
-                .unwrap_or(0.0);
-
-            let last_result_str = question.last_result.map(|r| r.as_str().to_string());
-
-            let reason = Self::generate_reason(
-                review_count,
-                question.due_at.map(|d| d.as_i64()),
-                now.as_i64(),
-                &last_result_str,
-                error_rate,
-            );
+        // Step 2: 先应用手动干预，再按科目分组
+        let overrides = self.override_dao.list_by_day(Self::logical_day(now))?;
+        let (pinned, buried, scored_questions) = Self::apply_overrides(scored_questions, &overrides);
+        let variant_groups = question_link::variant_groups(self.conn)?;
+        let (scored_questions, held_back) = Self::hold_back_variants(scored_questions, &pinned, &variant_groups);
+        let cloze_groups = QuestionPartDao::new(self.conn).cloze_groups()?;
+        let (scored_questions, held_siblings) = Self::hold_back_variants(scored_questions, &pinned, &cloze_groups);
+        let weekday = LogicalDay::from_timestamp(now, &ClockConfig::default()).weekday();
+        let carry = self.carry_over_dao.list()?;
+        let quota = |subject: &str| Self::subject_quota(settings, subject, weekday, &carry);
+        let prerequisite_pool = Self::prerequisite_pool(&scored_questions, focus, |s| Self::pulls_allowed(&quota(s)));
+        let subject_groups = Self::group_by_subject(scored_questions);
 
-            let subject = self.meta_dao
-                .get_by_question_key(question.id.clone(), subject_key)
-                .ok()
-                .flatten()
-                .map(|m| m.value);
+        let mut results: Vec<PreviewRecommendationItem> = Vec::new();
 
-            let score_detail = if show_score_detail {
-                Some(detail)
+        for q in pinned {
+            results.push(PreviewRecommendationItem {
+                question_id: q.question_id,
+                name: q.name.unwrap_or_default(),
+                subject: q.subject,
+                score: q.score,
+                selected: true,
+                reason: q.reason.unwrap_or_default(),
+                exclusion_reason: vec![],
+                score_detail: q.score_detail,
+                subject_rank: 0,
+                subject_limit: 0,
+                by_score: false,
+                score_cutoff: None,
+            });
+        }
+        for q in buried {
+            let exclusion_reason: Vec<ExclusionCode> = if show_exclusion_reason {
+                vec![ExclusionCode::Buried]
             } else {
-                None
+                vec![]
             };
-
-            scored_questions.push(RecommendedQuestion {
-                question_id: qid,
-                name: question.name,
-                score: detail.final_score,
-                state: question.state.as_str().to_string(),
-                due_at: question.due_at.map(|t| t.as_i64()),
-                correct_streak: question.correct_streak,
-                wrong_count: question.wrong_count,
-                last_result: last_result_str,
-                error_rate,
-                subject,
-                reason,
-                score_detail,
-                review_count,
-                created_at: question.created_at.as_i64(),
+            results.push(PreviewRecommendationItem {
+                question_id: q.question_id,
+                name: q.name.unwrap_or_default(),
+                subject: q.subject,
+                score: q.score,
+                selected: false,
+                reason: q.reason.unwrap_or_default(),
+                exclusion_reason,
+                score_detail: q.score_detail,
+                subject_rank: 0,
+                subject_limit: 0,
+                by_score: false,
+                score_cutoff: None,
             });
         }
 
-        // Step 2: 按科目分组
-        let mut subject_groups: HashMap<String, Vec<RecommendedQuestion>> = HashMap::new();
-        for q in scored_questions {
-            let subject = q.subject.clone().unwrap_or_else(|| "未分类".to_string());
-            subject_groups.entry(subject).or_default().push(q);
+        let held_back = held_back
+            .into_iter()
+            .map(|(q, id)| (q, ExclusionCode::VariantScheduled { question_id: id }))
+            .chain(held_siblings.into_iter().map(|(q, id)| (q, ExclusionCode::SiblingScheduled { question_id: id })));
+        for (q, code) in held_back {
+            let exclusion_reason: Vec<ExclusionCode> = if show_exclusion_reason { vec![code] } else { vec![] };
+            results.push(PreviewRecommendationItem {
+                question_id: q.question_id,
+                name: q.name.unwrap_or_default(),
+                subject: q.subject,
+                score: q.score,
+                selected: false,
+                reason: q.reason.unwrap_or_default(),
+                exclusion_reason,
+                score_detail: q.score_detail,
+                subject_rank: 0,
+                subject_limit: 0,
+                by_score: false,
+                score_cutoff: None,
+            });
         }
 
         // Step 3: 每组内使用分池逻辑标记入选/落选
-        let mut results: Vec<PreviewRecommendationItem> = Vec::new();
 
         for (subject, questions) in subject_groups {
-            let (archived, limit) = if subject == "未分类" {
-                (false, per_subject_default_limit as usize)
-            } else {
-                match subject_configs.get(&subject) {
-                    None => (false, per_subject_default_limit as usize),
-                    Some(cfg) => {
-                        if cfg.archived {
-                            (true, 0)
+            let limit = match quota(&subject) {
+                Ok(limit) => limit,
+                Err(off) => {
+                    // 今日不参与推荐的科目：全部标记为落选
+                    for q in questions {
+                        let exclusion_reason: Vec<ExclusionCode> = if show_exclusion_reason {
+                            vec![off.exclusion()]
                         } else {
-                            match cfg.recommendation_limit {
-                                Some(0) => (true, 0),
-                                Some(n) => (false, n.max(1) as usize),
-                                None => (false, per_subject_default_limit as usize),
-                            }
-                        }
+                            vec![]
+                        };
+                        results.push(PreviewRecommendationItem {
+                            question_id: q.question_id,
+                            name: q.name.unwrap_or_default(),
+                            subject: q.subject,
+                            score: q.score,
+                            selected: false,
+                            reason: q.reason.unwrap_or_default(),
+                            exclusion_reason,
+                            score_detail: q.score_detail,
+                            subject_rank: 0,
+                            subject_limit: 0,
+                            by_score: false,
+                            score_cutoff: None,
+                        });
                     }
+                    continue;
                 }
             };
-
-            if archived {
-                // 已归档科目：全部标记为落选
-                for q in questions {
-                    let exclusion_reason: Vec<String> = if show_exclusion_reason {
-                        vec!["科目已归档".to_string()]
-                    } else {
-                        vec![]
-                    };
-                    results.push(PreviewRecommendationItem {
-                        question_id: q.question_id,
-                        name: q.name.unwrap_or_default(),
-                        subject: q.subject,
-                        score: q.score,
-                        selected: false,
-                        reason: q.reason.unwrap_or_default(),
-                        exclusion_reason,
-                        score_detail: q.score_detail,
-                        subject_rank: 0,
-                        subject_limit: 0,
-                    });
-                }
-                continue;
-            }
+            let kp_cap = Self::subject_kp_cap(&settings.subjects, &subject, settings.max_per_knowledge_point);
 
             // 分池
             let (mut new_questions, other_questions): (Vec<_>, Vec<_>) =
                 questions.into_iter().partition(|q| q.review_count == 0);
 
-            let guarantee = (limit as f64 * new_question_guarantee_ratio).ceil() as usize;
+            let guarantee = (limit as f64 * settings.new_question_guarantee_ratio).ceil() as usize;
 
             // Pool A: new questions sorted by created_at ascending
             new_questions.sort_by_key(|q| q.created_at);
@@ -279,21 +400,27 @@ impl<'a> RecommendationSystem<'a> {
             // Consume new_questions into pool_a items and remaining
             let mut new_iter = new_questions.into_iter();
             let mut new_rank = 0usize;
+            let mut kp_counts: HashMap<String, usize> = HashMap::new();
             // Phase A: push pool A selected items as results
             for _ in 0..take_new_count {
                 new_rank += 1;
                 if let Some(q) = new_iter.next() {
+                    for kp in &q.knowledge_points {
+                        *kp_counts.entry(kp.clone()).or_insert(0) += 1;
+                    }
                     results.push(PreviewRecommendationItem {
                         question_id: q.question_id,
                         name: q.name.unwrap_or_default(),
                         subject: q.subject,
                         score: q.score,
                         selected: true,
-                        reason: vec!["新题保送".to_string()],
+                        reason: vec![ReasonCode::NewGuaranteed],
                         exclusion_reason: vec![],
                         score_detail: q.score_detail,
                         subject_rank: new_rank,
                         subject_limit: take_new_count,
+                        by_score: false,
+                        score_cutoff: None,
                     });
                 }
             }
@@ -305,14 +432,20 @@ impl<'a> RecommendationSystem<'a> {
             let pool_b_limit = limit - take_new_count;
 
             // Collect pool B selected question_ids
-            let pool_b_selected_ids: std::collections::HashSet<i64> = pool_b
-                .iter()
-                .take(pool_b_limit)
-                .map(|q| q.question_id)
-                .collect();
+            let (pool_b_selected_ids, kp_capped) =
+                Self::select_pool_b(&pool_b, pool_b_limit, kp_cap, &mut kp_counts);
+            // 名额已满时，最后一道入选题的分数就是入选门槛
+            let score_cutoff = if pool_b_limit > 0 && pool_b_selected_ids.len() >= pool_b_limit {
+                pool_b
+                    .iter()
+                    .filter(|q| pool_b_selected_ids.contains(&q.question_id))
+                    .map(|q| q.score)
+                    .reduce(f64::min)
+            } else {
+                None
+            };
 
             // Build preview items: pass through pool_b in score-sorted order for rank
-            let total = pool_b.len();
             let mut b_rank = 0usize;
             for q in pool_b {
                 b_rank += 1;
@@ -320,10 +453,14 @@ impl<'a> RecommendationSystem<'a> {
                 // but new questions in pool B might have been pool A rejects
                 let selected = pool_b_selected_ids.contains(&q.question_id);
 
-                let reason: Vec<String> = q.reason.clone().unwrap_or_default();
-                let score_detail = q.score_detail;
-                let exclusion_reason: Vec<String> = if !selected && show_exclusion_reason {
-                    Self::generate_exclusion_reason(&q, now.as_i64(), b_rank)
+                let reason: Vec<ReasonCode> = q.reason.clone().unwrap_or_default();
+                let exclusion_reason: Vec<ExclusionCode> = if !selected && show_exclusion_reason {
+                    Self::generate_exclusion_reason(
+                        &q,
+                        now.as_i64(),
+                        b_rank,
+                        kp_capped.get(&q.question_id).map(|kp| kp.as_str()),
+                    )
                 } else {
                     vec![]
                 };
@@ -339,46 +476,442 @@ impl<'a> RecommendationSystem<'a> {
                     score_detail: q.score_detail,
                     subject_rank: b_rank,
                     subject_limit: limit,
+                    by_score: selected,
+                    score_cutoff,
                 });
             }
         }
 
+        // 补推的前置题改标为入选
+        let selected_ids: HashSet<i64> = results.iter().filter(|r| r.selected).map(|r| r.question_id).collect();
+        let pulled = Self::select_prerequisite_pulls(&prerequisite_pool, &selected_ids, focus, PREREQUISITE_PULL_LIMIT);
+        for item in results.iter_mut().filter(|r| pulled.contains(&r.question_id)) {
+            item.selected = true;
+            item.exclusion_reason.clear();
+        }
+
         // 整体按分数降序
         results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
 
         Ok(results)
     }
 
+    /// 单题解释：评分明细、同科排名、入选门槛、差距最大的因子，以及状态不变时预计入选的日期。
+    ///
+    /// 今天不是该科学习日时，排名与门槛按"假如今天上课"计算，便于判断下一个学习日能否入选。
+    /// 预测假设题目与同科门槛都保持今天的状态，只有遗忘风险、新鲜度和随机扰动随日期变化。
+    pub fn explain_question(
+        &self,
+        question_id: i64,
+        settings: &AppSettings,
+    ) -> Result<QuestionExplanation, DbError> {
+        let question = self
+            .question_dao
+            .get_by_id(QuestionId::from(question_id))?
+            .filter(|q| q.deleted_at.is_none())
+            .ok_or(DbError::NotFound)?;
+        let now = now_ts();
+
+        // 评分只做一次：排名在内存中进行，非学习日重新排名时沿用同一份评分
+        let focus = self.prerequisite_focus(now)?;
+        let scored = self.score_all_questions(now, true, &focus)?;
+        if !scored.iter().any(|q| q.question_id == question_id) {
+            // 不在评分池中：暂停的题
+            return self.explain_suspended(question, now);
+        }
+        let preview = self.rank_preview(scored.clone(), &focus, now, true, settings)?;
+        let item = preview
+            .iter()
+            .find(|i| i.question_id == question_id)
+            .cloned()
+            .ok_or(DbError::NotFound)?;
+
+        // 非学习日：放开该科学习日限制重新排一次
+        let subject = item.subject.clone().unwrap_or_else(|| UNCATEGORIZED.to_string());
+        let reopened;
+        let ranking: &[PreviewRecommendationItem] = if item.exclusion_reason.contains(&ExclusionCode::NotStudyDay) {
+            let mut open = settings.clone();
+            if let Some(cfg) = open.subjects.get_mut(&subject) {
+                cfg.study_days = None;
+            }
+            reopened = self.rank_preview(scored, &focus, now, false, &open)?;
+            &reopened
+        } else {
+            &preview
+        };
+        let mut same_subject: Vec<&PreviewRecommendationItem> =
+            ranking.iter().filter(|i| i.subject == item.subject).collect();
+        same_subject.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        let subject_rank = same_subject
+            .iter()
+            .position(|i| i.question_id == question_id)
+            .map(|pos| pos + 1);
+        let required_score = same_subject.iter().find_map(|i| i.score_cutoff);
+        let threshold = same_subject
+            .iter()
+            .filter(|i| i.by_score)
+            .min_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(std::cmp::Ordering::Equal))
+            .and_then(|i| i.score_detail);
+
+        let detail = match item.score_detail {
+            Some(detail) => detail,
+            None => self.score_detail_of(&question, now)?,
+        };
+        let main_deficit = if item.selected {
+            None
+        } else {
+            threshold.and_then(|t| Self::main_deficit(&detail, &t))
+        };
+
+        let projected_in_days = if item.selected {
+            Some(0)
+        } else {
+            self.project_entry_day(&question, &subject, required_score, now, settings)?
+        };
+        let projected_date = projected_in_days.map(|days| {
+            let ts = Timestamp(now.as_i64() + days * DAY_SECONDS);
+            LogicalDay::from_timestamp(ts, &ClockConfig::default()).to_string(&ClockConfig::default())
+        });
+
+        Ok(QuestionExplanation {
+            question_id,
+            name: question.name.clone(),
+            subject: item.subject,
+            selected: item.selected,
+            score: item.score,
+            score_detail: detail,
+            reason: item.reason,
+            exclusion_reason: item.exclusion_reason,
+            subject_rank,
+            subject_size: same_subject.len(),
+            required_score,
+            main_deficit,
+            projected_date,
+            projected_in_days,
+        })
+    }
+
+    /// 暂停的题：只给出评分明细，恢复复习前不会入选
+    fn explain_suspended(&self, question: Question, now: Timestamp) -> Result<QuestionExplanation, DbError> {
+        let qid = i64::from(question.id);
+        let detail = self.score_detail_of(&question, now)?;
+        let subject = self
+            .meta_dao
+            .get_by_question_key(question.id, "system.Subject")?
+            .map(|m| m.value);
+        Ok(QuestionExplanation {
+            question_id: qid,
+            name: question.name,
+            subject,
+            selected: false,
+            score: detail.final_score,
+            score_detail: detail,
+            reason: vec![],
+            exclusion_reason: vec![ExclusionCode::Suspended],
+            subject_rank: None,
+            subject_size: 0,
+            required_score: None,
+            main_deficit: None,
+            projected_date: None,
+            projected_in_days: None,
+        })
+    }
+
+    /// 按当前复习摘要计算单题评分明细
+    fn score_detail_of(&self, question: &Question, now: Timestamp) -> Result<ScoreDetail, DbError> {
+        let (review_count, error_rate, skip_count) = self.score_inputs(i64::from(question.id), now)?;
+        Ok(self.calculate_score(question, now, review_count, error_rate, skip_count))
+    }
+
+    /// 单题评分所需的统计量：(复习次数, 错误率, 近期跳过次数)
+    fn score_inputs(&self, question_id: i64, now: Timestamp) -> Result<(i64, Option<f64>, i64), DbError> {
+        let (review_count, error_rate) = self
+            .review_dao
+            .get_all_error_rates()?
+            .get(&question_id)
+            .map(|&(rate, cnt)| (cnt, Some(rate)))
+            .unwrap_or((0, None));
+        let skip_count = self
+            .skip_dao
+            .count_since(now.as_i64() - SKIP_WINDOW_DAYS * DAY_SECONDS)?
+            .get(&question_id)
+            .copied()
+            .unwrap_or(0);
+        Ok((review_count, error_rate, skip_count))
+    }
+
+    /// 与门槛题相比，倍数差距最大的评分因子（各因子相乘得到总分）
+    fn main_deficit(own: &ScoreDetail, threshold: &ScoreDetail) -> Option<ScoreFactor> {
+        let factors = [
+            (ScoreFactor::ForgetRisk, 1.0 + own.forget_risk, 1.0 + threshold.forget_risk),
+            (ScoreFactor::Freshness, own.freshness_bonus, threshold.freshness_bonus),
+            (ScoreFactor::LastWrong, own.last_wrong_bonus, threshold.last_wrong_bonus),
+            (ScoreFactor::ErrorRate, own.error_rate_bonus, threshold.error_rate_bonus),
+            (ScoreFactor::Skip, own.skip_factor, threshold.skip_factor),
+            (ScoreFactor::Randomness, own.randomness, threshold.randomness),
+        ];
+        factors
+            .iter()
+            .filter(|(_, own, _)| *own > 0.0)
+            .map(|&(factor, own, threshold)| (factor, threshold / own))
+            .filter(|&(_, gap)| gap > 1.0)
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(factor, _)| factor)
+    }
+
+    /// 状态不变时，第几天能进入每日推荐（从明天起逐日推算，最多 `EXPLAIN_PROJECTION_DAYS` 天）。
+    ///
+    /// 逐日考虑手动干预（置顶 / 搁置）和该科学习日；分数达到今天的门槛即视为入选，
+    /// 没有门槛（名额未满）时只要该科上课就能入选。
+    fn project_entry_day(
+        &self,
+        question: &Question,
+        subject: &str,
+        required_score: Option<f64>,
+        now: Timestamp,
+        settings: &AppSettings,
+    ) -> Result<Option<i64>, DbError> {
+        let qid = i64::from(question.id);
+        let (review_count, error_rate, skip_count) = self.score_inputs(qid, now)?;
+        let no_carry = HashMap::new();
+
+        for days in 1..=EXPLAIN_PROJECTION_DAYS {
+            let at = Timestamp(now.as_i64() + days * DAY_SECONDS);
+            let day = Self::logical_day(at);
+            match self.override_dao.list_by_day(day)?.get(&qid) {
+                Some(OverrideAction::PIN) => return Ok(Some(days)),
+                Some(OverrideAction::BURY) => continue,
+                None => {}
+            }
+            match Self::subject_quota(settings, subject, LogicalDay(day as i32).weekday(), &no_carry) {
+                Ok(_) => {}
+                Err(SubjectOffReason::NotStudyDay { .. }) => continue,
+                // 归档或题数为 0 的科目不会恢复
+                Err(_) => return Ok(None),
+            }
+            let score = self.calculate_score(question, at, review_count, error_rate, skip_count).final_score;
+            let enters = match required_score {
+                Some(required) => score >= required,
+                None => true,
+            };
+            if enters {
+                return Ok(Some(days));
+            }
+        }
+        Ok(None)
+    }
+
     /// 复习会话用：评分每道题 + 产出 reason 词条，但**不写 recommendation 表**。
     ///
     /// 走 `generate_recommendation` 同样的分池 / 评分 / 标签逻辑；
     /// `limit` 是总上限（None = 不截断，每 subject 用 `per_subject_daily_limit`）。
+    /// 截断按分数进行，之后再按 `order`（None = 设置中的默认顺序）重排出题顺序；
+    /// 随机顺序的 `seed` 缺省取当天逻辑日，同一天内重进会话顺序不变。
     pub fn recommend_for_review(
         &self,
         limit: Option<usize>,
         subject: Option<&str>,
+        order: Option<ReviewOrder>,
+        seed: Option<u64>,
     ) -> Result<Vec<RecommendedQuestion>, DbError> {
-        let settings = crate::app::config::load_settings();
-        let mut qs = self.generate_recommendation(
-            now_ts(),
-            &settings.subjects,
-            settings.per_subject_daily_limit,
-            settings.new_question_guarantee_ratio,
-        )?;
+        let settings = crate::server::subject::load_settings(self.conn)?;
+        let now = now_ts();
+        let mut qs = self.generate_recommendation(now, &settings)?;
         if let Some(target) = subject.filter(|s| !s.is_empty()) {
             qs.retain(|q| q.subject.as_deref() == Some(target));
         }
         if let Some(n) = limit {
             qs.truncate(n);
         }
-        Ok(qs)
+
+        let order = order.unwrap_or(settings.review_order);
+        let seed = seed.unwrap_or_else(|| LogicalDay::from_timestamp(now, &ClockConfig::default()).0 as u64);
+        Ok(review_order::arrange(qs, order, seed, |q| OrderKey {
+            subject: q.subject.clone(),
+            error_rate: q.error_rate.unwrap_or(0.0),
+            wrong_count: q.wrong_count,
+        }))
+    }
+
+    /// 手动置顶：题目在 `days_ahead` 天后（0 = 今天）必定出现在推荐中。
+    ///
+    /// 今天的推荐已生成时直接并入；不存在、暂停或已删除的题目返回 `DbError::NotFound`，
+    /// 置顶到以后某天时也一样，免得留下到期时不会生效的干预。
+    pub fn pin_question(&self, question_id: i64, days_ahead: i64) -> Result<(), DbError> {
+        let question = self
+            .question_dao
+            .get_by_id(QuestionId::from(question_id))?
+            .ok_or(DbError::NotFound)?;
+        if question.deleted_at.is_some() || question.state == QuestionState::SUSPENDED {
+            return Err(DbError::NotFound);
+        }
+
+        let now = now_ts();
+        let today = Self::logical_day(now);
+        let day = today + days_ahead;
+
+        if day == today && self.recommendation_dao.count_by_tier(today, RecommendationTier::DAILY)? > 0 {
+            let mut q = self
+                .score_all_questions(now, false, &self.prerequisite_focus(now)?)?
+                .into_iter()
+                .find(|q| q.question_id == question_id)
+                .ok_or(DbError::NotFound)?;
+            q.reason = Some(vec![ReasonCode::Pinned]);
+            self.recommendation_dao.insert_one(today, &q)?;
+        }
+
+        self.override_dao.set(day, question_id, OverrideAction::PIN, now.as_i64())
+    }
+
+    /// 今日搁置：题目今天不再推荐，不产生复习记录
+    pub fn bury_question(&self, question_id: i64) -> Result<(), DbError> {
+        let now = now_ts();
+        let today = Self::logical_day(now);
+        self.override_dao.set(today, question_id, OverrideAction::BURY, now.as_i64())?;
+        self.recommendation_dao.delete_one(today, question_id)
+    }
+
+    /// 推迟 `days` 天：从今天起连续 `days` 天不推荐，并把到期时间顺延 `days` 天。
+    ///
+    /// 不写复习记录，连对次数等复习字段保持不变；新题没有到期时间，只做搁置。
+    pub fn postpone_question(&self, question_id: i64, days: i64) -> Result<(), DbError> {
+        let now = now_ts();
+        let today = Self::logical_day(now);
+        let qid = QuestionId::from(question_id);
+
+        let question = self.question_dao.get_by_id(qid)?.ok_or(DbError::NotFound)?;
+
+        for offset in 0..days {
+            self.override_dao.set(today + offset, question_id, OverrideAction::BURY, now.as_i64())?;
+        }
+        self.recommendation_dao.delete_one(today, question_id)?;
+
+        if let Some(due) = question.due_at {
+            let new_due = Timestamp(due.as_i64().max(now.as_i64()) + days * DAY_SECONDS);
+            self.question_dao.update_review_fields(
+                qid,
+                question.last_review_at,
+                question.last_result.as_ref().map(|r| r.as_str()),
+                question.correct_streak,
+                question.wrong_count,
+                Some(new_due),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 取消 `days_ahead` 天后（0 = 今天）的手动干预。
+    ///
+    /// 今天已生成的推荐中，取消置顶会移出该题；取消搁置不会立即补回，重新生成今日推荐后生效。
+    pub fn clear_override(&self, question_id: i64, days_ahead: i64) -> Result<(), DbError> {
+        let today = Self::logical_day(now_ts());
+        let day = today + days_ahead;
+
+        let was_pinned = self.override_dao.list_by_day(day)?.get(&question_id) == Some(&OverrideAction::PIN);
+        self.override_dao.delete(day, question_id)?;
+        if day == today && was_pinned {
+            self.recommendation_dao.delete_one(today, question_id)?;
+        }
+        Ok(())
+    }
+
+    /// 答错后追加巩固题：把 `question_id` 的变式题和与它共享知识点的其他题目取前
+    /// `remedial_follow_up_limit` 道（变式题优先，其余按分数），作为 REMEDIAL 层并入今天的推荐。
+    /// 巩固题不受"变式题同一天只排一道"的限制。
+    ///
+    /// 已在今日推荐中、今天已复习过、今天被搁置或所属科目已归档的题目不会追加。
+    /// 返回本次追加的题目 ID。
+    pub fn queue_remedial_follow_ups(
+        &self,
+        question_id: i64,
+        now: Timestamp,
+        settings: &AppSettings,
+    ) -> Result<Vec<i64>, DbError> {
+        let limit = settings.remedial_follow_up_limit as usize;
+        if limit == 0 {
+            return Ok(vec![]);
+        }
+
+        let today = Self::logical_day(now);
+        let scored = self.score_all_questions(now, false, &self.prerequisite_focus(now)?)?;
+        let knowledge_points: HashSet<String> = match scored.iter().find(|q| q.question_id == question_id) {
+            Some(q) => q.knowledge_points.iter().cloned().collect(),
+            None => return Ok(vec![]),
+        };
+        let groups = question_link::variant_groups(self.conn)?;
+        let variants: HashSet<i64> = match groups.get(&question_id) {
+            Some(group) => groups.iter().filter(|(_, g)| *g == group).map(|(&id, _)| id).collect(),
+            None => HashSet::new(),
+        };
+        if knowledge_points.is_empty() && variants.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // 排除：今日已推荐 + 今天已复习 + 今天被搁置
+        let mut excluded = self.recommendation_dao.question_ids_by_day(today)?;
+        let (day_start, day_end) = range_of_day(LogicalDay(today as i32), &ClockConfig::default());
+        for review in crate::db::select_reviews_by_time_range(self.conn, day_start.0, day_end.0 - 1)? {
+            excluded.insert(review.question_id);
+        }
+        for (qid, action) in self.override_dao.list_by_day(today)? {
+            if action == OverrideAction::BURY {
+                excluded.insert(qid);
+            }
+        }
+        excluded.insert(question_id);
+        // 同一笔记的其他填空卡不在同一天出现
+        let cloze_groups = QuestionPartDao::new(self.conn).cloze_groups()?;
+        if let Some(note) = cloze_groups.get(&question_id) {
+            excluded.extend(cloze_groups.iter().filter(|(_, n)| *n == note).map(|(&id, _)| id));
+        }
+
+        let candidates: Vec<RecommendedQuestion> = scored
+            .into_iter()
+            .filter(|q| {
+                let subject = q.subject.as_deref().unwrap_or(UNCATEGORIZED);
+                !settings.subjects.get(subject).is_some_and(|c| c.archived)
+            })
+            .collect();
+        let follow_ups = Self::select_remedial(candidates, &knowledge_points, &variants, &excluded, limit);
+        self.recommendation_dao.insert_remedial(today, &follow_ups)?;
+
+        Ok(follow_ups.iter().map(|q| q.question_id).collect())
+    }
+
+    /// 从候选中挑出属于 `variants` 或与 `knowledge_points` 有交集、未被排除的题目，
+    /// 变式题在前、同类按分数降序，取前 `limit` 道
+    fn select_remedial(
+        mut candidates: Vec<RecommendedQuestion>,
+        knowledge_points: &HashSet<String>,
+        variants: &HashSet<i64>,
+        excluded: &HashSet<i64>,
+        limit: usize,
+    ) -> Vec<RecommendedQuestion> {
+        candidates.retain(|q| {
+            !excluded.contains(&q.question_id)
+                && (variants.contains(&q.question_id)
+                    || q.knowledge_points.iter().any(|kp| knowledge_points.contains(kp)))
+        });
+        candidates.sort_by(|a, b| {
+            variants
+                .contains(&b.question_id)
+                .cmp(&variants.contains(&a.question_id))
+                .then(b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal))
+        });
+        candidates.truncate(limit);
+        for q in candidates.iter_mut() {
+            q.tier = RecommendationTier::REMEDIAL.as_str().to_string();
+            q.reason = Some(vec![ReasonCode::Remedial]);
+        }
+        candidates
     }
 
     /// 获取推荐统计概览
     pub fn get_recommendation_stats(
         &self,
-        subject_configs: &HashMap<String, SubjectConfig>,
+        settings: &AppSettings,
     ) -> Result<RecommendationStats, DbError> {
+        let subject_configs = &settings.subjects;
         let all_questions = self.get_all_active_questions()?;
         let review_summaries = self.review_dao.get_all_error_rates()?;
         let subject_key = "system.Subject";
@@ -389,6 +922,7 @@ impl<'a> RecommendationSystem<'a> {
         let mut participating = 0usize;
         let mut new_count = 0usize;
         let mut pending_review = 0usize;
+        let mut known_subjects: HashSet<String> = subject_configs.keys().cloned().collect();
 
         // 收集已归档科目
         let mut archived_subjects: Vec<String> = Vec::new();
@@ -412,6 +946,9 @@ impl<'a> RecommendationSystem<'a> {
                 .ok()
                 .flatten()
                 .map(|m| m.value);
+            if let Some(s) = &subject {
+                known_subjects.insert(s.clone());
+            }
 
             let is_archived = subject
                 .as_ref()
@@ -437,6 +974,25 @@ impl<'a> RecommendationSystem<'a> {
             .get_by_day(day)?
             .map(|qs| qs.len())
             .unwrap_or(0);
+        let reason_counts = self
+            .recommendation_dao
+            .count_reason_codes(day)?
+            .into_iter()
+            .map(|(code, count)| ReasonCodeCount { code, count })
+            .collect();
+
+        // 今日休息的科目
+        let weekday = LogicalDay(day as i32).weekday();
+        let carry = self.carry_over_dao.list()?;
+        let mut off_subjects: Vec<OffSubject> = known_subjects
+            .into_iter()
+            .filter_map(|subject| {
+                Self::subject_quota(settings, &subject, weekday, &carry)
+                    .err()
+                    .map(|reason| OffSubject { subject, reason })
+            })
+            .collect();
+        off_subjects.sort_by(|a, b| a.subject.cmp(&b.subject));
 
         Ok(RecommendationStats {
             total_questions,
@@ -445,6 +1001,8 @@ impl<'a> RecommendationSystem<'a> {
             recommended_count,
             new_questions: new_count,
             pending_review,
+            reason_counts,
+            off_subjects,
         })
     }
 
@@ -452,9 +1010,108 @@ impl<'a> RecommendationSystem<'a> {
     fn generate_recommendation(
         &self,
         now: Timestamp,
-        subject_configs: &HashMap<String, SubjectConfig>,
-        per_subject_default_limit: u32,
-        new_question_guarantee_ratio: f64,
+        settings: &AppSettings,
+    ) -> Result<Vec<RecommendedQuestion>, DbError> {
+        // 需要补前置的知识点：prerequisite → 薄弱知识点，推荐理由与补推共用
+        let focus = self.prerequisite_focus(now)?;
+
+        // 为每道题计算推荐分数并获取科目（仅 debug 构建填充评分明细）
+        let scored_questions = self.score_all_questions(now, cfg!(debug_assertions), &focus)?;
+
+        if scored_questions.is_empty() {
+            return Ok(vec![]);
+        }
+
+        // 手动干预先于分池：搁置的题直接剔除，置顶的题不占科目名额
+        let overrides = self.override_dao.list_by_day(Self::logical_day(now))?;
+        let (pinned, _, scored_questions) = Self::apply_overrides(scored_questions, &overrides);
+
+        // 同一组变式题、同一笔记的填空卡当天只排一道：置顶的优先，否则留分数最高的
+        let variant_groups = question_link::variant_groups(self.conn)?;
+        let (scored_questions, _) = Self::hold_back_variants(scored_questions, &pinned, &variant_groups);
+        let cloze_groups = QuestionPartDao::new(self.conn).cloze_groups()?;
+        let (scored_questions, _) = Self::hold_back_variants(scored_questions, &pinned, &cloze_groups);
+
+        // 今天是星期几决定哪些科目上课，顺延名额叠加到当天上限
+        let weekday = LogicalDay::from_timestamp(now, &ClockConfig::default()).weekday();
+        let carry = self.carry_over_dao.list()?;
+        let quota = |subject: &str| Self::subject_quota(settings, subject, weekday, &carry);
+
+        // 薄弱知识点的前置题候选（分池之后再补推）
+        let prerequisite_pool = Self::prerequisite_pool(&scored_questions, &focus, |s| Self::pulls_allowed(&quota(s)));
+
+        // 按科目分组（无科目的归入 "未分类"）
+        let subject_groups = Self::group_by_subject(scored_questions);
+
+        let mut final_questions: Vec<RecommendedQuestion> = pinned;
+
+        for (subject, questions) in subject_groups {
+            // 按科目配置确定该科今日题数上限；休息的科目跳过
+            let limit = match quota(&subject) {
+                Ok(limit) => limit,
+                Err(_) => continue,
+            };
+            let kp_cap = Self::subject_kp_cap(&settings.subjects, &subject, settings.max_per_knowledge_point);
+
+            // 分池
+            let (mut new_questions, other_questions): (Vec<_>, Vec<_>) =
+                questions.into_iter().partition(|q| q.review_count == 0);
+
+            let guarantee = (limit as f64 * settings.new_question_guarantee_ratio).ceil() as usize;
+
+            // 池A：新题按创建时间升序，取前 guarantee 题，标记"新题保送"
+            new_questions.sort_by_key(|q| q.created_at);
+            let take_new_count = new_questions.len().min(guarantee);
+
+            let mut selected: Vec<RecommendedQuestion> = Vec::new();
+            let mut kp_counts: HashMap<String, usize> = HashMap::new();
+            let mut new_iter = new_questions.into_iter();
+            for _ in 0..take_new_count {
+                if let Some(mut q) = new_iter.next() {
+                    for kp in &q.knowledge_points {
+                        *kp_counts.entry(kp.clone()).or_insert(0) += 1;
+                    }
+                    q.reason = Some(vec![ReasonCode::NewGuaranteed]);
+                    selected.push(q);
+                }
+            }
+            let new_remaining: Vec<RecommendedQuestion> = new_iter.collect();
+
+            // 池B：池A落选的新题 + 其他题，按分数降序；知识点触顶的题让位给下一题
+            let mut pool_b: Vec<RecommendedQuestion> = Vec::new();
+            pool_b.extend(new_remaining);
+            pool_b.extend(other_questions);
+            pool_b.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+            let remaining_slots = limit - selected.len();
+            let (pool_b_selected_ids, _) =
+                Self::select_pool_b(&pool_b, remaining_slots, kp_cap, &mut kp_counts);
+            pool_b.retain(|q| pool_b_selected_ids.contains(&q.question_id));
+            selected.extend(pool_b);
+
+            final_questions.extend(selected);
+        }
+
+        // 补推薄弱知识点的前置题，不占科目名额
+        let selected_ids: HashSet<i64> = final_questions.iter().map(|q| q.question_id).collect();
+        let pulled = Self::select_prerequisite_pulls(&prerequisite_pool, &selected_ids, &focus, PREREQUISITE_PULL_LIMIT);
+        final_questions.extend(prerequisite_pool.into_iter().filter(|q| pulled.contains(&q.question_id)));
+
+        // 整体按分数排序
+        final_questions.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+        Ok(final_questions)
+    }
+
+    /// 对全部参与推荐的题目评分，并附上科目、知识点与推荐理由。
+    ///
+    /// `keep_detail` 为 false 时不保留 `score_detail`；`prerequisite_focus` 为 `prerequisite_focus()` 的结果，
+    /// 用于给前置知识点的题目标注推荐理由。
+    fn score_all_questions(
+        &self,
+        now: Timestamp,
+        keep_detail: bool,
+        prerequisite_focus: &HashMap<String, String>,
     ) -> Result<Vec<RecommendedQuestion>, DbError> {
         // 获取所有未删除的题目
         let all_questions = self.get_all_active_questions()?;
@@ -466,10 +1123,18 @@ impl<'a> RecommendationSystem<'a> {
         // 获取所有题目的复习摘要（错误率 + 复习次数）
         let review_summaries = self.review_dao.get_all_error_rates()?;
 
+        // 近期跳过次数
+        let skip_counts = self.skip_dao.count_since(now.as_i64() - SKIP_WINDOW_DAYS * DAY_SECONDS)?;
+
         // 科目元信息key
         let subject_key = "system.Subject";
 
-        // 为每道题计算推荐分数并获取科目
+        // 批量取知识点
+        let qids: Vec<i64> = all_questions.iter().map(|q| i64::from(q.id.clone())).collect();
+        let mut kp_map = self
+            .meta_dao
+            .list_values_by_question_ids(&qids, "system.KnowledgePoint")?;
+
         let mut scored_questions: Vec<RecommendedQuestion> = Vec::new();
 
         for question in all_questions {
@@ -481,16 +1146,18 @@ impl<'a> RecommendationSystem<'a> {
                 .map(|&(err_rate, cnt)| (cnt, Some(err_rate)))
                 .unwrap_or((0, None));
 
-            let detail = self.calculate_score(&question, now, review_count, error_rate);
-
-            // 计算超期天数
-            let overdue_days = question.due_at
-                .map(|d| ((now.as_i64() - d.as_i64()) as f64 / DAY_SECONDS as f64).max(0.0))
-                .unwrap_or(0.0);
+            let skip_count = skip_counts.get(&qid).copied().unwrap_or(0);
+            let detail = self.calculate_score(&question, now, review_count, error_rate, skip_count);
 
             // 提取 last_result 字符串
             let last_result_str = question.last_result.map(|r| r.as_str().to_string());
 
+            let knowledge_points = kp_map.remove(&qid).unwrap_or_default();
+            let prerequisite_for = knowledge_points
+                .iter()
+                .find_map(|kp| prerequisite_focus.get(kp))
+                .map(|weak| weak.as_str());
+
             // 生成推荐理由
             let reason = Self::generate_reason(
                 review_count,
@@ -498,6 +1165,7 @@ impl<'a> RecommendationSystem<'a> {
                 now.as_i64(),
                 &last_result_str,
                 error_rate,
+                prerequisite_for,
             );
 
             // 获取科目
@@ -507,13 +1175,6 @@ impl<'a> RecommendationSystem<'a> {
                 .flatten()
                 .map(|m| m.value);
 
-            // 仅 debug 构建填充评分明细
-            let score_detail = if cfg!(debug_assertions) {
-                Some(detail)
-            } else {
-                None
-            };
-
             scored_questions.push(RecommendedQuestion {
                 question_id: qid,
                 name: question.name,
@@ -525,93 +1186,287 @@ impl<'a> RecommendationSystem<'a> {
                 last_result: last_result_str,
                 error_rate,
                 subject,
+                tier: RecommendationTier::DAILY.as_str().to_string(),
                 reason,
-                score_detail,
+                score_detail: if keep_detail { Some(detail) } else { None },
                 review_count,
                 created_at: question.created_at.as_i64(),
+                knowledge_points,
             });
         }
 
-        // 按科目分组（无科目的归入 "未分类"）
+        Ok(scored_questions)
+    }
+
+    /// 按科目分组（无科目的归入 "未分类"）
+    fn group_by_subject(
+        questions: Vec<RecommendedQuestion>,
+    ) -> HashMap<String, Vec<RecommendedQuestion>> {
         let mut subject_groups: HashMap<String, Vec<RecommendedQuestion>> = HashMap::new();
+        for q in questions {
+            let subject = q.subject.clone().unwrap_or_else(|| UNCATEGORIZED.to_string());
+            subject_groups.entry(subject).or_default().push(q);
+        }
+        subject_groups
+    }
 
-        for q in scored_questions {
-            let subject = q.subject.clone().unwrap_or_else(|| "未分类".to_string());
-            subject_groups.entry(subject).or_insert_with(Vec::new).push(q);
+    /// 按当天的手动干预拆分题目：返回 (置顶, 搁置, 其余)。置顶题的理由替换为"手动置顶"。
+    fn apply_overrides(
+        questions: Vec<RecommendedQuestion>,
+        overrides: &HashMap<i64, OverrideAction>,
+    ) -> (Vec<RecommendedQuestion>, Vec<RecommendedQuestion>, Vec<RecommendedQuestion>) {
+        let mut pinned = Vec::new();
+        let mut buried = Vec::new();
+        let mut rest = Vec::new();
+        for mut q in questions {
+            match overrides.get(&q.question_id) {
+                Some(OverrideAction::PIN) => {
+                    q.reason = Some(vec![ReasonCode::Pinned]);
+                    pinned.push(q);
+                }
+                Some(OverrideAction::BURY) => buried.push(q),
+                None => rest.push(q),
+            }
         }
+        (pinned, buried, rest)
+    }
 
-        let mut final_questions: Vec<RecommendedQuestion> = Vec::new();
+    /// 同一组题目（变式题、同一笔记的填空卡；`groups`：题目 ID → 组号）只留一道参与当天推荐：
+    /// 组内已有置顶题时全部让位给置顶题，否则保留分数最高的一道。
+    /// 返回 (参与推荐的题, [(让位的题, 代替它排进今天的题)])。
+    fn hold_back_variants(
+        questions: Vec<RecommendedQuestion>,
+        pinned: &[RecommendedQuestion],
+        groups: &HashMap<i64, i64>,
+    ) -> (Vec<RecommendedQuestion>, Vec<(RecommendedQuestion, i64)>) {
+        if groups.is_empty() {
+            return (questions, vec![]);
+        }
+        let mut chosen: HashMap<i64, i64> = HashMap::new();
+        for q in pinned {
+            if let Some(&group) = groups.get(&q.question_id) {
+                chosen.entry(group).or_insert(q.question_id);
+            }
+        }
+        let mut by_score: Vec<&RecommendedQuestion> = questions.iter().collect();
+        by_score.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        for q in by_score {
+            if let Some(&group) = groups.get(&q.question_id) {
+                chosen.entry(group).or_insert(q.question_id);
+            }
+        }
 
-        for (subject, questions) in subject_groups {
-            // 按科目配置确定该科题数上限
-            let limit = if subject == "未分类" {
-                per_subject_default_limit
-            } else {
-                match subject_configs.get(&subject) {
-                    None => per_subject_default_limit,
-                    Some(cfg) => {
-                        if cfg.archived {
-                            continue;
-                        }
-                        match cfg.recommendation_limit {
-                            Some(0) => continue,
-                            Some(n) => n.max(1),
-                            None => per_subject_default_limit,
-                        }
-                    }
-                }
-            } as usize;
+        let mut kept = Vec::new();
+        let mut held_back = Vec::new();
+        for q in questions {
+            match groups.get(&q.question_id).map(|group| chosen[group]) {
+                Some(scheduled) if scheduled != q.question_id => held_back.push((q, scheduled)),
+                _ => kept.push(q),
+            }
+        }
+        (kept, held_back)
+    }
 
-            // 分池
-            let (mut new_questions, other_questions): (Vec<_>, Vec<_>) =
-                questions.into_iter().partition(|q| q.review_count == 0);
+    /// 时间戳所在的逻辑日
+    fn logical_day(now: Timestamp) -> i64 {
+        LogicalDay::from_timestamp(now, &ClockConfig::default()).0 as i64
+    }
 
-            let guarantee = (limit as f64 * new_question_guarantee_ratio).ceil() as usize;
+    /// 近期薄弱知识点的补推目标：最薄弱的前置知识点 → 薄弱知识点
+    fn prerequisite_focus(&self, now: Timestamp) -> Result<HashMap<String, String>, DbError> {
+        let graph = KnowledgeGraph::load(self.conn)?;
+        if graph.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let since = now.as_i64() - PREREQUISITE_WINDOW_DAYS * DAY_SECONDS;
+        let error_rates = self.review_dao.get_knowledge_point_error_rates(since)?;
+        Ok(graph.prerequisite_focus(&error_rates, PREREQUISITE_ERROR_THRESHOLD, PREREQUISITE_MIN_REVIEWS))
+    }
 
-            // 池A：新题按创建时间升序，取前 guarantee 题，标记"新题保送"
-            new_questions.sort_by_key(|q| q.created_at);
-            let take_new_count = new_questions.len().min(guarantee);
+    /// 补推前置题：对每个需要补的前置知识点，从未入选的题中按分数取前 `limit` 道
+    fn select_prerequisite_pulls(
+        pool: &[RecommendedQuestion],
+        selected: &HashSet<i64>,
+        focus: &HashMap<String, String>,
+        limit: usize,
+    ) -> HashSet<i64> {
+        let mut prerequisites: Vec<&String> = focus.keys().collect();
+        prerequisites.sort();
+
+        let mut pulled = HashSet::new();
+        for prereq in prerequisites {
+            let mut candidates: Vec<&RecommendedQuestion> = pool
+                .iter()
+                .filter(|q| !selected.contains(&q.question_id) && !pulled.contains(&q.question_id))
+                .filter(|q| q.knowledge_points.contains(prereq))
+                .collect();
+            candidates.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+            pulled.extend(candidates.into_iter().take(limit).map(|q| q.question_id));
+        }
+        pulled
+    }
 
-            let mut selected: Vec<RecommendedQuestion> = Vec::new();
-            let mut new_iter = new_questions.into_iter();
-            for _ in 0..take_new_count {
-                if let Some(mut q) = new_iter.next() {
-                    q.reason = Some(vec!["新题保送".to_string()]);
-                    selected.push(q);
-                }
+    /// 可参与前置补推的题：属于某个补推前置、且 `subject_allowed` 接受其科目
+    fn prerequisite_pool(
+        questions: &[RecommendedQuestion],
+        focus: &HashMap<String, String>,
+        subject_allowed: impl Fn(&str) -> bool,
+    ) -> Vec<RecommendedQuestion> {
+        questions
+            .iter()
+            .filter(|q| q.knowledge_points.iter().any(|kp| focus.contains_key(kp)))
+            .filter(|q| subject_allowed(q.subject.as_deref().unwrap_or(UNCATEGORIZED)))
+            .cloned()
+            .collect()
+    }
+
+    /// 前置补推不占名额，题数为 0 的科目仍可补推；归档或非学习日的科目不补
+    fn pulls_allowed(quota: &Result<usize, SubjectOffReason>) -> bool {
+        !matches!(quota, Err(SubjectOffReason::Archived) | Err(SubjectOffReason::NotStudyDay { .. }))
+    }
+
+    /// 该科今日题数上限：基础上限 + 顺延名额（仅开启顺延的科目）。
+    ///
+    /// 依次检查归档、学习日、题数为 0，返回今日休息的原因；未分类题目不受科目配置约束。
+    fn subject_quota(
+        settings: &AppSettings,
+        subject: &str,
+        weekday: u32,
+        carry: &HashMap<String, i64>,
+    ) -> Result<usize, SubjectOffReason> {
+        if subject == UNCATEGORIZED {
+            return Ok(settings.per_subject_daily_limit as usize);
+        }
+        if let Some(cfg) = settings.subjects.get(subject) {
+            if cfg.archived {
+                return Err(SubjectOffReason::Archived);
             }
-            let new_remaining: Vec<RecommendedQuestion> = new_iter.collect();
+            if !cfg.studies_on(weekday) {
+                let study_days = cfg.study_days.clone().unwrap_or_default();
+                return Err(SubjectOffReason::NotStudyDay { study_days });
+            }
+        }
+        let limit = Self::subject_limit(&settings.subjects, subject, settings.per_subject_daily_limit)
+            .ok_or(SubjectOffReason::NoQuota)?;
+        let carried = match settings.subjects.get(subject) {
+            Some(cfg) if cfg.carry_over_unused => carry.get(subject).copied().unwrap_or(0).max(0) as usize,
+            _ => 0,
+        };
+        Ok(limit + carried)
+    }
 
-            // 池B：池A落选的新题 + 其他题，按分数降序
-            let mut pool_b: Vec<RecommendedQuestion> = Vec::new();
-            pool_b.extend(new_remaining);
-            pool_b.extend(other_questions);
-            pool_b.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    /// 结算上一个推荐日各科未复习完的名额。
+    ///
+    /// 开启顺延的科目记为顺延名额（不超过该科基础上限，避免越滚越多），其余科目清零；
+    /// 上一个推荐日没有该科题目（例如非学习日）时保留原顺延名额，留给下一个学习日。
+    fn update_carry_over(&self, today: i64, settings: &AppSettings) -> Result<(), DbError> {
+        let prev = match self.recommendation_dao.latest_day_before(today)? {
+            Some(day) => day,
+            None => return Ok(()),
+        };
+        let (start, end) = range_of_day(LogicalDay(prev as i32), &ClockConfig::default());
+        let progress = self.recommendation_dao.subject_progress(prev, start.as_i64(), end.as_i64())?;
 
-            let remaining_slots = limit - selected.len();
-            pool_b.truncate(remaining_slots);
-            selected.extend(pool_b);
+        for (subject, (recommended, reviewed)) in progress {
+            let subject = match subject {
+                Some(subject) => subject,
+                None => continue,
+            };
+            match settings.subjects.get(&subject) {
+                Some(cfg) if cfg.carry_over_unused => {
+                    let base = Self::subject_limit(&settings.subjects, &subject, settings.per_subject_daily_limit)
+                        .unwrap_or(0) as i64;
+                    let unused = (recommended - reviewed).clamp(0, base);
+                    self.carry_over_dao.set(&subject, unused, today)?;
+                }
+                _ => self.carry_over_dao.delete(&subject)?,
+            }
+        }
+        Ok(())
+    }
 
-            final_questions.extend(selected);
+    /// 该科每日题数上限；科目已归档或上限为 0 时返回 None（不参与推荐）
+    fn subject_limit(
+        subject_configs: &HashMap<String, SubjectConfig>,
+        subject: &str,
+        per_subject_default_limit: u32,
+    ) -> Option<usize> {
+        if subject == UNCATEGORIZED {
+            return Some(per_subject_default_limit as usize);
         }
+        match subject_configs.get(subject) {
+            None => Some(per_subject_default_limit as usize),
+            Some(cfg) if cfg.archived => None,
+            Some(cfg) => match cfg.recommendation_limit {
+                Some(0) => None,
+                Some(n) => Some(n.max(1) as usize),
+                None => Some(per_subject_default_limit as usize),
+            },
+        }
+    }
 
-        // 整体按分数排序
-        final_questions.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    /// 该科单个知识点每日入选上限（0 = 不限制）
+    fn subject_kp_cap(
+        subject_configs: &HashMap<String, SubjectConfig>,
+        subject: &str,
+        default_cap: u32,
+    ) -> usize {
+        subject_configs
+            .get(subject)
+            .and_then(|cfg| cfg.max_per_knowledge_point)
+            .unwrap_or(default_cap) as usize
+    }
 
-        Ok(final_questions)
+    /// 池B 选题：按分数从高到低依次入选，直到填满 `slots`。
+    ///
+    /// 题目的任一知识点当日入选数已达 `kp_cap` 时跳过该题，名额顺延给下一题；
+    /// `kp_cap = 0` 不限制，无知识点的题不受约束。`kp_counts` 需预先计入池A 的入选题。
+    ///
+    /// 返回 (入选题目 id, 因知识点触顶被跳过的题目 id → 触顶的知识点)。
+    fn select_pool_b(
+        pool_b: &[RecommendedQuestion],
+        slots: usize,
+        kp_cap: usize,
+        kp_counts: &mut HashMap<String, usize>,
+    ) -> (HashSet<i64>, HashMap<i64, String>) {
+        let mut selected: HashSet<i64> = HashSet::new();
+        let mut capped: HashMap<i64, String> = HashMap::new();
+
+        for q in pool_b {
+            if selected.len() >= slots {
+                break;
+            }
+            if kp_cap > 0 {
+                let full = q
+                    .knowledge_points
+                    .iter()
+                    .find(|kp| kp_counts.get(*kp).copied().unwrap_or(0) >= kp_cap);
+                if let Some(kp) = full {
+                    capped.insert(q.question_id, kp.clone());
+                    continue;
+                }
+            }
+            for kp in &q.knowledge_points {
+                *kp_counts.entry(kp.clone()).or_insert(0) += 1;
+            }
+            selected.insert(q.question_id);
+        }
+
+        (selected, capped)
     }
 
     /// 获取所有未删除、未暂停的题目（SUSPENDED 不参与推荐打分池）
     fn get_all_active_questions(&self) -> Result<Vec<Question>, DbError> {
         let mut questions = Vec::new();
 
-        // 查询所有未删除、未暂停的题目
+        // 查询所有未删除、未暂停的题目；有小题的大题由小题参与推荐
         let rows = crate::db::select_all_active_questions(self.conn)?;
+        let parents = QuestionPartDao::new(self.conn).parent_ids()?;
 
         for row in rows {
             let q = crate::repo::question_row_to_domain(&row)
                 .map_err(|e| DbError::Migration(format!("convert error: {:?}", e)))?;
-            if q.state == QuestionState::SUSPENDED {
+            if q.state == QuestionState::SUSPENDED || parents.contains(&row.id) {
                 continue;
             }
             questions.push(q);
@@ -621,13 +1476,14 @@ impl<'a> RecommendationSystem<'a> {
     }
 
     /// 计算推荐分数
-    /// score = (1 + forget_risk) * freshness_bonus * last_wrong_bonus * error_rate_bonus * randomness
+    /// score = (1 + forget_risk) * freshness_bonus * last_wrong_bonus * error_rate_bonus * skip_factor * randomness
     fn calculate_score(
         &self,
         question: &Question,
         now: Timestamp,
         review_count: i64,
         error_rate: Option<f64>,
+        skip_count: i64,
     ) -> ScoreDetail {
         // 1. 遗忘风险 (软对数上限)
         let forget_risk = match (question.last_review_at, question.due_at) {
@@ -660,7 +1516,10 @@ impl<'a> RecommendationSystem<'a> {
             None => 1.0,
         };
 
-        // 5. 随机扰动
+        // 5. 跳过惩罚：近期反复跳过的题往后放，但不低于下限
+        let skip_factor = Self::skip_factor(skip_count);
+
+        // 6. 随机扰动
         let qid_i64: i64 = question.id.clone().into();
         let day_seed = (qid_i64 * 31 + now.as_i64() / DAY_SECONDS) % 1000;
         let randomness = 0.95 + (day_seed as f64 / 10000.0); // 0.95 ~ 1.05
@@ -670,6 +1529,7 @@ impl<'a> RecommendationSystem<'a> {
             * freshness_bonus
             * last_wrong_bonus
             * error_rate_bonus
+            * skip_factor
             * randomness;
 
         ScoreDetail {
@@ -677,20 +1537,27 @@ impl<'a> RecommendationSystem<'a> {
             freshness_bonus,
             last_wrong_bonus,
             error_rate_bonus,
+            skip_factor,
             randomness,
             final_score,
         }
     }
 
+    /// 近期跳过 `skip_count` 次对应的评分系数
+    fn skip_factor(skip_count: i64) -> f64 {
+        SKIP_PENALTY.powi(skip_count.clamp(0, 32) as i32).max(SKIP_FACTOR_FLOOR)
+    }
+
     /// 生成推荐理由，按固定优先级排序
-    /// 生成"被推荐"理由标签（按优先级排序）。
+    /// 生成"被推荐"理由编码（按优先级排序）。
     ///
-    /// 词条：
-    /// - "新题"        review_count ≤ 3
-    /// - "到期"        due_at 存在且 overdue_days = 0（且不是新题）
-    /// - "超期 N 天"   due_at 存在且 overdue_days > 0
-    /// - "上次出错"    last_result == "wrong" 或 "fuzzy"
-    /// - "错误率 X%"   error_rate > 0.5
+    /// 编码：
+    /// - `new_question`              review_count ≤ 3
+    /// - `due`                       due_at 存在且 overdue_days = 0（且不是新题）
+    /// - `overdue { days }`          due_at 存在且 overdue_days > 0
+    /// - `last_wrong`                last_result == "wrong" 或 "fuzzy"
+    /// - `high_error_rate { percent }` error_rate > 0.5
+    /// - `prerequisite { knowledge_point }` 题目属于薄弱知识点的前置知识点
     ///
     /// `due_at = None` 时不输出"到期"/"超期"（新题未设置 due_at 属正常）。
     fn generate_reason(
@@ -699,84 +1566,99 @@ impl<'a> RecommendationSystem<'a> {
         now: i64,
         last_result: &Option<String>,
         error_rate: Option<f64>,
-    ) -> Option<Vec<String>> {
-        let mut reasons: Vec<(u8, String)> = Vec::new();
+        prerequisite_for: Option<&str>,
+    ) -> Option<Vec<ReasonCode>> {
+        let mut reasons: Vec<(u8, ReasonCode)> = Vec::new();
 
         if review_count <= 3 {
-            reasons.push((1, "新题".to_string()));
+            reasons.push((1, ReasonCode::NewQuestion));
         }
         if let Some(d) = due_at {
             let overdue_days = (now - d) / DAY_SECONDS;
             if overdue_days > 0 {
-                reasons.push((2, format!("超期{overdue_days}天")));
+                reasons.push((2, ReasonCode::Overdue { days: overdue_days }));
             } else if review_count > 0 {
-                reasons.push((2, "到期".to_string()));
+                reasons.push((2, ReasonCode::Due));
             }
         }
         if let Some(r) = last_result {
             if r == "wrong" || r == "fuzzy" {
-                reasons.push((3, "上次出错".to_string()));
+                reasons.push((3, ReasonCode::LastWrong));
             }
         }
         if let Some(rate) = error_rate {
             if rate > 0.5 {
-                reasons.push((4, format!("错误率{:.0}%", rate * 100.0)));
+                reasons.push((4, ReasonCode::HighErrorRate { percent: (rate * 100.0).round() as i64 }));
             }
         }
+        if let Some(weak) = prerequisite_for {
+            reasons.push((5, ReasonCode::Prerequisite { knowledge_point: weak.to_string() }));
+        }
 
         if reasons.is_empty() {
             return None;
         }
         reasons.sort_by_key(|(priority, _)| *priority);
-        Some(reasons.into_iter().map(|(_, text)| text).collect())
+        Some(reasons.into_iter().map(|(_, code)| code).collect())
     }
 
-    /// "遗忘风险低" / "错误率低" 阈值：评分超过阈值时算"高"，不输出该词条。
+    /// `low_forget_risk` / `low_error_rate` 阈值：评分超过阈值时算"高"，不输出该编码。
     const EXCLUSION_THRESHOLD: f64 = 1.5;
 
-    /// 生成"落选"原因标签。
+    /// 生成"落选"原因编码。
     ///
     /// 规则：
-    /// - SUSPENDED → ["暂停复习"]
-    /// - 已掌握（STABLE 且未到期）→ ["已掌握"]
+    /// - SUSPENDED → [`suspended`]
+    /// - 知识点当日入选数已达上限 → [`knowledge_point_full { knowledge_point }`]
+    /// - 已掌握（STABLE 且未到期）→ [`mastered`]
     /// - 其他：
-    ///   - forget_risk < 阈值 → "遗忘风险低"
-    ///   - error_rate_bonus < 阈值 → "错误率低"
-    ///   - 都 ≥ 阈值 → "同科排名低于 N"（N = subject_rank）
+    ///   - forget_risk < 阈值 → `low_forget_risk`
+    ///   - error_rate_bonus < 阈值 → `low_error_rate`
+    ///   - 近期被跳过（skip_factor < 1）→ `often_skipped`
+    ///   - 都 ≥ 阈值 → `subject_rank_below { rank }`（rank = subject_rank）
     fn generate_exclusion_reason(
         q: &RecommendedQuestion,
         now: i64,
         subject_rank: usize,
-    ) -> Vec<String> {
+        capped_knowledge_point: Option<&str>,
+    ) -> Vec<ExclusionCode> {
         // 1. 暂停复习
         if q.state == "SUSPENDED" {
-            return vec!["暂停复习".to_string()];
+            return vec![ExclusionCode::Suspended];
         }
-        // 2. 已掌握
+        // 2. 名额被同知识点的高分题占满
+        if let Some(kp) = capped_knowledge_point {
+            return vec![ExclusionCode::KnowledgePointFull { knowledge_point: kp.to_string() }];
+        }
+        // 3. 已掌握
         let overdue_days = q.due_at
             .map(|d| (now - d) / DAY_SECONDS)
             .unwrap_or(0);
         if q.state == "STABLE" && overdue_days == 0 {
-            return vec!["已掌握".to_string()];
+            return vec![ExclusionCode::Mastered];
         }
-        // 3. 按评分给分
+        // 4. 按评分给分
         let detail = q.score_detail.unwrap_or(ScoreDetail {
             forget_risk: 0.0,
             freshness_bonus: 1.0,
             last_wrong_bonus: 1.0,
             error_rate_bonus: 1.0,
+            skip_factor: 1.0,
             randomness: 1.0,
             final_score: 0.0,
         });
-        let mut reasons: Vec<String> = Vec::new();
+        let mut reasons: Vec<ExclusionCode> = Vec::new();
         if detail.forget_risk < Self::EXCLUSION_THRESHOLD {
-            reasons.push("遗忘风险低".to_string());
+            reasons.push(ExclusionCode::LowForgetRisk);
         }
         if detail.error_rate_bonus < Self::EXCLUSION_THRESHOLD {
-            reasons.push("错误率低".to_string());
+            reasons.push(ExclusionCode::LowErrorRate);
+        }
+        if detail.skip_factor < 1.0 {
+            reasons.push(ExclusionCode::OftenSkipped);
         }
         if reasons.is_empty() {
-            reasons.push(format!("同科排名低于{subject_rank}"));
+            reasons.push(ExclusionCode::SubjectRankBelow { rank: subject_rank });
         }
         reasons
     }
@@ -863,6 +1745,7 @@ impl<'a> RecommendationSystem<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::question_link::LinkType;
 
     fn dummy_recommended_question(
         state: &str,
@@ -884,17 +1767,20 @@ mod tests {
             last_result: last_result.map(|s| s.to_string()),
             error_rate: Some(0.5),
             subject: Some("数学".to_string()),
+            tier: "DAILY".to_string(),
             reason: None,
             score_detail: Some(ScoreDetail {
                 forget_risk,
                 freshness_bonus: 1.0,
                 last_wrong_bonus: 1.0,
                 error_rate_bonus,
+                skip_factor: 1.0,
                 randomness: 1.0,
                 final_score: 1.0,
             }),
             review_count,
             created_at: 0,
+            knowledge_points: vec![],
         }
     }
 
@@ -903,15 +1789,15 @@ mod tests {
     #[test]
     fn test_reason_new_q() {
         // 新题: review_count = 0
-        let r = RecommendationSystem::generate_reason(0, None, 0, &None, None);
-        assert_eq!(r, Some(vec!["新题".to_string()]));
+        let r = RecommendationSystem::generate_reason(0, None, 0, &None, None, None);
+        assert_eq!(r, Some(vec![ReasonCode::NewQuestion]));
     }
 
     #[test]
     fn test_reason_review_count_3_still_new_q() {
         // review_count = 3 仍标"新题"
-        let r = RecommendationSystem::generate_reason(3, None, 100, &None, None);
-        assert_eq!(r, Some(vec!["新题".to_string()]));
+        let r = RecommendationSystem::generate_reason(3, None, 100, &None, None, None);
+        assert_eq!(r, Some(vec![ReasonCode::NewQuestion]));
     }
 
     #[test]
@@ -924,66 +1810,67 @@ mod tests {
             1_000_000,
             &None,
             None,
+            None,
         );
         // 4 次复习, due=now-50天, now → overdue 50 天 → "超期 50 天"
-        assert_eq!(r, Some(vec!["超期50天".to_string()]));
+        assert_eq!(r, Some(vec![ReasonCode::Overdue { days: 50 }]));
     }
 
     #[test]
     fn test_reason_due_now_no_overdue_reviewed() {
         // due_at = now, review_count > 0 → "到期"
-        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &None, None);
-        assert_eq!(r, Some(vec!["到期".to_string()]));
+        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &None, None, None);
+        assert_eq!(r, Some(vec![ReasonCode::Due]));
     }
 
     #[test]
     fn test_reason_due_in_future_not_overdue() {
         // due_at 在未来, overdue = 0, review_count > 0 → "到期"
-        let r = RecommendationSystem::generate_reason(5, Some(2000), 1000, &None, None);
-        assert_eq!(r, Some(vec!["到期".to_string()]));
+        let r = RecommendationSystem::generate_reason(5, Some(2000), 1000, &None, None, None);
+        assert_eq!(r, Some(vec![ReasonCode::Due]));
     }
 
     #[test]
     fn test_reason_due_in_past_overdue_days() {
         // due_at 过去 7 天 → "超期 7 天"
-        let r = RecommendationSystem::generate_reason(5, Some(1000 - 7 * 86400), 1000, &None, None);
-        assert_eq!(r, Some(vec!["超期7天".to_string()]));
+        let r = RecommendationSystem::generate_reason(5, Some(1000 - 7 * 86400), 1000, &None, None, None);
+        assert_eq!(r, Some(vec![ReasonCode::Overdue { days: 7 }]));
     }
 
     #[test]
     fn test_reason_new_q_due_in_past() {
         // 新题 (review_count=0) 即便 due_at 在过去也不标"超期" — 新题没有 due_at
         // 测试 due_at = None 路径
-        let r = RecommendationSystem::generate_reason(0, None, 1000, &None, None);
-        assert_eq!(r, Some(vec!["新题".to_string()]));
+        let r = RecommendationSystem::generate_reason(0, None, 1000, &None, None, None);
+        assert_eq!(r, Some(vec![ReasonCode::NewQuestion]));
     }
 
     #[test]
     fn test_reason_last_wrong() {
-        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &Some("wrong".to_string()), None);
-        assert_eq!(r, Some(vec!["到期".to_string(), "上次出错".to_string()]));
+        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &Some("wrong".to_string()), None, None);
+        assert_eq!(r, Some(vec![ReasonCode::Due, ReasonCode::LastWrong]));
     }
 
     #[test]
     fn test_reason_last_fuzzy() {
-        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &Some("fuzzy".to_string()), None);
-        assert_eq!(r, Some(vec!["到期".to_string(), "上次出错".to_string()]));
+        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &Some("fuzzy".to_string()), None, None);
+        assert_eq!(r, Some(vec![ReasonCode::Due, ReasonCode::LastWrong]));
     }
 
     #[test]
     fn test_reason_last_correct_not_included() {
-        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &Some("correct".to_string()), None);
-        assert_eq!(r, Some(vec!["到期".to_string()]));
+        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &Some("correct".to_string()), None, None);
+        assert_eq!(r, Some(vec![ReasonCode::Due]));
     }
 
     #[test]
     fn test_reason_combined_new_q_overdue() {
         // 新题 due_at 在过去: 只显示"新题"
-        let r = RecommendationSystem::generate_reason(2, Some(1000 - 3 * 86400), 1000, &Some("wrong".to_string()), None);
+        let r = RecommendationSystem::generate_reason(2, Some(1000 - 3 * 86400), 1000, &Some("wrong".to_string()), None, None);
         // 新题: 优先级 1
         // 超期: 优先级 2
         // 上次出错: 优先级 3
-        assert_eq!(r, Some(vec!["新题".to_string(), "超期3天".to_string(), "上次出错".to_string()]));
+        assert_eq!(r, Some(vec![ReasonCode::NewQuestion, ReasonCode::Overdue { days: 3 }, ReasonCode::LastWrong]));
     }
 
     #[test]
@@ -992,22 +1879,22 @@ mod tests {
         // 没有"到期"标签(因 due_at = None), 没"超期", 没"上次出错"
         // 只有 review_count = 4 没有"新题"标签
         // 所以没标签 → None
-        let r = RecommendationSystem::generate_reason(4, None, 1000, &Some("correct".to_string()), None);
+        let r = RecommendationSystem::generate_reason(4, None, 1000, &Some("correct".to_string()), None, None);
         assert_eq!(r, None);
     }
 
     #[test]
     fn test_reason_error_rate_above_threshold() {
         // error_rate > 0.5 → "错误率 80%"
-        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &None, Some(0.8));
-        assert_eq!(r, Some(vec!["到期".to_string(), "错误率80%".to_string()]));
+        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &None, Some(0.8), None);
+        assert_eq!(r, Some(vec![ReasonCode::Due, ReasonCode::HighErrorRate { percent: 80 }]));
     }
 
     #[test]
     fn test_reason_error_rate_below_threshold() {
         // error_rate <= 0.5 不标
-        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &None, Some(0.5));
-        assert_eq!(r, Some(vec!["到期".to_string()]));
+        let r = RecommendationSystem::generate_reason(5, Some(1000), 1000, &None, Some(0.5), None);
+        assert_eq!(r, Some(vec![ReasonCode::Due]));
     }
 
     #[test]
@@ -1019,13 +1906,14 @@ mod tests {
             1000,
             &Some("wrong".to_string()),
             Some(0.6),
+            None,
         );
         assert_eq!(
             r,
             Some(vec![
-                "到期".to_string(),
-                "上次出错".to_string(),
-                "错误率60%".to_string(),
+                ReasonCode::Due,
+                ReasonCode::LastWrong,
+                ReasonCode::HighErrorRate { percent: 60 },
             ])
         );
     }
@@ -1035,21 +1923,21 @@ mod tests {
     #[test]
     fn test_exclusion_suspended() {
         let q = dummy_recommended_question("SUSPENDED", 5, 0, Some(1000), None, 1.0, 1.0);
-        assert_eq!(RecommendationSystem::generate_exclusion_reason(&q, 1000, 5), vec!["暂停复习".to_string()]);
+        assert_eq!(RecommendationSystem::generate_exclusion_reason(&q, 1000, 5, None), vec![ExclusionCode::Suspended]);
     }
 
     #[test]
     fn test_exclusion_stable_no_overdue() {
         // STABLE + due_at 未来 → "已掌握"
         let q = dummy_recommended_question("STABLE", 10, 0, Some(2000), None, 1.0, 1.0);
-        assert_eq!(RecommendationSystem::generate_exclusion_reason(&q, 1000, 5), vec!["已掌握".to_string()]);
+        assert_eq!(RecommendationSystem::generate_exclusion_reason(&q, 1000, 5, None), vec![ExclusionCode::Mastered]);
     }
 
     #[test]
     fn test_exclusion_stable_due_now() {
         // STABLE + due_at = now → "已掌握" (overdue = 0)
         let q = dummy_recommended_question("STABLE", 10, 0, Some(1000), None, 1.0, 1.0);
-        assert_eq!(RecommendationSystem::generate_exclusion_reason(&q, 1000, 5), vec!["已掌握".to_string()]);
+        assert_eq!(RecommendationSystem::generate_exclusion_reason(&q, 1000, 5, None), vec![ExclusionCode::Mastered]);
     }
 
     #[test]
@@ -1057,8 +1945,8 @@ mod tests {
         // STABLE + overdue 7 天 → 走评分路径 (不再算"已掌握")
         let q = dummy_recommended_question("STABLE", 10, 0, Some(1000 - 7 * 86400), None, 0.5, 1.0);
         // forget_risk = 0.5 < 1.5 → "遗忘风险低"
-        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 5);
-        assert!(res.contains(&"遗忘风险低".to_string()));
+        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 5, None);
+        assert!(res.contains(&ExclusionCode::LowForgetRisk));
     }
 
     #[test]
@@ -1073,8 +1961,8 @@ mod tests {
             1.0,  // forget_risk 低 (< 1.5)
             2.0,  // error_rate_bonus 高
         );
-        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 5);
-        assert_eq!(res, vec!["遗忘风险低".to_string()]);
+        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 5, None);
+        assert_eq!(res, vec![ExclusionCode::LowForgetRisk]);
     }
 
     #[test]
@@ -1088,8 +1976,8 @@ mod tests {
             2.0,  // forget_risk 高
             1.0,  // error_rate_bonus 低
         );
-        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 5);
-        assert_eq!(res, vec!["错误率低".to_string()]);
+        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 5, None);
+        assert_eq!(res, vec![ExclusionCode::LowErrorRate]);
     }
 
     #[test]
@@ -1103,8 +1991,8 @@ mod tests {
             1.0,
             1.0,
         );
-        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 5);
-        assert_eq!(res, vec!["遗忘风险低".to_string(), "错误率低".to_string()]);
+        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 5, None);
+        assert_eq!(res, vec![ExclusionCode::LowForgetRisk, ExclusionCode::LowErrorRate]);
     }
 
     #[test]
@@ -1118,8 +2006,8 @@ mod tests {
             2.0,
             2.0,
         );
-        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 7);
-        assert_eq!(res, vec!["同科排名低于7".to_string()]);
+        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 7, None);
+        assert_eq!(res, vec![ExclusionCode::SubjectRankBelow { rank: 7 }]);
     }
 
     #[test]
@@ -1127,9 +2015,555 @@ mod tests {
         // 没 score_detail 时, forget_risk = 0, error_rate_bonus = 0 都 < 1.5
         let mut q = dummy_recommended_question("LEARNING", 10, 0, Some(1000 - 86400), None, 0.0, 0.0);
         q.score_detail = None;
-        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 5);
+        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 5, None);
         // forget_risk = 0 < 1.5 → "遗忘风险低"
         // error_rate_bonus = 0 < 1.5 → "错误率低"
-        assert_eq!(res, vec!["遗忘风险低".to_string(), "错误率低".to_string()]);
+        assert_eq!(res, vec![ExclusionCode::LowForgetRisk, ExclusionCode::LowErrorRate]);
+    }
+
+    #[test]
+    fn test_exclusion_knowledge_point_capped() {
+        // 知识点触顶优先于"已掌握"/评分路径
+        let q = dummy_recommended_question("STABLE", 10, 0, Some(2000), None, 2.0, 2.0);
+        let res = RecommendationSystem::generate_exclusion_reason(&q, 1000, 3, Some("导数"));
+        assert_eq!(res, vec![ExclusionCode::KnowledgePointFull { knowledge_point: "导数".to_string() }]);
+    }
+
+    // ===== select_pool_b =====
+
+    fn pool_item(id: i64, score: f64, kps: &[&str]) -> RecommendedQuestion {
+        let mut q = dummy_recommended_question("LEARNING", 5, 0, None, None, 1.0, 1.0);
+        q.question_id = id;
+        q.score = score;
+        q.knowledge_points = kps.iter().map(|s| s.to_string()).collect();
+        q
+    }
+
+    #[test]
+    fn test_pool_b_kp_cap_overflows_to_next_best() {
+        // 导数 占据前三名，上限 2 → 第三题让位给 4 号
+        let pool = vec![
+            pool_item(1, 9.0, &["导数"]),
+            pool_item(2, 8.0, &["导数"]),
+            pool_item(3, 7.0, &["导数"]),
+            pool_item(4, 6.0, &["数列"]),
+            pool_item(5, 5.0, &[]),
+        ];
+        let mut counts = HashMap::new();
+        let (selected, capped) = RecommendationSystem::select_pool_b(&pool, 3, 2, &mut counts);
+        assert_eq!(selected, HashSet::from([1, 2, 4]));
+        assert_eq!(capped.get(&3).map(|s| s.as_str()), Some("导数"));
+        assert!(!capped.contains_key(&5));
+    }
+
+    #[test]
+    fn test_pool_b_kp_cap_zero_is_unlimited() {
+        let pool = vec![
+            pool_item(1, 9.0, &["导数"]),
+            pool_item(2, 8.0, &["导数"]),
+            pool_item(3, 7.0, &["导数"]),
+        ];
+        let mut counts = HashMap::new();
+        let (selected, capped) = RecommendationSystem::select_pool_b(&pool, 3, 0, &mut counts);
+        assert_eq!(selected.len(), 3);
+        assert!(capped.is_empty());
+    }
+
+    #[test]
+    fn test_pool_b_kp_cap_counts_pool_a() {
+        // 池A 已选 1 道"导数"，上限 1 → 池B 的导数题全部跳过
+        let pool = vec![pool_item(1, 9.0, &["导数", "函数"]), pool_item(2, 8.0, &["函数"])];
+        let mut counts = HashMap::from([("导数".to_string(), 1usize)]);
+        let (selected, capped) = RecommendationSystem::select_pool_b(&pool, 2, 1, &mut counts);
+        assert_eq!(selected, HashSet::from([2]));
+        assert!(capped.contains_key(&1));
+    }
+
+    // ===== 手动干预 =====
+
+    fn setup_db() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn insert_q(conn: &Connection, name: &str, created_at: i64) -> i64 {
+        crate::db::insert_question(conn, Some(name), "NEW", created_at).unwrap()
+    }
+
+    #[test]
+    fn test_apply_overrides_splits_pinned_and_buried() {
+        let qs = vec![pool_item(1, 3.0, &[]), pool_item(2, 2.0, &[]), pool_item(3, 1.0, &[])];
+        let overrides = HashMap::from([(1, OverrideAction::BURY), (3, OverrideAction::PIN)]);
+        let (pinned, buried, rest) = RecommendationSystem::apply_overrides(qs, &overrides);
+        assert_eq!(pinned.iter().map(|q| q.question_id).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(pinned[0].reason, Some(vec![ReasonCode::Pinned]));
+        assert_eq!(buried.iter().map(|q| q.question_id).collect::<Vec<_>>(), vec![1]);
+        assert_eq!(rest.iter().map(|q| q.question_id).collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_generate_respects_pin_and_bury() {
+        let conn = setup_db();
+        let now = now_ts();
+        let a = insert_q(&conn, "A", now.as_i64() - 10);
+        let b = insert_q(&conn, "B", now.as_i64() - 5);
+        let c = insert_q(&conn, "C", now.as_i64());
+
+        let rs = RecommendationSystem::new(&conn);
+        rs.bury_question(a).unwrap();
+        rs.pin_question(c, 0).unwrap();
+
+        let settings = AppSettings { per_subject_daily_limit: 1, ..AppSettings::default() };
+        let picked: Vec<i64> = rs
+            .generate_recommendation(now, &settings)
+            .unwrap()
+            .iter()
+            .map(|q| q.question_id)
+            .collect();
+
+        // A 搁置；C 置顶不占名额；B 占据唯一名额
+        assert!(!picked.contains(&a));
+        assert!(picked.contains(&b));
+        assert!(picked.contains(&c));
+        assert_eq!(picked.len(), 2);
+    }
+
+    #[test]
+    fn test_pin_rejects_inactive_questions_on_any_day() {
+        let conn = setup_db();
+        let now = now_ts();
+        let deleted = insert_q(&conn, "deleted", now.as_i64());
+        let suspended = insert_q(&conn, "suspended", now.as_i64());
+        QuestionDao::new(&conn).update_deleted_at(QuestionId::from(deleted), Some(now)).unwrap();
+        conn.execute("UPDATE question SET state = 'SUSPENDED' WHERE id = ?1", [suspended]).unwrap();
+
+        let rs = RecommendationSystem::new(&conn);
+        for id in [deleted, suspended, 9999] {
+            assert!(matches!(rs.pin_question(id, 3), Err(DbError::NotFound)));
+        }
+        assert!(OverrideDao::new(&conn).list_by_day(RecommendationSystem::logical_day(now) + 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_generate_schedules_one_variant_per_day() {
+        let conn = setup_db();
+        let now = now_ts();
+        let a = insert_q(&conn, "A", now.as_i64() - 10);
+        let b = insert_q(&conn, "B", now.as_i64() - 5);
+        let c = insert_q(&conn, "C", now.as_i64());
+        question_link::link_questions(&conn, a, b, LinkType::VARIANT_OF, now).unwrap();
+
+        let rs = RecommendationSystem::new(&conn);
+        let settings = AppSettings { per_subject_daily_limit: 3, ..AppSettings::default() };
+        let picked: Vec<i64> = rs
+            .generate_recommendation(now, &settings)
+            .unwrap()
+            .iter()
+            .map(|q| q.question_id)
+            .collect();
+
+        // A、B 互为变式，名额够也只排一道；空出的名额不会被另一道占用
+        assert!(picked.contains(&a) ^ picked.contains(&b));
+        assert!(picked.contains(&c));
+        assert_eq!(picked.len(), 2);
+    }
+
+    #[test]
+    fn test_generate_schedules_one_cloze_card_per_day() {
+        let conn = setup_db();
+        let now = now_ts();
+        let note = insert_q(&conn, "N", now.as_i64() - 10);
+        let other = insert_q(&conn, "C", now.as_i64());
+        let text = crate::domain::question_text::QuestionText {
+            prompt: Some("{{c1::北京}}是{{c2::中国}}的首都".to_string()),
+            ..Default::default()
+        };
+        crate::server::question_manager::update_question(&conn, QuestionId::from(note), None, None, None, text, now)
+            .unwrap();
+        let cards = QuestionPartDao::new(&conn).list_part_ids(note, false).unwrap();
+        assert_eq!(cards.len(), 2);
+
+        let rs = RecommendationSystem::new(&conn);
+        let settings = AppSettings { per_subject_daily_limit: 3, ..AppSettings::default() };
+        let picked: Vec<i64> = rs
+            .generate_recommendation(now, &settings)
+            .unwrap()
+            .iter()
+            .map(|q| q.question_id)
+            .collect();
+
+        // 笔记本身不排；两张填空卡互为兄弟，只排一张
+        assert!(!picked.contains(&note));
+        assert!(picked.contains(&cards[0]) ^ picked.contains(&cards[1]));
+        assert!(picked.contains(&other));
+        assert_eq!(picked.len(), 2);
+    }
+
+    #[test]
+    fn test_postpone_buries_each_day_and_shifts_due() {
+        let conn = setup_db();
+        let now = now_ts();
+        let qid = insert_q(&conn, "A", now.as_i64());
+        let due = now.as_i64() - DAY_SECONDS;
+        conn.execute("UPDATE question SET due_at = ?1 WHERE id = ?2", rusqlite::params![due, qid])
+            .unwrap();
+
+        let rs = RecommendationSystem::new(&conn);
+        rs.postpone_question(qid, 3).unwrap();
+
+        let today = RecommendationSystem::logical_day(now);
+        for offset in 0..3 {
+            let day = rs.override_dao.list_by_day(today + offset).unwrap();
+            assert_eq!(day.get(&qid), Some(&OverrideAction::BURY));
+        }
+        assert!(rs.override_dao.list_by_day(today + 3).unwrap().is_empty());
+
+        let q = rs.question_dao.get_by_id(QuestionId::from(qid)).unwrap().unwrap();
+        assert!(q.due_at.unwrap().as_i64() >= now.as_i64() + 3 * DAY_SECONDS);
+        // 不写复习记录
+        let reviews: i64 = conn
+            .query_row("SELECT COUNT(*) FROM review WHERE question_id = ?1", [qid], |r| r.get(0))
+            .unwrap();
+        assert_eq!(reviews, 0);
+    }
+
+    // ===== remedial follow-ups =====
+
+    #[test]
+    fn test_select_remedial_filters_and_ranks() {
+        let candidates = vec![
+            pool_item(1, 9.0, &["导数"]),
+            pool_item(2, 8.0, &["数列"]),
+            pool_item(3, 7.0, &["导数", "极限"]),
+            pool_item(4, 6.0, &["极限"]),
+            pool_item(5, 5.0, &["导数"]),
+        ];
+        let kps = HashSet::from(["导数".to_string(), "极限".to_string()]);
+        let excluded = HashSet::from([1]);
+        let picked = RecommendationSystem::select_remedial(candidates.clone(), &kps, &HashSet::new(), &excluded, 2);
+
+        assert_eq!(picked.iter().map(|q| q.question_id).collect::<Vec<_>>(), vec![3, 4]);
+        assert!(picked.iter().all(|q| q.tier == "REMEDIAL"));
+        assert!(picked.iter().all(|q| q.reason == Some(vec![ReasonCode::Remedial])));
+
+        // 变式题不看知识点，且排在前面
+        let variants = HashSet::from([2]);
+        let picked = RecommendationSystem::select_remedial(candidates, &kps, &variants, &excluded, 2);
+        assert_eq!(picked.iter().map(|q| q.question_id).collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_hold_back_variants_keeps_one_per_group() {
+        let questions = vec![
+            pool_item(1, 5.0, &[]),
+            pool_item(2, 9.0, &[]),
+            pool_item(3, 7.0, &[]),
+            pool_item(4, 1.0, &[]),
+            pool_item(5, 2.0, &[]),
+        ];
+        let pinned = vec![pool_item(6, 0.5, &[])];
+        // {1, 2, 3} 一组；{5, 6} 一组，6 已置顶
+        let groups = HashMap::from([(1, 1), (2, 1), (3, 1), (5, 5), (6, 5)]);
+        let (kept, held) = RecommendationSystem::hold_back_variants(questions, &pinned, &groups);
+
+        assert_eq!(kept.iter().map(|q| q.question_id).collect::<Vec<_>>(), vec![2, 4]);
+        let held: Vec<(i64, i64)> = held.iter().map(|(q, by)| (q.question_id, *by)).collect();
+        assert_eq!(held, vec![(1, 2), (3, 2), (5, 6)]);
+    }
+
+    #[test]
+    fn test_queue_remedial_follow_ups_survives_daily_generation() {
+        let conn = setup_db();
+        let now = now_ts();
+        let a = insert_q(&conn, "A", now.as_i64());
+        let b = insert_q(&conn, "B", now.as_i64());
+        let c = insert_q(&conn, "C", now.as_i64());
+        let d = insert_q(&conn, "D", now.as_i64());
+        for (qid, kp) in [(a, "导数"), (b, "导数"), (c, "导数"), (d, "数列")] {
+            crate::db::insert_meta(&conn, qid, "system.KnowledgePoint", kp).unwrap();
+        }
+
+        let rs = RecommendationSystem::new(&conn);
+        rs.bury_question(c).unwrap();
+
+        let settings = AppSettings::default();
+        let queued = rs.queue_remedial_follow_ups(a, now, &settings).unwrap();
+        // C 今日搁置，D 知识点不同，A 是答错的题本身
+        assert_eq!(queued, vec![b]);
+        // 已在今日推荐中，不重复追加
+        assert!(rs.queue_remedial_follow_ups(a, now, &settings).unwrap().is_empty());
+
+        // 生成每日推荐时保留巩固层
+        let daily = rs.get_daily_recommendation(&settings).unwrap();
+        let b_row = daily.questions.iter().find(|q| q.question_id == b).unwrap();
+        assert_eq!(b_row.tier, "REMEDIAL");
+        assert_eq!(daily.questions[0].question_id, b);
+
+        let today = RecommendationSystem::logical_day(now);
+        assert!(rs.recommendation_dao.count_by_tier(today, RecommendationTier::DAILY).unwrap() > 0);
+        let counts = rs.recommendation_dao.count_reason_codes(today).unwrap();
+        assert!(counts.contains(&("remedial".to_string(), 1)));
+    }
+
+    #[test]
+    fn test_queue_remedial_disabled_by_zero_limit() {
+        let conn = setup_db();
+        let now = now_ts();
+        let a = insert_q(&conn, "A", now.as_i64());
+        let b = insert_q(&conn, "B", now.as_i64());
+        for qid in [a, b] {
+            crate::db::insert_meta(&conn, qid, "system.KnowledgePoint", "导数").unwrap();
+        }
+
+        let settings = AppSettings { remedial_follow_up_limit: 0, ..AppSettings::default() };
+        let rs = RecommendationSystem::new(&conn);
+        assert!(rs.queue_remedial_follow_ups(a, now, &settings).unwrap().is_empty());
+    }
+
+    // ===== prerequisite pulls =====
+
+    #[test]
+    fn test_reason_prerequisite_for_weak_point() {
+        let r = RecommendationSystem::generate_reason(5, None, 1000, &None, None, Some("二次函数"));
+        assert_eq!(r, Some(vec![ReasonCode::Prerequisite { knowledge_point: "二次函数".to_string() }]));
+    }
+
+    #[test]
+    fn test_select_prerequisite_pulls_skips_selected() {
+        let pool = vec![
+            pool_item(1, 9.0, &["一次函数"]),
+            pool_item(2, 8.0, &["一次函数"]),
+            pool_item(3, 7.0, &["一次函数"]),
+            pool_item(4, 6.0, &["极限"]),
+        ];
+        let focus = HashMap::from([
+            ("一次函数".to_string(), "二次函数".to_string()),
+            ("极限".to_string(), "导数".to_string()),
+        ]);
+        let pulled = RecommendationSystem::select_prerequisite_pulls(&pool, &HashSet::from([1]), &focus, 2);
+        assert_eq!(pulled, HashSet::from([2, 3, 4]));
+    }
+
+    #[test]
+    fn test_generate_pulls_prerequisites_of_weak_point() {
+        let conn = setup_db();
+        let now = now_ts();
+        let t = now.as_i64();
+        let oldest_new = insert_q(&conn, "N", t - 100);
+        let weak = insert_q(&conn, "W", t - 50);
+        let p1 = insert_q(&conn, "P1", t - 10);
+        let p2 = insert_q(&conn, "P2", t - 5);
+        crate::db::insert_meta(&conn, weak, "system.KnowledgePoint", "二次函数").unwrap();
+        for qid in [p1, p2] {
+            crate::db::insert_meta(&conn, qid, "system.KnowledgePoint", "一次函数").unwrap();
+        }
+        for i in 0..3 {
+            crate::db::insert_review(&conn, weak, "WRONG", t - 3600 * (i + 1)).unwrap();
+        }
+        crate::server::knowledge_graph::add_prerequisite(&conn, "二次函数", "一次函数", now).unwrap();
+
+        let settings = AppSettings { per_subject_daily_limit: 1, ..AppSettings::default() };
+        let rs = RecommendationSystem::new(&conn);
+        let picked = rs.generate_recommendation(now, &settings).unwrap();
+        let ids: HashSet<i64> = picked.iter().map(|q| q.question_id).collect();
+
+        // 名额被最早的新题占用，前置题额外补推
+        assert_eq!(ids, HashSet::from([oldest_new, p1, p2]));
+        let p1_reason = picked.iter().find(|q| q.question_id == p1).unwrap().reason.clone().unwrap();
+        assert!(p1_reason.contains(&ReasonCode::Prerequisite { knowledge_point: "二次函数".to_string() }));
+
+        let preview = rs.preview_recommendation(false, true, &settings).unwrap();
+        let selected: HashSet<i64> = preview.iter().filter(|i| i.selected).map(|i| i.question_id).collect();
+        assert_eq!(selected, ids);
+    }
+
+    // ===== study schedule =====
+
+    fn subject_settings(cfg: SubjectConfig) -> AppSettings {
+        AppSettings {
+            per_subject_daily_limit: 5,
+            subjects: HashMap::from([("数学".to_string(), cfg)]),
+            ..AppSettings::default()
+        }
+    }
+
+    #[test]
+    fn test_subject_quota_by_schedule() {
+        let carry = HashMap::from([("数学".to_string(), 2)]);
+        let mwf = SubjectConfig { study_days: Some(vec![1, 3, 5]), ..SubjectConfig::default() };
+        let settings = subject_settings(mwf.clone());
+        assert_eq!(RecommendationSystem::subject_quota(&settings, "数学", 3, &carry), Ok(5));
+        assert_eq!(
+            RecommendationSystem::subject_quota(&settings, "数学", 2, &carry),
+            Err(SubjectOffReason::NotStudyDay { study_days: vec![1, 3, 5] })
+        );
+        // 未配置的科目和未分类每天都推
+        assert_eq!(RecommendationSystem::subject_quota(&settings, "英语", 2, &carry), Ok(5));
+        assert_eq!(RecommendationSystem::subject_quota(&settings, UNCATEGORIZED, 2, &carry), Ok(5));
+
+        // 开启顺延后叠加顺延名额
+        let settings = subject_settings(SubjectConfig { carry_over_unused: true, ..mwf.clone() });
+        assert_eq!(RecommendationSystem::subject_quota(&settings, "数学", 5, &carry), Ok(7));
+
+        // 归档优先于学习日
+        let settings = subject_settings(SubjectConfig { archived: true, ..mwf });
+        assert_eq!(RecommendationSystem::subject_quota(&settings, "数学", 2, &carry), Err(SubjectOffReason::Archived));
+        let settings = subject_settings(SubjectConfig { recommendation_limit: Some(0), ..SubjectConfig::default() });
+        assert_eq!(RecommendationSystem::subject_quota(&settings, "数学", 2, &carry), Err(SubjectOffReason::NoQuota));
+    }
+
+    #[test]
+    fn test_generate_skips_subject_off_today() {
+        let conn = setup_db();
+        let now = now_ts();
+        let math = insert_q(&conn, "M", now.as_i64() - 10);
+        let english = insert_q(&conn, "E", now.as_i64() - 5);
+        crate::db::insert_meta(&conn, math, "system.Subject", "数学").unwrap();
+        crate::db::insert_meta(&conn, english, "system.Subject", "英语").unwrap();
+
+        let today = LogicalDay::from_timestamp(now, &ClockConfig::default()).weekday();
+        let other_days: Vec<u32> = (1..=7).filter(|d| *d != today).collect();
+        let settings = subject_settings(SubjectConfig { study_days: Some(other_days), ..SubjectConfig::default() });
+
+        let rs = RecommendationSystem::new(&conn);
+        let ids: Vec<i64> = rs
+            .generate_recommendation(now, &settings)
+            .unwrap()
+            .iter()
+            .map(|q| q.question_id)
+            .collect();
+        assert_eq!(ids, vec![english]);
+
+        let preview = rs.preview_recommendation(false, true, &settings).unwrap();
+        let item = preview.iter().find(|i| i.question_id == math).unwrap();
+        assert!(!item.selected);
+        assert_eq!(item.exclusion_reason, vec![ExclusionCode::NotStudyDay]);
+
+        let stats = rs.get_recommendation_stats(&settings).unwrap();
+        assert_eq!(stats.off_subjects.len(), 1);
+        assert_eq!(stats.off_subjects[0].subject, "数学");
+        assert!(matches!(stats.off_subjects[0].reason, SubjectOffReason::NotStudyDay { .. }));
+    }
+
+    #[test]
+    fn test_update_carry_over_settles_previous_day() {
+        let conn = setup_db();
+        let now = now_ts();
+        let today = RecommendationSystem::logical_day(now);
+        let yesterday = today - 1;
+        let (start, _) = range_of_day(LogicalDay(yesterday as i32), &ClockConfig::default());
+
+        let rs = RecommendationSystem::new(&conn);
+        let mut rows = Vec::new();
+        for name in ["A", "B", "C"] {
+            let mut q = pool_item(insert_q(&conn, name, start.as_i64()), 1.0, &[]);
+            q.subject = Some("数学".to_string());
+            rows.push(q);
+        }
+        rs.recommendation_dao.insert_batch(yesterday, &rows).unwrap();
+        crate::db::insert_review(&conn, rows[0].question_id, "CORRECT", start.as_i64() + 60).unwrap();
+
+        // 3 题复习了 1 题，剩 2 题顺延
+        let settings = subject_settings(SubjectConfig { carry_over_unused: true, ..SubjectConfig::default() });
+        rs.update_carry_over(today, &settings).unwrap();
+        assert_eq!(rs.carry_over_dao.list().unwrap(), HashMap::from([("数学".to_string(), 2)]));
+
+        // 关闭顺延后结算即清零
+        rs.update_carry_over(today, &subject_settings(SubjectConfig::default())).unwrap();
+        assert!(rs.carry_over_dao.list().unwrap().is_empty());
+    }
+
+    // ===== explain =====
+
+    #[test]
+    fn test_main_deficit_picks_largest_gap() {
+        let own = ScoreDetail {
+            forget_risk: 0.5,
+            freshness_bonus: 1.0,
+            last_wrong_bonus: 1.0,
+            error_rate_bonus: 1.2,
+            skip_factor: 1.0,
+            randomness: 1.0,
+            final_score: 1.8,
+        };
+        let threshold = ScoreDetail { last_wrong_bonus: 3.0, error_rate_bonus: 1.5, final_score: 6.75, ..own };
+        assert_eq!(RecommendationSystem::main_deficit(&own, &threshold), Some(ScoreFactor::LastWrong));
+        // 各因子都不落后时没有差距因子
+        assert_eq!(RecommendationSystem::main_deficit(&threshold, &own), None);
+    }
+
+    #[test]
+    fn test_explain_question_below_cutoff() {
+        let conn = setup_db();
+        let now = now_ts();
+        let fresh = insert_q(&conn, "Fresh", now.as_i64());
+        let stale = insert_q(&conn, "Stale", now.as_i64() - 10 * DAY_SECONDS);
+
+        // 不保送新题，两题靠分数竞争唯一名额
+        let settings = AppSettings {
+            per_subject_daily_limit: 1,
+            new_question_guarantee_ratio: 0.0,
+            ..AppSettings::default()
+        };
+        let rs = RecommendationSystem::new(&conn);
+
+        let picked = rs.explain_question(fresh, &settings).unwrap();
+        assert!(picked.selected);
+        assert_eq!(picked.subject_rank, Some(1));
+        assert_eq!(picked.projected_in_days, Some(0));
+
+        let dropped = rs.explain_question(stale, &settings).unwrap();
+        assert!(!dropped.selected);
+        assert_eq!(dropped.subject_rank, Some(2));
+        assert_eq!(dropped.subject_size, 2);
+        assert_eq!(dropped.required_score, Some(picked.score));
+        assert_eq!(dropped.main_deficit, Some(ScoreFactor::Freshness));
+        // 新鲜度只会继续衰减，状态不变就追不上门槛
+        assert_eq!(dropped.projected_in_days, None);
+    }
+
+    #[test]
+    fn test_explain_suspended_and_missing_question() {
+        let conn = setup_db();
+        let now = now_ts();
+        let qid = insert_q(&conn, "S", now.as_i64());
+        conn.execute("UPDATE question SET state = 'SUSPENDED' WHERE id = ?1", [qid]).unwrap();
+
+        let rs = RecommendationSystem::new(&conn);
+        let settings = AppSettings::default();
+        let explained = rs.explain_question(qid, &settings).unwrap();
+        assert_eq!(explained.exclusion_reason, vec![ExclusionCode::Suspended]);
+        assert_eq!(explained.subject_rank, None);
+        assert!(matches!(rs.explain_question(qid + 100, &settings), Err(DbError::NotFound)));
+    }
+
+    // ===== skip =====
+
+    #[test]
+    fn test_skip_factor_penalizes_with_floor() {
+        assert_eq!(RecommendationSystem::skip_factor(0), 1.0);
+        assert!((RecommendationSystem::skip_factor(2) - 0.85 * 0.85).abs() < 1e-9);
+        assert_eq!(RecommendationSystem::skip_factor(20), SKIP_FACTOR_FLOOR);
+    }
+
+    #[test]
+    fn test_recent_skips_lower_score_but_not_error_rate() {
+        let conn = setup_db();
+        let now = now_ts();
+        let skipped = insert_q(&conn, "S", now.as_i64());
+        let plain = insert_q(&conn, "P", now.as_i64());
+        let skip_dao = SkipDao::new(&conn);
+        skip_dao.insert(skipped, now.as_i64() - 3600).unwrap();
+        skip_dao.insert(skipped, now.as_i64() - 60).unwrap();
+        // 窗口外的跳过不计
+        skip_dao.insert(plain, now.as_i64() - (SKIP_WINDOW_DAYS + 1) * DAY_SECONDS).unwrap();
+
+        let rs = RecommendationSystem::new(&conn);
+        let scored = rs.score_all_questions(now, true, &HashMap::new()).unwrap();
+        let detail_of = |qid: i64| scored.iter().find(|q| q.question_id == qid).unwrap().score_detail.unwrap();
+        assert!((detail_of(skipped).skip_factor - 0.85 * 0.85).abs() < 1e-9);
+        assert_eq!(detail_of(plain).skip_factor, 1.0);
+        assert!(rs.review_dao.get_all_error_rates().unwrap().is_empty());
     }
 }
@@ -4,16 +4,23 @@
 //! - 处理复习结果并更新题目状态
 //! - 检查题目是否到期需要复习
 //! - 暂停/恢复题目的复习
+//! - 有小题的大题：逐道小题评分，大题状态由小题推导
 //! - 根据推荐算法返回待复习题目列表
 
 use crate::dao::meta_dao::MetaDao;
+use crate::dao::subject_dao::SubjectDao;
 use crate::dao::question_dao::QuestionDao;
+use crate::dao::question_part_dao::QuestionPartDao;
 use crate::dao::review_dao::ReviewDao;
+use crate::dao::skip_dao::SkipDao;
+use crate::dao::tag_dao::TagDao;
 use crate::dao::Connection;
 use crate::domain::enums::{MetaKey, QuestionState, ReviewResult, SystemMetaKey};
 use crate::domain::ids::QuestionId;
 use crate::domain::question::Question;
+use crate::domain::review_order::ReviewOrder;
 use crate::domain::state_machine::QuestionStateMachine;
+use crate::server::review_order::{self, OrderKey};
 use crate::util::time::{now_ts, range_of_day, ClockConfig, LogicalDay, Timestamp};
 
 /// 推荐的默认最大数量
@@ -29,7 +36,11 @@ const SUBJECT_META_KEY: &str = "system.Subject";
 pub struct ReviewManager<'a> {
     question_dao: QuestionDao<'a>,
     review_dao: ReviewDao<'a>,
+    skip_dao: SkipDao<'a>,
     meta_dao: MetaDao<'a>,
+    subject_dao: SubjectDao<'a>,
+    tag_dao: TagDao<'a>,
+    part_dao: QuestionPartDao<'a>,
 }
 
 /// 推荐结果
@@ -93,10 +104,87 @@ impl<'a> ReviewManager<'a> {
         Self {
             question_dao: QuestionDao::new(conn),
             review_dao: ReviewDao::new(conn),
+            skip_dao: SkipDao::new(conn),
             meta_dao: MetaDao::new(conn),
+            subject_dao: SubjectDao::new(conn),
+            tag_dao: TagDao::new(conn),
+            part_dao: QuestionPartDao::new(conn),
         }
     }
 
+    /// 大题下未删除的小题 ID；不是大题时为空
+    fn part_ids(&self, question_id: QuestionId) -> Result<Vec<i64>, String> {
+        self.part_dao
+            .list_part_ids(i64::from(question_id), false)
+            .map_err(|e| format!("failed to load parts: {}", e))
+    }
+
+    /// 按小题重新推导大题的状态与复习字段；没有未删除的小题时保持不变
+    pub fn sync_parent(&self, parent_id: QuestionId) -> Result<(), String> {
+        let parts = self
+            .question_dao
+            .get_by_ids(&self.part_ids(parent_id)?)
+            .map_err(|e| format!("failed to load parts: {}", e))?;
+        if parts.is_empty() {
+            return Ok(());
+        }
+        let transition = QuestionStateMachine::derive_from_parts(&parts);
+        let latest = parts.iter().filter(|p| p.last_review_at.is_some()).max_by_key(|p| p.last_review_at);
+        self.question_dao
+            .update_state(parent_id, transition.new_state)
+            .map_err(|e| format!("failed to update state: {}", e))?;
+        self.question_dao
+            .update_review_fields(
+                parent_id,
+                latest.and_then(|p| p.last_review_at),
+                latest.and_then(|p| p.last_result.as_ref()).map(|r| r.as_str()),
+                transition.correct_streak,
+                transition.wrong_count,
+                transition.due_at,
+            )
+            .map_err(|e| format!("failed to update review fields: {}", e))
+    }
+
+    /// 小题状态变化后同步其大题
+    fn sync_parent_of(&self, question_id: QuestionId) -> Result<(), String> {
+        let parent = self
+            .part_dao
+            .parent_of(i64::from(question_id))
+            .map_err(|e| format!("failed to load parent: {}", e))?;
+        match parent {
+            Some(parent) => self.sync_parent(QuestionId::from(parent)),
+            None => Ok(()),
+        }
+    }
+
+    /// 对大题的每道小题执行 `op`（`applies` 为 false 的跳过），再推导大题；
+    /// 没有一道小题适用时返回 `none_error`
+    fn apply_to_parts(
+        &self,
+        question_id: QuestionId,
+        part_ids: Vec<i64>,
+        applies: impl Fn(&Question) -> bool,
+        op: impl Fn(QuestionId) -> Result<Question, String>,
+        none_error: &str,
+    ) -> Result<Question, String> {
+        let parts = self
+            .question_dao
+            .get_by_ids(&part_ids)
+            .map_err(|e| format!("failed to load parts: {}", e))?;
+        let targets: Vec<&Question> = parts.iter().filter(|p| applies(p)).collect();
+        if targets.is_empty() {
+            return Err(none_error.to_string());
+        }
+        for part in targets {
+            op(part.id)?;
+        }
+        self.sync_parent(question_id)?;
+        self.question_dao
+            .get_by_id(question_id)
+            .map_err(|e| format!("failed to get updated question: {}", e))?
+            .ok_or("question not found after update".to_string())
+    }
+
     /// 处理复习结果
     ///
     /// # 参数
@@ -119,6 +207,11 @@ impl<'a> ReviewManager<'a> {
             .map_err(|e| format!("failed to get question: {}", e))?
             .ok_or("question not found")?;
 
+        // 有小题的大题不单独评分
+        if !self.part_ids(question_id)?.is_empty() {
+            return Err("question has parts; review each part separately".to_string());
+        }
+
         // 检查是否被暂停
         if question.state == QuestionState::SUSPENDED {
             return Err("cannot review a suspended question".to_string());
@@ -151,6 +244,7 @@ impl<'a> ReviewManager<'a> {
                 transition.due_at,
             )
             .map_err(|e| format!("failed to update review fields: {}", e))?;
+        self.sync_parent_of(question_id)?;
 
         // 返回更新后的题目
         self.question_dao
@@ -159,9 +253,42 @@ impl<'a> ReviewManager<'a> {
             .ok_or("question not found after update".to_string())
     }
 
+    /// 记录一次跳过。
+    ///
+    /// 跳过不是作答：只写 `review_skip`，不改题目状态，也不影响错误率；
+    /// 近期跳过次数由推荐评分中的跳过因子使用。
+    pub fn skip(&self, question_id: QuestionId, now: Timestamp) -> Result<(), String> {
+        let question = self
+            .question_dao
+            .get_by_id(question_id)
+            .map_err(|e| format!("failed to get question: {}", e))?
+            .ok_or("question not found")?;
+
+        if question.state == QuestionState::SUSPENDED {
+            return Err("cannot skip a suspended question".to_string());
+        }
+
+        self.skip_dao
+            .insert(i64::from(question_id), now.as_i64())
+            .map_err(|e| format!("failed to insert skip: {}", e))?;
+        Ok(())
+    }
+
     /// 暂停题目（任意非 SUSPENDED 状态 → SUSPENDED）。
     /// 把原状态写入 `system.PreSuspendState` meta，恢复时还原。
+    /// 有小题的大题暂停全部小题。
     pub fn suspend(&self, question_id: QuestionId) -> Result<Question, String> {
+        let part_ids = self.part_ids(question_id)?;
+        if !part_ids.is_empty() {
+            return self.apply_to_parts(
+                question_id,
+                part_ids,
+                |p| p.state != QuestionState::SUSPENDED,
+                |id| self.suspend(id),
+                "question already suspended",
+            );
+        }
+
         // 获取题目
         let question = self
             .question_dao
@@ -202,6 +329,7 @@ impl<'a> ReviewManager<'a> {
                 transition.due_at,
             )
             .map_err(|e| format!("failed to update review fields: {}", e))?;
+        self.sync_parent_of(question_id)?;
 
         // 返回更新后的题目
         self.question_dao
@@ -211,8 +339,19 @@ impl<'a> ReviewManager<'a> {
     }
 
     /// 恢复题目（SUSPENDED → 暂停前的状态，缺省回退 LEARNING）。
-    /// 恢复后清掉 `system.PreSuspendState` meta。
+    /// 恢复后清掉 `system.PreSuspendState` meta。有小题的大题恢复全部暂停的小题。
     pub fn recover(&self, question_id: QuestionId) -> Result<Question, String> {
+        let part_ids = self.part_ids(question_id)?;
+        if !part_ids.is_empty() {
+            return self.apply_to_parts(
+                question_id,
+                part_ids,
+                |p| p.state == QuestionState::SUSPENDED,
+                |id| self.recover(id),
+                "only suspended questions can be recovered",
+            );
+        }
+
         // 获取题目
         let question = self
             .question_dao
@@ -262,6 +401,7 @@ impl<'a> ReviewManager<'a> {
         self.meta_dao
             .delete_by_question_and_key(question_id, pre_suspend_key)
             .map_err(|e| format!("failed to clear pre-suspend meta: {}", e))?;
+        self.sync_parent_of(question_id)?;
 
         // 返回更新后的题目
         self.question_dao
@@ -270,17 +410,86 @@ impl<'a> ReviewManager<'a> {
             .ok_or("question not found after update".to_string())
     }
 
+    /// 重置学习进度（任意状态 → NEW）：清空连续正确、错误次数、上次结果与到期时间，
+    /// 暂停前状态一并清掉；复习记录保留。有小题的大题重置全部小题。
+    pub fn reset(&self, question_id: QuestionId) -> Result<Question, String> {
+        let part_ids = self.part_ids(question_id)?;
+        if !part_ids.is_empty() {
+            return self.apply_to_parts(question_id, part_ids, |_| true, |id| self.reset(id), "question has no parts");
+        }
+
+        let question = self
+            .question_dao
+            .get_by_id(question_id)
+            .map_err(|e| format!("failed to get question: {}", e))?
+            .ok_or("question not found")?;
+
+        let transition = QuestionStateMachine::reset();
+        self.question_dao
+            .update_state(question_id, transition.new_state)
+            .map_err(|e| format!("failed to update state: {}", e))?;
+        self.question_dao
+            .update_review_fields(
+                question_id,
+                question.last_review_at,
+                None,
+                transition.correct_streak,
+                transition.wrong_count,
+                transition.due_at,
+            )
+            .map_err(|e| format!("failed to update review fields: {}", e))?;
+        self.meta_dao
+            .delete_by_question_and_key(question_id, MetaKey::System(SystemMetaKey::PreSuspendState))
+            .map_err(|e| format!("failed to clear pre-suspend meta: {}", e))?;
+        self.sync_parent_of(question_id)?;
+
+        self.question_dao
+            .get_by_id(question_id)
+            .map_err(|e| format!("failed to get updated question: {}", e))?
+            .ok_or("question not found after update".to_string())
+    }
+
     /// 获取推荐的复习题目
     ///
     /// # 参数
     /// - limit: 最大推荐数量，默认 10
     /// - subject: 可选的科目筛选
+    /// - tags: 标签范围，非空时只推荐同时带有这些标签的题目
     ///
     /// # 返回
     /// 返回推荐结果
-    pub fn recommend(&self, limit: Option<usize>, subject: Option<&str>) -> Result<RecommendResult, String> {
+    pub fn recommend(
+        &self,
+        limit: Option<usize>,
+        subject: Option<&str>,
+        tags: &[String],
+    ) -> Result<RecommendResult, String> {
         let limit = limit.unwrap_or(DEFAULT_RECOMMEND_LIMIT);
         let now = now_ts();
+        let parents = self
+            .part_dao
+            .parent_ids()
+            .map_err(|e| format!("failed to load parts: {}", e))?;
+
+        // 标签记在大题（笔记）上：带标签的大题由其小题、填空卡、遮罩卡片代为进入范围
+        let tag_scope = if tags.is_empty() {
+            None
+        } else {
+            let tags = crate::server::tag::normalize_tags(tags);
+            let mut scope = self
+                .tag_dao
+                .question_ids_with_all(&tags)
+                .map_err(|e| format!("failed to load tagged questions: {}", e))?;
+            let tagged_parents: Vec<i64> = scope.iter().filter(|id| parents.contains(id)).copied().collect();
+            for parent in tagged_parents {
+                scope.extend(
+                    self.part_dao
+                        .list_part_ids(parent, false)
+                        .map_err(|e| format!("failed to load parts: {}", e))?,
+                );
+            }
+            Some(scope)
+        };
 
         let mut questions = Vec::new();
         let mut reasons = Vec::new();
@@ -288,11 +497,21 @@ impl<'a> ReviewManager<'a> {
 
         // 辅助函数：检查题目是否进入推荐池
         // 1. SUSPENDED 状态：用户暂停复习，不参与推荐（无论怎么筛选都不选）
-        // 2. 科目筛选：subject 为 None 或 "" 时全选；否则 meta 的科目严格匹配
+        // 2. 有小题的大题：推荐的是小题
+        // 3. 标签范围：指定了标签时，题目（小题看所属大题）须带有全部标签
+        // 4. 科目筛选：subject 为 None 或 "" 时全选；否则 meta 的科目严格匹配
         let subject_matches = |q: &Question, subject: Option<&str>| -> bool {
             if q.state == QuestionState::SUSPENDED {
                 return false;
             }
+            if parents.contains(&i64::from(q.id)) {
+                return false;
+            }
+            if let Some(scope) = &tag_scope {
+                if !scope.contains(&i64::from(q.id)) {
+                    return false;
+                }
+            }
             if subject.is_none() || subject == Some("") {
                 return true;
             }
@@ -382,10 +601,47 @@ impl<'a> ReviewManager<'a> {
         })
     }
 
-    /// 获取所有科目列表
+    /// 按出题顺序策略重排推荐结果，题目与推荐原因一一对应地移动。
+    pub fn arrange(
+        &self,
+        result: RecommendResult,
+        order: ReviewOrder,
+        seed: u64,
+    ) -> Result<RecommendResult, String> {
+        let error_rates = self
+            .review_dao
+            .get_all_error_rates()
+            .map_err(|e| format!("failed to load error rates: {}", e))?;
+
+        let qids: Vec<i64> = result.questions.iter().map(|q| i64::from(q.id)).collect();
+        let subjects = self
+            .meta_dao
+            .list_values_by_question_ids(&qids, SUBJECT_META_KEY)
+            .map_err(|e| format!("failed to get subjects: {}", e))?;
+
+        let mut keyed = Vec::with_capacity(result.questions.len());
+        for (q, reason) in result.questions.into_iter().zip(result.reasons) {
+            let subject = subjects.get(&i64::from(q.id)).and_then(|v| v.first().cloned());
+            let error_rate = error_rates.get(&i64::from(q.id)).map(|&(rate, _)| rate).unwrap_or(0.0);
+            let key = OrderKey { subject, error_rate, wrong_count: q.wrong_count };
+            keyed.push((key, q, reason));
+        }
+
+        let arranged = review_order::arrange(keyed, order, seed, |(key, _, _)| key.clone());
+        let (questions, reasons) = arranged.into_iter().map(|(_, q, r)| (q, r)).unzip();
+
+        Ok(RecommendResult {
+            questions,
+            reasons,
+            subject: result.subject,
+        })
+    }
+
+    /// 获取所有科目列表（按科目的显示顺序）
     pub fn list_subjects(&self) -> Result<Vec<String>, String> {
-        self.meta_dao
-            .list_all_subjects()
+        self.subject_dao
+            .list()
+            .map(|subjects| subjects.into_iter().map(|s| s.name).collect())
             .map_err(|e| format!("failed to list subjects: {}", e))
     }
 
@@ -506,6 +762,26 @@ mod tests {
         let _ = RecommendReason::StaleReview;
     }
 
+    #[test]
+    fn test_skip_leaves_question_and_reviews_untouched() {
+        let conn = setup();
+        let qid = insert_question(&conn, "题", QuestionState::LEARNING);
+        let now = now_ts();
+
+        let mgr = ReviewManager::new(&conn);
+        mgr.skip(qid, now).unwrap();
+        mgr.skip(qid, now).unwrap();
+
+        assert_eq!(mgr.skip_dao.count_since(0).unwrap().get(&i64::from(qid)), Some(&2));
+        assert_eq!(mgr.review_dao.count_all().unwrap(), 0);
+        let q = mgr.question_dao.get_by_id(qid).unwrap().unwrap();
+        assert_eq!(q.state, QuestionState::LEARNING);
+        assert!(q.last_review_at.is_none());
+
+        mgr.suspend(qid).unwrap();
+        assert!(mgr.skip(qid, now).is_err());
+    }
+
     #[test]
     fn test_suspend_writes_pre_suspend_meta() {
         let conn = setup();
@@ -608,7 +884,7 @@ mod tests {
         .unwrap();
 
         let mgr = ReviewManager::new(&conn);
-        let result = mgr.recommend(Some(10), None).unwrap();
+        let result = mgr.recommend(Some(10), None, &[]).unwrap();
         let ids: Vec<i64> = result
             .questions
             .iter()
@@ -626,4 +902,26 @@ mod tests {
             ids
         );
     }
+
+    #[test]
+    fn test_recommend_tag_scope_covers_parts_of_tagged_question() {
+        let conn = setup();
+        let parent = insert_question(&conn, "大题", QuestionState::NEW);
+        let other = insert_question(&conn, "无标签", QuestionState::NEW);
+        let part = crate::server::question_part::add_part(
+            &conn,
+            parent,
+            None,
+            crate::domain::question_text::QuestionText::default(),
+            now_ts(),
+        )
+        .unwrap();
+        crate::server::tag::tag_questions(&conn, &[i64::from(parent)], &["期中".to_string()]).unwrap();
+
+        let mgr = ReviewManager::new(&conn);
+        let result = mgr.recommend(Some(10), None, &["期中".to_string()]).unwrap();
+        let ids: Vec<i64> = result.questions.iter().map(|q| i64::from(q.id)).collect();
+        assert_eq!(ids, vec![i64::from(part)]);
+        assert!(!ids.contains(&i64::from(other)));
+    }
 }
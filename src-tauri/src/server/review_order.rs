@@ -0,0 +1,159 @@
+//! 复习会话出题顺序
+//!
+//! 推荐只决定"今天复习哪些题"，本模块决定"按什么顺序出题"。
+//! 输入默认已按推荐分数降序排列，各策略只重排，不增删题目。
+
+use crate::domain::review_order::ReviewOrder;
+
+/// 排序所需的题目特征
+#[derive(Debug, Clone)]
+pub struct OrderKey {
+    pub subject: Option<String>,
+    pub error_rate: f64,
+    pub wrong_count: i64,
+}
+
+/// 按策略重排 `items`；`key` 从每项取排序特征。
+///
+/// 同等难度的题保持原有（分数）先后，排序是稳定的。
+pub fn arrange<T, F>(items: Vec<T>, order: ReviewOrder, seed: u64, key: F) -> Vec<T>
+where
+    F: Fn(&T) -> OrderKey,
+{
+    match order {
+        ReviewOrder::Score => items,
+        ReviewOrder::SubjectRoundRobin => round_robin(items, key),
+        ReviewOrder::Shuffle => shuffle(items, seed),
+        ReviewOrder::HardestFirst => {
+            let mut keyed: Vec<(OrderKey, T)> = items.into_iter().map(|t| (key(&t), t)).collect();
+            keyed.sort_by(|(a, _), (b, _)| compare_difficulty(b, a));
+            keyed.into_iter().map(|(_, t)| t).collect()
+        }
+        ReviewOrder::EasiestFirst => {
+            let mut keyed: Vec<(OrderKey, T)> = items.into_iter().map(|t| (key(&t), t)).collect();
+            keyed.sort_by(|(a, _), (b, _)| compare_difficulty(a, b));
+            keyed.into_iter().map(|(_, t)| t).collect()
+        }
+    }
+}
+
+fn compare_difficulty(a: &OrderKey, b: &OrderKey) -> std::cmp::Ordering {
+    a.error_rate
+        .partial_cmp(&b.error_rate)
+        .unwrap_or(std::cmp::Ordering::Equal)
+        .then(a.wrong_count.cmp(&b.wrong_count))
+}
+
+/// 科目轮转：按科目首次出现的顺序轮流取题，科内保持原顺序
+fn round_robin<T, F>(items: Vec<T>, key: F) -> Vec<T>
+where
+    F: Fn(&T) -> OrderKey,
+{
+    let mut groups: Vec<(Option<String>, std::collections::VecDeque<T>)> = Vec::new();
+    for item in items {
+        let subject = key(&item).subject;
+        match groups.iter_mut().find(|(s, _)| *s == subject) {
+            Some((_, queue)) => queue.push_back(item),
+            None => groups.push((subject, std::collections::VecDeque::from([item]))),
+        }
+    }
+
+    let mut result = Vec::new();
+    loop {
+        let mut progressed = false;
+        for (_, queue) in groups.iter_mut() {
+            if let Some(item) = queue.pop_front() {
+                result.push(item);
+                progressed = true;
+            }
+        }
+        if !progressed {
+            break;
+        }
+    }
+    result
+}
+
+/// Fisher-Yates 洗牌，随机源为 splitmix64
+fn shuffle<T>(mut items: Vec<T>, seed: u64) -> Vec<T> {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    for i in (1..items.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn k(subject: &str, error_rate: f64, wrong_count: i64) -> OrderKey {
+        OrderKey { subject: Some(subject.to_string()), error_rate, wrong_count }
+    }
+
+    fn items() -> Vec<(i64, OrderKey)> {
+        vec![
+            (1, k("数学", 0.2, 1)),
+            (2, k("数学", 0.8, 3)),
+            (3, k("数学", 0.5, 2)),
+            (4, k("物理", 0.1, 0)),
+            (5, k("英语", 0.8, 5)),
+        ]
+    }
+
+    fn ids(v: &[(i64, OrderKey)]) -> Vec<i64> {
+        v.iter().map(|(id, _)| *id).collect()
+    }
+
+    #[test]
+    fn test_score_keeps_input_order() {
+        let r = arrange(items(), ReviewOrder::Score, 0, |(_, key)| key.clone());
+        assert_eq!(ids(&r), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_subject_round_robin() {
+        let r = arrange(items(), ReviewOrder::SubjectRoundRobin, 0, |(_, key)| key.clone());
+        assert_eq!(ids(&r), vec![1, 4, 5, 2, 3]);
+    }
+
+    #[test]
+    fn test_hardest_and_easiest_first() {
+        let hard = arrange(items(), ReviewOrder::HardestFirst, 0, |(_, key)| key.clone());
+        assert_eq!(ids(&hard), vec![5, 2, 3, 1, 4]);
+        let easy = arrange(items(), ReviewOrder::EasiestFirst, 0, |(_, key)| key.clone());
+        assert_eq!(ids(&easy), vec![4, 1, 3, 2, 5]);
+    }
+
+    #[test]
+    fn test_shuffle_is_deterministic_per_seed() {
+        let a = arrange(items(), ReviewOrder::Shuffle, 42, |(_, key)| key.clone());
+        let b = arrange(items(), ReviewOrder::Shuffle, 42, |(_, key)| key.clone());
+        assert_eq!(ids(&a), ids(&b));
+        let mut sorted = ids(&a);
+        sorted.sort();
+        assert_eq!(sorted, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_order_str_round_trip() {
+        for o in [
+            ReviewOrder::Score,
+            ReviewOrder::SubjectRoundRobin,
+            ReviewOrder::Shuffle,
+            ReviewOrder::HardestFirst,
+            ReviewOrder::EasiestFirst,
+        ] {
+            assert_eq!(o.as_str().parse::<ReviewOrder>(), Ok(o));
+        }
+        assert!("bogus".parse::<ReviewOrder>().is_err());
+    }
+}
@@ -0,0 +1,444 @@
+//! 题目修订历史
+//!
+//! 题名、科目、知识点、资源集合与顺序、文本字段的每次编辑都记一条修订（编辑后的完整快照），
+//! 可以列出、比较任意两条修订，并把题目恢复到某条修订。题目第一次被编辑时，
+//! 编辑前的状态先记为一条修订，这样升级前录入的题目也能恢复到最初的样子。
+//! 恢复时，已移除但仍在回收区（软删除）的资源会一并恢复。科目和知识点按 id 找回，
+//! 改名后用新名称；已被合并或删除的记为无法恢复。
+
+use serde::Serialize;
+
+use crate::dao::asset_dao::AssetDao;
+use crate::dao::knowledge_point_dao::KnowledgePointDao;
+use crate::dao::meta_dao::MetaDao;
+use crate::dao::question_dao::QuestionDao;
+use crate::dao::question_revision_dao::QuestionRevisionDao;
+use crate::dao::question_text_dao::QuestionTextDao;
+use crate::dao::subject_dao::SubjectDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::domain::enums::{MetaKey, SystemMetaKey, TextField};
+use crate::domain::ids::QuestionId;
+use crate::domain::question_revision::{AssetSnapshot, QuestionRevision, QuestionSnapshot};
+use crate::error::AppError;
+use crate::server::question_manager;
+use crate::util::time::Timestamp;
+
+/// 两条修订之间的一处差异
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "field", rename_all = "snake_case")]
+pub enum RevisionChange {
+    Name { before: Option<String>, after: Option<String> },
+    Subject { before: Option<String>, after: Option<String> },
+    KnowledgePoints { added: Vec<String>, removed: Vec<String> },
+    /// `reordered`：两边都有的资源顺序是否变化
+    Assets { added: Vec<AssetSnapshot>, removed: Vec<AssetSnapshot>, reordered: bool },
+    /// `text_field` 为 PROMPT / ANSWER / EXPLANATION
+    Text { text_field: String, before: Option<String>, after: Option<String> },
+}
+
+/// 恢复结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RevertOutcome {
+    /// 恢复后新记的修订；题目本来就与该修订一致时为 None
+    pub revision_id: Option<i64>,
+    /// 修订中有、但已被永久删除而无法恢复的资源
+    pub missing_assets: Vec<AssetSnapshot>,
+    /// 修订中的科目已被合并或删除而无法恢复时为其名称
+    pub missing_subject: Option<String>,
+    /// 修订中有、但已被合并或删除而无法恢复的知识点
+    pub missing_knowledge_points: Vec<String>,
+}
+
+/// `apply_snapshot` 中无法恢复的部分
+#[derive(Debug, Default)]
+struct Unrestorable {
+    assets: Vec<AssetSnapshot>,
+    subject: Option<String>,
+    knowledge_points: Vec<String>,
+}
+
+/// 题目当前可编辑内容的快照
+pub fn snapshot(conn: &Connection, qid: QuestionId) -> Result<QuestionSnapshot, AppError> {
+    let question = QuestionDao::new(conn)
+        .get_by_id(qid)?
+        .ok_or_else(|| AppError::NotFound(format!("question {}", i64::from(qid))))?;
+    let md = MetaDao::new(conn);
+    let subject = md
+        .get_values_by_question_key(qid, &MetaKey::System(SystemMetaKey::Subject).as_str())?
+        .into_iter()
+        .next();
+    let subject_id = match &subject {
+        Some(name) => SubjectDao::new(conn).get_by_name(name)?.map(|s| s.id),
+        None => None,
+    };
+    let knowledge_points =
+        md.get_values_by_question_key(qid, &MetaKey::System(SystemMetaKey::KnowledgePoint).as_str())?;
+    let kd = KnowledgePointDao::new(conn);
+    let knowledge_point_ids = knowledge_points
+        .iter()
+        .map(|kp| Ok(kd.get_by_name(kp)?.map(|n| n.id)))
+        .collect::<Result<Vec<_>, DbError>>()?;
+
+    let mut assets: Vec<AssetSnapshot> = AssetDao::new(conn)
+        .list_by_question(qid)?
+        .into_iter()
+        .map(|a| AssetSnapshot {
+            id: i64::from(a.id),
+            asset_type: a.asset_type.as_str().to_string(),
+            path: a.path.as_str(),
+            sort_order: a.sort_order,
+        })
+        .collect();
+    assets.sort_by(|a, b| (&a.asset_type, a.sort_order, a.id).cmp(&(&b.asset_type, b.sort_order, b.id)));
+
+    let text = QuestionTextDao::new(conn).get_current(i64::from(qid))?;
+    Ok(QuestionSnapshot {
+        name: question.name,
+        subject,
+        subject_id,
+        knowledge_points,
+        knowledge_point_ids,
+        assets,
+        prompt: text.prompt,
+        answer: text.answer,
+        explanation: text.explanation,
+    })
+}
+
+/// 题目当前内容与最近一条修订不同时追加一条修订，返回新修订的 id
+pub fn record(conn: &Connection, qid: QuestionId, now: Timestamp) -> Result<Option<i64>, AppError> {
+    let dao = QuestionRevisionDao::new(conn);
+    let current = snapshot(conn, qid)?;
+    if dao.latest(i64::from(qid))?.is_some_and(|r| r.snapshot == current) {
+        return Ok(None);
+    }
+    Ok(Some(dao.insert(i64::from(qid), &current, now)?))
+}
+
+/// 执行一次编辑并记修订：题目还没有任何修订时先记下编辑前的状态。
+/// 不自带事务，需要原子性时由调用方包在事务里。
+pub fn tracked<T>(
+    conn: &Connection,
+    qid: QuestionId,
+    now: Timestamp,
+    edit: impl FnOnce() -> Result<T, AppError>,
+) -> Result<T, AppError> {
+    if QuestionRevisionDao::new(conn).latest(i64::from(qid))?.is_none() {
+        record(conn, qid, now)?;
+    }
+    let out = edit()?;
+    record(conn, qid, now)?;
+    Ok(out)
+}
+
+/// 题目的全部修订，新的在前
+pub fn list_revisions(conn: &Connection, qid: QuestionId) -> Result<Vec<QuestionRevision>, AppError> {
+    Ok(QuestionRevisionDao::new(conn).list(i64::from(qid))?)
+}
+
+fn get_revision(conn: &Connection, qid: QuestionId, revision_id: i64) -> Result<QuestionRevision, AppError> {
+    QuestionRevisionDao::new(conn)
+        .get(revision_id)?
+        .filter(|r| r.question_id == i64::from(qid))
+        .ok_or_else(|| AppError::NotFound(format!("revision {}", revision_id)))
+}
+
+/// 比较题目的两条修订，列出从 `from` 到 `to` 的变化
+pub fn diff_revisions(
+    conn: &Connection,
+    qid: QuestionId,
+    from: i64,
+    to: i64,
+) -> Result<Vec<RevisionChange>, AppError> {
+    let from = get_revision(conn, qid, from)?;
+    let to = get_revision(conn, qid, to)?;
+    Ok(diff(&from.snapshot, &to.snapshot))
+}
+
+/// 两份快照之间的差异
+pub fn diff(before: &QuestionSnapshot, after: &QuestionSnapshot) -> Vec<RevisionChange> {
+    let mut changes = Vec::new();
+    if before.name != after.name {
+        changes.push(RevisionChange::Name { before: before.name.clone(), after: after.name.clone() });
+    }
+    if before.subject != after.subject {
+        changes.push(RevisionChange::Subject { before: before.subject.clone(), after: after.subject.clone() });
+    }
+
+    let added: Vec<String> = after
+        .knowledge_points
+        .iter()
+        .filter(|kp| !before.knowledge_points.contains(kp))
+        .cloned()
+        .collect();
+    let removed: Vec<String> = before
+        .knowledge_points
+        .iter()
+        .filter(|kp| !after.knowledge_points.contains(kp))
+        .cloned()
+        .collect();
+    if !added.is_empty() || !removed.is_empty() {
+        changes.push(RevisionChange::KnowledgePoints { added, removed });
+    }
+
+    let has = |list: &[AssetSnapshot], id: i64| list.iter().any(|a| a.id == id);
+    let added: Vec<AssetSnapshot> = after.assets.iter().filter(|a| !has(&before.assets, a.id)).cloned().collect();
+    let removed: Vec<AssetSnapshot> = before.assets.iter().filter(|a| !has(&after.assets, a.id)).cloned().collect();
+    let kept_order = |list: &[AssetSnapshot], other: &[AssetSnapshot]| -> Vec<i64> {
+        list.iter().filter(|a| has(other, a.id)).map(|a| a.id).collect()
+    };
+    let reordered = kept_order(&before.assets, &after.assets) != kept_order(&after.assets, &before.assets);
+    if !added.is_empty() || !removed.is_empty() || reordered {
+        changes.push(RevisionChange::Assets { added, removed, reordered });
+    }
+
+    for (field, b, a) in [
+        (TextField::PROMPT, &before.prompt, &after.prompt),
+        (TextField::ANSWER, &before.answer, &after.answer),
+        (TextField::EXPLANATION, &before.explanation, &after.explanation),
+    ] {
+        if b != a {
+            changes.push(RevisionChange::Text { text_field: field.as_str().to_string(), before: b.clone(), after: a.clone() });
+        }
+    }
+    changes
+}
+
+/// 把题目恢复到某条修订，恢复本身也记为一条新修订。整个操作在一个事务里完成。
+pub fn revert_to_revision(
+    conn: &Connection,
+    qid: QuestionId,
+    revision_id: i64,
+    now: Timestamp,
+) -> Result<RevertOutcome, AppError> {
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let target = get_revision(&tx, qid, revision_id)?.snapshot;
+    let question = QuestionDao::new(&tx)
+        .get_by_id(qid)?
+        .ok_or_else(|| AppError::NotFound(format!("question {}", i64::from(qid))))?;
+    if question.deleted_at.is_some() {
+        return Err(AppError::InvalidInput("question is in the recycle bin".to_string()));
+    }
+
+    let missing = apply_snapshot(&tx, qid, &target, now)?;
+    crate::server::cloze::sync_cards(&tx, qid, now)?;
+    crate::server::occlusion::sync_masks(&tx, qid, now)?;
+    crate::server::question_part::sync_part_metas(&tx, qid)?;
+    let revision_id = record(&tx, qid, now)?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(RevertOutcome {
+        revision_id,
+        missing_assets: missing.assets,
+        missing_subject: missing.subject,
+        missing_knowledge_points: missing.knowledge_points,
+    })
+}
+
+/// 把题目改成 `target` 的样子，返回无法恢复的部分。
+/// 科目和知识点按 id 找回，用的是现在的名称；早期修订没有 id 时按名称找。
+/// 已被合并或删除的不再重新登记，记为无法恢复。
+fn apply_snapshot(
+    conn: &Connection,
+    qid: QuestionId,
+    target: &QuestionSnapshot,
+    now: Timestamp,
+) -> Result<Unrestorable, AppError> {
+    QuestionDao::new(conn).update_name(qid, target.name.as_deref())?;
+    let mut missing = Unrestorable::default();
+
+    let md = MetaDao::new(conn);
+    md.delete_by_question_and_key(qid, MetaKey::System(SystemMetaKey::Subject))?;
+    if let Some(name) = &target.subject {
+        let sd = SubjectDao::new(conn);
+        let subject = match target.subject_id {
+            Some(id) => sd.get(id)?,
+            None => sd.get_by_name(name)?,
+        };
+        match subject {
+            Some(subject) => {
+                md.insert(qid, MetaKey::System(SystemMetaKey::Subject), &subject.name)?;
+            }
+            None => missing.subject = Some(name.clone()),
+        }
+    }
+    md.delete_by_question_and_key(qid, MetaKey::System(SystemMetaKey::KnowledgePoint))?;
+    let kd = KnowledgePointDao::new(conn);
+    for (i, name) in target.knowledge_points.iter().enumerate() {
+        let node = match target.knowledge_point_ids.get(i).copied().flatten() {
+            Some(id) => kd.get(id)?,
+            None => kd.get_by_name(name)?,
+        };
+        match node {
+            Some(node) => {
+                md.insert(qid, MetaKey::System(SystemMetaKey::KnowledgePoint), &node.name)?;
+            }
+            None => missing.knowledge_points.push(name.clone()),
+        }
+    }
+
+    // 资源：修订中有的恢复（含软删除的）并还原序号，修订中没有的移除
+    let ad = AssetDao::new(conn);
+    let existing = ad.list_all_by_question(qid)?;
+    for wanted in &target.assets {
+        match existing.iter().find(|a| i64::from(a.id) == wanted.id) {
+            Some(asset) => {
+                if asset.deleted_at.is_some() {
+                    ad.restore(asset.id)?;
+                }
+                if asset.sort_order != wanted.sort_order {
+                    ad.update_sort_order(asset.id, wanted.sort_order)?;
+                }
+            }
+            None => missing.assets.push(wanted.clone()),
+        }
+    }
+    for asset in existing.iter().filter(|a| a.deleted_at.is_none()) {
+        if !target.assets.iter().any(|w| w.id == i64::from(asset.id)) {
+            ad.delete(asset.id)?;
+        }
+    }
+
+    for (field, content) in [
+        (TextField::PROMPT, &target.prompt),
+        (TextField::ANSWER, &target.answer),
+        (TextField::EXPLANATION, &target.explanation),
+    ] {
+        question_manager::write_question_text(conn, qid, field, content.as_deref().unwrap_or(""), now)?;
+    }
+    Ok(missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::store::AssetStore;
+    use crate::domain::question_text::QuestionText;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_edits_record_revisions_and_revert_restores_assets() {
+        let conn = setup();
+        let root = tempdir().unwrap();
+        let src = tempdir().unwrap();
+        let store = AssetStore::new(root.path().to_path_buf());
+        let file = |name: &str| {
+            let p = src.path().join(name);
+            fs::write(&p, b"x").unwrap();
+            p.to_string_lossy().into_owned()
+        };
+        let text = QuestionText { prompt: Some("求顶点".into()), ..Default::default() };
+        let qid = question_manager::create_question(
+            &conn,
+            &store,
+            "二次函数".into(),
+            vec![file("q1.png"), file("q2.png")],
+            vec![],
+            vec![],
+            vec![],
+            Some("数学".into()),
+            vec!["函数".into()],
+            text,
+            None,
+        )
+        .unwrap();
+        let created = list_revisions(&conn, qid).unwrap();
+        assert_eq!(created.len(), 1);
+        let first = created[0].id;
+        let removed = created[0].snapshot.assets[0].clone();
+
+        let now = Timestamp::from(100);
+        let edit = QuestionText { prompt: Some("求对称轴".into()), ..Default::default() };
+        question_manager::update_question(&conn, qid, Some("改名".into()), None, Some(vec!["数列".into()]), edit, now)
+            .unwrap();
+        let aid = AssetDao::new(&conn).list_by_question(qid).unwrap()[0].id;
+        question_manager::delete_question_image(&conn, aid.0.to_string()).unwrap();
+        // 内容没有变化的保存不记修订
+        question_manager::update_question_meta(&conn, qid, Some("数学".into()), None).unwrap();
+
+        let revisions = list_revisions(&conn, qid).unwrap();
+        assert_eq!(revisions.len(), 3);
+        let latest = revisions[0].id;
+        let changes = diff_revisions(&conn, qid, first, latest).unwrap();
+        assert!(changes.contains(&RevisionChange::Name { before: Some("二次函数".into()), after: Some("改名".into()) }));
+        assert!(changes.contains(&RevisionChange::KnowledgePoints { added: vec!["数列".into()], removed: vec!["函数".into()] }));
+        assert!(changes.contains(&RevisionChange::Assets { added: vec![], removed: vec![removed.clone()], reordered: false }));
+        assert!(changes.iter().any(|c| matches!(c, RevisionChange::Text { text_field, .. } if text_field == "PROMPT")));
+        assert!(!changes.iter().any(|c| matches!(c, RevisionChange::Subject { .. })));
+
+        let outcome = revert_to_revision(&conn, qid, first, Timestamp::from(200)).unwrap();
+        assert!(outcome.missing_assets.is_empty());
+        assert!(outcome.revision_id.is_some());
+        let now_snapshot = snapshot(&conn, qid).unwrap();
+        assert_eq!(now_snapshot, created[0].snapshot);
+        assert_eq!(list_revisions(&conn, qid).unwrap().len(), 4);
+        assert!(diff_revisions(&conn, qid, first, 999).is_err());
+    }
+
+    #[test]
+    fn test_revert_follows_renamed_and_reports_merged_subject_and_knowledge_points() {
+        use crate::server::{knowledge_point, subject};
+
+        let conn = setup();
+        let store = AssetStore::new(tempdir().unwrap().path().to_path_buf());
+        let qid = question_manager::create_question(
+            &conn,
+            &store,
+            "数列求和".into(),
+            vec![],
+            vec![],
+            vec![],
+            vec![],
+            Some("数学".into()),
+            vec!["函数".into(), "数列".into()],
+            QuestionText::default(),
+            None,
+        )
+        .unwrap();
+        let first = list_revisions(&conn, qid).unwrap()[0].id;
+
+        let kp = |name: &str| KnowledgePointDao::new(&conn).get_by_name(name).unwrap().unwrap();
+        let math = SubjectDao::new(&conn).get_by_name("数学").unwrap().unwrap();
+        subject::rename_subject(&conn, math.id, "代数").unwrap();
+        knowledge_point::rename_knowledge_point(&conn, kp("函数").id, "函数图像").unwrap();
+        let series = knowledge_point::create_knowledge_point(&conn, "级数", Some(math.id), None, Timestamp(0)).unwrap();
+        knowledge_point::merge_knowledge_points(&conn, kp("数列").id, series.id).unwrap();
+        question_manager::update_question_meta(&conn, qid, Some("物理".into()), Some(vec![])).unwrap();
+
+        // 改名的按 id 找回，用新名称；合并掉的不再以旧名称登记
+        let outcome = revert_to_revision(&conn, qid, first, Timestamp::from(100)).unwrap();
+        assert_eq!(outcome.missing_subject, None);
+        assert_eq!(outcome.missing_knowledge_points, vec!["数列".to_string()]);
+        let current = snapshot(&conn, qid).unwrap();
+        assert_eq!(current.subject.as_deref(), Some("代数"));
+        assert_eq!(current.knowledge_points, vec!["函数图像".to_string()]);
+        assert!(SubjectDao::new(&conn).get_by_name("数学").unwrap().is_none());
+        assert!(KnowledgePointDao::new(&conn).get_by_name("数列").unwrap().is_none());
+
+        let physics = SubjectDao::new(&conn).get_by_name("物理").unwrap().unwrap();
+        subject::merge_subjects(&conn, math.id, physics.id).unwrap();
+        let outcome = revert_to_revision(&conn, qid, first, Timestamp::from(200)).unwrap();
+        assert_eq!(outcome.missing_subject.as_deref(), Some("数学"));
+        assert_eq!(snapshot(&conn, qid).unwrap().subject, None);
+    }
+
+    #[test]
+    fn test_first_edit_records_baseline() {
+        let conn = setup();
+        let qid = QuestionId::from(crate::db::insert_question(&conn, Some("旧题"), "NEW", 0).unwrap());
+        assert!(list_revisions(&conn, qid).unwrap().is_empty());
+
+        question_manager::rename_question(&conn, qid, "新题".into()).unwrap();
+        let revisions = list_revisions(&conn, qid).unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[1].snapshot.name.as_deref(), Some("旧题"));
+        assert_eq!(revisions[0].snapshot.name.as_deref(), Some("新题"));
+    }
+}
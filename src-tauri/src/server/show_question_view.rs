@@ -4,17 +4,17 @@
 //! 题目管理器（`question_manager.rs`）会调用 `ViewDao` 来获取题目视图数据，并将其转换为领域模型 `Question`，供上层调用。
 //! 主要功能为向UI层提供题目基本信息，但不包含图片等具体信息。
 //! 只在题目管理与查询界面使用。
-use crate::dao::meta_dao::MetaDao;
 use crate::dao::ViewDao;
 use crate::domain::ids::QuestionId;
 use crate::domain::view::View;
 use crate::error::AppError;
+use crate::server::knowledge_point;
+use crate::server::user_field::{self, UserFieldFilter};
 use rusqlite::Connection;
 
-/// 列出所有科目（去重）。
+/// 列出所有科目（按科目的显示顺序）。
 pub fn list_subjects(conn: &Connection) -> Result<Vec<String>, AppError> {
-    let md = MetaDao::new(conn);
-    Ok(md.list_all_subjects()?)
+    crate::server::subject::list_subject_names(conn)
 }
 
 /// 按页显示已删除题目。
@@ -40,20 +40,31 @@ pub fn find_question_by_name(conn: &Connection, name: String) -> Result<Vec<View
     Ok(vd.get_by_name(&name)?)
 }
 
-/// 分类查询：按 `subject` 与 `state` 过滤，`page` 为 0-indexed。
+/// 分类查询：按 `subject` 与 `state` 过滤，可叠加知识点、标签与用户自定义字段过滤；`page` 为 0-indexed。
+/// 选中的知识点包含其全部后代节点；`tags` 须全部带有。
+#[allow(clippy::too_many_arguments)]
 pub fn classify_questions(
     conn: &Connection,
     subject: Option<String>,
     question_state: Option<String>,
+    knowledge_point: Option<String>,
+    tags: &[String],
+    field_filters: &[UserFieldFilter],
     page: usize,
     page_size: usize,
 ) -> Result<Vec<View>, AppError> {
     let vd = ViewDao::new(conn);
     let offset = (page * page_size) as i64;
     let limit = page_size as i64;
+    let mut meta_filters = user_field::to_meta_filters(conn, field_filters)?;
+    if let Some(kp) = knowledge_point.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        meta_filters.push(knowledge_point::knowledge_point_filter(conn, kp)?);
+    }
     Ok(vd.list_classified(
         subject.as_deref().filter(|s| !s.is_empty()),
         question_state.as_deref().filter(|s| !s.is_empty()),
+        tags,
+        &meta_filters,
         offset,
         limit,
     )?)
@@ -62,13 +73,14 @@ pub fn classify_questions(
 /// 搜索查询：`query` 是用户输入的字符串。
 /// - 若能解析为 `i64`，则走 ID 精确模式（0 或 1 行，`page` 必须为 0）。
 /// - 否则走模糊模式：`name` 或知识点（`meta.key='system.KnowledgePoint'`）LIKE `%query%`。
-/// - 可叠加 `subject` / `state`（Mode B）。
+/// - 可叠加 `subject` / `state` / `tags`（Mode B），标签须全部带有。
 /// - `query` 去除首尾空格后若为空，直接返回空列表（不查库）。
 pub fn search_questions(
     conn: &Connection,
     query: String,
     subject: Option<String>,
     question_state: Option<String>,
+    tags: &[String],
     page: usize,
     page_size: usize,
 ) -> Result<Vec<View>, AppError> {
@@ -91,5 +103,5 @@ pub fn search_questions(
     // 模糊模式
     let offset = (page * page_size) as i64;
     let limit = page_size as i64;
-    Ok(vd.search_fuzzy(trimmed, subject_filter, state_filter, offset, limit)?)
+    Ok(vd.search_fuzzy(trimmed, subject_filter, state_filter, tags, offset, limit)?)
 }
\ No newline at end of file
@@ -0,0 +1,317 @@
+//! 科目管理
+//!
+//! 科目登记在 `subject` 表，包含显示名、颜色、排序和科目配置（归档、每日题数、学习日等）。
+//! 题目以 `system.Subject` 元信息引用科目 id，名称经 `meta_view` 联查得到，改名不必改动题目；
+//! 推荐缓存（`recommendation.subject`）和顺延名额（`subject_carry_over`）仍按名称记录，
+//! 改名与合并时级联更新。统计均由元信息实时聚合，随之生效。
+
+use std::collections::HashMap;
+
+use crate::app::config::{self, AppSettings};
+use crate::dao::subject_dao::SubjectDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::domain::subject::{Subject, SubjectConfig};
+use crate::error::AppError;
+use crate::util::time::Timestamp;
+
+fn get_subject(conn: &Connection, id: i64) -> Result<Subject, AppError> {
+    SubjectDao::new(conn)
+        .get(id)?
+        .ok_or_else(|| AppError::NotFound(format!("subject {}", id)))
+}
+
+fn check_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("subject name must not be empty".to_string()));
+    }
+    Ok(name.to_string())
+}
+
+/// 全部科目，按显示顺序
+pub fn list_subjects(conn: &Connection) -> Result<Vec<Subject>, AppError> {
+    Ok(SubjectDao::new(conn).list()?)
+}
+
+/// 全部科目名称，按显示顺序
+pub fn list_subject_names(conn: &Connection) -> Result<Vec<String>, AppError> {
+    Ok(list_subjects(conn)?.into_iter().map(|s| s.name).collect())
+}
+
+/// 新建科目（排在最后）
+pub fn create_subject(conn: &Connection, name: &str, now: Timestamp) -> Result<Subject, AppError> {
+    let name = check_name(name)?;
+    let dao = SubjectDao::new(conn);
+    if dao.get_by_name(&name)?.is_some() {
+        return Err(AppError::InvalidInput(format!("subject already exists: {}", name)));
+    }
+    let id = dao.insert(&name, now)?;
+    get_subject(conn, id)
+}
+
+/// 修改科目颜色与配置
+pub fn update_subject(
+    conn: &Connection,
+    id: i64,
+    color: Option<String>,
+    config: SubjectConfig,
+) -> Result<Subject, AppError> {
+    let mut subject = get_subject(conn, id)?;
+    subject.color = color.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+    subject.config = config;
+    SubjectDao::new(conn).update(&subject)?;
+    Ok(subject)
+}
+
+/// 按给定 id 顺序重排科目；未列出的科目排在其后，保持原有相对顺序
+pub fn reorder_subjects(conn: &Connection, ids: &[i64]) -> Result<(), AppError> {
+    let dao = SubjectDao::new(conn);
+    let mut order: Vec<i64> = Vec::new();
+    for &id in ids {
+        get_subject(conn, id)?;
+        if !order.contains(&id) {
+            order.push(id);
+        }
+    }
+    for s in dao.list()? {
+        if !order.contains(&s.id) {
+            order.push(s.id);
+        }
+    }
+    for (i, id) in order.into_iter().enumerate() {
+        dao.set_sort_order(id, i as i64 + 1)?;
+    }
+    Ok(())
+}
+
+/// 科目改名，级联更新推荐缓存和顺延名额中的名称；题目和试卷引用的是 id，无需改动。
+/// 新名称已被其他科目使用时拒绝，应改用 `merge_subjects`。
+pub fn rename_subject(conn: &Connection, id: i64, new_name: &str) -> Result<Subject, AppError> {
+    let new_name = check_name(new_name)?;
+    let subject = get_subject(conn, id)?;
+    if subject.name == new_name {
+        return Ok(subject);
+    }
+    if SubjectDao::new(conn).get_by_name(&new_name)?.is_some() {
+        return Err(AppError::InvalidInput(format!(
+            "subject already exists: {} (merge instead)",
+            new_name
+        )));
+    }
+
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    SubjectDao::new(&tx).rename(id, &new_name)?;
+    tx.execute("UPDATE recommendation SET subject = ?2 WHERE subject = ?1", [&subject.name, &new_name])
+        .map_err(DbError::from)?;
+    tx.execute(
+        "UPDATE subject_carry_over SET subject = ?2 WHERE subject = ?1",
+        [&subject.name, &new_name],
+    )
+    .map_err(DbError::from)?;
+    tx.commit().map_err(DbError::from)?;
+
+    get_subject(conn, id)
+}
+
+/// 把科目 `from_id` 合并进 `into_id`：题目、试卷和推荐缓存改指向目标科目，
+/// 顺延名额累加到目标科目，知识点树并入目标科目，最后删除源科目。目标科目的配置保持不变。
+pub fn merge_subjects(conn: &Connection, from_id: i64, into_id: i64) -> Result<Subject, AppError> {
+    if from_id == into_id {
+        return Err(AppError::InvalidInput("cannot merge a subject into itself".to_string()));
+    }
+    let from = get_subject(conn, from_id)?;
+    let into = get_subject(conn, into_id)?;
+
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    tx.execute(
+        "UPDATE meta SET value = ?2 WHERE key = 'system.Subject' AND value = ?1",
+        [from_id.to_string(), into_id.to_string()],
+    )
+    .map_err(DbError::from)?;
+    tx.execute("UPDATE recommendation SET subject = ?2 WHERE subject = ?1", [&from.name, &into.name])
+        .map_err(DbError::from)?;
+    tx.execute(
+        "INSERT INTO subject_carry_over (subject, amount, updated_day)
+         SELECT ?2, amount, updated_day FROM subject_carry_over WHERE subject = ?1
+         ON CONFLICT(subject) DO UPDATE SET
+             amount = amount + excluded.amount,
+             updated_day = MAX(updated_day, excluded.updated_day)",
+        [&from.name, &into.name],
+    )
+    .map_err(DbError::from)?;
+    tx.execute("DELETE FROM subject_carry_over WHERE subject = ?1", [&from.name])
+        .map_err(DbError::from)?;
+    crate::dao::KnowledgePointDao::new(&tx).move_subject(from_id, into_id)?;
+    crate::dao::PaperDao::new(&tx).move_subject(from_id, into_id)?;
+    SubjectDao::new(&tx).delete(from_id)?;
+    tx.commit().map_err(DbError::from)?;
+
+    get_subject(conn, into_id)
+}
+
+/// 科目名 -> 配置，供推荐系统使用
+pub fn subject_configs(conn: &Connection) -> Result<HashMap<String, SubjectConfig>, DbError> {
+    Ok(SubjectDao::new(conn)
+        .list()?
+        .into_iter()
+        .map(|s| (s.name, s.config))
+        .collect())
+}
+
+/// 读取设置并填入库中的科目配置。需要科目配置的地方都应通过它取设置。
+pub fn load_settings(conn: &Connection) -> Result<AppSettings, DbError> {
+    let mut settings = config::load_settings();
+    settings.subjects = subject_configs(conn)?;
+    Ok(settings)
+}
+
+/// 导入旧版配置文件中按名称保存的科目配置：
+/// 科目不存在时新建；已存在且仍是默认配置时写入。返回导入的科目数。
+pub fn import_legacy_configs(
+    conn: &Connection,
+    legacy: &HashMap<String, SubjectConfig>,
+    now: Timestamp,
+) -> Result<usize, AppError> {
+    let dao = SubjectDao::new(conn);
+    let mut names: Vec<&String> = legacy.keys().collect();
+    names.sort();
+
+    let mut imported = 0;
+    for name in names {
+        let Ok(name_checked) = check_name(name) else { continue };
+        let mut subject = match dao.get_by_name(&name_checked)? {
+            Some(s) => s,
+            None => get_subject(conn, dao.insert(&name_checked, now)?)?,
+        };
+        if subject.config != SubjectConfig::default() {
+            continue;
+        }
+        subject.config = legacy[name].clone();
+        dao.update(&subject)?;
+        imported += 1;
+    }
+    Ok(imported)
+}
+
+/// 启动时调用：把配置文件中的旧版科目配置导入科目表，并从配置文件中移除
+/// （`AppSettings.subjects` 不再序列化，重新保存即移除）。
+pub fn import_legacy_settings(conn: &Connection) -> Result<usize, AppError> {
+    let settings = config::load_settings();
+    if settings.subjects.is_empty() {
+        return Ok(0);
+    }
+    let imported = import_legacy_configs(conn, &settings.subjects, crate::util::time::now_ts())?;
+    config::save_settings(&settings).map_err(AppError::InvalidInput)?;
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn question_with_subject(conn: &Connection, subject: &str) -> i64 {
+        let qid = crate::db::insert_question(conn, Some("q"), "NEW", 0).unwrap();
+        crate::db::insert_meta(conn, qid, "system.Subject", subject).unwrap();
+        qid
+    }
+
+    fn subject_of(conn: &Connection, qid: i64) -> Vec<String> {
+        crate::db::select_meta_values_by_question_key(conn, qid, "system.Subject").unwrap()
+    }
+
+    #[test]
+    fn test_new_subject_values_are_registered_in_order() {
+        let conn = setup();
+        question_with_subject(&conn, "数学");
+        question_with_subject(&conn, "英语");
+        question_with_subject(&conn, "数学");
+        create_subject(&conn, "物理", Timestamp(0)).unwrap();
+        assert_eq!(list_subject_names(&conn).unwrap(), vec!["数学", "英语", "物理"]);
+        assert!(create_subject(&conn, " 物理 ", Timestamp(0)).is_err());
+
+        let ids: Vec<i64> = list_subjects(&conn).unwrap().iter().map(|s| s.id).collect();
+        reorder_subjects(&conn, &[ids[2], ids[0]]).unwrap();
+        assert_eq!(list_subject_names(&conn).unwrap(), vec!["物理", "数学", "英语"]);
+    }
+
+    #[test]
+    fn test_rename_keeps_config_and_cascades() {
+        let conn = setup();
+        let q = question_with_subject(&conn, "数学");
+        let math = SubjectDao::new(&conn).get_by_name("数学").unwrap().unwrap();
+        let cfg = SubjectConfig { recommendation_limit: Some(5), ..SubjectConfig::default() };
+        update_subject(&conn, math.id, Some("#2196F3".into()), cfg.clone()).unwrap();
+        conn.execute(
+            "INSERT INTO recommendation (day, question_id, score, subject) VALUES (1, ?1, 1.0, '数学')",
+            [q],
+        )
+        .unwrap();
+        crate::dao::CarryOverDao::new(&conn).set("数学", 2, 1).unwrap();
+
+        // 题目上存的是科目 id，改名不改动题目
+        let stored = || -> String {
+            conn.query_row("SELECT value FROM meta WHERE question_id = ?1 AND key = 'system.Subject'", [q], |r| r.get(0))
+                .unwrap()
+        };
+        assert_eq!(stored(), math.id.to_string());
+
+        let renamed = rename_subject(&conn, math.id, "高等数学").unwrap();
+        assert_eq!(renamed.config, cfg);
+        assert_eq!(subject_of(&conn, q), vec!["高等数学"]);
+        assert_eq!(stored(), math.id.to_string());
+        let rec: String = conn
+            .query_row("SELECT subject FROM recommendation WHERE question_id = ?1", [q], |r| r.get(0))
+            .unwrap();
+        assert_eq!(rec, "高等数学");
+        assert_eq!(subject_configs(&conn).unwrap()["高等数学"], cfg);
+        assert_eq!(crate::dao::CarryOverDao::new(&conn).list().unwrap().get("高等数学"), Some(&2));
+
+        question_with_subject(&conn, "物理");
+        assert!(rename_subject(&conn, math.id, "物理").is_err());
+    }
+
+    #[test]
+    fn test_merge_moves_questions_and_removes_source() {
+        let conn = setup();
+        let q1 = question_with_subject(&conn, "代数");
+        let q2 = question_with_subject(&conn, "数学");
+        let dao = SubjectDao::new(&conn);
+        let algebra = dao.get_by_name("代数").unwrap().unwrap();
+        let math = dao.get_by_name("数学").unwrap().unwrap();
+        crate::dao::CarryOverDao::new(&conn).set("代数", 2, 1).unwrap();
+        crate::dao::CarryOverDao::new(&conn).set("数学", 3, 2).unwrap();
+
+        merge_subjects(&conn, algebra.id, math.id).unwrap();
+        assert_eq!(subject_of(&conn, q1), vec!["数学"]);
+        assert_eq!(subject_of(&conn, q2), vec!["数学"]);
+        assert_eq!(list_subject_names(&conn).unwrap(), vec!["数学"]);
+        let carry = crate::dao::CarryOverDao::new(&conn).list().unwrap();
+        assert_eq!(carry.len(), 1);
+        assert_eq!(carry.get("数学"), Some(&5));
+    }
+
+    #[test]
+    fn test_import_legacy_configs_only_fills_defaults() {
+        let conn = setup();
+        question_with_subject(&conn, "数学");
+        let math = SubjectDao::new(&conn).get_by_name("数学").unwrap().unwrap();
+        let mine = SubjectConfig { archived: true, ..SubjectConfig::default() };
+        update_subject(&conn, math.id, None, mine.clone()).unwrap();
+
+        let legacy_cfg = SubjectConfig { carry_over_unused: true, ..SubjectConfig::default() };
+        let legacy: HashMap<String, SubjectConfig> =
+            [("数学".to_string(), legacy_cfg.clone()), ("化学".to_string(), legacy_cfg.clone())].into();
+        assert_eq!(import_legacy_configs(&conn, &legacy, Timestamp(0)).unwrap(), 1);
+
+        let configs = subject_configs(&conn).unwrap();
+        assert_eq!(configs["数学"], mine);
+        assert_eq!(configs["化学"], legacy_cfg);
+    }
+}
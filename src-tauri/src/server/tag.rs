@@ -0,0 +1,127 @@
+//! 题目标签
+//!
+//! 标签是轻量的自由标记（如 期中、老师讲过、易混），与知识点树分开：不分科目、没有层级，
+//! 也无需预先登记，打到题目上即存在，最后一道题去掉后即消失。
+//! 批量打标签 / 去标签在一个事务里完成。
+
+use serde::Serialize;
+
+use crate::dao::tag_dao::TagDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::error::AppError;
+
+/// 标签及带有该标签的题目数
+#[derive(Debug, Clone, Serialize)]
+pub struct TagCount {
+    pub name: String,
+    pub question_count: i64,
+}
+
+/// 去掉首尾空白、空标签与重复项，保持原顺序
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if !out.iter().any(|t| t == tag) {
+            out.push(tag.to_string());
+        }
+    }
+    out
+}
+
+fn check_tags(tags: &[String]) -> Result<Vec<String>, AppError> {
+    let tags = normalize_tags(tags);
+    if tags.is_empty() {
+        return Err(AppError::InvalidInput("tags must not be empty".to_string()));
+    }
+    Ok(tags)
+}
+
+/// 给一组题目打上标签，已删除或不存在的题目跳过；返回新增的（题目, 标签）对数
+pub fn tag_questions(conn: &Connection, question_ids: &[i64], tags: &[String]) -> Result<usize, AppError> {
+    let tags = check_tags(tags)?;
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let dao = TagDao::new(&tx);
+    let mut added = 0;
+    for &qid in question_ids {
+        for tag in &tags {
+            if dao.add(qid, tag)? {
+                added += 1;
+            }
+        }
+    }
+    tx.commit().map_err(DbError::from)?;
+    Ok(added)
+}
+
+/// 去掉一组题目上的标签；返回实际去掉的（题目, 标签）对数
+pub fn untag_questions(conn: &Connection, question_ids: &[i64], tags: &[String]) -> Result<usize, AppError> {
+    let tags = check_tags(tags)?;
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    let dao = TagDao::new(&tx);
+    let mut removed = 0;
+    for &qid in question_ids {
+        for tag in &tags {
+            if dao.remove(qid, tag)? {
+                removed += 1;
+            }
+        }
+    }
+    tx.commit().map_err(DbError::from)?;
+    Ok(removed)
+}
+
+/// 全部标签及题目数（只计未删除题目），按名称排序
+pub fn list_tags(conn: &Connection) -> Result<Vec<TagCount>, AppError> {
+    Ok(TagDao::new(conn)
+        .list_with_counts()?
+        .into_iter()
+        .map(|(name, question_count)| TagCount { name, question_count })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::view_schema::{select_views_classified, select_views_search_fuzzy};
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_bulk_tag_untag_and_filter() {
+        let conn = setup();
+        let q1 = crate::db::insert_question(&conn, Some("二次函数顶点"), "NEW", 0).unwrap();
+        let q2 = crate::db::insert_question(&conn, Some("二次函数图像"), "NEW", 0).unwrap();
+        let q3 = crate::db::insert_question(&conn, Some("数列求和"), "NEW", 0).unwrap();
+
+        assert_eq!(tag_questions(&conn, &[q1, q2], &tags(&["期中", " 易混 ", "期中"])).unwrap(), 4);
+        // 重复打标签不会产生重复行
+        assert_eq!(tag_questions(&conn, &[q1, q3], &tags(&["期中"])).unwrap(), 1);
+        assert!(tag_questions(&conn, &[q1], &tags(&[" "])).is_err());
+
+        let listed = list_tags(&conn).unwrap();
+        let counts: Vec<(&str, i64)> = listed.iter().map(|t| (t.name.as_str(), t.question_count)).collect();
+        assert_eq!(counts, vec![("易混", 2), ("期中", 3)]);
+
+        let both = tags(&["期中", "易混"]);
+        let rows = select_views_classified(&conn, None, None, &both, &[], 10, 0).unwrap();
+        let mut ids: Vec<i64> = rows.iter().map(|r| r.id).collect();
+        ids.sort();
+        assert_eq!(ids, vec![q1, q2]);
+        let rows = select_views_search_fuzzy(&conn, "%二次%", None, None, &tags(&["期中"]), 10, 0).unwrap();
+        assert_eq!(rows.len(), 2);
+
+        assert_eq!(untag_questions(&conn, &[q1, q2, q3], &tags(&["易混"])).unwrap(), 2);
+        assert!(select_views_classified(&conn, None, None, &both, &[], 10, 0).unwrap().is_empty());
+        let view = crate::dao::ViewDao::new(&conn).get_by_id(q1.into()).unwrap();
+        assert_eq!(view.tags, vec!["期中"]);
+    }
+}
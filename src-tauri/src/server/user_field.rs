@@ -0,0 +1,387 @@
+//! 用户自定义元信息字段
+//!
+//! 字段定义（名称、类型、可选值、基数）登记在 `user_field` 表，值存放在 `meta` 表（key 为 `user.<name>`）。
+//! 写入值时按字段类型校验并规范化，因此同一字段的值可以直接比较和过滤；
+//! 修改字段定义时，现有值必须全部符合新定义，否则拒绝修改。
+
+use serde::{Deserialize, Serialize};
+
+use crate::dao::meta_dao::MetaDao;
+use crate::dao::question_dao::QuestionDao;
+use crate::dao::user_field_dao::UserFieldDao;
+use crate::db::connection::Connection;
+use crate::db::error::DbError;
+use crate::db::schema::view_schema::{MetaCond, MetaFilter};
+use crate::domain::enums::{Cardinality, MetaKey, UserFieldType};
+use crate::domain::ids::QuestionId;
+use crate::domain::user_field::UserField;
+use crate::error::AppError;
+use crate::util::time::Timestamp;
+
+/// 某题某字段的值
+#[derive(Debug, Clone, Serialize)]
+pub struct UserFieldValues {
+    pub name: String,
+    pub values: Vec<String>,
+}
+
+/// 列表页的字段过滤条件；按字段类型解释：
+/// - `equals`：值相等（会先按字段类型规范化）
+/// - `contains`：仅 TEXT 字段，值包含子串
+/// - `min` / `max`：仅 NUMBER / DATE 字段，闭区间，端点可缺省
+#[derive(Debug, Clone, Deserialize)]
+pub struct UserFieldFilter {
+    pub name: String,
+    pub equals: Option<String>,
+    pub contains: Option<String>,
+    pub min: Option<String>,
+    pub max: Option<String>,
+}
+
+/// 校验字段定义本身：名称非空；ENUM 必须有可选值，其余类型不能有可选值
+fn check_definition(field: &mut UserField) -> Result<(), AppError> {
+    field.name = field.name.trim().to_string();
+    if field.name.is_empty() {
+        return Err(AppError::InvalidInput("field name must not be empty".to_string()));
+    }
+    let mut allowed: Vec<String> = Vec::new();
+    for v in &field.allowed_values {
+        let v = v.trim();
+        if !v.is_empty() && !allowed.iter().any(|a| a == v) {
+            allowed.push(v.to_string());
+        }
+    }
+    field.allowed_values = allowed;
+    match (field.field_type, field.allowed_values.is_empty()) {
+        (UserFieldType::ENUM, true) => Err(AppError::InvalidInput(format!(
+            "{}: enum field needs allowed values",
+            field.name
+        ))),
+        (UserFieldType::ENUM, false) | (_, true) => Ok(()),
+        (_, false) => Err(AppError::InvalidInput(format!(
+            "{}: only enum fields take allowed values",
+            field.name
+        ))),
+    }
+}
+
+fn get_field(conn: &Connection, name: &str) -> Result<UserField, AppError> {
+    UserFieldDao::new(conn)
+        .get(name)?
+        .ok_or_else(|| AppError::NotFound(format!("user field {}", name)))
+}
+
+/// 新建字段
+pub fn define_user_field(
+    conn: &Connection,
+    name: &str,
+    field_type: UserFieldType,
+    allowed_values: Vec<String>,
+    cardinality: Cardinality,
+    now: Timestamp,
+) -> Result<UserField, AppError> {
+    let mut field = UserField {
+        name: name.to_string(),
+        field_type,
+        allowed_values,
+        cardinality,
+        created_at: now,
+    };
+    check_definition(&mut field)?;
+    let dao = UserFieldDao::new(conn);
+    if dao.get(&field.name)?.is_some() {
+        return Err(AppError::InvalidInput(format!("user field {} already exists", field.name)));
+    }
+    dao.insert(&field)?;
+    Ok(field)
+}
+
+/// 修改字段的类型、可选值与基数。
+/// 现有值按新定义重新规范化；有值不合法、或改为 SINGLE 时有题目存在多个值，则拒绝修改。
+pub fn update_user_field(
+    conn: &Connection,
+    name: &str,
+    field_type: UserFieldType,
+    allowed_values: Vec<String>,
+    cardinality: Cardinality,
+) -> Result<UserField, AppError> {
+    let dao = UserFieldDao::new(conn);
+    let old = get_field(conn, name)?;
+    let mut field = UserField {
+        field_type,
+        allowed_values,
+        cardinality,
+        ..old
+    };
+    check_definition(&mut field)?;
+
+    // 按题目分组重新规范化现有值
+    let mut grouped: Vec<(i64, Vec<String>, bool)> = Vec::new();
+    for (qid, value) in dao.list_values(&field.name)? {
+        let normalized = field.normalize(&value).map_err(AppError::InvalidInput)?;
+        let changed = normalized != value;
+        match grouped.last_mut() {
+            Some((last, values, dirty)) if *last == qid => {
+                if !values.contains(&normalized) {
+                    values.push(normalized);
+                }
+                *dirty |= changed;
+            }
+            _ => grouped.push((qid, vec![normalized], changed)),
+        }
+    }
+    if field.cardinality == Cardinality::SINGLE {
+        if let Some((qid, _, _)) = grouped.iter().find(|(_, values, _)| values.len() > 1) {
+            return Err(AppError::InvalidInput(format!(
+                "{}: question {} has several values",
+                field.name, qid
+            )));
+        }
+    }
+
+    // 定义与现有值一起改写，中途失败时整体回滚
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    UserFieldDao::new(&tx).update(&field)?;
+    let md = MetaDao::new(&tx);
+    for (qid, values, dirty) in grouped {
+        if dirty {
+            write_values(&md, QuestionId::from(qid), &field, &values)?;
+        }
+    }
+    tx.commit().map_err(DbError::from)?;
+    Ok(field)
+}
+
+/// 删除字段及其在所有题目上的值，定义与值在一个事务里删除
+pub fn delete_user_field(conn: &Connection, name: &str) -> Result<(), AppError> {
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    if !UserFieldDao::new(&tx).delete(name)? {
+        return Err(AppError::NotFound(format!("user field {}", name)));
+    }
+    tx.commit().map_err(DbError::from)?;
+    Ok(())
+}
+
+/// 全部字段定义
+pub fn list_user_fields(conn: &Connection) -> Result<Vec<UserField>, AppError> {
+    Ok(UserFieldDao::new(conn).list()?)
+}
+
+fn write_values(md: &MetaDao, qid: QuestionId, field: &UserField, values: &[String]) -> Result<(), AppError> {
+    md.delete_by_question_and_key(qid, field.meta_key())?;
+    for value in values {
+        md.insert(qid, field.meta_key(), value)?;
+    }
+    Ok(())
+}
+
+/// 设置某题某字段的值（整体替换）；空列表表示清空。
+/// 值按字段类型校验并规范化，重复值只保留一个；SINGLE 字段最多一个值。
+/// 题目不存在或在回收站中时拒绝写入。
+pub fn set_user_field_values(
+    conn: &Connection,
+    qid: QuestionId,
+    name: &str,
+    values: Vec<String>,
+) -> Result<Vec<String>, AppError> {
+    let question = QuestionDao::new(conn)
+        .get_by_id(qid)?
+        .ok_or_else(|| AppError::NotFound(format!("question {}", i64::from(qid))))?;
+    if question.deleted_at.is_some() {
+        return Err(AppError::InvalidInput("question is in the recycle bin".to_string()));
+    }
+    let field = get_field(conn, name)?;
+    let mut normalized: Vec<String> = Vec::new();
+    for value in values {
+        let value = field.normalize(&value).map_err(AppError::InvalidInput)?;
+        if !normalized.contains(&value) {
+            normalized.push(value);
+        }
+    }
+    if field.cardinality == Cardinality::SINGLE && normalized.len() > 1 {
+        return Err(AppError::InvalidInput(format!("{}: only one value allowed", field.name)));
+    }
+    // 先删后插，放在一个事务里，中途失败不会丢掉原有值
+    let tx = conn.unchecked_transaction().map_err(DbError::from)?;
+    write_values(&MetaDao::new(&tx), qid, &field, &normalized)?;
+    tx.commit().map_err(DbError::from)?;
+    Ok(normalized)
+}
+
+/// 某题全部已定义字段的值（按字段定义顺序，未填写的字段值为空列表）
+pub fn get_user_field_values(conn: &Connection, qid: QuestionId) -> Result<Vec<UserFieldValues>, AppError> {
+    let md = MetaDao::new(conn);
+    let mut out = Vec::new();
+    for field in UserFieldDao::new(conn).list()? {
+        let values = md.get_values_by_question_key(qid, &field.meta_key().as_str())?;
+        out.push(UserFieldValues { name: field.name, values });
+    }
+    Ok(out)
+}
+
+/// 把列表页的字段过滤条件翻译为元信息过滤
+pub fn to_meta_filters(conn: &Connection, filters: &[UserFieldFilter]) -> Result<Vec<MetaFilter>, AppError> {
+    let mut out = Vec::new();
+    for filter in filters {
+        let field = get_field(conn, &filter.name)?;
+        let key = MetaKey::User(field.name.clone()).as_str();
+        let non_empty = |v: &Option<String>| v.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(String::from);
+
+        if let Some(v) = non_empty(&filter.equals) {
+            let v = field.normalize(&v).map_err(AppError::InvalidInput)?;
+            out.push(MetaFilter { key: key.clone(), cond: MetaCond::Equals(v) });
+        }
+        if let Some(v) = non_empty(&filter.contains) {
+            if field.field_type != UserFieldType::TEXT {
+                return Err(AppError::InvalidInput(format!("{}: contains only applies to text fields", field.name)));
+            }
+            out.push(MetaFilter { key: key.clone(), cond: MetaCond::Contains(v) });
+        }
+        let (min, max) = (non_empty(&filter.min), non_empty(&filter.max));
+        if min.is_some() || max.is_some() {
+            let cond = match field.field_type {
+                UserFieldType::NUMBER => {
+                    let parse = |v: Option<String>| -> Result<Option<f64>, AppError> {
+                        match v {
+                            Some(v) => field.normalize(&v).map_err(AppError::InvalidInput).map(|n| n.parse().ok()),
+                            None => Ok(None),
+                        }
+                    };
+                    MetaCond::NumberRange { min: parse(min)?, max: parse(max)? }
+                }
+                UserFieldType::DATE => {
+                    let parse = |v: Option<String>| v.map(|v| field.normalize(&v)).transpose().map_err(AppError::InvalidInput);
+                    MetaCond::TextRange { min: parse(min)?, max: parse(max)? }
+                }
+                _ => {
+                    return Err(AppError::InvalidInput(format!(
+                        "{}: range only applies to number and date fields",
+                        field.name
+                    )))
+                }
+            };
+            out.push(MetaFilter { key, cond });
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::view_schema::select_views_classified;
+
+    fn setup() -> rusqlite::Connection {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::db::migrate(&mut conn).unwrap();
+        conn
+    }
+
+    fn question(conn: &Connection, name: &str) -> QuestionId {
+        QuestionId::from(crate::db::insert_question(conn, Some(name), "NEW", 0).unwrap())
+    }
+
+    #[test]
+    fn test_values_are_validated_and_normalized() {
+        let conn = setup();
+        let now = Timestamp(0);
+        define_user_field(&conn, "页码", UserFieldType::NUMBER, vec![], Cardinality::SINGLE, now).unwrap();
+        define_user_field(&conn, "考试名称", UserFieldType::ENUM, vec!["期中".into(), "期末".into()], Cardinality::MULTIPLE, now)
+            .unwrap();
+        let q = question(&conn, "q");
+
+        assert_eq!(set_user_field_values(&conn, q, "页码", vec![" 12.0 ".into()]).unwrap(), vec!["12"]);
+        assert!(matches!(
+            set_user_field_values(&conn, q, "页码", vec!["十二".into()]),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            set_user_field_values(&conn, q, "页码", vec!["1".into(), "2".into()]),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            set_user_field_values(&conn, q, "考试名称", vec!["月考".into()]),
+            Err(AppError::InvalidInput(_))
+        ));
+        set_user_field_values(&conn, q, "考试名称", vec!["期中".into(), "期末".into(), "期中".into()]).unwrap();
+
+        let values = get_user_field_values(&conn, q).unwrap();
+        assert_eq!(values[0].values, vec!["12"]);
+        assert_eq!(values[1].values, vec!["期中", "期末"]);
+    }
+
+    #[test]
+    fn test_values_require_active_question() {
+        let conn = setup();
+        define_user_field(&conn, "页码", UserFieldType::NUMBER, vec![], Cardinality::SINGLE, Timestamp(0)).unwrap();
+        assert!(matches!(
+            set_user_field_values(&conn, QuestionId::from(999), "页码", vec!["1".into()]),
+            Err(AppError::NotFound(_))
+        ));
+
+        let q = question(&conn, "q");
+        set_user_field_values(&conn, q, "页码", vec!["1".into()]).unwrap();
+        QuestionDao::new(&conn).update_deleted_at(q, Some(Timestamp(1))).unwrap();
+        assert!(matches!(
+            set_user_field_values(&conn, q, "页码", vec!["2".into()]),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert_eq!(MetaDao::new(&conn).get_values_by_question_key(q, "user.页码").unwrap(), vec!["1"]);
+    }
+
+    #[test]
+    fn test_update_rejects_incompatible_values() {
+        let conn = setup();
+        define_user_field(&conn, "老师批注", UserFieldType::TEXT, vec![], Cardinality::MULTIPLE, Timestamp(0)).unwrap();
+        let q = question(&conn, "q");
+        set_user_field_values(&conn, q, "老师批注", vec!["3.50".into(), "注意符号".into()]).unwrap();
+
+        // 现有值不是数字，不能改为 NUMBER；有多个值，不能改为 SINGLE
+        assert!(update_user_field(&conn, "老师批注", UserFieldType::NUMBER, vec![], Cardinality::MULTIPLE).is_err());
+        assert!(update_user_field(&conn, "老师批注", UserFieldType::TEXT, vec![], Cardinality::SINGLE).is_err());
+
+        set_user_field_values(&conn, q, "老师批注", vec!["3.50".into()]).unwrap();
+        update_user_field(&conn, "老师批注", UserFieldType::NUMBER, vec![], Cardinality::SINGLE).unwrap();
+        assert_eq!(get_user_field_values(&conn, q).unwrap()[0].values, vec!["3.5"]);
+
+        delete_user_field(&conn, "老师批注").unwrap();
+        assert!(get_user_field_values(&conn, q).unwrap().is_empty());
+        assert!(MetaDao::new(&conn).get_values_by_question_key(q, "user.老师批注").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_classified_filters_by_user_fields() {
+        let conn = setup();
+        let now = Timestamp(0);
+        define_user_field(&conn, "页码", UserFieldType::NUMBER, vec![], Cardinality::SINGLE, now).unwrap();
+        define_user_field(&conn, "考试日期", UserFieldType::DATE, vec![], Cardinality::SINGLE, now).unwrap();
+        let q1 = question(&conn, "q1");
+        let q2 = question(&conn, "q2");
+        set_user_field_values(&conn, q1, "页码", vec!["9".into()]).unwrap();
+        set_user_field_values(&conn, q2, "页码", vec!["12".into()]).unwrap();
+        set_user_field_values(&conn, q2, "考试日期", vec!["2026-01-15".into()]).unwrap();
+
+        let ids = |filters: Vec<UserFieldFilter>| -> Vec<i64> {
+            let filters = to_meta_filters(&conn, &filters).unwrap();
+            select_views_classified(&conn, None, None, &[], &filters, 10, 0)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.id)
+                .collect()
+        };
+        let filter = |name: &str, equals: Option<&str>, min: Option<&str>, max: Option<&str>| UserFieldFilter {
+            name: name.to_string(),
+            equals: equals.map(String::from),
+            contains: None,
+            min: min.map(String::from),
+            max: max.map(String::from),
+        };
+
+        // 数值区间按数字比较（"9" < "12"）
+        assert_eq!(ids(vec![filter("页码", None, Some("10"), None)]), vec![i64::from(q2)]);
+        assert_eq!(ids(vec![filter("页码", Some("9.0"), None, None)]), vec![i64::from(q1)]);
+        assert_eq!(ids(vec![filter("考试日期", None, Some("2026-01-01"), Some("2026-01-31"))]), vec![i64::from(q2)]);
+        assert!(ids(vec![filter("页码", Some("9"), None, None), filter("考试日期", None, Some("2026-01-01"), None)]).is_empty());
+        assert!(to_meta_filters(&conn, &[filter("不存在", Some("1"), None, None)]).is_err());
+    }
+}
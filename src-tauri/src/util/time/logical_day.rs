@@ -53,6 +53,13 @@ impl LogicalDay {
         LogicalDay(day_index)
     }
 
+    /// 星期几（ISO：周一 = 1 … 周日 = 7）。
+    pub fn weekday(&self) -> u32 {
+        chrono::NaiveDate::from_num_days_from_ce_opt(self.0)
+            .map(|d| d.weekday().number_from_monday())
+            .unwrap_or(1)
+    }
+
     /// LogicalDay → "YYYY-MM-DD" 字符串（本地日历日）。
     pub fn to_string(&self, cfg: &ClockConfig) -> String {
         let offset_sec = cfg.offset_seconds;
@@ -111,4 +111,13 @@ mod tests {
         let target = chrono::NaiveDate::from_ymd_opt(2026, 2, 8).unwrap();
         assert_eq!(day.0 as i64, num_days_from_ce_via_chrono(target));
     }
+
+    #[test]
+    fn test_logical_day_weekday() {
+        // 2026-02-09 是周一，2026-02-15 是周日
+        let monday = chrono::NaiveDate::from_ymd_opt(2026, 2, 9).unwrap();
+        let sunday = chrono::NaiveDate::from_ymd_opt(2026, 2, 15).unwrap();
+        assert_eq!(LogicalDay(num_days_from_ce_via_chrono(monday) as i32).weekday(), 1);
+        assert_eq!(LogicalDay(num_days_from_ce_via_chrono(sunday) as i32).weekday(), 7);
+    }
 }
@@ -0,0 +1,56 @@
+// src/api/bulk.ts
+import { call } from "./core";
+import type { UserFieldFilter } from "./userField";
+
+/** 按条件选题；各项可缺省。恢复 / 永久删除在回收站中查找，其余在未删除题目中查找 */
+export interface BulkFilter {
+  subject?: string | null;
+  question_state?: string | null;
+  knowledge_point?: string | null;
+  tags?: string[];
+  fields?: UserFieldFilter[];
+}
+
+/** 批量操作 */
+export type BulkAction =
+  | { type: "delete" }
+  | { type: "restore" }
+  | { type: "permanently_delete" }
+  | { type: "suspend" }
+  | { type: "recover" }
+  | { type: "set_subject"; subject: string }
+  | { type: "add_knowledge_points"; knowledge_points: string[] }
+  | { type: "remove_knowledge_points"; knowledge_points: string[] }
+  | { type: "reset_progress" }
+  | { type: "set_paper"; paper_id: number | null };
+
+/** 单道题的执行结果 */
+export interface BulkOutcome {
+  id: number;
+  ok: boolean;
+  error: string | null;
+}
+
+/**
+ * 对一组题目执行批量操作（一个事务，逐题返回结果）
+ * 题目二选一：直接给 id 列表，或给过滤条件
+ */
+export function bulkQuestions(
+  selection: { questionIds: number[] } | { filter: BulkFilter },
+  action: BulkAction,
+) {
+  return call<BulkOutcome[]>("bulk_questions_comm", {
+    questionIds: "questionIds" in selection ? selection.questionIds : null,
+    filter: "filter" in selection ? selection.filter : null,
+    action,
+  });
+}
+
+/** 汇总结果，如 "成功 3 道，失败 1 道（#12 question is in the recycle bin）" */
+export function summarizeOutcomes(outcomes: BulkOutcome[]): string {
+  const failed = outcomes.filter(o => !o.ok);
+  const summary = `成功 ${outcomes.length - failed.length} 道`;
+  if (failed.length === 0) return summary;
+  const details = failed.slice(0, 3).map(o => `#${o.id} ${o.error ?? ""}`).join("；");
+  return `${summary}，失败 ${failed.length} 道（${details}${failed.length > 3 ? "…" : ""}）`;
+}
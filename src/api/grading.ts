@@ -0,0 +1,43 @@
+// src/api/grading.ts
+import { call } from "./core";
+import type { ReviewResult } from "@/types/question";
+
+/** 一次判分建议 */
+export interface AnswerGrading {
+  id: number;
+  question_id: number;
+  response: string;
+  /** 判分时的标准答案 */
+  expected: string;
+  suggested: ReviewResult;
+}
+
+/** 判分建议与最终结果的对照 */
+export interface GradingStats {
+  decided_count: number;
+  agreed_count: number;
+  /** 建议被采纳的比例，0..1 */
+  accuracy: number;
+  pairs: { suggested: ReviewResult; final_result: ReviewResult; count: number }[];
+}
+
+/**
+ * 给复习时输入的作答判分，返回建议结果
+ */
+export function gradeAnswer(questionId: number, response: string) {
+  return call<AnswerGrading>("grade_answer_comm", { questionId, response });
+}
+
+/**
+ * 记下最终给出的结果（采纳建议或改判）
+ */
+export function decideGrading(gradingId: number, result: ReviewResult) {
+  return call<void>("decide_grading_comm", { gradingId, result });
+}
+
+/**
+ * 判分建议的准确率
+ */
+export function getGradingStats() {
+  return call<GradingStats>("get_grading_stats_comm");
+}
@@ -0,0 +1,50 @@
+// src/api/knowledgeGraph.ts
+import { call } from "./core";
+
+/** 一条前置关系：学 knowledge_point 之前需要先掌握 prerequisite */
+export interface PrerequisiteEdge {
+  knowledge_point: string;
+  prerequisite: string;
+}
+
+/**
+ * 添加前置关系；会形成环时后端报错
+ */
+export function addPrerequisite(knowledgePoint: string, prerequisite: string) {
+  return call<void>("add_prerequisite_comm", { knowledgePoint, prerequisite });
+}
+
+/**
+ * 删除前置关系
+ */
+export function removePrerequisite(knowledgePoint: string, prerequisite: string) {
+  return call<void>("remove_prerequisite_comm", { knowledgePoint, prerequisite });
+}
+
+/**
+ * 列出全部前置关系
+ */
+export function listPrerequisites() {
+  return call<PrerequisiteEdge[]>("list_prerequisites_comm", {});
+}
+
+/**
+ * 知识点的全部前置（直接与间接），由近及远
+ */
+export function getKnowledgePointAncestors(knowledgePoint: string) {
+  return call<string[]>("get_knowledge_point_ancestors_comm", { knowledgePoint });
+}
+
+/**
+ * 依赖该知识点的全部知识点（直接与间接），由近及远
+ */
+export function getKnowledgePointDescendants(knowledgePoint: string) {
+  return call<string[]>("get_knowledge_point_descendants_comm", { knowledgePoint });
+}
+
+/**
+ * 知识点最薄弱的前置（近 days 天错误率最高，缺省 14 天）
+ */
+export function getWeakestPrerequisite(knowledgePoint: string, days?: number) {
+  return call<string | null>("get_weakest_prerequisite_comm", { knowledgePoint, days });
+}
@@ -0,0 +1,76 @@
+// src/api/knowledgePoint.ts
+import { call } from "./core";
+
+/** 知识点树中的一个节点（名称全库唯一） */
+export interface KnowledgePoint {
+  id: number;
+  name: string;
+  /** 所属科目，null 表示未分类 */
+  subject_id: number | null;
+  /** 父节点，null 表示根节点 */
+  parent_id: number | null;
+  sort_order: number;
+}
+
+/** 知识点及其子树（含全部后代节点）的统计 */
+export interface KnowledgePointStats {
+  id: number;
+  name: string;
+  subject_id: number | null;
+  parent_id: number | null;
+  /** 从根到该节点的名称路径，如 ["函数", "二次函数", "顶点式"] */
+  path: string[];
+  question_count: number;
+  review_count: number;
+  /** 没有复习记录时为 null */
+  error_rate: number | null;
+}
+
+/**
+ * 全部知识点节点，按显示顺序；按 parent_id 组装成树
+ */
+export function getKnowledgePointTree() {
+  return call<KnowledgePoint[]>("get_knowledge_point_tree_comm", {});
+}
+
+/**
+ * 新建知识点；指定父节点时科目取父节点的科目
+ */
+export function createKnowledgePoint(name: string, subjectId: number | null, parentId: number | null) {
+  return call<KnowledgePoint>("create_knowledge_point_comm", { name, subjectId, parentId });
+}
+
+/**
+ * 知识点改名；题目与前置关系中的名称一并更新。新名称已存在时报错，应改用合并
+ */
+export function renameKnowledgePoint(id: number, name: string) {
+  return call<KnowledgePoint>("rename_knowledge_point_comm", { id, name });
+}
+
+/**
+ * 移动到新的父节点下（parentId 为 null 则移为根节点），只能在同一科目内移动
+ */
+export function moveKnowledgePoint(id: number, parentId: number | null) {
+  return call<KnowledgePoint>("move_knowledge_point_comm", { id, parentId });
+}
+
+/**
+ * 把知识点 fromId 合并进 intoId（题目改指向目标节点，源节点删除）
+ */
+export function mergeKnowledgePoints(fromId: number, intoId: number) {
+  return call<KnowledgePoint>("merge_knowledge_points_comm", { fromId, intoId });
+}
+
+/**
+ * 删除知识点：从所有题目上移除，子节点挂到其父节点下
+ */
+export function deleteKnowledgePoint(id: number) {
+  return call<void>("delete_knowledge_point_comm", { id });
+}
+
+/**
+ * 各知识点子树的题目数与错误率；subjectId 给定时只列该科
+ */
+export function getKnowledgePointStats(subjectId: number | null = null) {
+  return call<KnowledgePointStats[]>("get_knowledge_point_stats_comm", { subjectId });
+}
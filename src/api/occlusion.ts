@@ -0,0 +1,33 @@
+// src/api/occlusion.ts
+import { call } from "./core";
+import type { OcclusionMask } from "@/types/question";
+
+/** 编辑遮罩时提交的矩形；card_id 为 null 表示新画的遮罩 */
+export interface MaskInput {
+  card_id: number | null;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+/**
+ * 图片上当前的遮罩
+ */
+export function listImageMasks(assetId: string) {
+  return call<OcclusionMask[]>("list_image_masks_comm", { assetId });
+}
+
+/**
+ * 整体设置图片上的遮罩：新画的生成卡片，没有列出的移除（卡片进回收站）
+ */
+export function setImageMasks(assetId: string, masks: MaskInput[]) {
+  return call<OcclusionMask[]>("set_image_masks_comm", { assetId, masks });
+}
+
+/**
+ * 换图：遮罩挪到新图并升版本，返回新图的 asset_id
+ */
+export function replaceQuestionImage(assetId: string, imagePath: string) {
+  return call<string>("replace_question_image_comm", { assetId, imagePath });
+}
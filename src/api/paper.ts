@@ -0,0 +1,84 @@
+// src/api/paper.ts
+import { call } from "./core";
+
+/** 试卷上题目的掌握情况（只统计未删除的题目） */
+export interface PaperStats {
+  question_count: number;
+  new_count: number;
+  learning_count: number;
+  stable_count: number;
+  suspended_count: number;
+  review_count: number;
+  wrong_review_count: number;
+  /** 已掌握（STABLE）的比例，0..1 */
+  stable_rate: number;
+}
+
+/** 试卷 */
+export interface Paper {
+  id: number;
+  name: string;
+  /** 考试日期（YYYY-MM-DD） */
+  exam_date: string | null;
+  subject: string | null;
+  total_score: number | null;
+  stats: PaperStats;
+}
+
+/** 新建 / 修改试卷的字段 */
+export interface PaperInput {
+  name: string;
+  exam_date: string | null;
+  subject: string | null;
+  total_score: number | null;
+}
+
+/**
+ * 全部试卷（含统计），考试日期新的在前
+ */
+export function listPapers() {
+  return call<Paper[]>("list_papers_comm", {});
+}
+
+export function createPaper(input: PaperInput) {
+  return call<Paper>("create_paper_comm", {
+    name: input.name,
+    examDate: input.exam_date,
+    subject: input.subject,
+    totalScore: input.total_score,
+  });
+}
+
+/**
+ * 修改试卷；改名时题目上的来源试卷一并更新
+ */
+export function updatePaper(id: number, input: PaperInput) {
+  return call<Paper>("update_paper_comm", {
+    id,
+    name: input.name,
+    examDate: input.exam_date,
+    subject: input.subject,
+    totalScore: input.total_score,
+  });
+}
+
+/**
+ * 删除试卷；题目保留，只清除来源试卷
+ */
+export function deletePaper(id: number) {
+  return call<void>("delete_paper_comm", { id });
+}
+
+/**
+ * 把一组题目归到试卷下；paperId 为 null 时清除来源试卷
+ */
+export function assignPaper(questionIds: number[], paperId: number | null) {
+  return call<void>("assign_paper_comm", { questionIds, paperId });
+}
+
+/**
+ * 整套复习一张试卷时要做的题目 ID（大题换成小题，暂停的跳过）
+ */
+export function getPaperSession(id: number) {
+  return call<number[]>("get_paper_session_comm", { id });
+}
@@ -1,15 +1,32 @@
 // src/api/question.ts
 import { call } from "./core";
-import type { CreateQuestion, QuestionInfo } from "@/types/question";
+import type {
+  CreateQuestion,
+  LinkType,
+  QuestionInfo,
+  QuestionLink,
+  QuestionRevision,
+  RevertOutcome,
+  RevisionChange,
+  TextField,
+  TextVersion,
+} from "@/types/question";
 import type { ActiveQuestion, DeleteQuestion } from "@/types/question";
+import type { UserFieldFilter } from "./userField";
 
 export function createQuestion(context: CreateQuestion) {
   const args = {
     name: context.name,
     questionImagePaths: context.question_image_paths,
     answerImagePaths: context.answer_image_paths,
+    explainImagePaths: context.explain_image_paths ?? [],
+    otherPaths: context.other_paths ?? [],
     subject: context.subject,
     knowledgePoints: context.knowledge_points,
+    prompt: context.prompt ?? null,
+    answer: context.answer ?? null,
+    explanation: context.explanation ?? null,
+    paperId: context.paper_id ?? null,
   }
   return call<string>("create_question_comm", args);
 }
@@ -36,17 +53,27 @@ export function getQuestionData(id: number) {
 
 /**
  * 分类查询：按科目 + 状态分页过滤；两者均可不传（"ALL" 或 null）。
+ * knowledgePoint 选中某知识点时包含其全部后代节点；tags 须全部带有。
+ * fields 为用户自定义字段过滤条件，全部满足才命中。
  * page 为 0-indexed。
  */
 export function classifyQuestions(params: {
   subject?: string | null
   questionState?: string | null
+  /** 知识点名称；包含其全部后代节点 */
+  knowledgePoint?: string | null
+  /** 须全部带有的标签 */
+  tags?: string[]
+  fields?: UserFieldFilter[]
   page: number
   pageSize: number
 }): Promise<ActiveQuestion[]> {
   return call<ActiveQuestion[]>("classify_questions", {
     subject: params.subject ?? null,
     questionState: params.questionState ?? null,
+    knowledgePoint: params.knowledgePoint ?? null,
+    tags: params.tags ?? null,
+    fields: params.fields ?? null,
     page: params.page,
     pageSize: params.pageSize,
   })
@@ -55,13 +82,14 @@ export function classifyQuestions(params: {
 /**
  * 搜索查询：单字符串输入。
  * - 后端能解析为 i64 → 题目 ID 精确查询；
- * - 否则 → 名称 / 知识点模糊匹配。
- * 可叠加 subject / questionState 进行二次过滤（Mode B）。
+ * - 否则 → 名称 / 知识点 / 题干、答案、解析文本模糊匹配。
+ * 可叠加 subject / questionState / tags 进行二次过滤（Mode B），标签须全部带有。
  */
 export function searchQuestions(params: {
   query: string
   subject?: string | null
   questionState?: string | null
+  tags?: string[]
   page: number
   pageSize: number
 }): Promise<ActiveQuestion[]> {
@@ -69,6 +97,7 @@ export function searchQuestions(params: {
     query: params.query,
     subject: params.subject ?? null,
     questionState: params.questionState ?? null,
+    tags: params.tags ?? null,
     page: params.page,
     pageSize: params.pageSize,
   })
@@ -89,6 +118,10 @@ export interface UpdateQuestionInput {
   name?: string;
   subject?: string;
   knowledge_points?: string[];
+  /** 文本字段：不传表示不修改，空字符串表示清空 */
+  prompt?: string;
+  answer?: string;
+  explanation?: string;
 }
 
 export function updateQuestion(id: number, data: UpdateQuestionInput) {
@@ -98,14 +131,83 @@ export function updateQuestion(id: number, data: UpdateQuestionInput) {
       name: data.name,
       subject: data.subject,
       knowledge_points: data.knowledge_points,
+      prompt: data.prompt,
+      answer: data.answer,
+      explanation: data.explanation,
     }
   });
 }
 
+/**
+ * 文本字段的历史版本，新版本在前
+ */
+export function getQuestionTextHistory(id: number, field: TextField) {
+  return call<TextVersion[]>("get_question_text_history_comm", { id, field });
+}
+
+/**
+ * 题目的修订历史，新的在前
+ */
+export function listQuestionRevisions(id: number) {
+  return call<QuestionRevision[]>("list_question_revisions_comm", { id });
+}
+
+/**
+ * 比较两条修订：从 fromRevision 到 toRevision 的变化
+ */
+export function diffQuestionRevisions(id: number, fromRevision: number, toRevision: number) {
+  return call<RevisionChange[]>("diff_question_revisions_comm", { id, fromRevision, toRevision });
+}
+
+/**
+ * 把题目恢复到某条修订（已移除但仍在回收区的图片会一并恢复）
+ */
+export function revertQuestionRevision(id: number, revisionId: number) {
+  return call<RevertOutcome>("revert_question_revision_comm", { id, revisionId });
+}
+
+export function listQuestionLinks(id: number) {
+  return call<QuestionLink[]>("list_question_links_comm", { id });
+}
+
+/**
+ * 关联两道题；FOLLOWS 表示 fromQuestionId 承接 toQuestionId，其余类型不分方向
+ */
+export function linkQuestions(fromQuestionId: number, toQuestionId: number, linkType: LinkType) {
+  return call<number>("link_questions_comm", { fromQuestionId, toQuestionId, linkType });
+}
+
+export function unlinkQuestions(linkId: number) {
+  return call<void>("unlink_questions_comm", { linkId });
+}
+
+/**
+ * 给大题追加一道小题（沿用大题的科目与知识点），返回小题 ID
+ */
+export function addQuestionPart(
+  id: number,
+  name: string | null,
+  prompt: string | null,
+  answer: string | null,
+  explanation: string | null,
+) {
+  return call<number>("add_question_part_comm", { id, name, prompt, answer, explanation });
+}
+
+/**
+ * 按 partIds 的顺序重排小题；必须是大题下全部小题
+ */
+export function reorderQuestionParts(id: number, partIds: number[]) {
+  return call<void>("reorder_question_parts_comm", { id, partIds });
+}
+
 export function getImageBase64(path: string) {
   return call<string>("get_image_base64", { path });
 }
 
+/**
+ * 添加资源；imageType 为 question / answer / explain / other，新资源排在同类型末尾
+ */
 export function addQuestionImages(id: number, imagePaths: string[], imageType: string) {
   return call<string>("add_question_images_comm", {
     id,
@@ -118,6 +220,9 @@ export function deleteQuestionImage(assetId: string) {
   return call<string>("delete_question_image_comm", { assetId });
 }
 
+/**
+ * 调整某类资源的排序；type 为 QUESTION / ANSWER / EXPLAIN / OTHER
+ */
 export function updateImageSortOrder(questionId: number, type: string, updates: { asset_id: string; sort_order: number }[]) {
   return call<string>("update_image_sort_order_comm", { questionId, type, updates });
 }
\ No newline at end of file
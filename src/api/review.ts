@@ -9,10 +9,42 @@ export interface ScoreDetail {
   freshness_bonus: number
   last_wrong_bonus: number
   error_rate_bonus: number
+  /** 近期跳过惩罚（无跳过为 1） */
+  skip_factor: number
   randomness: number
   final_score: number
 }
 
+/** 推荐理由编码（文案由 utils/reason.ts 渲染） */
+export type ReasonCode =
+  | { code: 'new_question' }
+  | { code: 'due' }
+  | { code: 'overdue'; days: number }
+  | { code: 'last_wrong' }
+  | { code: 'high_error_rate'; percent: number }
+  | { code: 'prerequisite'; knowledge_point: string }
+  | { code: 'new_guaranteed' }
+  | { code: 'pinned' }
+  | { code: 'remedial' }
+  | { code: 'legacy'; text: string }
+
+/** 落选原因编码 */
+export type ExclusionCode =
+  | { code: 'suspended' }
+  | { code: 'buried' }
+  | { code: 'subject_archived' }
+  | { code: 'not_study_day' }
+  | { code: 'subject_no_quota' }
+  | { code: 'knowledge_point_full'; knowledge_point: string }
+  | { code: 'mastered' }
+  | { code: 'low_forget_risk' }
+  | { code: 'low_error_rate' }
+  | { code: 'often_skipped' }
+  | { code: 'subject_rank_below'; rank: number }
+  | { code: 'variant_scheduled'; question_id: number }
+  | { code: 'sibling_scheduled'; question_id: number }
+  | { code: 'legacy'; text: string }
+
 export interface RecommendedQuestion {
   question_id: number
   name: string | null
@@ -24,10 +56,20 @@ export interface RecommendedQuestion {
   last_result: string | null
   error_rate: number | null
   subject: string | null
-  reason?: string[]
+  /** 推荐层级：DAILY 每日推荐，REMEDIAL 答错后追加的同知识点巩固题 */
+  tier: 'DAILY' | 'REMEDIAL'
+  reason?: ReasonCode[]
   score_detail?: ScoreDetail
 }
 
+/** 复习会话出题顺序 */
+export type ReviewOrder =
+  | 'score'
+  | 'subject_round_robin'
+  | 'shuffle'
+  | 'hardest_first'
+  | 'easiest_first'
+
 export interface DailyRecommendation {
   day: number
   questions: RecommendedQuestion[]
@@ -53,13 +95,19 @@ export interface PreviewRecommendationItem {
   subject: string | null
   score: number
   selected: boolean
-  reason: string[]
-  exclusion_reason: string[]
+  reason: ReasonCode[]
+  exclusion_reason: ExclusionCode[]
   score_detail: ScoreDetail | null
   subject_rank: number
   subject_limit: number
 }
 
+/** 科目今日不参与推荐的原因 */
+export type SubjectOffReason =
+  | { code: 'archived' }
+  | { code: 'not_study_day'; study_days: number[] }
+  | { code: 'no_quota' }
+
 export interface RecommendationStats {
   total_questions: number
   participating_questions: number
@@ -67,6 +115,10 @@ export interface RecommendationStats {
   recommended_count: number
   new_questions: number
   pending_review: number
+  /** 今日推荐中各理由编码的出现次数 */
+  reason_counts: { code: ReasonCode['code']; count: number }[]
+  /** 今日不参与推荐的科目及原因 */
+  off_subjects: { subject: string; reason: SubjectOffReason }[]
 }
 
 /**
@@ -97,12 +149,21 @@ export function getTodayReviewRecords() {
  * 获取推荐题目列表（新推荐系统）
  * @param limit 推荐数量，默认 10
  * @param subject 可选的科目筛选；"全部"/不传 返回所有科目
+ * @param order 可选的出题顺序；不传使用设置中的默认顺序
+ * @param seed 随机顺序的种子；不传则同一天内顺序固定
  * @returns 推荐题目列表
  */
-export function getRecommendationList(limit?: number, subject?: string) {
+export function getRecommendationList(
+  limit?: number,
+  subject?: string,
+  order?: ReviewOrder,
+  seed?: number,
+) {
   return call<RecommendedQuestion[]>("get_recommendation_list_comm", {
     limit,
     subject,
+    order,
+    seed,
   });
 }
 
@@ -119,16 +180,35 @@ export function processReview(questionId: number, result: ReviewResult) {
   });
 }
 
+/**
+ * 跳过题目：只记录跳过，不改变题目状态，也不计入错误率
+ */
+export function skipQuestion(questionId: number) {
+  return call<void>("skip_question_comm", { questionId });
+}
+
 /**
  * 获取推荐的复习题目（旧接口，兼容用）
  * @param limit 推荐数量，默认 10
  * @param subject 可选的科目筛选
+ * @param order 可选的出题顺序；不传使用设置中的默认顺序
+ * @param seed 随机顺序的种子
+ * @param tags 标签范围：只推荐同时带有这些标签的题目
  * @returns 推荐结果
  */
-export function recommendQuestions(limit?: number, subject?: string) {
+export function recommendQuestions(
+  limit?: number,
+  subject?: string,
+  order?: ReviewOrder,
+  seed?: number,
+  tags?: string[],
+) {
   return call<RecommendResult>("recommend_questions_comm", {
     limit,
     subject,
+    tags,
+    order,
+    seed,
   });
 }
 
@@ -154,6 +234,21 @@ export function recoverQuestion(questionId: number) {
   });
 }
 
+/** 重置学习进度的范围：指定题目、知识点（含后代节点）或科目 */
+export type ForgetScope =
+  | { type: "questions"; question_ids: number[] }
+  | { type: "knowledge_point"; id: number }
+  | { type: "subject"; id: number };
+
+/**
+ * 重置学习进度：题目退回新题，排程从头开始
+ * @param archiveReviews 为 true 时归档原有复习记录（复习历史统计仍可见），否则删除
+ * @returns 重置的题目数
+ */
+export function forgetProgress(scope: ForgetScope, archiveReviews: boolean) {
+  return call<number>("forget_progress_comm", { scope, archiveReviews });
+}
+
 /**
  * 获取所有科目列表
  * @returns 科目列表
@@ -200,6 +295,10 @@ export interface SubjectStat {
   correct_count: number;
   wrong_count: number;
   fuzzy_count: number;
+  /** 跳过次数（不计入作答） */
+  skip_count: number;
+  /** 跳过次数 / (复习次数 + 跳过次数) */
+  skip_rate: number;
 }
 
 /**
@@ -254,6 +353,59 @@ export function getQuestionsByIds(questionIds: number[]) {
   });
 }
 
+/**
+ * 获取今日待做的巩固题（答错后按知识点追加，尚未复习的部分）
+ */
+export function getRemedialQueue() {
+  return call<RecommendedQuestion[]>("get_remedial_queue_comm", {});
+}
+
+/**
+ * 手动置顶：题目在指定日期必定出现在推荐中
+ * @param questionId 题目ID
+ * @param daysAhead 距今天数，默认 0（今天）
+ */
+export function pinQuestion(questionId: number, daysAhead?: number) {
+  return call<void>("pin_question_comm", {
+    questionId,
+    daysAhead,
+  });
+}
+
+/**
+ * 今日搁置：题目今天不再推荐，不记录复习
+ * @param questionId 题目ID
+ */
+export function buryQuestion(questionId: number) {
+  return call<void>("bury_question_comm", {
+    questionId,
+  });
+}
+
+/**
+ * 推迟 N 天：期间不推荐，到期时间顺延，不记录复习
+ * @param questionId 题目ID
+ * @param days 推迟天数（≥ 1）
+ */
+export function postponeQuestion(questionId: number, days: number) {
+  return call<void>("postpone_question_comm", {
+    questionId,
+    days,
+  });
+}
+
+/**
+ * 取消置顶 / 搁置
+ * @param questionId 题目ID
+ * @param daysAhead 距今天数，默认 0（今天）
+ */
+export function clearRecommendationOverride(questionId: number, daysAhead?: number) {
+  return call<void>("clear_recommendation_override_comm", {
+    questionId,
+    daysAhead,
+  });
+}
+
 /**
  * 预览推荐（展示全部题目的评分和入选状态，不写库）
  * @param showScoreDetail 是否显示评分详情
@@ -266,6 +418,38 @@ export function previewRecommendation(showScoreDetail: boolean, showExclusionRea
   });
 }
 
+/** 评分因子 */
+export type ScoreFactor = 'forget_risk' | 'freshness' | 'last_wrong' | 'error_rate' | 'skip' | 'randomness'
+
+/** 单题入选 / 落选解释 */
+export interface QuestionExplanation {
+  question_id: number
+  name: string | null
+  subject: string | null
+  selected: boolean
+  score: number
+  score_detail: ScoreDetail
+  reason: ReasonCode[]
+  exclusion_reason: ExclusionCode[]
+  /** 同科按分数的排名（从 1 开始）；暂停的题为 null */
+  subject_rank: number | null
+  subject_size: number
+  /** 入选所需分数；名额未满时为 null */
+  required_score: number | null
+  /** 与入选门槛题相比差距最大的因子 */
+  main_deficit: ScoreFactor | null
+  /** 状态不变时预计入选的日期（YYYY-MM-DD） */
+  projected_date: string | null
+  projected_in_days: number | null
+}
+
+/**
+ * 解释单题为什么入选 / 没入选
+ */
+export function explainQuestion(questionId: number) {
+  return call<QuestionExplanation>("explain_question_comm", { questionId });
+}
+
 /**
  * 重新生成今日推荐（删除缓存后重新生成）
  */
@@ -1,17 +1,44 @@
 import { call } from "./core";
+import type { ReviewOrder } from "./review";
 
 export interface SubjectConfig {
   archived: boolean;
   recommendation_limit: number | null;
+  /** 该科单个知识点每日最多入选题数；null 使用全局值，0 不限制 */
+  max_per_knowledge_point?: number | null;
+  /** 每周学习日（ISO 星期：周一 = 1 … 周日 = 7）；null 表示每天 */
+  study_days?: number[] | null;
+  /** 学习日没复习完的名额是否顺延到下一个学习日 */
+  carry_over_unused?: boolean;
+}
+
+/** 简答题自动判分的规范化规则 */
+export interface GradingConfig {
+  /** 忽略全部空白；关闭时连续空白视为一个空格 */
+  ignore_whitespace: boolean;
+  /** 全角字母、数字、标点折叠为半角 */
+  fold_width: boolean;
+  ignore_case: boolean;
+  /** 数值答案的相对误差容限（0 = 必须相等）；标准答案绝对值小于 1 时按绝对误差 */
+  numeric_tolerance: number;
+  /** 文本相似度（0..1）不低于该值时建议“模糊” */
+  fuzzy_threshold: number;
 }
 
 export interface AppSettings {
   default_review_limit: number;
   per_subject_daily_limit: number;
-  subjects: Record<string, SubjectConfig>;
   new_question_ratio: number;
   new_question_guarantee_ratio: number;
   recommendation_randomness: number;
+  /** 单个知识点每日最多入选题数（0 = 不限制） */
+  max_per_knowledge_point: number;
+  /** 复习会话默认出题顺序 */
+  review_order: ReviewOrder;
+  /** 答错后追加的同知识点巩固题数（0 = 关闭） */
+  remedial_follow_up_limit: number;
+  /** 简答题自动判分 */
+  answer_grading: GradingConfig;
   show_debug_info: boolean;
   /** 本地时区相对 UTC 的偏移小时数（东时区为正，例如 +8）。 */
   timezone_offset_hours: number;
@@ -0,0 +1,55 @@
+// src/api/subject.ts
+import { call } from "./core";
+import type { SubjectConfig } from "./settings";
+
+/** 科目 */
+export interface Subject {
+  id: number;
+  name: string;
+  /** 显示颜色，null 表示默认 */
+  color: string | null;
+  sort_order: number;
+  config: SubjectConfig;
+}
+
+/**
+ * 全部科目（含配置），按显示顺序
+ */
+export function getSubjects() {
+  return call<Subject[]>("get_subjects_comm", {});
+}
+
+/**
+ * 新建科目
+ */
+export function createSubject(name: string) {
+  return call<Subject>("create_subject_comm", { name });
+}
+
+/**
+ * 修改科目颜色与配置
+ */
+export function updateSubject(id: number, color: string | null, config: SubjectConfig) {
+  return call<Subject>("update_subject_comm", { id, color, config });
+}
+
+/**
+ * 科目改名；题目、推荐缓存中的科目名一并更新。新名称已存在时报错，应改用合并
+ */
+export function renameSubject(id: number, name: string) {
+  return call<Subject>("rename_subject_comm", { id, name });
+}
+
+/**
+ * 把科目 fromId 合并进 intoId（源科目的题目归入目标科目，源科目删除）
+ */
+export function mergeSubjects(fromId: number, intoId: number) {
+  return call<Subject>("merge_subjects_comm", { fromId, intoId });
+}
+
+/**
+ * 按 id 顺序重排科目
+ */
+export function reorderSubjects(ids: number[]) {
+  return call<void>("reorder_subjects_comm", { ids });
+}
@@ -0,0 +1,29 @@
+// src/api/tag.ts
+import { call } from "./core";
+
+/** 标签及带有该标签的题目数 */
+export interface TagCount {
+  name: string;
+  question_count: number;
+}
+
+/**
+ * 全部标签及题目数，按名称排序
+ */
+export function listTags() {
+  return call<TagCount[]>("list_tags_comm", {});
+}
+
+/**
+ * 给一组题目打上标签；返回新增的（题目, 标签）对数
+ */
+export function tagQuestions(questionIds: number[], tags: string[]) {
+  return call<number>("tag_questions_comm", { questionIds, tags });
+}
+
+/**
+ * 去掉一组题目上的标签；返回去掉的（题目, 标签）对数
+ */
+export function untagQuestions(questionIds: number[], tags: string[]) {
+  return call<number>("untag_questions_comm", { questionIds, tags });
+}
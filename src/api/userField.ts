@@ -0,0 +1,73 @@
+// src/api/userField.ts
+import { call } from "./core";
+
+export type UserFieldType = 'TEXT' | 'NUMBER' | 'DATE' | 'ENUM' | 'BOOL';
+export type Cardinality = 'SINGLE' | 'MULTIPLE';
+
+/** 用户自定义字段定义 */
+export interface UserField {
+  name: string;
+  field_type: UserFieldType;
+  /** ENUM 字段的可选值 */
+  allowed_values: string[];
+  cardinality: Cardinality;
+}
+
+/** 某题某字段的值 */
+export interface UserFieldValues {
+  name: string;
+  values: string[];
+}
+
+/**
+ * 列表页字段过滤：equals 值相等；contains 仅 TEXT；min / max 仅 NUMBER / DATE
+ */
+export interface UserFieldFilter {
+  name: string;
+  equals?: string | null;
+  contains?: string | null;
+  min?: string | null;
+  max?: string | null;
+}
+
+/**
+ * 新建字段
+ */
+export function defineUserField(name: string, fieldType: UserFieldType, cardinality: Cardinality, allowedValues?: string[]) {
+  return call<UserField>("define_user_field_comm", { name, fieldType, cardinality, allowedValues });
+}
+
+/**
+ * 修改字段；现有值不符合新定义时后端报错
+ */
+export function updateUserField(name: string, fieldType: UserFieldType, cardinality: Cardinality, allowedValues?: string[]) {
+  return call<UserField>("update_user_field_comm", { name, fieldType, cardinality, allowedValues });
+}
+
+/**
+ * 删除字段及其全部值
+ */
+export function deleteUserField(name: string) {
+  return call<void>("delete_user_field_comm", { name });
+}
+
+/**
+ * 全部字段定义
+ */
+export function listUserFields() {
+  return call<UserField[]>("list_user_fields_comm", {});
+}
+
+/**
+ * 设置某题某字段的值（整体替换，空数组清空），返回规范化后的值
+ */
+export function setQuestionFieldValues(id: number, name: string, values: string[]) {
+  return call<string[]>("set_question_field_values_comm", { id, name, values });
+}
+
+/**
+ * 某题全部字段的值
+ */
+export function getQuestionFieldValues(id: number) {
+  return call<UserFieldValues[]>("get_question_field_values_comm", { id });
+}
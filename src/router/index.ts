@@ -49,6 +49,12 @@ const router = createRouter({
       component: () => import('../views/QuestionNewView.vue'),
       meta: { requiresInit: true }
     },
+    {
+      path: '/papers',
+      name: 'papers',
+      component: () => import('../views/PapersView.vue'),
+      meta: { requiresInit: true }
+    },
     {
       path: '/stats',
       name: 'stats',
@@ -1,8 +1,10 @@
 import { defineStore } from 'pinia'
-import { ref } from 'vue'
+import { ref, computed } from 'vue'
 import { getAppSettings, saveAppSettings, openDataDirectory } from '@/api/settings'
-import { listSubjects } from '@/api/review'
-import type { AppSettings, SubjectConfig } from '@/api/settings'
+import { getSubjects, updateSubject, renameSubject as renameSubjectApi, mergeSubjects as mergeSubjectsApi, reorderSubjects } from '@/api/subject'
+import type { AppSettings, GradingConfig, SubjectConfig } from '@/api/settings'
+import type { Subject } from '@/api/subject'
+import type { ReviewOrder } from '@/api/review'
 
 export const useSettingsStore = defineStore('settings', () => {
   // ===== 设置状态 =====
@@ -11,19 +13,37 @@ export const useSettingsStore = defineStore('settings', () => {
   const newQuestionRatio = ref<number>(0.3)
   const newQuestionGuaranteeRatio = ref<number>(0.2)
   const recommendationRandomness = ref<number>(1.0)
+  const maxPerKnowledgePoint = ref<number>(3)
+  const reviewOrder = ref<ReviewOrder>('score')
+  const remedialFollowUpLimit = ref<number>(3)
+  // 简答题自动判分
+  const defaultAnswerGrading: GradingConfig = {
+    ignore_whitespace: true,
+    fold_width: true,
+    ignore_case: true,
+    numeric_tolerance: 0.001,
+    fuzzy_threshold: 0.8,
+  }
+  const answerGrading = ref<GradingConfig>({ ...defaultAnswerGrading })
   const showDebugInfo = ref<boolean>(true)
 
   // 时区与逻辑日
   const timezoneOffsetHours = ref<number>(8)
   const dayCutoffHour = ref<number>(3)
 
-  // 科目池管理
-  const subjectConfigs = ref<Record<string, SubjectConfig>>({})
-  const allSubjects = ref<string[]>([])
+  // 科目池管理（科目与配置存放在数据库，修改即时保存）
+  const subjects = ref<Subject[]>([])
+  const subjectConfigs = computed<Record<string, SubjectConfig>>(() =>
+    Object.fromEntries(subjects.value.map(s => [s.name, s.config]))
+  )
+  const allSubjects = computed<string[]>(() => subjects.value.map(s => s.name))
 
   // 开发者模式（纯运行时，不持久化）
   const developerMode = ref<boolean>(false)
 
+  // 最近一次从后端读到的完整设置；保存时以此为底，避免覆盖前端未展示的字段
+  const rawSettings = ref<Partial<AppSettings>>({})
+
   // 数据目录（只读展示）
   const dataRoot = ref<string>('')
   const loaded = ref<boolean>(false)
@@ -36,15 +56,20 @@ export const useSettingsStore = defineStore('settings', () => {
       const resp = await getAppSettings()
       dataRoot.value = resp.root || ''
       const s = resp.settings
+      rawSettings.value = s
       defaultReviewLimit.value = s.default_review_limit ?? 10
       perSubjectDailyLimit.value = s.per_subject_daily_limit ?? 10
       newQuestionRatio.value = s.new_question_ratio ?? 0.3
       newQuestionGuaranteeRatio.value = s.new_question_guarantee_ratio ?? 0.2
       recommendationRandomness.value = s.recommendation_randomness ?? 1.0
+      maxPerKnowledgePoint.value = s.max_per_knowledge_point ?? 3
+      reviewOrder.value = s.review_order ?? 'score'
+      remedialFollowUpLimit.value = s.remedial_follow_up_limit ?? 3
+      answerGrading.value = { ...defaultAnswerGrading, ...(s.answer_grading ?? {}) }
       showDebugInfo.value = s.show_debug_info ?? true
       timezoneOffsetHours.value = s.timezone_offset_hours ?? 8
       dayCutoffHour.value = s.day_cutoff_hour ?? 3
-      subjectConfigs.value = s.subjects || {}
+      await loadSubjectPool()
       loaded.value = true
     } catch (e) {
       console.error('加载设置失败，使用默认值:', e)
@@ -55,15 +80,19 @@ export const useSettingsStore = defineStore('settings', () => {
   /** 保存设置到后端 */
   async function saveSettings() {
     const settings: AppSettings = {
+      ...(rawSettings.value as AppSettings),
       default_review_limit: defaultReviewLimit.value,
       per_subject_daily_limit: perSubjectDailyLimit.value,
       new_question_ratio: newQuestionRatio.value,
       new_question_guarantee_ratio: newQuestionGuaranteeRatio.value,
       recommendation_randomness: recommendationRandomness.value,
+      max_per_knowledge_point: maxPerKnowledgePoint.value,
+      review_order: reviewOrder.value,
+      remedial_follow_up_limit: remedialFollowUpLimit.value,
+      answer_grading: answerGrading.value,
       show_debug_info: showDebugInfo.value,
       timezone_offset_hours: timezoneOffsetHours.value,
       day_cutoff_hour: dayCutoffHour.value,
-      subjects: subjectConfigs.value,
     }
     await saveAppSettings(settings)
   }
@@ -71,25 +100,79 @@ export const useSettingsStore = defineStore('settings', () => {
   /** 从数据库加载全部科目 */
   async function loadSubjectPool() {
     try {
-      const subjectList = await listSubjects()
-      allSubjects.value = subjectList.filter(s => s !== '')
+      subjects.value = await getSubjects()
     } catch (e) {
       console.error('加载科目列表失败:', e)
     }
   }
 
+  /** 修改某科配置并立即保存 */
+  async function patchSubjectConfig(name: string, patch: (config: SubjectConfig) => void) {
+    const subject = subjects.value.find(s => s.name === name)
+    if (!subject) return
+    const config = { ...subject.config }
+    patch(config)
+    const updated = await updateSubject(subject.id, subject.color, config)
+    subjects.value = subjects.value.map(s => (s.id === updated.id ? updated : s))
+  }
+
   /** 切换科目归档状态 */
   function toggleSubjectArchive(subject: string) {
-    const config = subjectConfigs.value[subject] ?? { archived: false, recommendation_limit: null }
-    config.archived = !config.archived
-    subjectConfigs.value = { ...subjectConfigs.value, [subject]: config }
+    return patchSubjectConfig(subject, c => { c.archived = !c.archived })
   }
 
   /** 设置科目每⽇推荐题数限制；传 null 表示回退到全局默认 */
   function setSubjectRecommendationLimit(subject: string, limit: number | null) {
-    const config = subjectConfigs.value[subject] ?? { archived: false, recommendation_limit: null }
-    config.recommendation_limit = limit
-    subjectConfigs.value = { ...subjectConfigs.value, [subject]: config }
+    return patchSubjectConfig(subject, c => { c.recommendation_limit = limit })
+  }
+
+  /** 切换科目某个学习日（ISO 星期 1~7）；七天全选时回退为"每天" */
+  function toggleSubjectStudyDay(subject: string, day: number) {
+    return patchSubjectConfig(subject, c => {
+      const days = new Set(c.study_days ?? [1, 2, 3, 4, 5, 6, 7])
+      if (days.has(day)) {
+        days.delete(day)
+      } else {
+        days.add(day)
+      }
+      c.study_days = days.size === 7 ? null : [...days].sort((a, b) => a - b)
+    })
+  }
+
+  /** 切换科目未用完名额是否顺延 */
+  function toggleSubjectCarryOver(subject: string) {
+    return patchSubjectConfig(subject, c => { c.carry_over_unused = !c.carry_over_unused })
+  }
+
+  /** 设置科目显示颜色；null 恢复默认 */
+  async function setSubjectColor(id: number, color: string | null) {
+    const subject = subjects.value.find(s => s.id === id)
+    if (!subject) return
+    const updated = await updateSubject(id, color, subject.config)
+    subjects.value = subjects.value.map(s => (s.id === updated.id ? updated : s))
+  }
+
+  /** 科目改名 */
+  async function renameSubject(id: number, name: string) {
+    await renameSubjectApi(id, name)
+    await loadSubjectPool()
+  }
+
+  /** 把科目 fromId 合并进 intoId */
+  async function mergeSubjects(fromId: number, intoId: number) {
+    await mergeSubjectsApi(fromId, intoId)
+    await loadSubjectPool()
+  }
+
+  /** 科目上移（-1）或下移（+1）一位 */
+  async function moveSubject(id: number, delta: number) {
+    const ids = subjects.value.map(s => s.id)
+    const from = ids.indexOf(id)
+    const to = from + delta
+    if (from < 0 || to < 0 || to >= ids.length) return
+    ids.splice(to, 0, ids.splice(from, 1)[0])
+    await reorderSubjects(ids)
+    await loadSubjectPool()
   }
 
   /** 打开数据目录 */
@@ -104,10 +187,15 @@ export const useSettingsStore = defineStore('settings', () => {
     newQuestionRatio,
     newQuestionGuaranteeRatio,
     recommendationRandomness,
+    maxPerKnowledgePoint,
+    reviewOrder,
+    remedialFollowUpLimit,
+    answerGrading,
     showDebugInfo,
     timezoneOffsetHours,
     dayCutoffHour,
     developerMode,
+    subjects,
     subjectConfigs,
     allSubjects,
     dataRoot,
@@ -118,6 +206,12 @@ export const useSettingsStore = defineStore('settings', () => {
     loadSubjectPool,
     toggleSubjectArchive,
     setSubjectRecommendationLimit,
+    toggleSubjectStudyDay,
+    toggleSubjectCarryOver,
+    setSubjectColor,
+    renameSubject,
+    mergeSubjects,
+    moveSubject,
     openDataDir,
   }
 })
@@ -4,8 +4,18 @@ export interface CreateQuestion {
   name: string;
   question_image_paths: string[];
   answer_image_paths: string[];
+  /** 解析图 */
+  explain_image_paths?: string[];
+  /** 其他附件（任意文件） */
+  other_paths?: string[];
   subject: string;
   knowledge_points: string[];
+  /** 题干 / 答案 / 解析文本（Markdown，行内 LaTeX 用 $...$） */
+  prompt?: string | null;
+  answer?: string | null;
+  explanation?: string | null;
+  /** 来源试卷 ID */
+  paper_id?: number | null;
 }
 
 export interface ActiveQuestion {
@@ -14,6 +24,8 @@ export interface ActiveQuestion {
   title: string;
   status: string;
   knowledge_points: string[];
+  /** 自由标签 */
+  tags: string[];
   created_at: string;
   last_review: string;
   /** 累计答错次数 */
@@ -28,6 +40,7 @@ export interface DeleteQuestion {
   title: string;
   status: string;
   knowledge_points: string[];
+  tags: string[];
   deleted_at: string;
 }
 
@@ -45,9 +58,126 @@ export interface QuestionInfo {
   deleted_at: string | null;
   subject: string | null;
   knowledge_points: string[];
+  /** 自由标签 */
+  tags: string[];
+  /** 来源试卷名称 */
+  source_paper: string | null;
   question_images: QuestionImage[];
   answer_images: QuestionImage[];
+  explain_images: QuestionImage[];
+  /** 其他附件（不一定是图片） */
+  other_assets: QuestionImage[];
   last_reviewed_at: string | null;
+  prompt: string | null;
+  answer: string | null;
+  explanation: string | null;
+  /** 与其他题目的关联（对方在回收站中的不列出） */
+  links: QuestionLink[];
+  /** 小题 (1)(2)(3)，按顺序；有小题时大题不单独复习 */
+  parts: QuestionPart[];
+  /** 本题是小题时所属的大题 */
+  parent_id: number | null;
+  /** 本题是填空卡时的填空编号；文本由笔记题干渲染，在笔记中编辑 */
+  cloze_index: number | null;
+  /** 本题是遮罩卡片时要遮住的区域，画在 question_images 中 asset_id 相同的那张图上 */
+  occlusion: OcclusionMask | null;
+}
+
+/** 图片上的一个遮罩；坐标与宽高按图片尺寸归一化到 0..1 */
+export interface OcclusionMask {
+  id: number;
+  /** 复习这一遮罩的卡片 */
+  card_id: number;
+  asset_id: string;
+  /** 换图一次升一版 */
+  version: number;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+/** 大题下的一道小题 */
+export interface QuestionPart {
+  id: number;
+  name: string | null;
+  state: QuestionState;
+  last_result: string | null;
+  /** 下次到期的逻辑日（YYYY-MM-DD） */
+  due_at: string | null;
+}
+
+/** 题目关联类型：变式 / 承接 / 对比 / 同源 */
+export type LinkType = 'VARIANT_OF' | 'FOLLOWS' | 'CONTRASTS_WITH' | 'SAME_SOURCE';
+
+/** 从某道题看出去的一条关联 */
+export interface QuestionLink {
+  id: number;
+  link_type: LinkType;
+  question_id: number;
+  question_name: string | null;
+  /** 仅对 FOLLOWS 有意义：true 表示本题承接对方 */
+  outgoing: boolean;
+  /** Unix 秒 */
+  created_at: number;
+}
+
+/** 文本字段 */
+export type TextField = 'prompt' | 'answer' | 'explanation';
+
+/** 文本字段的一个历史版本 */
+export interface TextVersion {
+  version: number;
+  content: string;
+  created_at: string;
+}
+
+/** 修订快照中的一个资源 */
+export interface AssetSnapshot {
+  id: number;
+  asset_type: string;
+  path: string;
+  sort_order: number;
+}
+
+/** 题目可编辑内容的完整快照 */
+export interface QuestionSnapshot {
+  name: string | null;
+  subject: string | null;
+  subject_id?: number | null;
+  knowledge_points: string[];
+  /** 与 knowledge_points 一一对应的知识点 id */
+  knowledge_point_ids?: (number | null)[];
+  assets: AssetSnapshot[];
+  prompt: string | null;
+  answer: string | null;
+  explanation: string | null;
+}
+
+/** 题目的一条修订 */
+export interface QuestionRevision {
+  id: number;
+  /** Unix 秒 */
+  created_at: number;
+  snapshot: QuestionSnapshot;
+}
+
+/** 两条修订之间的一处差异 */
+export type RevisionChange =
+  | { field: 'name' | 'subject'; before: string | null; after: string | null }
+  | { field: 'knowledge_points'; added: string[]; removed: string[] }
+  | { field: 'assets'; added: AssetSnapshot[]; removed: AssetSnapshot[]; reordered: boolean }
+  | { field: 'text'; text_field: 'PROMPT' | 'ANSWER' | 'EXPLANATION'; before: string | null; after: string | null };
+
+/** 恢复修订的结果 */
+export interface RevertOutcome {
+  revision_id: number | null;
+  /** 已被永久删除、无法恢复的资源 */
+  missing_assets: AssetSnapshot[];
+  /** 已被合并或删除、无法恢复的科目 */
+  missing_subject: string | null;
+  /** 已被合并或删除、无法恢复的知识点 */
+  missing_knowledge_points: string[];
 }
 
 export type QuestionState = 'NEW' | 'LEARNING' | 'STABLE' | 'SUSPENDED';
@@ -0,0 +1,4 @@
+// 重置学习进度前询问是否保留原有复习记录（归档）
+export function askArchiveReviews(): boolean {
+  return confirm('是否保留原有复习记录？\n确定：归档，复习历史统计中仍可见\n取消：直接删除')
+}
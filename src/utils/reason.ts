@@ -0,0 +1,79 @@
+import type { ReasonCode, ExclusionCode, ScoreFactor, SubjectOffReason } from '@/api/review'
+
+/** 推荐理由编码 → 显示文案 */
+export function formatReason(r: ReasonCode): string {
+  switch (r.code) {
+    case 'new_question': return '新题'
+    case 'due': return '到期'
+    case 'overdue': return `超期${r.days}天`
+    case 'last_wrong': return '上次出错'
+    case 'high_error_rate': return `错误率${r.percent}%`
+    case 'prerequisite': return `补前置：${r.knowledge_point}`
+    case 'new_guaranteed': return '新题保送'
+    case 'pinned': return '手动置顶'
+    case 'remedial': return '同知识点巩固'
+    case 'legacy': return r.text
+  }
+}
+
+/** 落选原因编码 → 显示文案 */
+export function formatExclusion(r: ExclusionCode): string {
+  switch (r.code) {
+    case 'suspended': return '暂停复习'
+    case 'buried': return '今日已搁置'
+    case 'subject_archived': return '科目已归档'
+    case 'not_study_day': return '今天不是该科学习日'
+    case 'subject_no_quota': return '该科每日题数为 0'
+    case 'knowledge_point_full': return `知识点已满：${r.knowledge_point}`
+    case 'mastered': return '已掌握'
+    case 'low_forget_risk': return '遗忘风险低'
+    case 'low_error_rate': return '错误率低'
+    case 'often_skipped': return '近期常被跳过'
+    case 'subject_rank_below': return `同科排名低于${r.rank}`
+    case 'variant_scheduled': return `变式题 #${r.question_id} 已排进今天`
+    case 'sibling_scheduled': return `同一笔记的填空卡 #${r.question_id} 已排进今天`
+    case 'legacy': return r.text
+  }
+}
+
+const WEEKDAY_NAMES = ['一', '二', '三', '四', '五', '六', '日']
+
+/** ISO 星期（1~7）→ "周一" */
+export function weekdayLabel(day: number): string {
+  return '周' + (WEEKDAY_NAMES[day - 1] ?? '?')
+}
+
+/** 科目休息原因 → 显示文案 */
+export function formatSubjectOff(r: SubjectOffReason): string {
+  switch (r.code) {
+    case 'archived': return '已归档'
+    case 'not_study_day':
+      return r.study_days.length > 0
+        ? `非学习日（${r.study_days.map(weekdayLabel).join('、')}）`
+        : '未设置学习日'
+    case 'no_quota': return '每日题数为 0'
+  }
+}
+
+/** 评分因子 → 显示文案 */
+export function formatScoreFactor(f: ScoreFactor): string {
+  switch (f) {
+    case 'forget_risk': return '遗忘风险'
+    case 'freshness': return '新鲜度'
+    case 'last_wrong': return '上次出错加成'
+    case 'error_rate': return '错误率加成'
+    case 'skip': return '跳过惩罚'
+    case 'randomness': return '随机扰动'
+  }
+}
+
+/** 理由编码名 → 统计用的简短标签（不含参数） */
+export function reasonCodeLabel(code: ReasonCode['code']): string {
+  switch (code) {
+    case 'overdue': return '超期'
+    case 'high_error_rate': return '错误率高'
+    case 'prerequisite': return '补前置'
+    case 'legacy': return '旧版理由'
+    default: return formatReason({ code } as ReasonCode)
+  }
+}